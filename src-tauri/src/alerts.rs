@@ -0,0 +1,164 @@
+use chrono::{DateTime, Duration, Utc};
+use tauri::{AppHandle, Manager};
+use tracing::{error, info};
+
+use crate::category::{CategoryConfig, SharedConfig};
+use crate::commands::category_limits_for_day;
+use crate::database::{self, DbConnection};
+use crate::menu::{self, format_duration};
+use crate::notifications::notify_once;
+use crate::tracker::SharedTrackerHealth;
+
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+// Meta diária/semanal atingida, uma vez por dia/semana do calendário configurado
+async fn check_goals(app: &AppHandle, config: &CategoryConfig) {
+    let calendar = &config.calendar;
+    let today = calendar.logical_date(Utc::now());
+    let state = menu::get_tray_state(app).await;
+    if state.daily_goal_minutes > 0 && state.goal_percentage >= 100 {
+        notify_once(
+            app,
+            &format!("daily_goal_{}", today),
+            "Daily goal reached",
+            &menu::describe_daily_goal(&state),
+        )
+        .await;
+    }
+    if let Some(weekly) = state.weekly_goal.as_ref().filter(|w| w.percentage >= 100) {
+        notify_once(
+            app,
+            &format!("weekly_goal_{}", calendar.logical_date(calendar.week_bounds(today).0)),
+            "Weekly goal reached",
+            &menu::describe_weekly_goal(weekly),
+        )
+        .await;
+    }
+}
+
+// Limites de categoria estourados hoje, uma vez por categoria e dia
+async fn check_category_limits(app: &AppHandle, config: &CategoryConfig) {
+    let today = config.calendar.logical_date(Utc::now());
+    let limits = match category_limits_for_day(app.state::<DbConnection>().inner(), config, today).await {
+        Ok(limits) => limits,
+        Err(e) => {
+            error!("Failed to compute category limits: {}", e);
+            return;
+        }
+    };
+
+    for limit in limits.iter().filter(|l| l.exceeded) {
+        notify_once(
+            app,
+            &format!("category_limit_{}_{}", limit.category_id, today),
+            &format!("{} limit exceeded", limit.category_name),
+            &format!(
+                "{} today, limit is {}",
+                format_duration(limit.used_seconds),
+                format_duration(limit.limit_minutes * 60),
+            ),
+        )
+        .await;
+    }
+}
+
+// Tracker sem registrar nada (travado, sem permissão) fora de uma pausa manual,
+// do horário sensível ou da pausa fora do expediente
+fn tracking_broken(app: &AppHandle, config: &CategoryConfig, now: DateTime<Utc>, threshold: Duration) -> bool {
+    if config.in_blackout(now) || config.schedule.pauses_at(&config.calendar, now) {
+        return false;
+    }
+    let health = match app.state::<SharedTrackerHealth>().lock() {
+        Ok(health) => health.clone(),
+        Err(e) => {
+            error!("Failed to lock tracker health: {}", e);
+            return false;
+        }
+    };
+    !health.paused
+        && health
+            .last_successful_tick
+            .or(health.started_at)
+            .map_or(false, |tick| now - tick >= threshold)
+}
+
+// Só ociosidade durante o expediente, para não avisar à noite ou no fim de semana
+async fn tracking_idle(app: &AppHandle, config: &CategoryConfig, now: DateTime<Utc>, threshold: Duration) -> bool {
+    let schedule = &config.schedule;
+    if !schedule.contains(&config.calendar, now - threshold) || !schedule.contains(&config.calendar, now) {
+        return false;
+    }
+
+    match database::get_activities_between(app.state::<DbConnection>().inner(), now - threshold, now).await {
+        Ok(activities) => !activities.is_empty() && activities.iter().all(|a| a.is_idle),
+        Err(e) => {
+            error!("Failed to read recent activities: {}", e);
+            false
+        }
+    }
+}
+
+// Motor de alertas: meta atingida, limite de categoria estourado e rastreamento
+// parado/ocioso há muito tempo, cada um com sua chave em NotificationConfig
+pub async fn run_alerts(app: AppHandle) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    // Avisa uma vez por ocorrência; volta a valer quando o rastreamento se normaliza
+    let mut broken_alerted = false;
+    let mut idle_alerted = false;
+
+    loop {
+        interval.tick().await;
+
+        let config = match app.state::<SharedConfig>().lock() {
+            Ok(config) => config.clone(),
+            Err(e) => {
+                error!("Failed to lock config: {}", e);
+                continue;
+            }
+        };
+        let alerts = &config.notifications;
+        if !alerts.enabled {
+            continue;
+        }
+
+        if alerts.goal_reached {
+            check_goals(&app, &config).await;
+        }
+        if alerts.category_limit_exceeded {
+            check_category_limits(&app, &config).await;
+        }
+        if !alerts.tracking_stalled {
+            continue;
+        }
+
+        let now = Utc::now();
+        let threshold = Duration::minutes(alerts.tracking_stalled_minutes.max(1));
+        let broken = tracking_broken(&app, &config, now, threshold);
+        if broken && !broken_alerted {
+            info!("Tracker hasn't recorded anything for {} minutes", threshold.num_minutes());
+            notify_once(
+                &app,
+                &format!("tracking_broken_{}", now.timestamp()),
+                "Tracking stopped",
+                &format!(
+                    "Nothing was recorded in the last {}. Check the tracker status and permissions.",
+                    format_duration(threshold.num_seconds()),
+                ),
+            )
+            .await;
+        }
+        broken_alerted = broken;
+
+        let idle = !broken && tracking_idle(&app, &config, now, threshold).await;
+        if idle && !idle_alerted {
+            notify_once(
+                &app,
+                &format!("tracking_idle_{}", now.timestamp()),
+                "Still there?",
+                &format!("You've been idle for {} during working hours.", format_duration(threshold.num_seconds())),
+            )
+            .await;
+        }
+        idle_alerted = idle;
+    }
+}
@@ -0,0 +1,149 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::{AppHandle, Manager};
+use tracing::error;
+
+use crate::category::SharedConfig;
+use crate::database::{self, DbConnection};
+use crate::project::activity_project;
+
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+// Antes disso o desvio ainda é ruído
+const MIN_EXPECTED_MINUTES_FOR_ALERT: i64 = 60;
+
+#[derive(Debug, Serialize)]
+pub struct ProjectAllocationEntry {
+    pub project: String,
+    pub target_minutes: i64,
+    pub actual_minutes: i64,
+    // Quanto da meta já deveria ter sido feito, pelo horário de trabalho decorrido
+    pub expected_minutes: i64,
+    pub percentage: i64,
+    pub drift_minutes: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProjectAllocation {
+    pub week_start: DateTime<Utc>,
+    pub week_end: DateTime<Utc>,
+    pub elapsed_fraction: f64,
+    pub projects: Vec<ProjectAllocationEntry>,
+    // Projetos com tempo registrado mas sem meta
+    pub untargeted: Vec<(String, i64)>,
+}
+
+pub async fn allocation(db: &DbConnection, config: &SharedConfig, at: DateTime<Utc>) -> Result<ProjectAllocation> {
+    let (calendar, schedule, targets) = {
+        let config = config.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        (config.calendar.clone(), config.schedule.clone(), config.allocation.weekly_targets.clone())
+    };
+    let (week_start, week_end) = calendar.week_bounds(calendar.logical_date(at));
+
+    let activities = database::get_activities_between(db, week_start, week_end).await?;
+    let mut actual: HashMap<String, i64> = HashMap::new();
+    for activity in activities.iter().filter(|a| !a.is_idle) {
+        if let Some(project) = activity_project(activity) {
+            *actual.entry(project).or_default() += (activity.end_time - activity.start_time).num_seconds();
+        }
+    }
+
+    // Fração do horário de trabalho da semana que já passou
+    let now = Utc::now().min(week_end);
+    let (mut total, mut elapsed) = (0i64, 0i64);
+    let first_day = calendar.logical_date(week_start);
+    for day in first_day.iter_days().take(7) {
+        if let Some((start, end)) = schedule.window(&calendar, day) {
+            total += (end - start).num_seconds();
+            elapsed += (now.min(end) - start).num_seconds().max(0);
+        }
+    }
+    let elapsed_fraction = if total > 0 { elapsed as f64 / total as f64 } else { 1.0 };
+
+    let mut projects: Vec<ProjectAllocationEntry> = targets
+        .iter()
+        .map(|(project, target_minutes)| {
+            let actual_minutes = actual.remove(project).unwrap_or(0) / 60;
+            let expected_minutes = (*target_minutes as f64 * elapsed_fraction).round() as i64;
+            ProjectAllocationEntry {
+                project: project.clone(),
+                target_minutes: *target_minutes,
+                actual_minutes,
+                expected_minutes,
+                percentage: if *target_minutes > 0 {
+                    ((actual_minutes as f64 / *target_minutes as f64) * 100.0).round() as i64
+                } else {
+                    0
+                },
+                drift_minutes: actual_minutes - expected_minutes,
+            }
+        })
+        .collect();
+    projects.sort_by(|a, b| b.target_minutes.cmp(&a.target_minutes).then_with(|| a.project.cmp(&b.project)));
+
+    let mut untargeted: Vec<(String, i64)> = actual.into_iter().map(|(p, s)| (p, s / 60)).collect();
+    untargeted.sort_by(|a, b| b.1.cmp(&a.1));
+
+    Ok(ProjectAllocation {
+        week_start,
+        week_end,
+        elapsed_fraction,
+        projects,
+        untargeted,
+    })
+}
+
+// Avisa (uma vez por projeto/semana/direção) quando um projeto se afasta do esperado
+pub async fn run_drift_alerts(app: AppHandle) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let config = app.state::<SharedConfig>();
+        let (enabled, threshold) = match config.lock() {
+            Ok(config) => (
+                config.allocation.drift_alerts && !config.allocation.weekly_targets.is_empty(),
+                config.allocation.drift_threshold_percent as f64 / 100.0,
+            ),
+            Err(e) => {
+                error!("Failed to lock config: {}", e);
+                continue;
+            }
+        };
+        if !enabled {
+            continue;
+        }
+
+        let now = Utc::now();
+        let allocation = match allocation(app.state::<DbConnection>().inner(), config.inner(), now).await {
+            Ok(allocation) => allocation,
+            Err(e) => {
+                error!("Failed to compute project allocation: {}", e);
+                continue;
+            }
+        };
+
+        for entry in allocation.projects.iter().filter(|e| e.expected_minutes >= MIN_EXPECTED_MINUTES_FOR_ALERT) {
+            let drift = entry.drift_minutes as f64 / entry.expected_minutes as f64;
+            if drift.abs() < threshold {
+                continue;
+            }
+
+            let direction = if drift < 0.0 { "behind" } else { "ahead" };
+            crate::notifications::notify_once(
+                &app,
+                &format!("allocation_{}_{}_{}", entry.project, direction, allocation.week_start.format("%Y-%m-%d")),
+                &format!("{} is {} its weekly allocation", entry.project, direction),
+                &format!(
+                    "{}h{:02} tracked, {}h{:02} expected by now (target {}h{:02})",
+                    entry.actual_minutes / 60, entry.actual_minutes % 60,
+                    entry.expected_minutes / 60, entry.expected_minutes % 60,
+                    entry.target_minutes / 60, entry.target_minutes % 60,
+                ),
+            )
+            .await;
+        }
+    }
+}
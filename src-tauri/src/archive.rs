@@ -0,0 +1,241 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tracing::info;
+
+use crate::category::CategoryConfig;
+use crate::database::{self, Adjustment, DbConnection};
+use crate::tracker::WindowActivity;
+
+pub const ARCHIVE_FORMAT: &str = "chronos-track-archive";
+// Incrementar ao mudar o formato; importação recusa versões mais novas
+pub const ARCHIVE_VERSION: u32 = 1;
+
+// Todos os dados do usuário em um único JSON, para levar para outra máquina
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Archive {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub config: CategoryConfig,
+    pub activities: Vec<WindowActivity>,
+    pub adjustments: Vec<Adjustment>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArchiveExport {
+    pub activities: usize,
+    pub adjustments: usize,
+    pub categories: usize,
+}
+
+pub async fn export(db: &DbConnection, config: CategoryConfig, path: &Path) -> Result<ArchiveExport> {
+    let archive = Archive {
+        format: ARCHIVE_FORMAT.to_string(),
+        version: ARCHIVE_VERSION,
+        exported_at: Utc::now(),
+        activities: database::get_all_activities(db).await?,
+        adjustments: database::get_all_adjustments(db).await?,
+        // O arquivo pode ir para outra máquina: sem tokens nem o id deste dispositivo
+        config: config.without_secrets(),
+    };
+
+    fs::write(path, serde_json::to_vec(&archive)?)?;
+    info!("Exported {} activities to archive {:?}", archive.activities.len(), path);
+
+    Ok(ArchiveExport {
+        activities: archive.activities.len(),
+        adjustments: archive.adjustments.len(),
+        categories: archive.config.categories.len(),
+    })
+}
+
+pub fn read(path: &Path) -> Result<Archive> {
+    let archive: Archive = serde_json::from_slice(&fs::read(path)?)
+        .map_err(|e| anyhow::anyhow!("Not a Chronos Track archive: {}", e))?;
+
+    if archive.format != ARCHIVE_FORMAT {
+        return Err(anyhow::anyhow!("Not a Chronos Track archive: {}", archive.format));
+    }
+    if archive.version > ARCHIVE_VERSION {
+        return Err(anyhow::anyhow!(
+            "Archive version {} is newer than this app supports ({})",
+            archive.version,
+            ARCHIVE_VERSION
+        ));
+    }
+    Ok(archive)
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArchiveImport {
+    pub exported_at: DateTime<Utc>,
+    pub activities: usize,
+    pub new_activities: usize,
+    pub adjustments: usize,
+    pub new_adjustments: usize,
+    pub categories_added: usize,
+    pub app_mappings_added: usize,
+    pub settings_replaced: bool,
+    pub committed: bool,
+}
+
+// Categorias com o mesmo nome são a mesma categoria (os ids são gerados em cada
+// instalação); mapeamentos e rótulos locais têm prioridade sobre os do arquivo.
+// Substituir as configurações mantém a identidade e as credenciais desta máquina.
+// Retorna o id local de cada categoria do arquivo
+pub fn merge_config(
+    local: &mut CategoryConfig,
+    incoming: &CategoryConfig,
+    replace_settings: bool,
+) -> (HashMap<String, String>, usize, usize) {
+    if replace_settings {
+        *local = CategoryConfig {
+            categories: std::mem::take(&mut local.categories),
+            app_categories: std::mem::take(&mut local.app_categories),
+            domain_labels: std::mem::take(&mut local.domain_labels),
+            sync: local.sync.clone(),
+            sync_feed: local.sync_feed.clone(),
+            cloud_backup: local.cloud_backup.clone(),
+            notifications: local.notifications.clone(),
+            ..incoming.clone()
+        };
+    }
+
+    let mut category_ids = HashMap::new();
+    let mut categories_added = 0;
+    for category in &incoming.categories {
+        let existing = local
+            .categories
+            .iter()
+            .find(|c| c.id == category.id || c.name.eq_ignore_ascii_case(&category.name));
+        let local_id = match existing {
+            Some(existing) => existing.id.clone(),
+            None => {
+                local.categories.push(category.clone());
+                categories_added += 1;
+                category.id.clone()
+            }
+        };
+        category_ids.insert(category.id.clone(), local_id);
+    }
+
+    let mut app_mappings_added = 0;
+    for (app, category_id) in &incoming.app_categories {
+        let Some(local_id) = category_ids.get(category_id) else {
+            continue;
+        };
+        if !local.app_categories.contains_key(app) {
+            local.app_categories.insert(app.clone(), local_id.clone());
+            app_mappings_added += 1;
+        }
+    }
+
+    for (domain, label) in &incoming.domain_labels {
+        local.domain_labels.entry(domain.clone()).or_insert_with(|| label.clone());
+    }
+
+    (category_ids, categories_added, app_mappings_added)
+}
+
+pub async fn import(
+    db: &DbConnection,
+    local: &mut CategoryConfig,
+    archive: Archive,
+    replace_settings: bool,
+    dry_run: bool,
+) -> Result<ArchiveImport> {
+    let (category_ids, categories_added, app_mappings_added) =
+        merge_config(local, &archive.config, replace_settings);
+
+    let adjustments: Vec<Adjustment> = archive
+        .adjustments
+        .iter()
+        .cloned()
+        .map(|mut adjustment| {
+            if let Some(local_id) = category_ids.get(&adjustment.category_id) {
+                adjustment.category_id = local_id.clone();
+            }
+            adjustment
+        })
+        .collect();
+
+    let activities: Vec<WindowActivity> = archive
+        .activities
+        .iter()
+        .cloned()
+        .map(|mut activity| {
+            if let Some(local_id) = activity.category_override.as_ref().and_then(|id| category_ids.get(id)) {
+                activity.category_override = Some(local_id.clone());
+            }
+            activity
+        })
+        .collect();
+
+    let (new_activities, new_adjustments) =
+        database::merge_archive_rows(db, &activities, &adjustments, !dry_run).await?;
+
+    Ok(ArchiveImport {
+        exported_at: archive.exported_at,
+        activities: archive.activities.len(),
+        new_activities,
+        adjustments: adjustments.len(),
+        new_adjustments,
+        categories_added,
+        app_mappings_added,
+        settings_replaced: replace_settings,
+        committed: !dry_run,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tokio::sync::Mutex;
+
+    fn open(dir: &Path, name: &str) -> DbConnection {
+        let conn = database::open_connection_with_key(&dir.join(name), None).unwrap();
+        Arc::new(Mutex::new(conn))
+    }
+
+    fn activity(title: &str, minute: u32) -> WindowActivity {
+        let start = DateTime::parse_from_rfc3339(&format!("2024-05-02T09:{:02}:00Z", minute))
+            .unwrap()
+            .with_timezone(&Utc);
+        WindowActivity {
+            title: title.to_string(),
+            application: "Code".to_string(),
+            start_time: start,
+            end_time: start + chrono::Duration::minutes(1),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn reimporting_an_archive_adds_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = open(dir.path(), "source.db");
+        database::save_activity(&source, &activity("local", 0)).await.unwrap();
+        let remote = vec![("laptop".to_string(), 42, activity("remote", 1))];
+        database::upsert_remote_activities(&source, &remote).await.unwrap();
+
+        let path = dir.path().join("archive.json");
+        export(&source, CategoryConfig::default(), &path).await.unwrap();
+
+        let target = open(dir.path(), "target.db");
+        let mut config = CategoryConfig::default();
+        let first = import(&target, &mut config, read(&path).unwrap(), false, false).await.unwrap();
+        assert_eq!(first.new_activities, 2);
+
+        let second = import(&target, &mut config, read(&path).unwrap(), false, false).await.unwrap();
+        assert_eq!(second.new_activities, 0);
+
+        let imported = database::get_all_activities(&target).await.unwrap();
+        let remote = imported.iter().find(|a| a.title == "remote").unwrap();
+        assert_eq!((remote.device_id.as_deref(), remote.source_id), (Some("laptop"), Some(42)));
+    }
+}
@@ -0,0 +1,138 @@
+use anyhow::Result;
+use rusqlite::backup::Backup;
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+use crate::database::{self, DbConnection};
+use crate::encryption;
+use crate::migrations;
+use crate::tracker::{SharedTrackerHealth, TrackerHandle};
+
+#[derive(Debug, Serialize)]
+pub struct BackupInfo {
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    pub schema_version: i64,
+}
+
+// Backup online do SQLite: cópia consistente mesmo com o tracker gravando (WAL)
+pub async fn backup(db: &DbConnection, path: &Path) -> Result<BackupInfo> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let schema_version = {
+        let conn = db.lock().await;
+        encryption::copy_to(&conn, path)?;
+        migrations::current_version(&conn)?
+    };
+
+    info!("Database backed up to {:?}", path);
+    Ok(BackupInfo {
+        path: path.to_path_buf(),
+        size_bytes: std::fs::metadata(path)?.len(),
+        schema_version,
+    })
+}
+
+// Backups são gravados com a chave do banco atual (encryption::copy_to)
+fn open_backup(path: &Path) -> Result<Connection> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    encryption::apply_key(&conn, encryption::stored_key().as_deref())?;
+    Ok(conn)
+}
+
+// Recusa arquivos corrompidos, que não são do Chronos ou de uma versão mais nova
+fn validate(path: &Path) -> Result<i64> {
+    let conn = open_backup(path)?;
+
+    let integrity: String = conn.query_row("PRAGMA integrity_check", [], |row| row.get(0))?;
+    if integrity != "ok" {
+        return Err(anyhow::anyhow!("Backup failed integrity check: {}", integrity));
+    }
+
+    let has_activities: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'activities')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_activities {
+        return Err(anyhow::anyhow!("Not a Chronos Track database"));
+    }
+
+    let has_schema_version: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'schema_version')",
+        [],
+        |row| row.get(0),
+    )?;
+    let version = if has_schema_version { migrations::current_version(&conn)? } else { 0 };
+    if version > migrations::latest_version() {
+        return Err(anyhow::anyhow!(
+            "Backup schema version {} is newer than this app supports ({})",
+            version,
+            migrations::latest_version()
+        ));
+    }
+
+    Ok(version)
+}
+
+#[derive(Debug, Serialize)]
+pub struct RestoreInfo {
+    pub restored_from: PathBuf,
+    pub previous_database: PathBuf,
+    pub schema_version: i64,
+}
+
+// Substitui o banco atual pelo backup. O tracker fica pausado e o lock da conexão
+// é mantido durante a cópia, então nenhuma escrita acontece no meio
+pub async fn restore(
+    db: &DbConnection,
+    health: &SharedTrackerHealth,
+    tracker: &TrackerHandle,
+    path: &Path,
+) -> Result<RestoreInfo> {
+    let backup_version = validate(path)?;
+
+    let was_paused = set_paused(health, true)?;
+    // O evento em andamento vai para o banco atual (e para a cópia pre-restore); sem
+    // isso o tracker atualizaria, no banco restaurado, uma linha pelo id antigo
+    tracker.reset().await;
+    let result = async {
+        let mut conn = db.lock().await;
+
+        // Cópia do banco atual para poder desfazer a restauração
+        let previous_database = crate::db_location::sidecar(&database::get_database_path()?, ".pre-restore");
+        encryption::copy_to(&conn, &previous_database)?;
+
+        // Com SQLCipher, origem e destino precisam estar abertos com a mesma chave
+        let source = open_backup(path)?;
+        Backup::new(&source, &mut conn)?.run_to_completion(100, std::time::Duration::ZERO, None)?;
+        migrations::run(&mut conn)?;
+
+        Ok(RestoreInfo {
+            restored_from: path.to_path_buf(),
+            previous_database,
+            schema_version: migrations::current_version(&conn)?,
+        })
+    }
+    .await;
+    set_paused(health, was_paused)?;
+
+    if let Ok(info) = &result {
+        info!(
+            "Database restored from {:?} (schema {} -> {}), previous copy at {:?}",
+            path, backup_version, info.schema_version, info.previous_database
+        );
+    } else {
+        warn!("Database restore from {:?} failed", path);
+    }
+    result
+}
+
+fn set_paused(health: &SharedTrackerHealth, paused: bool) -> Result<bool> {
+    let mut health = health.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    Ok(std::mem::replace(&mut health.paused, paused))
+}
@@ -0,0 +1,140 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+use tracing::{error, info};
+
+use crate::category::{CategoryConfig, SharedConfig};
+use crate::database::{self, DbConnection};
+use crate::menu::format_duration;
+use crate::timeline::{self, TimelineBlockKind};
+
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+// Até onde procurar a última pausa; sem nenhuma nesse período, conta desde o início dele
+const LOOKBACK_HOURS: i64 = 8;
+
+#[derive(Debug, Default)]
+pub struct BreakReminderState {
+    snoozed_until: Option<DateTime<Utc>>,
+    last_reminded: Option<DateTime<Utc>>,
+}
+
+pub type SharedBreakReminder = Arc<Mutex<BreakReminderState>>;
+
+impl BreakReminderState {
+    pub fn snooze(&mut self, until: DateTime<Utc>) {
+        self.snoozed_until = Some(until);
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakStatus {
+    pub enabled: bool,
+    // Início do trecho ativo atual; None = em pausa agora
+    pub working_since: Option<DateTime<Utc>>,
+    pub continuous_seconds: i64,
+    pub next_reminder_at: Option<DateTime<Utc>>,
+    pub snoozed_until: Option<DateTime<Utc>>,
+}
+
+// Fim da última pausa longa o bastante (ociosidade ou nada registrado), a partir das
+// próprias atividades gravadas. Pausas mais curtas não zeram o trecho contínuo
+async fn working_since(
+    db: &DbConnection,
+    config: &CategoryConfig,
+    now: DateTime<Utc>,
+) -> Result<Option<DateTime<Utc>>> {
+    let min_break = config.break_reminders.break_minutes.max(1) * 60;
+    let start = now - Duration::hours(LOOKBACK_HOURS);
+    let activities = database::get_activities_between(db, start, now).await?;
+    let blocks = timeline::build(&activities, config, start, now, min_break);
+
+    let last_break = blocks
+        .iter()
+        .rposition(|b| b.kind != TimelineBlockKind::Activity && b.duration >= min_break);
+    Ok(match last_break {
+        Some(index) if index + 1 == blocks.len() => None,
+        Some(index) => Some(blocks[index].end),
+        None => blocks.first().map(|b| b.start),
+    })
+}
+
+pub async fn status(
+    db: &DbConnection,
+    config: &CategoryConfig,
+    state: &SharedBreakReminder,
+    now: DateTime<Utc>,
+) -> Result<BreakStatus> {
+    let breaks = &config.break_reminders;
+    let working_since = working_since(db, config, now).await?;
+    let (snoozed_until, last_reminded) = {
+        let state = state.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        (state.snoozed_until.filter(|until| *until > now), state.last_reminded)
+    };
+
+    // Depois de um lembrete no mesmo trecho, o próximo vem após mais um intervalo
+    let interval = Duration::minutes(breaks.interval_minutes.max(1));
+    let next_reminder_at = working_since.filter(|_| breaks.enabled).map(|since| {
+        let due = match last_reminded.filter(|at| *at >= since) {
+            Some(at) => at + interval,
+            None => since + interval,
+        };
+        snoozed_until.map_or(due, |until| due.max(until))
+    });
+
+    Ok(BreakStatus {
+        enabled: breaks.enabled,
+        working_since,
+        continuous_seconds: working_since.map_or(0, |since| (now - since).num_seconds().max(0)),
+        next_reminder_at,
+        snoozed_until,
+    })
+}
+
+pub async fn run_break_reminders(app: AppHandle) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let config = match app.state::<SharedConfig>().lock() {
+            Ok(config) => config.clone(),
+            Err(e) => {
+                error!("Failed to lock config: {}", e);
+                continue;
+            }
+        };
+        if !config.break_reminders.enabled {
+            continue;
+        }
+
+        let now = Utc::now();
+        let state = app.state::<SharedBreakReminder>();
+        let status = match status(app.state::<DbConnection>().inner(), &config, state.inner(), now).await {
+            Ok(status) => status,
+            Err(e) => {
+                error!("Failed to compute break status: {}", e);
+                continue;
+            }
+        };
+        if !status.next_reminder_at.map_or(false, |at| now >= at) {
+            continue;
+        }
+
+        if let Ok(mut state) = state.lock() {
+            state.last_reminded = Some(now);
+            state.snoozed_until = None;
+        }
+        info!("Break reminder after {}s of continuous activity", status.continuous_seconds);
+
+        let body = format!(
+            "You've been working for {} without a break. Step away for {} minutes.",
+            format_duration(status.continuous_seconds),
+            config.break_reminders.break_minutes,
+        );
+        if let Err(e) = crate::notifications::notify(&app, "Time for a break", &body).await {
+            error!("Failed to send notification: {}", e);
+        }
+    }
+}
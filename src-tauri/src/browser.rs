@@ -0,0 +1,60 @@
+// Perfil do navegador ("Work", "Personal") extraído do título da janela.
+// Chromium mostra o perfil ao lado do nome do navegador quando há mais de um:
+//   "Inbox - Gmail - Google Chrome - Work"
+//   "Pull requests e mais 3 páginas - Work - Microsoft Edge"
+
+const BROWSER_APPS: &[&str] = &[
+    "Google Chrome",
+    "Chromium",
+    "Microsoft Edge",
+    "Brave Browser",
+    "Vivaldi",
+    "Opera",
+    "Arc",
+];
+
+const SEPARATORS: &[&str] = &[" — ", " – ", " - "];
+
+pub fn is_browser(application: &str) -> bool {
+    let application = application.trim_end_matches(".exe");
+    BROWSER_APPS.iter().any(|app| application.eq_ignore_ascii_case(app))
+}
+
+// Nome padrão que o Chromium dá a perfis sem nome ("Profile 2", "Person 1")
+fn is_default_profile_name(segment: &str) -> bool {
+    ["Profile ", "Person "].iter().any(|prefix| {
+        segment
+            .strip_prefix(prefix)
+            .map_or(false, |n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+    })
+}
+
+// Antes do nome do navegador pode ser só parte do título da página, então ali
+// só vale um perfil conhecido; depois dele, qualquer segmento é o perfil
+pub fn detect_profile(application: &str, title: &str, known: &[String]) -> Option<String> {
+    if !is_browser(application) {
+        return None;
+    }
+
+    let separator = SEPARATORS.iter().find(|sep| title.contains(*sep))?;
+    let parts: Vec<&str> = title.split(separator).map(str::trim).collect();
+    let browser_index = parts
+        .iter()
+        .rposition(|part| BROWSER_APPS.iter().any(|app| part.eq_ignore_ascii_case(app)))?;
+
+    let known_name = |segment: &str| {
+        known
+            .iter()
+            .find(|name| name.eq_ignore_ascii_case(segment))
+            .cloned()
+            .or_else(|| is_default_profile_name(segment).then(|| segment.to_string()))
+    };
+
+    if let Some(after) = parts.get(browser_index + 1).copied().filter(|s| !s.is_empty()) {
+        return known_name(after).or_else(|| Some(after.to_string()));
+    }
+    if browser_index >= 2 {
+        return known_name(parts[browser_index - 1]);
+    }
+    None
+}
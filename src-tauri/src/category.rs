@@ -1,24 +1,994 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use anyhow::Result;
 use std::fs;
+use std::io::Write;
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 use tauri::api::path::config_dir;
+use tracing::{error, warn};
 use uuid::Uuid;
 
+use crate::tracker::WindowActivity;
+
+pub type SharedConfig = Arc<Mutex<CategoryConfig>>;
+
+// Para os coletores: sem conseguir ler a configuração, trata como blackout (não registra)
+pub fn blackout_now(config: &SharedConfig) -> bool {
+    match config.lock() {
+        Ok(config) => config.in_blackout(Utc::now()),
+        Err(e) => {
+            error!("Failed to lock config, treating as blackout: {}", e);
+            true
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Category {
     pub id: String,
     pub name: String,
     pub color: String,
     pub is_productive: bool,
+    // Fora das contas de produtivo/distração (ex: utilitários do sistema)
+    #[serde(default)]
+    pub neutral: bool,
+}
+
+pub const SYSTEM_CATEGORY_ID: &str = "system";
+
+// Shells e utilitários do sistema mapeados para "System" por padrão
+pub const SYSTEM_APPS: &[&str] = &[
+    "Finder",
+    "Dock",
+    "Spotlight",
+    "loginwindow",
+    "System Settings",
+    "System Preferences",
+    "Notification Center",
+    "Control Center",
+    "Menu Bar",
+    "Explorer",
+    "explorer.exe",
+    "Windows Explorer",
+    "ShellExperienceHost.exe",
+    "SearchHost.exe",
+    "StartMenuExperienceHost.exe",
+    "gnome-shell",
+    "plasmashell",
+];
+
+// Categorização em vigor a partir de saved_at
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConfigVersion {
+    pub version: u64,
+    pub saved_at: DateTime<Utc>,
+    pub categories: Vec<Category>,
+    pub app_categories: HashMap<String, String>,
+    pub daily_goal_minutes: i64,
+}
+
+impl ConfigVersion {
+    pub fn to_config(&self) -> CategoryConfig {
+        CategoryConfig {
+            categories: self.categories.clone(),
+            app_categories: self.app_categories.clone(),
+            daily_goal_minutes: self.daily_goal_minutes,
+            ..CategoryConfig::default()
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct CategoryConfig {
     pub categories: Vec<Category>,
     pub app_categories: HashMap<String, String>, // app_name -> category_id
+    #[serde(default)]
+    pub domain_labels: HashMap<String, String>, // domínio -> rótulo legível nos relatórios
+    #[serde(default)]
+    pub domain_categories: HashMap<String, String>, // domínio -> category_id, acima da categoria do navegador
     pub daily_goal_minutes: i64, // Meta diária em minutos
+    #[serde(default)]
+    pub weekly_goal_minutes: i64, // Meta semanal em minutos (0 = desativada)
+    #[serde(default)]
+    pub media_playback: MediaPlaybackConfig,
+    #[serde(default)]
+    pub network_tagging: NetworkTaggingConfig,
+    #[serde(default)]
+    pub window_snapshots: WindowSnapshotConfig,
+    #[serde(default)]
+    pub notifications: NotificationConfig,
+    #[serde(default)]
+    pub git_branches: GitBranchConfig,
+    #[serde(default)]
+    pub calendar: CalendarConfig,
+    #[serde(default)]
+    pub idle: IdleConfig,
+    #[serde(default)]
+    pub schedule: ScheduleConfig,
+    #[serde(default)]
+    pub tray: TrayConfig,
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    #[serde(default)]
+    pub screenshots: ScreenshotConfig,
+    #[serde(default)]
+    pub allocation: AllocationConfig,
+    #[serde(default)]
+    pub project_patterns: Vec<ProjectPattern>,
+    #[serde(default)]
+    pub blackout: BlackoutConfig,
+    #[serde(default)]
+    pub maintenance: MaintenanceConfig,
+    #[serde(default)]
+    pub sync_feed: SyncFeedConfig,
+    #[serde(default)]
+    pub sync: SyncConfig,
+    #[serde(default)]
+    pub system_category_seeded: bool,
+    #[serde(default)]
+    pub cloud_backup: CloudBackupConfig,
+    #[serde(default)]
+    pub journal: JournalConfig,
+    #[serde(default)]
+    pub score_weights: ScoreWeights,
+    #[serde(default)]
+    pub browser_profiles: BrowserProfileConfig,
+    #[serde(default)]
+    pub timesheet: TimesheetConfig,
+    #[serde(default)]
+    pub category_limits: Vec<CategoryLimit>,
+    #[serde(default)]
+    pub break_reminders: BreakReminderConfig,
+    #[serde(default)]
+    pub idle_prompt: IdlePromptConfig,
+    #[serde(default)]
+    pub pomodoro: PomodoroConfig,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BarStyle {
+    #[default]
+    Blocks,
+    Ascii,
+    Emoji,
+    Custom,
+}
+
+// O que aparece ao lado do ícone (macOS) / no título da bandeja
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayTitle {
+    #[default]
+    Percentage,
+    RemainingMinutes,
+    ProductiveHours,
+    Score,
+    Hidden,
+}
+
+// Pesos da nota de produtividade (score.rs); só a proporção entre eles importa
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScoreWeights {
+    pub productive: f64,
+    pub idle: f64,
+    pub focus: f64,
+    pub goal: f64,
+    // Trocas de app por hora ativa a partir das quais o foco vale zero
+    pub switches_per_hour_ceiling: f64,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        ScoreWeights {
+            productive: 0.4,
+            idle: 0.2,
+            focus: 0.2,
+            goal: 0.2,
+            switches_per_hour_ceiling: 60.0,
+        }
+    }
+}
+
+// Perfis do navegador (browser.rs) e categorias por perfil, acima da do app e do domínio
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BrowserProfileConfig {
+    pub enabled: bool,
+    pub profiles: Vec<String>, // nomes conhecidos, reconhecidos também antes do nome do navegador
+    pub rules: Vec<ProfileRule>,
+}
+
+// Sem domínio vale para todo o perfil; regra com domínio tem precedência
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProfileRule {
+    pub profile: String,
+    #[serde(default)]
+    pub domain: Option<String>,
+    pub category_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrayConfig {
+    pub bar_length: usize,
+    pub bar_style: BarStyle,
+    // Usados apenas com BarStyle::Custom
+    pub custom_filled: String,
+    pub custom_empty: String,
+    pub title: TrayTitle,
+}
+
+impl Default for TrayConfig {
+    fn default() -> Self {
+        TrayConfig {
+            bar_length: 10,
+            bar_style: BarStyle::Blocks,
+            custom_filled: "#".to_string(),
+            custom_empty: "-".to_string(),
+            title: TrayTitle::Percentage,
+        }
+    }
+}
+
+impl TrayConfig {
+    pub fn glyphs(&self) -> (&str, &str) {
+        match self.bar_style {
+            BarStyle::Blocks => ("▰", "▱"),
+            BarStyle::Ascii => ("#", "-"),
+            BarStyle::Emoji => ("🟩", "⬜"),
+            BarStyle::Custom => (&self.custom_filled, &self.custom_empty),
+        }
+    }
+}
+
+// Horário de trabalho esperado (hora local), usado para avaliar a cobertura dos dados
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScheduleConfig {
+    pub workdays: Vec<Weekday>,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    // Reduz a meta do dia às horas de expediente que sobravam quando o registro começou
+    #[serde(default)]
+    pub prorate_goal: bool,
+    // O que fazer fora do expediente: registrar normalmente, pausar ou marcar como off-hours
+    #[serde(default)]
+    pub off_hours: OffHoursMode,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OffHoursMode {
+    #[default]
+    Track,
+    Pause,
+    // Grava com off_hours = true; as estatísticas deixam essas linhas de fora por padrão
+    Tag,
+}
+
+impl Default for ScheduleConfig {
+    fn default() -> Self {
+        ScheduleConfig {
+            workdays: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+            start: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+            prorate_goal: false,
+            off_hours: OffHoursMode::Track,
+        }
+    }
+}
+
+impl ScheduleConfig {
+    // Janela de trabalho do dia em UTC, no fuso do calendário, ou None se não for dia útil
+    pub fn window(&self, calendar: &CalendarConfig, date: NaiveDate) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        if !self.workdays.contains(&date.weekday()) || self.end <= self.start {
+            return None;
+        }
+        Some((calendar.to_utc(date.and_time(self.start)), calendar.to_utc(date.and_time(self.end))))
+    }
+
+    pub fn contains(&self, calendar: &CalendarConfig, at: DateTime<Utc>) -> bool {
+        self.window(calendar, calendar.local_date(at))
+            .map_or(false, |(start, end)| start <= at && at < end)
+    }
+
+    // Fora do expediente com o modo Pause, nada é registrado
+    pub fn pauses_at(&self, calendar: &CalendarConfig, at: DateTime<Utc>) -> bool {
+        self.off_hours == OffHoursMode::Pause && !self.contains(calendar, at)
+    }
+
+    pub fn tags_at(&self, calendar: &CalendarConfig, at: DateTime<Utc>) -> bool {
+        self.off_hours == OffHoursMode::Tag && !self.contains(calendar, at)
+    }
+
+    pub fn expected_seconds(&self, calendar: &CalendarConfig, date: NaiveDate) -> i64 {
+        self.window(calendar, date).map_or(0, |(start, end)| (end - start).num_seconds())
+    }
+
+    // Fração do expediente a partir do primeiro registro do dia (instalação nova,
+    // máquina desligada de manhã). None sem proporção: opção desligada, dia sem
+    // expediente, nada registrado ou registro iniciado depois do fim do expediente
+    pub fn goal_fraction(
+        &self,
+        calendar: &CalendarConfig,
+        date: NaiveDate,
+        first_activity: Option<DateTime<Utc>>,
+    ) -> Option<f64> {
+        if !self.prorate_goal {
+            return None;
+        }
+        let (start, end) = self.window(calendar, date)?;
+        let available_from = first_activity?.max(start);
+        if available_from >= end {
+            return None;
+        }
+        Some((end - available_from).num_seconds() as f64 / (end - start).num_seconds() as f64)
+    }
+}
+
+// Atividades brutas mais antigas que activity_days são apagadas; os resumos
+// por hora ficam para sempre, então as estatísticas antigas continuam disponíveis
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RetentionConfig {
+    pub enabled: bool,
+    pub activity_days: u32,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        RetentionConfig {
+            enabled: false,
+            activity_days: 90,
+        }
+    }
+}
+
+// Horários sensíveis em que nada é gravado, mesmo dentro do horário de trabalho
+// (diferente do silêncio de notificações, que só cala avisos). Janelas com
+// início depois do fim atravessam a meia-noite, ex: 22:00–07:00
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BlackoutWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    #[serde(default)]
+    pub days: Vec<Weekday>, // dia em que a janela começa; vazio = todos
+}
+
+impl BlackoutWindow {
+    fn contains(&self, at: NaiveDateTime) -> bool {
+        let time = at.time();
+        let applies_on = |day: Weekday| self.days.is_empty() || self.days.contains(&day);
+
+        if self.start < self.end {
+            applies_on(at.weekday()) && time >= self.start && time < self.end
+        } else if time >= self.start {
+            applies_on(at.weekday())
+        } else {
+            // Parte depois da meia-noite pertence à janela que começou no dia anterior
+            time < self.end && applies_on(at.weekday().pred())
+        }
+    }
+}
+
+// VACUUM/ANALYZE periódico (desligado por padrão); last_run é gravado a cada execução
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MaintenanceConfig {
+    pub scheduled: bool,
+    pub interval_days: u32,
+    #[serde(default)]
+    pub last_run: Option<DateTime<Utc>>,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        MaintenanceConfig {
+            scheduled: false,
+            interval_days: 30,
+            last_run: None,
+        }
+    }
+}
+
+impl MaintenanceConfig {
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.scheduled
+            && self
+                .last_run
+                .map_or(true, |last| now - last >= Duration::days(self.interval_days.max(1) as i64))
+    }
+}
+
+// Feed dos resumos diários para o app de celular; sem token nem clientes não sobe.
+// O token do celular só lê agregados; os clientes têm permissões próprias
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SyncFeedConfig {
+    pub enabled: bool,
+    pub bind_address: String,
+    pub port: u16,
+    #[serde(default)]
+    pub token: Option<String>,
+    #[serde(default)]
+    pub clients: Vec<ApiClient>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiPermission {
+    ReadActivities, // atividades brutas (títulos, URLs); inclui os agregados
+    ReadAggregates,
+    WriteManualEntries,
+    ManageConfig,
+}
+
+// Painel só com agregados, scripts de automação com escrita, cada um com seu token
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiClient {
+    pub id: String,
+    pub name: String,
+    pub token: String,
+    pub permissions: Vec<ApiPermission>,
+}
+
+impl SyncFeedConfig {
+    pub fn has_credentials(&self) -> bool {
+        self.token.is_some() || !self.clients.is_empty()
+    }
+
+    // None para token desconhecido
+    pub fn permissions_for(&self, token: &str) -> Option<Vec<ApiPermission>> {
+        if self.token.as_deref().map_or(false, |expected| tokens_equal(expected, token)) {
+            return Some(vec![ApiPermission::ReadAggregates]);
+        }
+        self.clients
+            .iter()
+            .find(|client| tokens_equal(&client.token, token))
+            .map(|client| client.permissions.clone())
+    }
+}
+
+// Comparação em tempo constante: o tempo da resposta não revela quantos caracteres
+// do token acertaram
+fn tokens_equal(expected: &str, given: &str) -> bool {
+    let (expected, given) = (expected.as_bytes(), given.as_bytes());
+    if expected.len() != given.len() {
+        return false;
+    }
+    expected.iter().zip(given).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+impl ApiPermission {
+    pub fn granted_by(self, permissions: &[ApiPermission]) -> bool {
+        permissions.contains(&self)
+            || (self == ApiPermission::ReadAggregates && permissions.contains(&ApiPermission::ReadActivities))
+    }
+}
+
+impl Default for SyncFeedConfig {
+    fn default() -> Self {
+        SyncFeedConfig {
+            enabled: false,
+            // Só este computador; para o celular na rede local, troque para 0.0.0.0
+            bind_address: "127.0.0.1".to_string(),
+            port: 47615,
+            token: None,
+            clients: Vec::new(),
+        }
+    }
+}
+
+// Sincronização entre dispositivos por um endpoint próprio (desligada por padrão).
+// device_id é gerado na primeira vez que a sincronização é configurada
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SyncConfig {
+    pub enabled: bool,
+    pub endpoint: String,
+    #[serde(default)]
+    pub token: Option<String>,
+    #[serde(default)]
+    pub device_id: String,
+    #[serde(default)]
+    pub device_name: String,
+    pub interval_minutes: u32,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        SyncConfig {
+            enabled: false,
+            endpoint: String::new(),
+            token: None,
+            device_id: String::new(),
+            device_name: String::new(),
+            interval_minutes: 15,
+        }
+    }
+}
+
+// Destinos remotos dos backups. O segredo de cada destino (chave secreta, senha,
+// token) vai para o chaveiro do sistema e fica vazio no arquivo de configuração
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BackupTargetConfig {
+    // S3, B2, MinIO e outros compatíveis (URL no estilo path)
+    S3 {
+        endpoint: String,
+        region: String,
+        bucket: String,
+        #[serde(default)]
+        prefix: String,
+        access_key_id: String,
+        #[serde(default)]
+        secret_access_key: String,
+    },
+    #[serde(rename = "webdav")]
+    WebDav {
+        url: String,
+        username: String,
+        #[serde(default)]
+        password: String,
+    },
+    Dropbox {
+        #[serde(default)]
+        access_token: String,
+        #[serde(default)]
+        folder: String,
+    },
+}
+
+impl BackupTargetConfig {
+    pub fn secret_mut(&mut self) -> &mut String {
+        match self {
+            BackupTargetConfig::S3 { secret_access_key, .. } => secret_access_key,
+            BackupTargetConfig::WebDav { password, .. } => password,
+            BackupTargetConfig::Dropbox { access_token, .. } => access_token,
+        }
+    }
+}
+
+fn default_cloud_backup_encrypt() -> bool {
+    true
+}
+
+// Envio periódico de snapshots, mantendo só os keep_last mais recentes no destino.
+// Com encrypt, o snapshot é cifrado (AES-256-GCM) antes de sair da máquina
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CloudBackupConfig {
+    pub enabled: bool,
+    pub interval_hours: u32,
+    pub keep_last: u32,
+    #[serde(default = "default_cloud_backup_encrypt")]
+    pub encrypt: bool,
+    #[serde(default)]
+    pub target: Option<BackupTargetConfig>,
+    #[serde(default)]
+    pub last_upload: Option<DateTime<Utc>>,
+}
+
+impl Default for CloudBackupConfig {
+    fn default() -> Self {
+        CloudBackupConfig {
+            enabled: false,
+            interval_hours: 24,
+            keep_last: 7,
+            encrypt: true,
+            target: None,
+            last_upload: None,
+        }
+    }
+}
+
+impl CloudBackupConfig {
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.enabled
+            && self.target.is_some()
+            && self
+                .last_upload
+                .map_or(true, |last| now - last >= Duration::hours(self.interval_hours.max(1) as i64))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalFormat {
+    #[default]
+    Csv,
+    Jsonl,
+}
+
+// Diário diário em CSV/JSONL ao lado do banco; directory None = pasta "journal" do banco
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct JournalConfig {
+    pub enabled: bool,
+    #[serde(default)]
+    pub format: JournalFormat,
+    #[serde(default)]
+    pub directory: Option<String>,
+}
+
+// Rascunho semanal de timesheet (timesheet.rs), gerado no dia e hora locais
+// configurados; directory None = pasta "timesheets" ao lado do banco
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimesheetConfig {
+    pub enabled: bool,
+    pub weekday: Weekday,
+    pub hour: u32,
+    pub increment_minutes: i64,
+    #[serde(default)]
+    pub directory: Option<String>,
+    #[serde(default)]
+    pub last_generated: Option<NaiveDate>,
+}
+
+impl Default for TimesheetConfig {
+    fn default() -> Self {
+        TimesheetConfig {
+            enabled: false,
+            weekday: Weekday::Fri,
+            hour: 16,
+            increment_minutes: 15,
+            directory: None,
+            last_generated: None,
+        }
+    }
+}
+
+impl TimesheetConfig {
+    pub fn is_due(&self, local_now: NaiveDateTime) -> bool {
+        let today = local_now.date();
+        self.enabled
+            && today.weekday() == self.weekday
+            && local_now.hour() >= self.hour
+            && self.last_generated != Some(today)
+    }
+}
+
+// Lembrete de pausa (breaks.rs) depois de interval_minutes ativos sem uma pausa
+// de pelo menos break_minutes; o adiamento vale snooze_minutes
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BreakReminderConfig {
+    pub enabled: bool,
+    pub interval_minutes: i64,
+    pub break_minutes: i64,
+    pub snooze_minutes: i64,
+}
+
+impl Default for BreakReminderConfig {
+    fn default() -> Self {
+        BreakReminderConfig {
+            enabled: false,
+            interval_minutes: 55,
+            break_minutes: 5,
+            snooze_minutes: 10,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BlackoutConfig {
+    pub enabled: bool,
+    pub windows: Vec<BlackoutWindow>,
+}
+
+// Pausas que passam do limite de ociosidade por menos que a tolerância são
+// absorvidas pela atividade em andamento em vez de virar um fragmento ocioso
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IdleConfig {
+    pub grace_seconds: u64,
+}
+
+impl Default for IdleConfig {
+    fn default() -> Self {
+        IdleConfig { grace_seconds: 60 }
+    }
+}
+
+// Ao voltar de uma ausência de pelo menos after_minutes, oferece rotular o intervalo
+// (reunião, almoço...); a resposta vira uma atividade do app "Away" com o rótulo
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IdlePromptConfig {
+    pub enabled: bool,
+    pub after_minutes: i64,
+    pub labels: Vec<String>,
+}
+
+impl Default for IdlePromptConfig {
+    fn default() -> Self {
+        IdlePromptConfig {
+            enabled: true,
+            after_minutes: 15,
+            labels: vec!["Meeting".to_string(), "Lunch".to_string(), "Break".to_string()],
+        }
+    }
+}
+
+// Ciclo do pomodoro (pomodoro.rs): a cada long_break_every pomodoros a pausa é longa
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PomodoroConfig {
+    pub work_minutes: i64,
+    pub short_break_minutes: i64,
+    pub long_break_minutes: i64,
+    pub long_break_every: u32,
+}
+
+impl Default for PomodoroConfig {
+    fn default() -> Self {
+        PomodoroConfig {
+            work_minutes: 25,
+            short_break_minutes: 5,
+            long_break_minutes: 15,
+            long_break_every: 4,
+        }
+    }
+}
+
+// Marca cada atividade com o contexto de rede (VPN corporativa / Wi-Fi do escritório)
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct NetworkTaggingConfig {
+    pub enabled: bool,
+    pub vpn_interfaces: Vec<String>, // ex: "utun4", "wg0" (prefixos)
+    pub office_ssids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Sunday,
+    Saturday,
+}
+
+impl WeekStart {
+    pub fn weekday(&self) -> Weekday {
+        match self {
+            WeekStart::Monday => Weekday::Mon,
+            WeekStart::Sunday => Weekday::Sun,
+            WeekStart::Saturday => Weekday::Sat,
+        }
+    }
+}
+
+// Início da semana, hora em que o "dia" vira e fuso horário, usados em estatísticas,
+// relatórios e sequências
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CalendarConfig {
+    pub week_start: WeekStart,
+    pub day_rollover_hour: u32, // 0-23; ex: 4 = madrugada conta no dia anterior
+    pub confirmed: bool, // o usuário já revisou as convenções do sistema
+    #[serde(default)]
+    pub time_zone: Option<String>, // nome IANA (ex: "Europe/Berlin"); None = fuso do sistema
+}
+
+impl CalendarConfig {
+    pub fn tz(&self) -> Tz {
+        self.time_zone
+            .as_deref()
+            .and_then(|name| name.parse().ok())
+            .or_else(|| iana_time_zone::get_timezone().ok().and_then(|name| name.parse().ok()))
+            .unwrap_or(Tz::UTC)
+    }
+
+    fn rollover(&self) -> Duration {
+        Duration::hours(self.day_rollover_hour.min(23) as i64)
+    }
+
+    // Data local de um instante, sem a hora de virada (ex.: datas vindas do frontend)
+    pub fn local_date(&self, at: DateTime<Utc>) -> NaiveDate {
+        at.with_timezone(&self.tz()).date_naive()
+    }
+
+    // Dia "lógico" de um instante, considerando a hora de virada
+    pub fn logical_date(&self, at: DateTime<Utc>) -> NaiveDate {
+        (at.with_timezone(&self.tz()).naive_local() - self.rollover()).date()
+    }
+
+    // Horário local do fuso configurado em UTC. Na repetição do outono vale a primeira
+    // ocorrência; no salto da primavera, a primeira hora que existe
+    pub fn to_utc(&self, local: NaiveDateTime) -> DateTime<Utc> {
+        let tz = self.tz();
+        (0..=2)
+            .find_map(|shift| tz.from_local_datetime(&(local + Duration::hours(shift))).earliest())
+            .map(|at| at.with_timezone(&Utc))
+            .unwrap_or_else(|| local.and_utc())
+    }
+
+    // Início do dia no fuso configurado. Dias de horário de verão têm 23 ou 25 horas
+    fn day_start(&self, day: NaiveDate) -> DateTime<Utc> {
+        self.to_utc(day.and_hms_opt(0, 0, 0).unwrap() + self.rollover())
+    }
+
+    pub fn day_bounds(&self, day: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+        (self.day_start(day), self.day_start(day + Duration::days(1)) - Duration::seconds(1))
+    }
+
+    pub fn week_first_day(&self, day: NaiveDate) -> NaiveDate {
+        let days_back = (day.weekday().num_days_from_monday() + 7
+            - self.week_start.weekday().num_days_from_monday()) % 7;
+        day - Duration::days(days_back as i64)
+    }
+
+    pub fn week_bounds(&self, day: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+        let first_day = self.week_first_day(day);
+        (
+            self.day_start(first_day),
+            self.day_start(first_day + Duration::days(7)) - Duration::nanoseconds(1),
+        )
+    }
+}
+
+// Registra o branch ativo dos repositórios configurados para cruzar com as atividades
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct GitBranchConfig {
+    pub enabled: bool,
+    pub repositories: Vec<String>, // caminhos absolutos dos repositórios
+}
+
+// Diretório -> projeto, aplicado ao diretório atual dos terminais e a caminhos no título
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectPattern {
+    pub prefix: String, // ex: "~/dev/client-a"
+    pub project: String,
+}
+
+// Meta de limite: no máximo max_minutes por dia numa categoria (ex.: Entertainment <= 30)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CategoryLimit {
+    pub category_id: String,
+    pub max_minutes: i64,
+}
+
+// Horas semanais planejadas por projeto (ex.: cliente A 20h, cliente B 10h)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AllocationConfig {
+    pub weekly_targets: HashMap<String, i64>, // projeto -> minutos por semana
+    pub drift_alerts: bool,
+    pub drift_threshold_percent: u32, // desvio em relação ao esperado até o momento
+}
+
+impl Default for AllocationConfig {
+    fn default() -> Self {
+        AllocationConfig {
+            weekly_targets: HashMap::new(),
+            drift_alerts: true,
+            drift_threshold_percent: 25,
+        }
+    }
+}
+
+// Miniaturas periódicas da tela para lembrar o que foi feito (desativado por padrão)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScreenshotConfig {
+    pub enabled: bool,
+    pub interval_minutes: u64,
+    pub max_width: u32, // px; a miniatura mantém a proporção
+    pub retention_days: u32,
+    pub excluded_apps: Vec<String>,
+}
+
+impl Default for ScreenshotConfig {
+    fn default() -> Self {
+        ScreenshotConfig {
+            enabled: false,
+            interval_minutes: 10,
+            max_width: 320,
+            retention_days: 7,
+            excluded_apps: Vec::new(),
+        }
+    }
+}
+
+impl ScreenshotConfig {
+    pub fn is_excluded(&self, app_name: &str) -> bool {
+        self.excluded_apps.iter().any(|app| app.eq_ignore_ascii_case(app_name))
+    }
+}
+
+// Snapshot periódico de todas as janelas abertas (desativado por padrão pelo volume)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WindowSnapshotConfig {
+    pub enabled: bool,
+    pub interval_minutes: u64,
+}
+
+impl Default for WindowSnapshotConfig {
+    fn default() -> Self {
+        WindowSnapshotConfig {
+            enabled: false,
+            interval_minutes: 5,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationBackendKind {
+    #[default]
+    Desktop,
+    Webhook,
+    Ntfy,
+    Pushover,
+}
+
+// Para onde vão os alertas de meta/orçamento (desktop, ou o celular via ntfy/Pushover)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NotificationConfig {
+    pub enabled: bool,
+    pub backend: NotificationBackendKind,
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    #[serde(default = "default_ntfy_server")]
+    pub ntfy_server: String,
+    #[serde(default)]
+    pub ntfy_topic: Option<String>,
+    #[serde(default)]
+    pub pushover_token: Option<String>,
+    #[serde(default)]
+    pub pushover_user: Option<String>,
+    // Quais alertas disparam (todos ligados por padrão)
+    #[serde(default = "default_alert_enabled")]
+    pub goal_reached: bool,
+    #[serde(default = "default_alert_enabled")]
+    pub category_limit_exceeded: bool,
+    #[serde(default = "default_alert_enabled")]
+    pub tracking_stalled: bool,
+    // Minutos parado (tracker sem registrar) ou ocioso no expediente antes de avisar
+    #[serde(default = "default_tracking_stalled_minutes")]
+    pub tracking_stalled_minutes: i64,
+}
+
+fn default_ntfy_server() -> String {
+    "https://ntfy.sh".to_string()
+}
+
+fn default_alert_enabled() -> bool {
+    true
+}
+
+fn default_tracking_stalled_minutes() -> i64 {
+    30
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        NotificationConfig {
+            enabled: true,
+            backend: NotificationBackendKind::Desktop,
+            webhook_url: None,
+            ntfy_server: default_ntfy_server(),
+            ntfy_topic: None,
+            pushover_token: None,
+            pushover_user: None,
+            goal_reached: true,
+            category_limit_exceeded: true,
+            tracking_stalled: true,
+            tracking_stalled_minutes: default_tracking_stalled_minutes(),
+        }
+    }
+}
+
+// Reprodução de áudio/vídeo no app ativo não conta como ociosidade
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MediaPlaybackConfig {
+    pub enabled: bool,
+    pub apps: Vec<String>, // vazio = qualquer app
+}
+
+impl Default for MediaPlaybackConfig {
+    fn default() -> Self {
+        MediaPlaybackConfig {
+            enabled: true,
+            apps: Vec::new(),
+        }
+    }
+}
+
+impl MediaPlaybackConfig {
+    pub fn applies_to(&self, app_name: &str) -> bool {
+        self.enabled
+            && (self.apps.is_empty()
+                || self.apps.iter().any(|app| app.eq_ignore_ascii_case(app_name)))
+    }
 }
 
 impl CategoryConfig {
@@ -29,39 +999,102 @@ impl CategoryConfig {
                 name: "Work".to_string(),
                 color: "#4F46E5".to_string(), // Indigo
                 is_productive: true,
+                neutral: false,
             },
             Category {
                 id: Uuid::new_v4().to_string(),
                 name: "Development".to_string(),
                 color: "#2563EB".to_string(), // Blue
                 is_productive: true,
+                neutral: false,
             },
             Category {
                 id: Uuid::new_v4().to_string(),
                 name: "Communication".to_string(),
                 color: "#7C3AED".to_string(), // Purple
                 is_productive: true,
+                neutral: false,
             },
             Category {
                 id: Uuid::new_v4().to_string(),
                 name: "Entertainment".to_string(),
                 color: "#DC2626".to_string(), // Red
                 is_productive: false,
+                neutral: false,
             },
             Category {
                 id: Uuid::new_v4().to_string(),
                 name: "Social Media".to_string(),
                 color: "#EA580C".to_string(), // Orange
                 is_productive: false,
+                neutral: false,
             },
+            Self::system_category(),
         ]
     }
 
+    fn system_category() -> Category {
+        Category {
+            id: SYSTEM_CATEGORY_ID.to_string(),
+            name: "System".to_string(),
+            color: "#6B7280".to_string(), // Gray
+            is_productive: false,
+            neutral: true,
+        }
+    }
+
+    // Cria a categoria System e mapeia os apps do sistema ainda sem categoria. Roda
+    // uma vez por configuração, para não recriar o que o usuário apagou
+    fn seed_system_category(&mut self) {
+        if !self.categories.iter().any(|c| c.id == SYSTEM_CATEGORY_ID) {
+            self.categories.push(Self::system_category());
+        }
+        for app in SYSTEM_APPS {
+            self.app_categories
+                .entry(app.to_string())
+                .or_insert_with(|| SYSTEM_CATEGORY_ID.to_string());
+        }
+        self.system_category_seeded = true;
+    }
+
     pub fn default() -> Self {
         CategoryConfig {
             categories: Self::create_default_categories(),
-            app_categories: HashMap::new(),
+            app_categories: SYSTEM_APPS
+                .iter()
+                .map(|app| (app.to_string(), SYSTEM_CATEGORY_ID.to_string()))
+                .collect(),
+            domain_labels: HashMap::new(),
+            domain_categories: HashMap::new(),
             daily_goal_minutes: 240, // Meta padrão de 4 horas
+            weekly_goal_minutes: 0,
+            media_playback: MediaPlaybackConfig::default(),
+            network_tagging: NetworkTaggingConfig::default(),
+            window_snapshots: WindowSnapshotConfig::default(),
+            notifications: NotificationConfig::default(),
+            git_branches: GitBranchConfig::default(),
+            calendar: CalendarConfig::default(),
+            idle: IdleConfig::default(),
+            schedule: ScheduleConfig::default(),
+            tray: TrayConfig::default(),
+            retention: RetentionConfig::default(),
+            screenshots: ScreenshotConfig::default(),
+            allocation: AllocationConfig::default(),
+            project_patterns: Vec::new(),
+            blackout: BlackoutConfig::default(),
+            maintenance: MaintenanceConfig::default(),
+            sync_feed: SyncFeedConfig::default(),
+            sync: SyncConfig::default(),
+            system_category_seeded: true,
+            cloud_backup: CloudBackupConfig::default(),
+            journal: JournalConfig::default(),
+            score_weights: ScoreWeights::default(),
+            browser_profiles: BrowserProfileConfig::default(),
+            timesheet: TimesheetConfig::default(),
+            category_limits: Vec::new(),
+            break_reminders: BreakReminderConfig::default(),
+            idle_prompt: IdlePromptConfig::default(),
+            pomodoro: PomodoroConfig::default(),
         }
     }
 
@@ -80,6 +1113,23 @@ impl CategoryConfig {
             config.daily_goal_minutes = 240; // Meta padrão de 4 horas
             config.save()?;
         }
+
+        if !config.system_category_seeded {
+            config.seed_system_category();
+            config.save()?;
+        }
+
+        // Segredo do destino de backup ainda no arquivo: vai para o chaveiro
+        match crate::cloud_backup::migrate_target_secret(&mut config.cloud_backup) {
+            Ok(true) => config.save()?,
+            Ok(false) => {}
+            Err(e) => warn!("Failed to move the cloud backup secret to the keychain: {}", e),
+        }
+
+        // Configurações anteriores ao histórico viram a versão 1
+        if Self::history()?.is_empty() {
+            config.record_version()?;
+        }
         
         Ok(config)
     }
@@ -94,9 +1144,67 @@ impl CategoryConfig {
 
         let content = serde_json::to_string_pretty(self)?;
         fs::write(config_file, content)?;
+
+        self.record_version()?;
+        Ok(())
+    }
+
+    // Guarda uma nova versão sempre que a categorização muda, para que relatórios
+    // antigos possam ser recalculados com as regras da época
+    fn record_version(&self) -> Result<()> {
+        let history = Self::history()?;
+        let unchanged = history.last().map_or(false, |last| {
+            last.daily_goal_minutes == self.daily_goal_minutes
+                && last.app_categories == self.app_categories
+                && serde_json::to_value(&last.categories).ok() == serde_json::to_value(&self.categories).ok()
+        });
+        if unchanged {
+            return Ok(());
+        }
+
+        let version = ConfigVersion {
+            version: history.last().map_or(1, |last| last.version + 1),
+            saved_at: Utc::now(),
+            categories: self.categories.clone(),
+            app_categories: self.app_categories.clone(),
+            daily_goal_minutes: self.daily_goal_minutes,
+        };
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(Self::get_history_path()?)?;
+        writeln!(file, "{}", serde_json::to_string(&version)?)?;
         Ok(())
     }
 
+    pub fn history() -> Result<Vec<ConfigVersion>> {
+        let path = Self::get_history_path()?;
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        Ok(fs::read_to_string(path)?
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    // Versão em vigor na data; datas anteriores ao histórico usam a primeira versão
+    pub fn version_at(date: DateTime<Utc>) -> Result<Option<ConfigVersion>> {
+        let history = Self::history()?;
+        let in_effect = history.iter().rev().find(|v| v.saved_at <= date).cloned();
+        Ok(in_effect.or_else(|| history.first().cloned()))
+    }
+
+    fn get_history_path() -> Result<PathBuf> {
+        let mut path = config_dir()
+            .ok_or_else(|| anyhow::anyhow!("Failed to get config directory"))?;
+        path.push("chronos-track");
+        path.push("categories_history.jsonl");
+        Ok(path)
+    }
+
     fn get_config_path() -> Result<PathBuf> {
         let mut path = config_dir()
             .ok_or_else(|| anyhow::anyhow!("Failed to get config directory"))?;
@@ -115,6 +1223,44 @@ impl CategoryConfig {
             })
     }
 
+    // Override gravado na atividade (recategorização por padrão) ou a categoria do app
+    pub fn category_for_activity(&self, activity: &WindowActivity) -> Option<&Category> {
+        activity
+            .category_override
+            .as_deref()
+            .and_then(|id| self.categories.iter().find(|cat| cat.id == id))
+            .or_else(|| {
+                let domain = activity.url.as_deref().and_then(crate::export::domain_of);
+                self.category_for_profile(activity.profile.as_deref()?, domain.as_deref())
+            })
+            .or_else(|| self.get_category_for_app(&activity.application))
+    }
+
+    // Regra do domínio (ou domínio pai) no perfil, senão a regra do perfil inteiro
+    pub fn category_for_profile(&self, profile: &str, domain: Option<&str>) -> Option<&Category> {
+        let rules: Vec<&ProfileRule> = self
+            .browser_profiles
+            .rules
+            .iter()
+            .filter(|rule| rule.profile.eq_ignore_ascii_case(profile))
+            .collect();
+        if rules.is_empty() {
+            return None;
+        }
+
+        let domain_rule = domain.and_then(|domain| {
+            let mut candidate = domain;
+            loop {
+                if let Some(rule) = rules.iter().find(|rule| rule.domain.as_deref() == Some(candidate)) {
+                    return Some(*rule);
+                }
+                candidate = candidate.split_once('.')?.1;
+            }
+        });
+        let rule = domain_rule.or_else(|| rules.iter().find(|rule| rule.domain.is_none()).copied())?;
+        self.categories.iter().find(|cat| cat.id == rule.category_id)
+    }
+
     pub fn set_app_category(&mut self, app_name: String, category_id: String) -> Result<()> {
         // Verifica se a categoria existe
         if !self.categories.iter().any(|cat| cat.id == category_id) {
@@ -128,33 +1274,487 @@ impl CategoryConfig {
         Ok(())
     }
 
-    pub fn add_category(&mut self, name: String, color: String, is_productive: bool) -> Result<Category> {
+    // Categorias neutras nunca contam como produtivas
+    pub fn add_category(&mut self, name: String, color: String, is_productive: bool, neutral: bool) -> Result<Category> {
         let id = uuid::Uuid::new_v4().to_string();
         let category = Category {
             id: id.clone(),
             name,
             color,
-            is_productive,
+            is_productive: is_productive && !neutral,
+            neutral,
         };
         self.categories.push(category.clone());
         self.save()?;
         Ok(category)
     }
 
-    pub fn update_category(&mut self, id: String, name: String, color: String, is_productive: bool) -> Result<()> {
+    pub fn update_category(
+        &mut self,
+        id: String,
+        name: String,
+        color: String,
+        is_productive: bool,
+        neutral: Option<bool>,
+    ) -> Result<()> {
         if let Some(category) = self.categories.iter_mut().find(|c| c.id == id) {
             category.name = name;
             category.color = color;
-            category.is_productive = is_productive;
+            category.neutral = neutral.unwrap_or(category.neutral);
+            category.is_productive = is_productive && !category.neutral;
             self.save()?;
         }
         Ok(())
     }
 
+    // Rótulo do domínio ou do domínio pai mais próximo (sso.corp.com -> corp.com)
+    pub fn label_for_domain(&self, domain: &str) -> Option<&str> {
+        let mut candidate = domain;
+        loop {
+            if let Some(label) = self.domain_labels.get(candidate) {
+                return Some(label);
+            }
+            candidate = candidate.split_once('.')?.1;
+        }
+    }
+
+    // Rótulo vazio remove
+    pub fn set_domain_label(&mut self, domain: &str, label: &str) -> Result<()> {
+        let domain = domain.trim().trim_start_matches("www.").to_lowercase();
+        if domain.is_empty() {
+            return Err(anyhow::anyhow!("Domain cannot be empty"));
+        }
+
+        let label = label.trim();
+        if label.is_empty() {
+            self.domain_labels.remove(&domain);
+        } else {
+            self.domain_labels.insert(domain, label.to_string());
+        }
+        self.save()
+    }
+
+    // Subdomínios herdam a categoria do domínio (docs.github.com -> github.com)
+    pub fn category_for_domain(&self, domain: &str) -> Option<&Category> {
+        let mut candidate = domain;
+        loop {
+            if let Some(category_id) = self.domain_categories.get(candidate) {
+                return self.categories.iter().find(|cat| &cat.id == category_id);
+            }
+            candidate = candidate.split_once('.')?.1;
+        }
+    }
+
+    // category_id None remove
+    pub fn set_domain_category(&mut self, domain: &str, category_id: Option<String>) -> Result<()> {
+        let domain = domain.trim().trim_start_matches("www.").to_lowercase();
+        if domain.is_empty() {
+            return Err(anyhow::anyhow!("Domain cannot be empty"));
+        }
+
+        match category_id {
+            Some(category_id) => {
+                if !self.categories.iter().any(|cat| cat.id == category_id) {
+                    return Err(anyhow::anyhow!("Category not found"));
+                }
+                self.domain_categories.insert(domain, category_id);
+            }
+            None => {
+                self.domain_categories.remove(&domain);
+            }
+        }
+        self.save()
+    }
+
+    pub fn set_media_playback(&mut self, media_playback: MediaPlaybackConfig) -> Result<()> {
+        self.media_playback = media_playback;
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn set_network_tagging(&mut self, network_tagging: NetworkTaggingConfig) -> Result<()> {
+        self.network_tagging = network_tagging;
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn set_project_patterns(&mut self, project_patterns: Vec<ProjectPattern>) -> Result<()> {
+        self.project_patterns = project_patterns;
+        self.save()?;
+        Ok(())
+    }
+
+    // Um limite por categoria; o último vence
+    pub fn set_category_limits(&mut self, limits: Vec<CategoryLimit>) -> Result<()> {
+        let mut category_limits: Vec<CategoryLimit> = Vec::new();
+        for limit in limits {
+            if !self.categories.iter().any(|c| c.id == limit.category_id) {
+                return Err(anyhow::anyhow!("Unknown category: {}", limit.category_id));
+            }
+            category_limits.retain(|l| l.category_id != limit.category_id);
+            category_limits.push(CategoryLimit { max_minutes: limit.max_minutes.max(0), ..limit });
+        }
+        self.category_limits = category_limits;
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn set_allocation(&mut self, allocation: AllocationConfig) -> Result<()> {
+        self.allocation = allocation;
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn set_screenshots(&mut self, screenshots: ScreenshotConfig) -> Result<()> {
+        self.screenshots = screenshots;
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn set_browser_profiles(&mut self, mut browser_profiles: BrowserProfileConfig) -> Result<()> {
+        for rule in &mut browser_profiles.rules {
+            if !self.categories.iter().any(|cat| cat.id == rule.category_id) {
+                return Err(anyhow::anyhow!("Category not found: {}", rule.category_id));
+            }
+            rule.profile = rule.profile.trim().to_string();
+            rule.domain = rule
+                .domain
+                .as_deref()
+                .map(|domain| domain.trim().trim_start_matches("www.").to_lowercase())
+                .filter(|domain| !domain.is_empty());
+        }
+        browser_profiles.profiles.retain(|name| !name.trim().is_empty());
+        self.browser_profiles = browser_profiles;
+        self.save()
+    }
+
+    pub fn set_score_weights(&mut self, score_weights: ScoreWeights) -> Result<()> {
+        self.score_weights = score_weights;
+        self.save()?;
+        Ok(())
+    }
+
+    // last_generated só muda por record_timesheet
+    pub fn set_timesheet(&mut self, timesheet: TimesheetConfig) -> Result<()> {
+        self.timesheet = TimesheetConfig {
+            last_generated: self.timesheet.last_generated,
+            ..timesheet
+        };
+        self.save()
+    }
+
+    pub fn record_timesheet(&mut self, date: NaiveDate) -> Result<()> {
+        self.timesheet.last_generated = Some(date);
+        self.save()
+    }
+
+    pub fn set_break_reminders(&mut self, break_reminders: BreakReminderConfig) -> Result<()> {
+        self.break_reminders = break_reminders;
+        self.save()
+    }
+
+    pub fn set_idle_prompt(&mut self, mut idle_prompt: IdlePromptConfig) -> Result<()> {
+        idle_prompt.labels = idle_prompt
+            .labels
+            .into_iter()
+            .map(|label| label.trim().to_string())
+            .filter(|label| !label.is_empty())
+            .collect();
+        self.idle_prompt = idle_prompt;
+        self.save()
+    }
+
+    pub fn set_pomodoro(&mut self, pomodoro: PomodoroConfig) -> Result<()> {
+        self.pomodoro = pomodoro;
+        self.save()
+    }
+
+    pub fn set_journal(&mut self, journal: JournalConfig) -> Result<()> {
+        self.journal = journal;
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn set_window_snapshots(&mut self, window_snapshots: WindowSnapshotConfig) -> Result<()> {
+        self.window_snapshots = window_snapshots;
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn set_notifications(&mut self, notifications: NotificationConfig) -> Result<()> {
+        self.notifications = notifications;
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn set_git_branches(&mut self, git_branches: GitBranchConfig) -> Result<()> {
+        self.git_branches = git_branches;
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn set_tray(&mut self, tray: TrayConfig) -> Result<()> {
+        self.tray = tray;
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn set_schedule(&mut self, schedule: ScheduleConfig) -> Result<()> {
+        self.schedule = schedule;
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn set_idle(&mut self, idle: IdleConfig) -> Result<()> {
+        self.idle = idle;
+        self.save()?;
+        Ok(())
+    }
+
+    // Horário local no fuso do calendário
+    pub fn in_blackout(&self, at: DateTime<Utc>) -> bool {
+        if !self.blackout.enabled {
+            return false;
+        }
+        let local = at.with_timezone(&self.calendar.tz()).naive_local();
+        self.blackout.windows.iter().any(|window| window.contains(local))
+    }
+
+    // Mantém o horário da última execução ao trocar as opções
+    pub fn set_maintenance(&mut self, maintenance: MaintenanceConfig) -> Result<()> {
+        self.maintenance = MaintenanceConfig {
+            last_run: self.maintenance.last_run,
+            ..maintenance
+        };
+        self.save()?;
+        Ok(())
+    }
+
+    // Cópia sem credenciais nem a identidade deste dispositivo, para o que sai do
+    // computador (GET /config do feed)
+    pub fn without_secrets(&self) -> CategoryConfig {
+        let mut config = self.clone();
+        config.sync_feed.token = None;
+        config.sync_feed.clients.iter_mut().for_each(|client| client.token.clear());
+        config.sync.token = None;
+        config.sync.device_id.clear();
+        if let Some(target) = config.cloud_backup.target.as_mut() {
+            target.secret_mut().clear();
+        }
+        config.notifications.webhook_url = None;
+        config.notifications.ntfy_topic = None;
+        config.notifications.pushover_token = None;
+        config.notifications.pushover_user = None;
+        config
+    }
+
+    // O token só muda pela geração explícita
+    pub fn set_sync_feed(&mut self, sync_feed: SyncFeedConfig) -> Result<()> {
+        // Token e clientes só mudam pelos métodos próprios
+        self.sync_feed = SyncFeedConfig {
+            token: self.sync_feed.token.clone(),
+            clients: self.sync_feed.clients.clone(),
+            ..sync_feed
+        };
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn regenerate_sync_feed_token(&mut self) -> Result<String> {
+        let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        self.sync_feed.token = Some(token.clone());
+        self.save()?;
+        Ok(token)
+    }
+
+    pub fn add_api_client(&mut self, name: &str, permissions: Vec<ApiPermission>) -> Result<ApiClient> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(anyhow::anyhow!("Client name cannot be empty"));
+        }
+        let client = ApiClient {
+            id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            token: format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple()),
+            permissions,
+        };
+        self.sync_feed.clients.push(client.clone());
+        self.save()?;
+        Ok(client)
+    }
+
+    pub fn set_api_client_permissions(&mut self, id: &str, permissions: Vec<ApiPermission>) -> Result<()> {
+        let client = self
+            .sync_feed
+            .clients
+            .iter_mut()
+            .find(|client| client.id == id)
+            .ok_or_else(|| anyhow::anyhow!("API client not found: {}", id))?;
+        client.permissions = permissions;
+        self.save()
+    }
+
+    pub fn remove_api_client(&mut self, id: &str) -> Result<()> {
+        self.sync_feed.clients.retain(|client| client.id != id);
+        self.save()
+    }
+
+    // O id do dispositivo é fixo depois de criado: trocá-lo duplicaria as linhas no servidor
+    pub fn set_sync(&mut self, sync: SyncConfig) -> Result<()> {
+        let device_id = if self.sync.device_id.is_empty() {
+            Uuid::new_v4().to_string()
+        } else {
+            self.sync.device_id.clone()
+        };
+        self.sync = SyncConfig { device_id, ..sync };
+        if self.sync.device_name.trim().is_empty() {
+            self.sync.device_name = self.sync.device_id.clone();
+        }
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn set_cloud_backup(&mut self, cloud_backup: CloudBackupConfig) -> Result<()> {
+        self.cloud_backup = CloudBackupConfig {
+            last_upload: self.cloud_backup.last_upload,
+            ..cloud_backup
+        };
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn record_cloud_backup(&mut self, at: DateTime<Utc>) -> Result<()> {
+        self.cloud_backup.last_upload = Some(at);
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn record_maintenance_run(&mut self, at: DateTime<Utc>) -> Result<()> {
+        self.maintenance.last_run = Some(at);
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn set_blackout(&mut self, blackout: BlackoutConfig) -> Result<()> {
+        self.blackout = blackout;
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn set_retention(&mut self, retention: RetentionConfig) -> Result<()> {
+        self.retention = retention;
+        self.save()?;
+        Ok(())
+    }
+
+    pub fn set_calendar(&mut self, calendar: CalendarConfig) -> Result<()> {
+        self.calendar = calendar;
+        self.save()?;
+        Ok(())
+    }
+
     pub fn delete_category(&mut self, id: &str) -> Result<()> {
         self.categories.retain(|c| c.id != id);
         self.app_categories.retain(|_, cat_id| cat_id != id);
+        self.category_limits.retain(|l| l.category_id != id);
         self.save()?;
         Ok(())
     }
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calendar_in(time_zone: &str, day_rollover_hour: u32) -> CalendarConfig {
+        CalendarConfig {
+            time_zone: Some(time_zone.to_string()),
+            day_rollover_hour,
+            ..Default::default()
+        }
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn day_length(calendar: &CalendarConfig, day: NaiveDate) -> i64 {
+        let (start, end) = calendar.day_bounds(day);
+        (end - start).num_seconds() + 1
+    }
+
+    #[test]
+    fn spring_forward_day_has_23_hours() {
+        let calendar = calendar_in("America/New_York", 0);
+        assert_eq!(day_length(&calendar, date(2024, 3, 10)), 23 * 3600);
+        assert_eq!(day_length(&calendar, date(2024, 3, 9)), 24 * 3600);
+
+        let berlin = calendar_in("Europe/Berlin", 0);
+        assert_eq!(day_length(&berlin, date(2024, 3, 31)), 23 * 3600);
+    }
+
+    #[test]
+    fn fall_back_day_has_25_hours() {
+        let calendar = calendar_in("America/New_York", 0);
+        assert_eq!(day_length(&calendar, date(2024, 11, 3)), 25 * 3600);
+
+        let berlin = calendar_in("Europe/Berlin", 0);
+        assert_eq!(day_length(&berlin, date(2024, 10, 27)), 25 * 3600);
+    }
+
+    #[test]
+    fn consecutive_days_across_dst_do_not_overlap_or_leave_gaps() {
+        let calendar = calendar_in("America/New_York", 0);
+        for day in date(2024, 3, 8).iter_days().take(5).chain(date(2024, 11, 1).iter_days().take(5)) {
+            let (_, end) = calendar.day_bounds(day);
+            let (next_start, _) = calendar.day_bounds(day + Duration::days(1));
+            assert_eq!(next_start - end, Duration::seconds(1), "{}", day);
+            assert_eq!(calendar.logical_date(next_start), day + Duration::days(1));
+            assert_eq!(calendar.logical_date(end), day);
+        }
+    }
+
+    #[test]
+    fn rollover_inside_the_spring_forward_gap_starts_at_the_first_valid_hour() {
+        // 02:00 não existe em 10/03/2024 em Nova York; o dia começa às 03:00 EDT
+        let calendar = calendar_in("America/New_York", 2);
+        let (start, _) = calendar.day_bounds(date(2024, 3, 10));
+        assert_eq!(start, Utc.with_ymd_and_hms(2024, 3, 10, 7, 0, 0).unwrap());
+        assert_eq!(calendar.logical_date(start), date(2024, 3, 10));
+    }
+
+    #[test]
+    fn fall_back_repeated_hour_counts_for_the_same_day() {
+        let calendar = calendar_in("America/New_York", 0);
+        // 01:30 acontece duas vezes em 03/11/2024 (EDT e depois EST)
+        let first = Utc.with_ymd_and_hms(2024, 11, 3, 5, 30, 0).unwrap();
+        let second = Utc.with_ymd_and_hms(2024, 11, 3, 6, 30, 0).unwrap();
+        assert_eq!(calendar.logical_date(first), date(2024, 11, 3));
+        assert_eq!(calendar.logical_date(second), date(2024, 11, 3));
+    }
+
+    #[test]
+    fn week_bounds_span_dst_changes() {
+        let calendar = calendar_in("America/New_York", 0);
+        let (start, end) = calendar.week_bounds(date(2024, 3, 10));
+        assert_eq!(calendar.logical_date(start), date(2024, 3, 4));
+        assert_eq!((end - start).num_hours(), 7 * 24 - 2);
+    }
+
+    #[test]
+    fn schedule_window_uses_the_calendar_time_zone() {
+        let schedule = ScheduleConfig::default();
+        let calendar = calendar_in("America/New_York", 0);
+
+        // Segunda-feira depois do salto: 09:00-18:00 EDT = 13:00-22:00 UTC
+        let (start, end) = schedule.window(&calendar, date(2024, 3, 11)).unwrap();
+        assert_eq!(start, Utc.with_ymd_and_hms(2024, 3, 11, 13, 0, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2024, 3, 11, 22, 0, 0).unwrap());
+        assert!(schedule.contains(&calendar, Utc.with_ymd_and_hms(2024, 3, 11, 13, 0, 0).unwrap()));
+        assert!(!schedule.contains(&calendar, Utc.with_ymd_and_hms(2024, 3, 11, 12, 59, 0).unwrap()));
+
+        // Segunda-feira antes: 09:00 EST = 14:00 UTC
+        let (start, _) = schedule.window(&calendar, date(2024, 3, 4)).unwrap();
+        assert_eq!(start, Utc.with_ymd_and_hms(2024, 3, 4, 14, 0, 0).unwrap());
+        assert!(schedule.window(&calendar, date(2024, 3, 10)).is_none());
+    }
+}
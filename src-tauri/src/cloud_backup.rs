@@ -0,0 +1,581 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::blocking::Client;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+use tracing::{error, info};
+
+use crate::backup::{self, RestoreInfo};
+use crate::category::{BackupTargetConfig, CloudBackupConfig, SharedConfig};
+use crate::database::DbConnection;
+use crate::secrets;
+use crate::tracker::{SharedTrackerHealth, TrackerHandle};
+
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+// Nomes ordenáveis: a ordem alfabética é a cronológica
+const SNAPSHOT_PREFIX: &str = "chronos-track-";
+const SNAPSHOT_SUFFIX: &str = ".db";
+const ENCRYPTED_SUFFIX: &str = ".db.enc";
+// Arquivo cifrado: MAGIC + nonce de 12 bytes + texto cifrado (AES-256-GCM)
+const MAGIC: &[u8] = b"CTBK1";
+const NONCE_LEN: usize = 12;
+
+// Contas no chaveiro: segredo de cada destino (prefixo + identidade do destino) e
+// chave de criptografia dos snapshots
+const TARGET_SECRET_ACCOUNT: &str = "cloud-backup-target-secret";
+const ENCRYPTION_KEY_ACCOUNT: &str = "cloud-backup-key";
+
+fn snapshot_name(at: DateTime<Utc>, encrypted: bool) -> String {
+    let suffix = if encrypted { ENCRYPTED_SUFFIX } else { SNAPSHOT_SUFFIX };
+    format!("{}{}{}", SNAPSHOT_PREFIX, at.format("%Y%m%dT%H%M%SZ"), suffix)
+}
+
+fn is_snapshot(name: &str) -> bool {
+    name.starts_with(SNAPSHOT_PREFIX) && (name.ends_with(SNAPSHOT_SUFFIX) || name.ends_with(ENCRYPTED_SUFFIX))
+}
+
+// Uma conta por destino: trocar de destino não envia o segredo de um para o outro
+fn secret_account(target: &BackupTargetConfig) -> String {
+    let identity = match target {
+        BackupTargetConfig::S3 { endpoint, bucket, access_key_id, .. } => {
+            format!("s3:{}/{}:{}", endpoint.trim_end_matches('/'), bucket, access_key_id)
+        }
+        BackupTargetConfig::WebDav { url, username, .. } => format!("webdav:{}:{}", url.trim_end_matches('/'), username),
+        BackupTargetConfig::Dropbox { folder, .. } => format!("dropbox:{}", folder),
+    };
+    format!("{}:{}", TARGET_SECRET_ACCOUNT, identity)
+}
+
+// Move o segredo digitado nas configurações para o chaveiro; vazio mantém o atual
+// do mesmo destino
+pub fn store_target_secret(target: &mut BackupTargetConfig) -> Result<()> {
+    let secret = std::mem::take(target.secret_mut());
+    if !secret.is_empty() {
+        secrets::set(&secret_account(target), &secret)?;
+    }
+    Ok(())
+}
+
+// Configurações antigas guardavam o segredo no arquivo, em texto puro, ou numa conta
+// única do chaveiro. Retorna se a configuração mudou e precisa ser salva
+pub fn migrate_target_secret(config: &mut CloudBackupConfig) -> Result<bool> {
+    let Some(target) = config.target.as_mut() else {
+        return Ok(false);
+    };
+    let in_file = !target.secret_mut().is_empty();
+    if in_file {
+        store_target_secret(target)?;
+    } else if let Some(secret) = secrets::get(TARGET_SECRET_ACCOUNT) {
+        let account = secret_account(target);
+        if secrets::get(&account).is_none() {
+            secrets::set(&account, &secret)?;
+        }
+    }
+    let _ = secrets::delete(TARGET_SECRET_ACCOUNT);
+    Ok(in_file)
+}
+
+fn parse_hex(value: &str) -> Result<Vec<u8>> {
+    let value = value.trim();
+    if value.len() % 2 != 0 {
+        return Err(anyhow::anyhow!("Invalid key: odd number of hex digits"));
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&value[i..i + 2], 16).map_err(|e| anyhow::anyhow!("Invalid key: {}", e)))
+        .collect()
+}
+
+// Chave gerada no primeiro backup cifrado. Sem ela (chaveiro perdido, outra
+// máquina) os snapshots não podem ser lidos, por isso pode ser exportada
+pub fn encryption_key() -> Result<String> {
+    if let Some(key) = secrets::get(ENCRYPTION_KEY_ACCOUNT) {
+        return Ok(key);
+    }
+    let key = hex(&Aes256Gcm::generate_key(OsRng));
+    secrets::set(ENCRYPTION_KEY_ACCOUNT, &key)?;
+    Ok(key)
+}
+
+fn cipher(key: &str) -> Result<Aes256Gcm> {
+    let key = parse_hex(key)?;
+    if key.len() != 32 {
+        return Err(anyhow::anyhow!("Invalid key: expected 32 bytes"));
+    }
+    Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+}
+
+fn encrypt(data: &[u8], key: &str) -> Result<Vec<u8>> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher(key)?
+        .encrypt(&nonce, data)
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt backup"))?;
+    Ok([MAGIC, nonce.as_slice(), ciphertext.as_slice()].concat())
+}
+
+fn decrypt(data: &[u8], key: &str) -> Result<Vec<u8>> {
+    if data.len() < MAGIC.len() + NONCE_LEN || !data.starts_with(MAGIC) {
+        return Err(anyhow::anyhow!("Not an encrypted Chronos Track backup"));
+    }
+    let (nonce, ciphertext) = data[MAGIC.len()..].split_at(NONCE_LEN);
+    cipher(key)?
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow::anyhow!("Failed to decrypt backup: wrong key or corrupted file"))
+}
+
+// Criado com permissão só para o dono (0600 no Unix), ao contrário de um caminho fixo
+// na pasta temporária compartilhada
+fn temp_file() -> Result<tempfile::NamedTempFile> {
+    Ok(tempfile::Builder::new().prefix(SNAPSHOT_PREFIX).suffix(SNAPSHOT_SUFFIX).tempfile()?)
+}
+
+// Destino remoto dos snapshots; as chamadas são bloqueantes e rodam fora do runtime
+pub trait RemoteTarget {
+    fn upload(&self, name: &str, data: Vec<u8>) -> Result<()>;
+    fn download(&self, name: &str) -> Result<Vec<u8>>;
+    fn list(&self) -> Result<Vec<String>>;
+    fn delete(&self, name: &str) -> Result<()>;
+}
+
+pub fn target_for(config: &BackupTargetConfig) -> Box<dyn RemoteTarget + Send> {
+    let mut config = config.clone();
+    if config.secret_mut().is_empty() {
+        *config.secret_mut() = secrets::get(&secret_account(&config)).unwrap_or_default();
+    }
+
+    match config {
+        BackupTargetConfig::S3 { endpoint, region, bucket, prefix, access_key_id, secret_access_key } => Box::new(S3Target {
+            client: Client::new(),
+            endpoint,
+            region,
+            bucket,
+            prefix,
+            access_key_id,
+            secret_access_key,
+        }),
+        BackupTargetConfig::WebDav { url, username, password } => Box::new(WebDavTarget {
+            client: Client::new(),
+            url,
+            username,
+            password,
+        }),
+        BackupTargetConfig::Dropbox { access_token, folder } => Box::new(DropboxTarget {
+            client: Client::new(),
+            access_token,
+            folder,
+        }),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// Texto entre <tag> e </tag>, para as poucas respostas XML que precisamos ler
+fn xml_values(xml: &str, tag: &str) -> Vec<String> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    xml.split(&open)
+        .skip(1)
+        .filter_map(|rest| rest.split(&close).next())
+        .map(|value| value.trim().to_string())
+        .collect()
+}
+
+// S3 e compatíveis (B2, MinIO), com URL no estilo path e assinatura SigV4
+struct S3Target {
+    client: Client,
+    endpoint: String,
+    region: String,
+    bucket: String,
+    prefix: String,
+    access_key_id: String,
+    secret_access_key: String,
+}
+
+impl S3Target {
+    fn uri_encode(value: &str, encode_slash: bool) -> String {
+        value
+            .bytes()
+            .map(|b| match b {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+                b'/' if !encode_slash => "/".to_string(),
+                _ => format!("%{:02X}", b),
+            })
+            .collect()
+    }
+
+    fn hmac(key: &[u8], data: &str) -> Result<Vec<u8>> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        mac.update(data.as_bytes());
+        Ok(mac.finalize().into_bytes().to_vec())
+    }
+
+    fn key(&self, name: &str) -> String {
+        let prefix = self.prefix.trim_matches('/');
+        if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{}", prefix, name)
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, key: Option<&str>, query: &str, body: Vec<u8>) -> Result<Vec<u8>> {
+        let base = reqwest::Url::parse(self.endpoint.trim_end_matches('/'))?;
+        let host = match (base.host_str(), base.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err(anyhow::anyhow!("Invalid S3 endpoint: {}", self.endpoint)),
+        };
+        let path = match key {
+            Some(key) => format!("/{}/{}", self.bucket, Self::uri_encode(key, false)),
+            None => format!("/{}", self.bucket),
+        };
+
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex(&Sha256::digest(&body));
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\nhost;x-amz-content-sha256;x-amz-date\n{}",
+            method, path, query, host, payload_hash, amz_date, payload_hash
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let date_key = Self::hmac(format!("AWS4{}", self.secret_access_key).as_bytes(), &date)?;
+        let region_key = Self::hmac(&date_key, &self.region)?;
+        let service_key = Self::hmac(&region_key, "s3")?;
+        let signing_key = Self::hmac(&service_key, "aws4_request")?;
+        let signature = hex(&Self::hmac(&signing_key, &string_to_sign)?);
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={}",
+            self.access_key_id, scope, signature
+        );
+
+        let url = if query.is_empty() {
+            format!("{}://{}{}", base.scheme(), host, path)
+        } else {
+            format!("{}://{}{}?{}", base.scheme(), host, path, query)
+        };
+        let response = self
+            .client
+            .request(method, url)
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("Authorization", authorization)
+            .body(body)
+            .send()?
+            .error_for_status()?;
+        Ok(response.bytes()?.to_vec())
+    }
+}
+
+impl RemoteTarget for S3Target {
+    fn upload(&self, name: &str, data: Vec<u8>) -> Result<()> {
+        self.request(reqwest::Method::PUT, Some(&self.key(name)), "", data)?;
+        Ok(())
+    }
+
+    fn download(&self, name: &str) -> Result<Vec<u8>> {
+        self.request(reqwest::Method::GET, Some(&self.key(name)), "", Vec::new())
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        let query = format!("list-type=2&prefix={}", Self::uri_encode(&self.key(SNAPSHOT_PREFIX), true));
+        let xml = String::from_utf8_lossy(&self.request(reqwest::Method::GET, None, &query, Vec::new())?).to_string();
+        Ok(xml_values(&xml, "Key")
+            .into_iter()
+            .filter_map(|key| key.rsplit('/').next().map(str::to_string))
+            .collect())
+    }
+
+    fn delete(&self, name: &str) -> Result<()> {
+        self.request(reqwest::Method::DELETE, Some(&self.key(name)), "", Vec::new())?;
+        Ok(())
+    }
+}
+
+// Pasta WebDAV (Nextcloud, ownCloud, NAS) com autenticação básica
+struct WebDavTarget {
+    client: Client,
+    url: String,
+    username: String,
+    password: String,
+}
+
+impl WebDavTarget {
+    fn file_url(&self, name: &str) -> String {
+        format!("{}/{}", self.url.trim_end_matches('/'), name)
+    }
+}
+
+impl RemoteTarget for WebDavTarget {
+    fn upload(&self, name: &str, data: Vec<u8>) -> Result<()> {
+        self.client
+            .put(self.file_url(name))
+            .basic_auth(&self.username, Some(&self.password))
+            .body(data)
+            .send()?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    fn download(&self, name: &str) -> Result<Vec<u8>> {
+        let response = self
+            .client
+            .get(self.file_url(name))
+            .basic_auth(&self.username, Some(&self.password))
+            .send()?
+            .error_for_status()?;
+        Ok(response.bytes()?.to_vec())
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        let method = reqwest::Method::from_bytes(b"PROPFIND")?;
+        let xml = self
+            .client
+            .request(method, format!("{}/", self.url.trim_end_matches('/')))
+            .basic_auth(&self.username, Some(&self.password))
+            .header("Depth", "1")
+            .send()?
+            .error_for_status()?
+            .text()?;
+
+        // O prefixo do namespace varia entre servidores (d:, D:, sem prefixo)
+        Ok(xml
+            .split("href>")
+            .filter_map(|rest| rest.split('<').next())
+            .filter_map(|href| href.trim_end_matches('/').rsplit('/').next().map(str::to_string))
+            .filter(|name| is_snapshot(name))
+            .collect())
+    }
+
+    fn delete(&self, name: &str) -> Result<()> {
+        self.client
+            .delete(self.file_url(name))
+            .basic_auth(&self.username, Some(&self.password))
+            .send()?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+// Dropbox pela API HTTP; o upload simples aceita arquivos de até 150 MB
+struct DropboxTarget {
+    client: Client,
+    access_token: String,
+    folder: String,
+}
+
+impl DropboxTarget {
+    fn folder(&self) -> String {
+        format!("/{}", self.folder.trim_matches('/'))
+    }
+
+    fn path(&self, name: &str) -> String {
+        format!("{}/{}", self.folder().trim_end_matches('/'), name)
+    }
+}
+
+impl RemoteTarget for DropboxTarget {
+    fn upload(&self, name: &str, data: Vec<u8>) -> Result<()> {
+        let arg = serde_json::json!({ "path": self.path(name), "mode": "overwrite" });
+        self.client
+            .post("https://content.dropboxapi.com/2/files/upload")
+            .bearer_auth(&self.access_token)
+            .header("Dropbox-API-Arg", arg.to_string())
+            .header("Content-Type", "application/octet-stream")
+            .body(data)
+            .send()?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    fn download(&self, name: &str) -> Result<Vec<u8>> {
+        let arg = serde_json::json!({ "path": self.path(name) });
+        let response = self
+            .client
+            .post("https://content.dropboxapi.com/2/files/download")
+            .bearer_auth(&self.access_token)
+            .header("Dropbox-API-Arg", arg.to_string())
+            .send()?
+            .error_for_status()?;
+        Ok(response.bytes()?.to_vec())
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        let folder = self.folder();
+        let mut response: serde_json::Value = self
+            .client
+            .post("https://api.dropboxapi.com/2/files/list_folder")
+            .bearer_auth(&self.access_token)
+            .json(&serde_json::json!({ "path": if folder == "/" { "" } else { folder.as_str() } }))
+            .send()?
+            .error_for_status()?
+            .json()?;
+
+        loop {
+            if let Some(entries) = response["entries"].as_array() {
+                names.extend(entries.iter().filter_map(|e| e["name"].as_str().map(str::to_string)));
+            }
+            let cursor = match (response["has_more"].as_bool(), response["cursor"].as_str()) {
+                (Some(true), Some(cursor)) => cursor.to_string(),
+                _ => break,
+            };
+            response = self
+                .client
+                .post("https://api.dropboxapi.com/2/files/list_folder/continue")
+                .bearer_auth(&self.access_token)
+                .json(&serde_json::json!({ "cursor": cursor }))
+                .send()?
+                .error_for_status()?
+                .json()?;
+        }
+
+        Ok(names)
+    }
+
+    fn delete(&self, name: &str) -> Result<()> {
+        self.client
+            .post("https://api.dropboxapi.com/2/files/delete_v2")
+            .bearer_auth(&self.access_token)
+            .json(&serde_json::json!({ "path": self.path(name) }))
+            .send()?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CloudBackupResult {
+    pub name: String,
+    pub size_bytes: u64,
+    pub deleted: Vec<String>,
+}
+
+// Snapshot consistente em um arquivo temporário, envio e remoção dos mais antigos
+// além de keep_last
+pub async fn run(db: &DbConnection, config: &CloudBackupConfig) -> Result<CloudBackupResult> {
+    let target_config = config.target.clone().context("No cloud backup target configured")?;
+    let name = snapshot_name(Utc::now(), config.encrypt);
+    // Arquivo só do usuário (0600), apagado ao sair do escopo
+    let temp = temp_file()?;
+
+    let info = backup::backup(db, temp.path()).await?;
+    let data = std::fs::read(temp.path())?;
+    drop(temp);
+    let data = if config.encrypt { encrypt(&data, &encryption_key()?)? } else { data };
+
+    let keep_last = config.keep_last.max(1) as usize;
+    let uploaded = name.clone();
+    let deleted = tokio::task::spawn_blocking(move || -> Result<Vec<String>> {
+        let target = target_for(&target_config);
+        target.upload(&uploaded, data)?;
+
+        let mut snapshots: Vec<String> = target.list()?.into_iter().filter(|n| is_snapshot(n)).collect();
+        snapshots.sort();
+        snapshots.dedup();
+        let excess = snapshots.len().saturating_sub(keep_last);
+        let mut deleted = Vec::new();
+        for old in snapshots.into_iter().take(excess) {
+            target.delete(&old)?;
+            deleted.push(old);
+        }
+        Ok(deleted)
+    })
+    .await??;
+
+    info!("Uploaded cloud backup {} ({} bytes), removed {} old snapshots", name, info.size_bytes, deleted.len());
+    Ok(CloudBackupResult { name, size_bytes: info.size_bytes, deleted })
+}
+
+pub async fn list(config: &CloudBackupConfig) -> Result<Vec<String>> {
+    let target_config = config.target.clone().context("No cloud backup target configured")?;
+    let mut snapshots = tokio::task::spawn_blocking(move || target_for(&target_config).list()).await??;
+    snapshots.retain(|name| is_snapshot(name));
+    snapshots.sort();
+    snapshots.reverse();
+    Ok(snapshots)
+}
+
+pub async fn run_cloud_backup_job(app: AppHandle) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let config = match app.state::<SharedConfig>().lock() {
+            Ok(config) => config.cloud_backup.clone(),
+            Err(e) => {
+                error!("Failed to lock config: {}", e);
+                continue;
+            }
+        };
+        if !config.is_due(Utc::now()) {
+            continue;
+        }
+
+        let db = app.state::<DbConnection>().inner().clone();
+        match run(&db, &config).await {
+            Ok(result) => {
+                if let Ok(mut config) = app.state::<SharedConfig>().lock() {
+                    if let Err(e) = config.record_cloud_backup(Utc::now()) {
+                        error!("Failed to record cloud backup: {}", e);
+                    }
+                }
+                info!("Scheduled cloud backup uploaded: {}", result.name);
+            }
+            Err(e) => error!("Scheduled cloud backup failed: {}", e),
+        }
+    }
+}
+
+// Baixa o snapshot, decifra se preciso e substitui o banco atual (com a cópia
+// pre-restore de backup::restore). key serve para snapshots de outra máquina
+pub async fn restore(
+    db: &DbConnection,
+    health: &SharedTrackerHealth,
+    tracker: &TrackerHandle,
+    config: &CloudBackupConfig,
+    name: &str,
+    key: Option<&str>,
+) -> Result<RestoreInfo> {
+    if !is_snapshot(name) || name.contains(['/', '\\']) {
+        return Err(anyhow::anyhow!("Unknown cloud backup: {}", name));
+    }
+    let target_config = config.target.clone().context("No cloud backup target configured")?;
+    let remote_name = name.to_string();
+    let data = tokio::task::spawn_blocking(move || target_for(&target_config).download(&remote_name)).await??;
+
+    let data = if name.ends_with(ENCRYPTED_SUFFIX) {
+        let key = match key {
+            Some(key) => key.to_string(),
+            None => secrets::get(ENCRYPTION_KEY_ACCOUNT).context("No backup encryption key in the keychain")?,
+        };
+        decrypt(&data, &key)?
+    } else {
+        data
+    };
+
+    let temp = temp_file()?;
+    std::fs::write(temp.path(), data)?;
+    let result = backup::restore(db, health, tracker, temp.path()).await;
+    drop(temp);
+
+    if result.is_ok() {
+        info!("Restored cloud backup {}", name);
+    }
+    result
+}
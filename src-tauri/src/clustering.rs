@@ -0,0 +1,165 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::category::CategoryConfig;
+use crate::export::domain_of;
+use crate::suggestions::tokens;
+use crate::tracker::WindowActivity;
+
+// Similaridade (Jaccard) mínima entre os termos de um título e os do cluster
+const MIN_SIMILARITY: f64 = 0.4;
+const MAX_EXAMPLES: usize = 5;
+
+// Rótulos sugeridos a partir de palavras-chave nos termos do cluster
+const LABELS: &[(&str, &[&str])] = &[
+    ("Documentation reading", &["docs", "documentation", "reference", "readme", "guide", "manual", "wiki"]),
+    ("CI dashboards", &["pipeline", "pipelines", "build", "builds", "actions", "workflow", "jenkins", "circleci"]),
+    ("Code review", &["pull", "request", "review", "merge", "diff", "commit"]),
+    ("Issue tracking", &["jira", "issue", "issues", "ticket", "board", "sprint", "linear"]),
+    ("Email", &["inbox", "mail", "gmail", "outlook"]),
+    ("Meetings", &["meet", "zoom", "call", "meeting", "teams", "huddle"]),
+    ("Video", &["youtube", "netflix", "twitch", "video"]),
+    ("Social media", &["twitter", "reddit", "facebook", "instagram", "linkedin"]),
+];
+
+#[derive(Debug, Serialize)]
+pub struct ActivityCluster {
+    pub label: String,
+    pub top_terms: Vec<String>,
+    pub domains: Vec<String>,
+    pub applications: Vec<String>,
+    pub total_duration: i64,
+    pub activity_count: usize,
+    pub examples: Vec<String>,
+}
+
+struct Bucket {
+    terms: HashMap<String, i64>,
+    signature: HashSet<String>,
+    domains: HashMap<String, i64>,
+    applications: HashMap<String, i64>,
+    titles: Vec<(String, i64)>,
+    total_duration: i64,
+    activity_count: usize,
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+fn top_keys(counts: &HashMap<String, i64>, limit: usize) -> Vec<String> {
+    let mut entries: Vec<(&String, &i64)> = counts.iter().collect();
+    entries.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    entries.into_iter().take(limit).map(|(key, _)| key.clone()).collect()
+}
+
+fn label_for(terms: &[String], domains: &[String]) -> String {
+    let haystack: HashSet<&str> = terms
+        .iter()
+        .map(|t| t.as_str())
+        .chain(domains.iter().flat_map(|d| d.split('.')))
+        .collect();
+
+    LABELS
+        .iter()
+        .find(|(_, keywords)| keywords.iter().any(|k| haystack.contains(k)))
+        .map(|(label, _)| format!("Looks like {}", label.to_lowercase()))
+        .unwrap_or_else(|| format!("Similar: {}", terms.iter().take(3).cloned().collect::<Vec<_>>().join(", ")))
+}
+
+// Agrupa o tempo sem categoria em blocos de títulos/URLs parecidos
+pub fn cluster_uncategorized(activities: &[WindowActivity], config: &CategoryConfig) -> Vec<ActivityCluster> {
+    // Soma primeiro por título idêntico para reduzir o trabalho do agrupamento
+    let mut by_title: HashMap<(String, String, Option<String>), (i64, usize)> = HashMap::new();
+    for activity in activities
+        .iter()
+        .filter(|a| !a.is_idle && config.get_category_for_app(&a.application).is_none())
+    {
+        let domain = activity.url.as_deref().and_then(domain_of);
+        let entry = by_title
+            .entry((activity.application.clone(), activity.title.clone(), domain))
+            .or_default();
+        entry.0 += (activity.end_time - activity.start_time).num_seconds();
+        entry.1 += 1;
+    }
+
+    // Títulos mais longos primeiro, para que os clusters nasçam dos casos mais relevantes
+    let mut entries: Vec<_> = by_title.into_iter().collect();
+    entries.sort_by(|a, b| b.1 .0.cmp(&a.1 .0));
+
+    let mut buckets: Vec<Bucket> = Vec::new();
+    for ((application, title, domain), (duration, count)) in entries {
+        let mut signature = tokens(&title);
+        if let Some(domain) = &domain {
+            signature.insert(domain.clone());
+        }
+        if signature.is_empty() {
+            continue;
+        }
+
+        let best = buckets
+            .iter()
+            .enumerate()
+            .map(|(index, bucket)| (jaccard(&signature, &bucket.signature), index))
+            .filter(|(similarity, _)| *similarity >= MIN_SIMILARITY)
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, index)| index);
+
+        let index = match best {
+            Some(index) => index,
+            None => {
+                buckets.push(Bucket {
+                    terms: HashMap::new(),
+                    signature: signature.clone(),
+                    domains: HashMap::new(),
+                    applications: HashMap::new(),
+                    titles: Vec::new(),
+                    total_duration: 0,
+                    activity_count: 0,
+                });
+                buckets.len() - 1
+            }
+        };
+        let bucket = &mut buckets[index];
+
+        for term in &signature {
+            *bucket.terms.entry(term.clone()).or_default() += duration;
+        }
+        if let Some(domain) = domain {
+            *bucket.domains.entry(domain).or_default() += duration;
+        }
+        *bucket.applications.entry(application).or_default() += duration;
+        bucket.titles.push((title, duration));
+        bucket.total_duration += duration;
+        bucket.activity_count += count;
+    }
+
+    let mut clusters: Vec<ActivityCluster> = buckets
+        .into_iter()
+        .map(|bucket| {
+            let top_terms = top_keys(&bucket.terms, 5);
+            let domains = top_keys(&bucket.domains, 5);
+            ActivityCluster {
+                label: label_for(&top_terms, &domains),
+                top_terms,
+                domains,
+                applications: top_keys(&bucket.applications, 5),
+                total_duration: bucket.total_duration,
+                activity_count: bucket.activity_count,
+                examples: bucket
+                    .titles
+                    .into_iter()
+                    .take(MAX_EXAMPLES)
+                    .map(|(title, _)| title)
+                    .collect(),
+            }
+        })
+        .collect();
+
+    clusters.sort_by(|a, b| b.total_duration.cmp(&a.total_duration));
+    clusters
+}
@@ -1,13 +1,36 @@
-use chrono::{DateTime, Utc, Duration, Datelike};
+use chrono::{DateTime, Utc, Duration, Datelike, NaiveDate, Timelike};
 use serde::{Deserialize, Serialize};
 use tauri::State;
 use std::sync::Mutex;
-use std::collections::HashSet;
-use tracing::{info, error};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use tracing::{info, error, instrument};
 
-use crate::database::{self, DbConnection};
-use crate::tracker::WindowActivity;
-use crate::category::{Category, CategoryConfig};
+use crate::database::{self, Adjustment, DbConnection, FocusSummary, HighFrequencySample, HighFrequencySession};
+use crate::high_frequency::{self, ActiveSession, SharedHighFrequency};
+use crate::tracker::{SharedTrackerHealth, TrackerErrorRecord, TrackerHandle, WindowActivity};
+use crate::category::{
+    AllocationConfig, ApiClient, ApiPermission, BlackoutConfig, BreakReminderConfig, BrowserProfileConfig,
+    CalendarConfig, Category, CategoryConfig, CategoryLimit, CloudBackupConfig, GitBranchConfig, IdleConfig,
+    IdlePromptConfig, JournalConfig, MaintenanceConfig, MediaPlaybackConfig, NetworkTaggingConfig,
+    NotificationConfig, PomodoroConfig, ProjectPattern, RetentionConfig, ScheduleConfig, ScoreWeights,
+    ScreenshotConfig, SharedConfig, SyncConfig, SyncFeedConfig, TimesheetConfig, TrayConfig, WeekStart,
+    WindowSnapshotConfig,
+};
+use crate::backup::{self, BackupInfo, RestoreInfo};
+use crate::breaks::{BreakStatus, SharedBreakReminder};
+use crate::coverage::{self, DayCoverage};
+use crate::export::{ExportExclusions, ReviewItem};
+use crate::focus::{self, FocusOptions, FocusSession};
+use crate::pomodoro::{self, PomodoroStats, PomodoroTimer};
+use crate::notifications::{QueuedNotification, SharedNotificationQueue};
+use crate::review::{ReviewResolution, ReviewResult};
+use crate::rollups;
+use crate::retention;
+use crate::locale::{self, CalendarSuggestion};
+use crate::permissions::{self, Permission, PermissionInfo, PermissionStatus};
+use crate::importer::{self, ImportPreview};
+use crate::project::activity_project;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TimeRange {
@@ -20,9 +43,272 @@ pub struct DailyStats {
     pub total_time: i64,
     pub productive_time: i64,
     pub goal_percentage: i64,
+    // Só em estatísticas diárias com a proporção ligada; goal_percentage continua bruto
+    pub prorated_goal_percentage: Option<i64>,
     pub idle_time: i64,
+    // Tempo ativo em categorias neutras (System), nem produtivo nem distração
+    pub neutral_time: i64,
     pub top_applications: Vec<ApplicationStats>,
     pub activities: Vec<WindowActivity>,
+    pub weekly_goal: Option<WeeklyGoalProgress>,
+    pub groups: Option<Vec<GroupStats>>,
+    // Ajustes manuais já somados aos totais, listados à parte para auditoria
+    pub adjustments: Vec<Adjustment>,
+    pub adjustment_time: i64,
+    // Apps além do top-N somados numa entrada sintética
+    pub other_applications: Option<OtherApplications>,
+    pub idle_breakdown: IdleBreakdown,
+    // Metas de limite configuradas (ex.: <= 30 min/dia de Entertainment)
+    pub category_limits: Vec<CategoryLimitProgress>,
+}
+
+// Consumo de um limite no período; o orçamento diário é multiplicado pelos dias
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryLimitProgress {
+    pub category_id: String,
+    pub category_name: String,
+    pub limit_minutes: i64,
+    pub used_seconds: i64,
+    pub remaining_seconds: i64,
+    pub exceeded: bool,
+}
+
+// Dias no intervalo, arredondado para absorver dias de 23h/25h no horário de verão
+fn range_days(start: DateTime<Utc>, end: DateTime<Utc>) -> i64 {
+    (((end - start).num_hours() + 12) / 24).max(1)
+}
+
+// Tempo ativo por categoria, com sobrescritas e ajustes manuais, comparado aos limites
+pub fn category_limit_progress(
+    config: &CategoryConfig,
+    applications: &[ApplicationStats],
+    overrides: &[database::OverrideTotals],
+    adjustments: &[Adjustment],
+    days: i64,
+) -> Vec<CategoryLimitProgress> {
+    if config.category_limits.is_empty() {
+        return Vec::new();
+    }
+
+    let mut used: HashMap<String, i64> = HashMap::new();
+    for app in applications {
+        if let Some(category) = &app.category {
+            *used.entry(category.id.clone()).or_default() += app.total_duration - app.idle_duration;
+        }
+    }
+    for moved in overrides {
+        if let Some(from) = config.get_category_for_app(&moved.application) {
+            *used.entry(from.id.clone()).or_default() -= moved.active_seconds;
+        }
+        *used.entry(moved.category_id.clone()).or_default() += moved.active_seconds;
+    }
+    for adjustment in adjustments {
+        *used.entry(adjustment.category_id.clone()).or_default() += adjustment.minutes * 60;
+    }
+
+    config
+        .category_limits
+        .iter()
+        .filter_map(|limit| {
+            let category = config.categories.iter().find(|c| c.id == limit.category_id)?;
+            let used_seconds = used.get(&limit.category_id).copied().unwrap_or(0).max(0);
+            let budget = limit.max_minutes * 60 * days;
+            Some(CategoryLimitProgress {
+                category_id: category.id.clone(),
+                category_name: category.name.clone(),
+                limit_minutes: limit.max_minutes * days,
+                used_seconds,
+                remaining_seconds: (budget - used_seconds).max(0),
+                exceeded: used_seconds > budget,
+            })
+        })
+        .collect()
+}
+
+// Limites de um dia direto dos totais no SQLite, sem montar as estatísticas inteiras
+pub async fn category_limits_for_day(
+    db: &DbConnection,
+    config: &CategoryConfig,
+    day: NaiveDate,
+) -> Result<Vec<CategoryLimitProgress>, String> {
+    if config.category_limits.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let (start, end) = config.calendar.day_bounds(day);
+    let totals = database::get_app_totals_between(db, start, end, false)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut overrides = database::get_override_totals(db, start, end, false)
+        .await
+        .map_err(|e| e.to_string())?;
+    let profiles = database::get_profile_totals(db, start, end, false)
+        .await
+        .map_err(|e| e.to_string())?;
+    overrides.extend(profile_overrides(config, &profiles));
+    let adjustments = database::get_adjustments_between(db, day, day)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let applications: Vec<ApplicationStats> = totals
+        .into_iter()
+        .map(|totals| ApplicationStats {
+            category: config.get_category_for_app(&totals.application).cloned(),
+            application: totals.application,
+            total_duration: totals.total_duration,
+            idle_duration: totals.idle_duration,
+            activities: Vec::new(),
+        })
+        .collect();
+    Ok(category_limit_progress(config, &applications, &overrides, &adjustments, 1))
+}
+
+// Pausas curtas (< 5 min), ausências médias como reuniões (5–30 min) e longas
+// (fim do expediente, almoço); trechos ociosos contíguos contam como uma pausa só
+const SHORT_IDLE_SECONDS: i64 = 5 * 60;
+const LONG_IDLE_SECONDS: i64 = 30 * 60;
+const IDLE_SPAN_GAP_SECONDS: i64 = 60;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IdleBucket {
+    pub count: usize,
+    pub total_seconds: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IdleBreakdown {
+    pub short: IdleBucket,
+    pub medium: IdleBucket,
+    pub long: IdleBucket,
+}
+
+fn neutral_time(applications: &[ApplicationStats]) -> i64 {
+    applications
+        .iter()
+        .filter(|app| app.category.as_ref().map_or(false, |c| c.neutral))
+        .map(|app| app.total_duration - app.idle_duration)
+        .sum()
+}
+
+// Overrides (e regras de perfil do navegador) a partir das atividades já carregadas,
+// no formato dos totais do banco
+fn override_totals(config: &CategoryConfig, activities: &[WindowActivity]) -> Vec<database::OverrideTotals> {
+    let mut totals: HashMap<(&str, &str), i64> = HashMap::new();
+    for activity in activities.iter().filter(|a| !a.is_idle) {
+        let category_id = match activity.category_override.as_deref() {
+            Some(category_id) => Some(category_id),
+            None => {
+                profile_category(config, &activity.application, activity.profile.as_deref(), activity.url.as_deref())
+            }
+        };
+        if let Some(category_id) = category_id {
+            *totals.entry((activity.application.as_str(), category_id)).or_default() +=
+                (activity.end_time - activity.start_time).num_seconds();
+        }
+    }
+    totals
+        .into_iter()
+        .map(|((application, category_id), active_seconds)| database::OverrideTotals {
+            application: application.to_string(),
+            category_id: category_id.to_string(),
+            active_seconds,
+        })
+        .collect()
+}
+
+// Categoria da regra do perfil quando ela difere da do app (category_for_activity)
+fn profile_category<'a>(
+    config: &'a CategoryConfig,
+    application: &str,
+    profile: Option<&str>,
+    url: Option<&str>,
+) -> Option<&'a str> {
+    let domain = url.and_then(crate::export::domain_of);
+    let category = config.category_for_profile(profile?, domain.as_deref())?;
+    if config.get_category_for_app(application).map_or(false, |c| c.id == category.id) {
+        return None;
+    }
+    Some(category.id.as_str())
+}
+
+// Tempo em perfis com regra própria sai da categoria do app como um override; junte
+// aos totais de get_override_totals antes de calcular categorias e tempo produtivo
+pub fn profile_overrides(
+    config: &CategoryConfig,
+    profiles: &[database::ProfileTotals],
+) -> Vec<database::OverrideTotals> {
+    let mut totals: HashMap<(&str, &str), i64> = HashMap::new();
+    for profile in profiles {
+        let category_id =
+            profile_category(config, &profile.application, Some(&profile.profile), profile.url.as_deref());
+        if let Some(category_id) = category_id {
+            *totals.entry((profile.application.as_str(), category_id)).or_default() += profile.active_seconds;
+        }
+    }
+    totals
+        .into_iter()
+        .map(|((application, category_id), active_seconds)| database::OverrideTotals {
+            application: application.to_string(),
+            category_id: category_id.to_string(),
+            active_seconds,
+        })
+        .collect()
+}
+
+// Tempo recategorizado sai da categoria do app e passa a contar na do override
+pub fn override_productive_delta(config: &CategoryConfig, overrides: &[database::OverrideTotals]) -> i64 {
+    overrides
+        .iter()
+        .map(|o| {
+            let from = config.get_category_for_app(&o.application).map_or(false, |c| c.is_productive);
+            let to = config.categories.iter().find(|c| c.id == o.category_id).map_or(false, |c| c.is_productive);
+            (to as i64 - from as i64) * o.active_seconds
+        })
+        .sum()
+}
+
+fn idle_breakdown(mut intervals: Vec<(DateTime<Utc>, DateTime<Utc>)>) -> IdleBreakdown {
+    intervals.sort_by_key(|(start, _)| *start);
+
+    let mut spans: Vec<(DateTime<Utc>, DateTime<Utc>)> = Vec::new();
+    for (start, end) in intervals {
+        match spans.last_mut() {
+            Some((_, span_end)) if (start - *span_end).num_seconds() <= IDLE_SPAN_GAP_SECONDS => {
+                *span_end = (*span_end).max(end);
+            }
+            _ => spans.push((start, end)),
+        }
+    }
+
+    let mut breakdown = IdleBreakdown::default();
+    for (start, end) in spans {
+        let seconds = (end - start).num_seconds();
+        let bucket = if seconds < SHORT_IDLE_SECONDS {
+            &mut breakdown.short
+        } else if seconds <= LONG_IDLE_SECONDS {
+            &mut breakdown.medium
+        } else {
+            &mut breakdown.long
+        };
+        bucket.count += 1;
+        bucket.total_seconds += seconds;
+    }
+    breakdown
+}
+
+#[derive(Debug, Serialize)]
+pub struct GroupStats {
+    pub key: Option<String>,
+    pub total_duration: i64,
+    pub idle_duration: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WeeklyGoalProgress {
+    pub goal_minutes: i64,
+    pub productive_minutes: i64,
+    pub percentage: i64,
+    pub streak: i64,
 }
 
 #[derive(Debug, Serialize)]
@@ -34,35 +320,298 @@ pub struct ApplicationStats {
     category: Option<Category>,
 }
 
+// Quantos apps as estatísticas listam antes de agrupar o resto em "Other"
+const TOP_APPLICATIONS: usize = 5;
+
+// top_n das estatísticas: ausente usa o padrão, 0 lista todos os apps
+fn top_applications_limit(top_n: Option<usize>) -> usize {
+    match top_n {
+        None => TOP_APPLICATIONS,
+        Some(0) => usize::MAX,
+        Some(n) => n,
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct OtherApplications {
+    pub application_count: usize,
+    pub total_duration: i64,
+    pub idle_duration: i64,
+    // Token opaco para get_app_stats_page
+    pub cursor: String,
+}
+
+// Conteúdo do cursor: intervalo, fonte dos totais e posição na lista ordenada
+#[derive(Debug, Serialize, Deserialize)]
+struct AppStatsCursor {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    from_rollups: bool,
+    #[serde(default)]
+    include_off_hours: bool,
+    offset: usize,
+}
+
+impl AppStatsCursor {
+    fn encode(&self) -> String {
+        serde_json::to_vec(self)
+            .unwrap_or_default()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    fn decode(token: &str) -> Result<Self, String> {
+        let invalid = || "Invalid stats cursor".to_string();
+        if token.len() % 2 != 0 {
+            return Err(invalid());
+        }
+        let bytes = (0..token.len())
+            .step_by(2)
+            .map(|i| token.get(i..i + 2).and_then(|hex| u8::from_str_radix(hex, 16).ok()))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(invalid)?;
+        serde_json::from_slice(&bytes).map_err(|_| invalid())
+    }
+}
+
+// Mantém os primeiros apps e resume o restante; empates pelo nome, na mesma
+// ordem usada por get_app_stats_page
+fn split_top_applications(
+    mut applications: Vec<ApplicationStats>,
+    limit: usize,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    from_rollups: bool,
+    include_off_hours: bool,
+) -> (Vec<ApplicationStats>, Option<OtherApplications>) {
+    applications.sort_by(|a, b| b.total_duration.cmp(&a.total_duration).then_with(|| a.application.cmp(&b.application)));
+    if applications.len() <= limit {
+        return (applications, None);
+    }
+
+    let rest = applications.split_off(limit);
+    let other = OtherApplications {
+        application_count: rest.len(),
+        total_duration: rest.iter().map(|app| app.total_duration).sum(),
+        idle_duration: rest.iter().map(|app| app.idle_duration).sum(),
+        cursor: AppStatsCursor { start, end, from_rollups, include_off_hours, offset: limit }.encode(),
+    };
+    (applications, Some(other))
+}
+
+#[derive(Debug, Serialize)]
+pub struct AppStatsPage {
+    pub applications: Vec<ApplicationStats>,
+    pub next_cursor: Option<String>,
+}
+
+// Cauda longa do "Other", sob demanda; só totais, sem a lista de atividades
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_app_stats_page(
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+    cursor: String,
+    limit: Option<usize>,
+) -> Result<AppStatsPage, String> {
+    let cursor = AppStatsCursor::decode(&cursor)?;
+    let limit = limit.unwrap_or(20).clamp(1, 500);
+
+    let mut totals = if cursor.from_rollups {
+        database::get_app_totals_from_rollups(&db, cursor.start, cursor.end).await
+    } else {
+        database::get_app_totals_between(&db, cursor.start, cursor.end, cursor.include_off_hours).await
+    }
+    .map_err(|e| e.to_string())?;
+    totals.sort_by(|a, b| b.total_duration.cmp(&a.total_duration).then_with(|| a.application.cmp(&b.application)));
+
+    let config = config.lock().map_err(|e| e.to_string())?;
+    let mut applications: Vec<ApplicationStats> = totals
+        .into_iter()
+        .skip(cursor.offset)
+        .take(limit + 1)
+        .map(|totals| ApplicationStats {
+            category: config.get_category_for_app(&totals.application).cloned(),
+            application: totals.application,
+            total_duration: totals.total_duration,
+            idle_duration: totals.idle_duration,
+            activities: Vec::new(),
+        })
+        .collect();
+
+    let next_cursor = (applications.len() > limit).then(|| {
+        applications.truncate(limit);
+        AppStatsCursor { offset: cursor.offset + limit, ..cursor }.encode()
+    });
+
+    Ok(AppStatsPage { applications, next_cursor })
+}
+
+const DEFAULT_PAGE_SIZE: i64 = 500;
+const MAX_PAGE_SIZE: i64 = 5000;
+
+#[derive(Debug, Serialize)]
+pub struct ActivityPage {
+    pub activities: Vec<WindowActivity>,
+    pub total_count: i64,
+    pub limit: i64,
+    pub offset: i64,
+    pub has_more: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ActivityFilters {
+    pub applications: Option<Vec<String>>,
+    pub category: Option<String>,
+    pub is_idle: Option<bool>,
+    pub is_browser: Option<bool>,
+    pub title: Option<String>,
+}
+
 #[tauri::command]
+#[instrument(skip_all)]
 pub async fn get_activities(
     range: TimeRange,
     db: State<'_, DbConnection>,
-) -> Result<Vec<WindowActivity>, String> {
-    database::get_activities_between(&db, range.start, range.end)
-        .await
-        .map_err(|e| e.to_string())
+    config: State<'_, SharedConfig>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+    filters: Option<ActivityFilters>,
+) -> Result<ActivityPage, String> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let offset = offset.unwrap_or(0).max(0);
+    let filters = filters.unwrap_or_default();
+
+    let filter = database::ActivityFilter {
+        applications: filters.applications,
+        is_idle: filters.is_idle,
+        is_browser: filters.is_browser,
+        title_contains: filters.title,
+    };
+
+    let (activities, total_count) = match &filters.category {
+        None => database::get_activities_page(&db, range.start, range.end, &filter, limit, offset)
+            .await
+            .map_err(|e| e.to_string())?,
+        // A categoria de cada atividade considera o override e as regras de perfil, não só o
+        // app (category_for_activity), então o filtro e a paginação são feitos aqui
+        Some(category) => {
+            let (activities, _) = database::get_activities_page(&db, range.start, range.end, &filter, i64::MAX, 0)
+                .await
+                .map_err(|e| e.to_string())?;
+            let config = config.lock().map_err(|e| e.to_string())?;
+            let matching: Vec<WindowActivity> = activities
+                .into_iter()
+                .filter(|activity| config.category_for_activity(activity).map_or(false, |c| &c.id == category))
+                .collect();
+            let total_count = matching.len() as i64;
+            (matching.into_iter().skip(offset as usize).take(limit as usize).collect(), total_count)
+        }
+    };
+
+    Ok(ActivityPage {
+        has_more: offset + (activities.len() as i64) < total_count,
+        activities,
+        total_count,
+        limit,
+        offset,
+    })
 }
 
 #[tauri::command]
+#[instrument(skip_all)]
 pub async fn get_daily_stats(
     date: String,
     db: State<'_, DbConnection>,
-    config: State<'_, Mutex<CategoryConfig>>,
+    config: State<'_, SharedConfig>,
+    group_by: Option<String>,
+    include_activities: Option<bool>,
+    top_n: Option<usize>,
+    include_off_hours: Option<bool>,
 ) -> Result<DailyStats, String> {
     let date = DateTime::parse_from_rfc3339(&date)
         .map_err(|e| e.to_string())?
         .with_timezone(&Utc);
     
-    let start = date.date_naive().and_hms_opt(0, 0, 0).unwrap();
-    let end = date.date_naive().and_hms_opt(23, 59, 59).unwrap();
-    
-    let activities = database::get_activities_between(&db, start.and_utc(), end.and_utc())
+    let calendar = config.lock().map_err(|e| e.to_string())?.calendar.clone();
+    let day = calendar.local_date(date);
+    let (start, end) = calendar.day_bounds(day);
+    let first_activity = database::get_first_activity_between(&db, start, end)
         .await
         .map_err(|e| e.to_string())?;
 
-    let config = config.lock().map_err(|e| e.to_string())?;
+    // A lista do dia é exibida no frontend, então por padrão as atividades vêm junto
+    let options = StatsOptions {
+        include_activities: include_activities.unwrap_or(true) || group_by.is_some(),
+        include_off_hours: include_off_hours.unwrap_or(false),
+        top_n: top_applications_limit(top_n),
+    };
+    let shared_config = config.inner().clone();
+    let mut stats = get_stats_for_range(&db, config, start, end, options).await?;
+    stats.prorated_goal_percentage = prorated_goal_percentage(
+        &shared_config.lock().map_err(|e| e.to_string())?,
+        day,
+        first_activity,
+        stats.productive_time,
+    );
+    stats.groups = group_by
+        .map(|group_by| group_activities(&stats.activities, &group_by))
+        .transpose()?;
+    Ok(stats)
+}
+
+// Soma os ajustes manuais aos totais (positivos ou negativos); ajustes em categorias
+// produtivas também contam para a meta
+fn apply_adjustments(stats: &mut DailyStats, adjustments: Vec<Adjustment>, config: &CategoryConfig) {
+    for adjustment in &adjustments {
+        let seconds = adjustment.minutes * 60;
+        stats.adjustment_time += seconds;
+        stats.total_time += seconds;
+
+        let is_productive = config
+            .categories
+            .iter()
+            .find(|c| c.id == adjustment.category_id)
+            .map_or(false, |c| c.is_productive);
+        if is_productive {
+            stats.productive_time += seconds;
+        }
+    }
+
+    stats.total_time = stats.total_time.max(0);
+    stats.productive_time = stats.productive_time.max(0);
+    stats.goal_percentage = if config.daily_goal_minutes > 0 {
+        ((stats.productive_time / 60) as f64 / config.daily_goal_minutes as f64 * 100.0).round() as i64
+    } else {
+        0
+    };
+    stats.adjustments = adjustments;
+}
+
+// Percentual sobre a meta reduzida às horas de expediente disponíveis no dia
+pub fn prorated_goal_percentage(
+    config: &CategoryConfig,
+    date: NaiveDate,
+    first_activity: Option<DateTime<Utc>>,
+    productive_seconds: i64,
+) -> Option<i64> {
+    if config.daily_goal_minutes <= 0 {
+        return None;
+    }
+    let fraction = config.schedule.goal_fraction(&config.calendar, date, first_activity)?;
+    let goal_minutes = config.daily_goal_minutes as f64 * fraction;
+    Some(((productive_seconds / 60) as f64 / goal_minutes * 100.0).round() as i64)
+}
 
+fn compute_daily_stats(
+    activities: Vec<WindowActivity>,
+    config: &CategoryConfig,
+    group_by: Option<String>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<DailyStats, String> {
     // Agrupa atividades por aplicativo
     let mut app_stats: std::collections::HashMap<String, Vec<WindowActivity>> = std::collections::HashMap::new();
     for activity in activities.iter() {
@@ -136,7 +685,8 @@ pub async fn get_daily_stats(
     let productive_time: i64 = top_applications.iter()
         .filter(|app| app.category.as_ref().map_or(false, |c| c.is_productive))
         .map(|app| app.total_duration - app.idle_duration)
-        .sum();
+        .sum::<i64>()
+        + override_productive_delta(config, &override_totals(config, &activities));
 
     // Calcula a porcentagem da meta
     let productive_minutes = productive_time / 60;
@@ -148,31 +698,71 @@ pub async fn get_daily_stats(
 
     info!("Total time: {}, Productive time: {}, Goal: {}%", total_time, productive_time, goal_percentage);
 
+    let groups = group_by
+        .map(|group_by| group_activities(&activities, &group_by))
+        .transpose()?;
+    let neutral_time = neutral_time(&top_applications);
+    let overrides = override_totals(config, &activities);
+    let category_limits = category_limit_progress(config, &top_applications, &overrides, &[], 1);
+    let (top_applications, other_applications) =
+        split_top_applications(top_applications, TOP_APPLICATIONS, start, end, false, true);
+    let idle_breakdown = idle_breakdown(
+        activities.iter().filter(|a| a.is_idle).map(|a| (a.start_time, a.end_time)).collect(),
+    );
+
     Ok(DailyStats {
         total_time,
         productive_time,
         idle_time,
         goal_percentage,
-        top_applications: top_applications.into_iter().take(5).collect(),
+        prorated_goal_percentage: None,
+        neutral_time,
+        top_applications,
         activities,
+        weekly_goal: None,
+        groups,
+        other_applications,
+        idle_breakdown,
+        adjustments: Vec::new(),
+        adjustment_time: 0,
+        category_limits,
     })
 }
 
-fn is_unproductive_app(app_name: &str) -> bool {
-    const UNPRODUCTIVE_APPS: &[&str] = &[
-        "Finder",
-        "System Settings",
-        "System Preferences",
-        "Notification Center",
-        "Dock",
-        "Spotlight",
-        "Menu Bar",
-    ];
+fn group_activities(activities: &[WindowActivity], group_by: &str) -> Result<Vec<GroupStats>, String> {
+    let key_of: fn(&WindowActivity) -> Option<String> = match group_by {
+        "desktop" => |a| a.desktop.clone(),
+        "network" => |a| a.network.clone(),
+        "project" => activity_project,
+        _ => return Err(format!("Unsupported group_by: {}", group_by)),
+    };
+
+    // Soma total e ociosidade por chave
+    let mut totals: HashMap<Option<String>, (i64, i64)> = HashMap::new();
+    for activity in activities {
+        let duration = (activity.end_time - activity.start_time).num_seconds();
+        let entry = totals.entry(key_of(activity)).or_default();
+        entry.0 += duration;
+        if activity.is_idle {
+            entry.1 += duration;
+        }
+    }
+
+    let mut groups: Vec<GroupStats> = totals
+        .into_iter()
+        .map(|(key, (total_duration, idle_duration))| GroupStats {
+            key,
+            total_duration,
+            idle_duration,
+        })
+        .collect();
+    groups.sort_by(|a, b| b.total_duration.cmp(&a.total_duration));
 
-    UNPRODUCTIVE_APPS.contains(&app_name)
+    Ok(groups)
 }
 
 #[tauri::command]
+#[instrument(skip_all)]
 pub async fn get_activities_for_day(
     state: tauri::State<'_, DbConnection>,
     date: String,
@@ -187,16 +777,18 @@ pub async fn get_activities_for_day(
 }
 
 #[tauri::command]
+#[instrument(skip_all)]
 pub async fn get_categories(
-    config: State<'_, Mutex<CategoryConfig>>,
+    config: State<'_, SharedConfig>,
 ) -> Result<Vec<Category>, String> {
     let config = config.lock().map_err(|e| e.to_string())?;
     Ok(config.categories.clone())
 }
 
 #[tauri::command]
+#[instrument(skip_all)]
 pub async fn get_app_categories(
-    config: State<'_, Mutex<CategoryConfig>>,
+    config: State<'_, SharedConfig>,
 ) -> Result<Vec<(String, String)>, String> {
     let config = config.lock().map_err(|e| e.to_string())?;
     Ok(config.app_categories
@@ -206,33 +798,38 @@ pub async fn get_app_categories(
 }
 
 #[tauri::command]
+#[instrument(skip_all)]
 pub async fn add_category(
-    config: State<'_, Mutex<CategoryConfig>>,
+    config: State<'_, SharedConfig>,
     name: String,
     color: String,
     is_productive: bool,
+    neutral: Option<bool>,
 ) -> Result<Category, String> {
     let mut config = config.lock().map_err(|e| e.to_string())?;
-    config.add_category(name, color, is_productive)
+    config.add_category(name, color, is_productive, neutral.unwrap_or(false))
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
+#[instrument(skip_all)]
 pub async fn update_category(
-    config: State<'_, Mutex<CategoryConfig>>,
+    config: State<'_, SharedConfig>,
     id: String,
     name: String,
     color: String,
     is_productive: bool,
+    neutral: Option<bool>,
 ) -> Result<(), String> {
     let mut config = config.lock().map_err(|e| e.to_string())?;
-    config.update_category(id, name, color, is_productive)
+    config.update_category(id, name, color, is_productive, neutral)
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
+#[instrument(skip_all)]
 pub async fn delete_category(
-    config: State<'_, Mutex<CategoryConfig>>,
+    config: State<'_, SharedConfig>,
     id: String,
 ) -> Result<(), String> {
     let mut config = config.lock().map_err(|e| e.to_string())?;
@@ -240,90 +837,373 @@ pub async fn delete_category(
         .map_err(|e| e.to_string())
 }
 
-#[tauri::command(rename_all = "snake_case")]
-pub async fn set_app_category(
-    app: tauri::AppHandle,
-    state: State<'_, Mutex<CategoryConfig>>,
-    app_name: String,
-    category_id: String,
-) -> Result<(), String> {
-    info!("Received request to set category. App: '{}', Category ID: '{}'", app_name, category_id);
-    
-    // Faz a alteração dentro de um escopo para garantir que o lock é liberado
-    {
-        let mut config = state.lock().map_err(|e| e.to_string())?;
-        config.set_app_category(app_name, category_id).map_err(|e| e.to_string())?;
-    } // lock é liberado aqui
-    
-    // Spawn a new task to update the menu
-    let app_handle = app.clone();
-    tauri::async_runtime::spawn(async move {
-        if let Err(e) = Box::pin(crate::menu::update_tray_menu(&app_handle)).await {
-            error!("Failed to update menu: {}", e);
-        }
-    });
-    
-    Ok(())
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_media_playback_config(
+    config: State<'_, SharedConfig>,
+) -> Result<MediaPlaybackConfig, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.media_playback.clone())
 }
 
 #[tauri::command]
-pub async fn get_uncategorized_apps(
-    db: State<'_, DbConnection>,
-    config: State<'_, Mutex<CategoryConfig>>,
-) -> Result<Vec<String>, String> {
-    // Busca todos os aplicativos únicos do banco
-    let apps = database::get_unique_applications(&db)
-        .await
-        .map_err(|e| e.to_string())?;
+#[instrument(skip_all)]
+pub async fn set_media_playback_config(
+    config: State<'_, SharedConfig>,
+    enabled: bool,
+    apps: Vec<String>,
+) -> Result<(), String> {
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_media_playback(MediaPlaybackConfig { enabled, apps })
+        .map_err(|e| e.to_string())
+}
 
-    // Pega os aplicativos que já têm categoria
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_idle_config(
+    config: State<'_, SharedConfig>,
+) -> Result<IdleConfig, String> {
     let config = config.lock().map_err(|e| e.to_string())?;
-    let categorized_apps: HashSet<_> = config.app_categories.keys().cloned().collect();
+    Ok(config.idle.clone())
+}
 
-    // Filtra apenas os apps não categorizados
-    let uncategorized = apps
-        .into_iter()
-        .filter(|app| !categorized_apps.contains(app))
-        .collect();
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_idle_config(
+    config: State<'_, SharedConfig>,
+    grace_seconds: u64,
+) -> Result<(), String> {
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_idle(IdleConfig { grace_seconds })
+        .map_err(|e| e.to_string())
+}
 
-    Ok(uncategorized)
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_idle_prompt_config(
+    config: State<'_, SharedConfig>,
+) -> Result<IdlePromptConfig, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.idle_prompt.clone())
 }
 
 #[tauri::command]
-pub async fn get_today_stats(
+#[instrument(skip_all)]
+pub async fn set_idle_prompt_config(
+    config: State<'_, SharedConfig>,
+    idle_prompt: IdlePromptConfig,
+) -> Result<(), String> {
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_idle_prompt(idle_prompt)
+        .map_err(|e| e.to_string())
+}
+
+// Resposta ao convite de "idle-return-prompt": o intervalo ocioso vira uma atividade
+// "Away" com o rótulo, opcionalmente numa categoria (reunião produtiva, almoço não)
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn label_idle_period(
     app: tauri::AppHandle,
     db: State<'_, DbConnection>,
-    config: State<'_, Mutex<CategoryConfig>>,
-) -> Result<(i64, i64), String> {
-    let result = get_today_stats_internal(db, config).await?;
-    
-    // Atualiza o menu em uma nova task
-    let app_handle = app.clone();
-    tauri::async_runtime::spawn(async move {
-        if let Err(e) = crate::menu::update_tray_menu(&app_handle).await {
-            error!("Failed to update menu: {}", e);
+    config: State<'_, SharedConfig>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    label: String,
+    category_id: Option<String>,
+) -> Result<usize, String> {
+    let label = label.trim();
+    if label.is_empty() {
+        return Err("Label cannot be empty".to_string());
+    }
+    if end <= start {
+        return Err("End time must be after start time".to_string());
+    }
+    if let Some(category_id) = &category_id {
+        let config = config.lock().map_err(|e| e.to_string())?;
+        if !config.categories.iter().any(|c| &c.id == category_id) {
+            return Err(format!("Category not found: {}", category_id));
         }
-    });
-    
-    Ok(result)
-}
+    }
 
-pub async fn get_today_stats_internal(
-    db: State<'_, DbConnection>,
-    config: State<'_, Mutex<CategoryConfig>>,
-) -> Result<(i64, i64), String> {
-    let now = Utc::now();
-    let start = now.date_naive().and_hms_opt(0, 0, 0).unwrap();
-    let end = now.date_naive().and_hms_opt(23, 59, 59).unwrap();
-    
-    let activities = database::get_activities_between(&db, start.and_utc(), end.and_utc())
+    let labeled = database::label_idle_period(&db, start, end, label, category_id.as_deref())
         .await
         .map_err(|e| e.to_string())?;
+    if labeled == 0 {
+        return Err("No idle time recorded in that period".to_string());
+    }
+    info!("Labeled {} idle rows as {:?}", labeled, label);
+
+    if let Err(e) = database::refresh_hourly_rollups(&db, start.timestamp(), Utc::now()).await {
+        error!("Failed to refresh hourly rollups after labeling idle time: {}", e);
+    }
+    tokio::spawn(crate::stats_cache::backfill(app));
+    Ok(labeled)
+}
 
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_network_tagging_config(
+    config: State<'_, SharedConfig>,
+) -> Result<NetworkTaggingConfig, String> {
     let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.network_tagging.clone())
+}
 
-    // Agrupa atividades por aplicativo
-    let mut app_stats: std::collections::HashMap<String, Vec<WindowActivity>> = std::collections::HashMap::new();
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_network_tagging_config(
+    config: State<'_, SharedConfig>,
+    network_tagging: NetworkTaggingConfig,
+) -> Result<(), String> {
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_network_tagging(network_tagging)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_window_snapshot_config(
+    config: State<'_, SharedConfig>,
+) -> Result<WindowSnapshotConfig, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.window_snapshots.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_window_snapshot_config(
+    config: State<'_, SharedConfig>,
+    window_snapshots: WindowSnapshotConfig,
+) -> Result<(), String> {
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_window_snapshots(window_snapshots)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_git_branch_config(
+    config: State<'_, SharedConfig>,
+) -> Result<GitBranchConfig, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.git_branches.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_git_branch_config(
+    config: State<'_, SharedConfig>,
+    git_branches: GitBranchConfig,
+) -> Result<(), String> {
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_git_branches(git_branches)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_calendar_config(
+    config: State<'_, SharedConfig>,
+) -> Result<CalendarConfig, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.calendar.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_calendar_suggestion(
+    config: State<'_, SharedConfig>,
+) -> Result<Option<CalendarSuggestion>, String> {
+    let calendar = config.lock().map_err(|e| e.to_string())?.calendar.clone();
+    Ok(locale::calendar_suggestion(&calendar))
+}
+
+// Aplica (e marca como revisados) o início da semana e a hora de virada do dia
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn confirm_calendar_settings(
+    config: State<'_, SharedConfig>,
+    week_start: WeekStart,
+    day_rollover_hour: u32,
+    time_zone: Option<String>,
+) -> Result<(), String> {
+    if day_rollover_hour > 23 {
+        return Err("Day rollover hour must be between 0 and 23".to_string());
+    }
+    if let Some(time_zone) = &time_zone {
+        time_zone
+            .parse::<chrono_tz::Tz>()
+            .map_err(|_| format!("Unknown time zone: {}", time_zone))?;
+    }
+
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_calendar(CalendarConfig {
+        week_start,
+        day_rollover_hour,
+        confirmed: true,
+        time_zone,
+    })
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_notification_config(
+    config: State<'_, SharedConfig>,
+) -> Result<NotificationConfig, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.notifications.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_notification_config(
+    app: tauri::AppHandle,
+    config: State<'_, SharedConfig>,
+    notifications: NotificationConfig,
+) -> Result<(), String> {
+    // Rejeita backends sem os campos obrigatórios (URL, tópico, tokens)
+    crate::notifications::build_notifier(&notifications, &app.config().tauri.bundle.identifier)
+        .map_err(|e| e.to_string())?;
+
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_notifications(notifications)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn send_test_notification(
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    crate::notifications::notify(&app, "Chronos Track", "Notifications are working")
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Serialize)]
+pub struct WindowSnapshotEntry {
+    taken_at: DateTime<Utc>,
+    application: String,
+    title: String,
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_window_snapshots(
+    range: TimeRange,
+    db: State<'_, DbConnection>,
+) -> Result<Vec<WindowSnapshotEntry>, String> {
+    let snapshots = database::get_window_snapshots_between(&db, range.start, range.end)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(snapshots
+        .into_iter()
+        .map(|(taken_at, application, title)| WindowSnapshotEntry {
+            taken_at,
+            application,
+            title,
+        })
+        .collect())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+#[instrument(skip_all)]
+pub async fn set_app_category(
+    app: tauri::AppHandle,
+    state: State<'_, SharedConfig>,
+    db: State<'_, DbConnection>,
+    app_name: String,
+    category_id: String,
+) -> Result<(), String> {
+    info!("Received request to set category. App: '{}', Category ID: '{}'", app_name, category_id);
+    
+    // Faz a alteração dentro de um escopo para garantir que o lock é liberado
+    {
+        let mut config = state.lock().map_err(|e| e.to_string())?;
+        config.set_app_category(app_name.clone(), category_id.clone()).map_err(|e| e.to_string())?;
+    } // lock é liberado aqui
+    
+    // Guarda a correção para as sugestões de regras
+    if let Err(e) = database::record_category_assignment(&db, &app_name, &category_id).await {
+        error!("Failed to record category assignment: {}", e);
+    }
+    
+    // Spawn a new task to update the menu
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = Box::pin(crate::menu::update_tray_menu(&app_handle)).await {
+            error!("Failed to update menu: {}", e);
+        }
+    });
+    
+    Ok(())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_uncategorized_apps(
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<Vec<String>, String> {
+    // Busca todos os aplicativos únicos do banco
+    let apps = database::get_unique_applications(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Pega os aplicativos que já têm categoria
+    let config = config.lock().map_err(|e| e.to_string())?;
+    let categorized_apps: HashSet<_> = config.app_categories.keys().cloned().collect();
+
+    // Filtra apenas os apps não categorizados
+    let uncategorized = apps
+        .into_iter()
+        .filter(|app| !categorized_apps.contains(app))
+        .collect();
+
+    Ok(uncategorized)
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_today_stats(
+    app: tauri::AppHandle,
+) -> Result<(i64, i64), String> {
+    // Na inicialização serve o cache até o cálculo completo terminar
+    let result = crate::stats_cache::get_today_totals(&app).await?;
+    
+    // Atualiza o menu em uma nova task
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = crate::menu::update_tray_menu(&app_handle).await {
+            error!("Failed to update menu: {}", e);
+        }
+    });
+    
+    Ok(result)
+}
+
+pub async fn get_today_stats_internal(
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<(i64, i64), String> {
+    let calendar = config.lock().map_err(|e| e.to_string())?.calendar.clone();
+    let today = calendar.logical_date(Utc::now());
+    let (start, end) = calendar.day_bounds(today);
+    
+    let mut activities = database::get_activities_between(&db, start, end)
+        .await
+        .map_err(|e| e.to_string())?;
+    activities.retain(|a| !a.off_hours);
+    let adjustments = database::get_adjustments_between(&db, today, today)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let config = config.lock().map_err(|e| e.to_string())?;
+
+    // Agrupa atividades por aplicativo
+    let mut app_stats: std::collections::HashMap<String, Vec<WindowActivity>> = std::collections::HashMap::new();
     for activity in activities.iter() {
         app_stats.entry(activity.application.clone())
             .or_default()
@@ -367,9 +1247,29 @@ pub async fn get_today_stats_internal(
     let productive_time: i64 = top_applications.iter()
         .filter(|app| app.category.as_ref().map_or(false, |c| c.is_productive))
         .map(|app| app.total_duration - app.idle_duration)
-        .sum();
+        .sum::<i64>()
+        + override_productive_delta(&config, &override_totals(&config, &activities));
+
+    let mut stats = DailyStats {
+        total_time,
+        productive_time,
+        idle_time,
+        goal_percentage: 0,
+        prorated_goal_percentage: None,
+        neutral_time: 0,
+        top_applications: Vec::new(),
+        activities: Vec::new(),
+        weekly_goal: None,
+        groups: None,
+        other_applications: None,
+        idle_breakdown: IdleBreakdown::default(),
+        adjustments: Vec::new(),
+        adjustment_time: 0,
+        category_limits: Vec::new(),
+    };
+    apply_adjustments(&mut stats, adjustments, &config);
 
-    Ok((total_time, productive_time))
+    Ok((stats.total_time, stats.productive_time))
 }
 
 async fn get_category_config() -> Result<CategoryConfig, String> {
@@ -380,13 +1280,24 @@ async fn save_category_config(config: &CategoryConfig) -> Result<(), String> {
     config.save().map_err(|e| e.to_string())
 }
 
+// Mesmo conteúdo da bandeja, em texto descritivo para leitores de tela
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_tray_state(
+    app: tauri::AppHandle,
+) -> Result<crate::menu::TrayState, String> {
+    Ok(crate::menu::get_tray_state(&app).await)
+}
+
 #[tauri::command]
+#[instrument(skip_all)]
 pub async fn get_daily_goal() -> Result<i64, String> {
     let config = get_category_config().await?;
     Ok(config.daily_goal_minutes)
 }
 
 #[tauri::command]
+#[instrument(skip_all)]
 pub async fn set_daily_goal(
     app: tauri::AppHandle,
     minutes: i64
@@ -402,81 +1313,311 @@ pub async fn set_daily_goal(
 }
 
 #[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_weekly_goal(
+    config: State<'_, SharedConfig>,
+) -> Result<i64, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.weekly_goal_minutes)
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_weekly_goal(
+    app: tauri::AppHandle,
+    config: State<'_, SharedConfig>,
+    minutes: i64,
+) -> Result<(), String> {
+    {
+        let mut config = config.lock().map_err(|e| e.to_string())?;
+        config.weekly_goal_minutes = minutes.max(0);
+        config.save().map_err(|e| e.to_string())?;
+    }
+
+    if let Err(e) = crate::menu::update_tray_menu(&app).await {
+        error!("Failed to update menu: {}", e);
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
 pub async fn get_weekly_stats(
     date: DateTime<Utc>,
     db: State<'_, DbConnection>,
-    config: State<'_, Mutex<CategoryConfig>>,
+    config: State<'_, SharedConfig>,
+    group_by: Option<String>,
+    include_activities: Option<bool>,
+    top_n: Option<usize>,
+    include_off_hours: Option<bool>,
 ) -> Result<DailyStats, String> {
-    let start_of_week = date.date_naive().and_hms_opt(0, 0, 0).unwrap()
-        - Duration::days(date.weekday().num_days_from_monday() as i64);
-    let end_of_week = start_of_week + Duration::days(7) - Duration::nanoseconds(1);
-    
-    get_stats_for_range(&db, config, start_of_week.and_utc(), end_of_week.and_utc()).await
+    let calendar = config.lock().map_err(|e| e.to_string())?.calendar.clone();
+    let (start_of_week, end_of_week) = calendar.week_bounds(calendar.local_date(date));
+    let weekly_goal = get_weekly_goal_progress(&db, config.inner(), date).await?;
+
+    let options = StatsOptions {
+        include_activities: include_activities.unwrap_or(false) || group_by.is_some(),
+        include_off_hours: include_off_hours.unwrap_or(false),
+        top_n: top_applications_limit(top_n),
+    };
+    let mut stats = get_stats_for_range(&db, config, start_of_week, end_of_week, options).await?;
+    stats.weekly_goal = weekly_goal;
+    stats.groups = group_by
+        .map(|group_by| group_activities(&stats.activities, &group_by))
+        .transpose()?;
+    Ok(stats)
+}
+
+// Quantas semanas anteriores são consideradas no cálculo da sequência
+const MAX_STREAK_WEEKS: i64 = 52;
+
+pub async fn get_weekly_goal_progress(
+    db: &DbConnection,
+    config: &Mutex<CategoryConfig>,
+    date: DateTime<Utc>,
+) -> Result<Option<WeeklyGoalProgress>, String> {
+    let config = config.lock().map_err(|e| e.to_string())?.clone();
+    let goal_minutes = config.weekly_goal_minutes;
+    if goal_minutes <= 0 {
+        return Ok(None);
+    }
+
+    // Lê os resumos e resultados diários (streaks.rs), não as atividades de um ano inteiro
+    let (productive_seconds, streak) =
+        crate::streaks::weekly_goal(db, &config, config.calendar.logical_date(date), MAX_STREAK_WEEKS)
+            .await
+            .map_err(|e| e.to_string())?;
+
+    let productive_minutes = productive_seconds / 60;
+    let percentage = ((productive_minutes as f64 / goal_minutes as f64) * 100.0).round() as i64;
+
+    Ok(Some(WeeklyGoalProgress {
+        goal_minutes,
+        productive_minutes,
+        percentage,
+        streak,
+    }))
 }
 
 #[tauri::command]
+#[instrument(skip_all)]
 pub async fn get_monthly_stats(
     date: DateTime<Utc>,
     db: State<'_, DbConnection>,
-    config: State<'_, Mutex<CategoryConfig>>,
+    config: State<'_, SharedConfig>,
+    group_by: Option<String>,
+    include_activities: Option<bool>,
+    top_n: Option<usize>,
+    include_off_hours: Option<bool>,
 ) -> Result<DailyStats, String> {
-    let start_of_month = date.date_naive().and_hms_opt(0, 0, 0).unwrap()
-        .with_day(1).unwrap();
-    let end_of_month = if let Some(next_month) = DateTime::<Utc>::from_timestamp(
-        start_of_month.and_utc().timestamp() + 32 * 24 * 60 * 60, 0
-    ) {
-        next_month.date_naive().with_day(1).unwrap()
-            .and_hms_opt(0, 0, 0).unwrap()
-            - Duration::nanoseconds(1)
-    } else {
-        start_of_month + Duration::days(30)
+    let calendar = config.lock().map_err(|e| e.to_string())?.calendar.clone();
+    let first_day = calendar.local_date(date).with_day(1).unwrap();
+    let last_day = first_day
+        .checked_add_months(chrono::Months::new(1))
+        .map_or(first_day + Duration::days(30), |next| next - Duration::days(1));
+    let (start_of_month, _) = calendar.day_bounds(first_day);
+    let (_, end_of_month) = calendar.day_bounds(last_day);
+
+    let options = StatsOptions {
+        include_activities: include_activities.unwrap_or(false) || group_by.is_some(),
+        include_off_hours: include_off_hours.unwrap_or(false),
+        top_n: top_applications_limit(top_n),
     };
-    
-    get_stats_for_range(&db, config, start_of_month.and_utc(), end_of_month.and_utc()).await
+    let mut stats = get_stats_for_range(&db, config, start_of_month, end_of_month, options).await?;
+    stats.groups = group_by
+        .map(|group_by| group_activities(&stats.activities, &group_by))
+        .transpose()?;
+    Ok(stats)
+}
+
+#[derive(Debug, Serialize)]
+pub struct MonthStats {
+    pub month: u32,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub total_time: i64,
+    pub productive_time: i64,
+    pub idle_time: i64,
+    pub top_applications: Vec<(String, i64)>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct YearlyStats {
+    pub year: i32,
+    pub total_time: i64,
+    pub productive_time: i64,
+    pub idle_time: i64,
+    pub months: Vec<MonthStats>,
+    pub top_applications: Vec<(String, i64)>,
+}
+
+const YEARLY_TOP_APPLICATIONS: usize = 10;
+
+fn top_active_applications(per_app: HashMap<String, i64>, limit: usize) -> Vec<(String, i64)> {
+    let mut applications: Vec<(String, i64)> = per_app.into_iter().filter(|(_, seconds)| *seconds > 0).collect();
+    applications.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    applications.truncate(limit);
+    applications
+}
+
+// Totais mês a mês vindos dos resumos por hora, sem ler as linhas brutas do ano.
+// Ajustes manuais e recategorizações entram como nas estatísticas do dia
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_yearly_stats(
+    year: i32,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<YearlyStats, String> {
+    let config = config.lock().map_err(|e| e.to_string())?.clone();
+    let now = Utc::now();
+
+    let mut months = Vec::new();
+    let mut year_apps: HashMap<String, i64> = HashMap::new();
+    for month in 1..=12 {
+        let first_day = NaiveDate::from_ymd_opt(year, month, 1).ok_or_else(|| format!("Invalid year: {}", year))?;
+        let last_day = first_day
+            .checked_add_months(chrono::Months::new(1))
+            .map_or(first_day + Duration::days(30), |next| next - Duration::days(1));
+        let (start, _) = config.calendar.day_bounds(first_day);
+        let (_, end) = config.calendar.day_bounds(last_day);
+
+        let mut stats = MonthStats {
+            month,
+            start,
+            end,
+            total_time: 0,
+            productive_time: 0,
+            idle_time: 0,
+            top_applications: Vec::new(),
+        };
+        if start > now {
+            months.push(stats);
+            continue;
+        }
+
+        let totals = database::get_app_totals_from_rollups(&db, start, end)
+            .await
+            .map_err(|e| e.to_string())?;
+        let mut overrides = database::get_override_totals(&db, start, end, false)
+            .await
+            .map_err(|e| e.to_string())?;
+        let profiles = database::get_profile_totals(&db, start, end, false)
+            .await
+            .map_err(|e| e.to_string())?;
+        overrides.extend(profile_overrides(&config, &profiles));
+        let adjustments = database::get_adjustments_between(&db, first_day, last_day)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut month_apps: HashMap<String, i64> = HashMap::new();
+        for total in &totals {
+            let active = total.total_duration - total.idle_duration;
+            stats.total_time += total.total_duration;
+            stats.idle_time += total.idle_duration;
+            if config.get_category_for_app(&total.application).map_or(false, |c| c.is_productive) {
+                stats.productive_time += active;
+            }
+            *month_apps.entry(total.application.clone()).or_default() += active;
+            *year_apps.entry(total.application.clone()).or_default() += active;
+        }
+        stats.productive_time += override_productive_delta(&config, &overrides);
+
+        for adjustment in &adjustments {
+            let seconds = adjustment.minutes * 60;
+            stats.total_time += seconds;
+            if config.categories.iter().any(|c| c.id == adjustment.category_id && c.is_productive) {
+                stats.productive_time += seconds;
+            }
+        }
+        stats.total_time = stats.total_time.max(0);
+        stats.productive_time = stats.productive_time.max(0);
+        stats.top_applications = top_active_applications(month_apps, TOP_APPLICATIONS);
+        months.push(stats);
+    }
+
+    Ok(YearlyStats {
+        year,
+        total_time: months.iter().map(|m| m.total_time).sum(),
+        productive_time: months.iter().map(|m| m.productive_time).sum(),
+        idle_time: months.iter().map(|m| m.idle_time).sum(),
+        top_applications: top_active_applications(year_apps, YEARLY_TOP_APPLICATIONS),
+        months,
+    })
 }
 
+// Opções comuns às estatísticas diárias, semanais e mensais
+struct StatsOptions {
+    include_activities: bool,
+    // Linhas marcadas como fora do expediente (modo Tag) ficam de fora por padrão
+    include_off_hours: bool,
+    top_n: usize,
+}
+
+// Totais por app agregados no SQLite; as atividades brutas só são carregadas quando
+// pedidas (lista no frontend ou agrupamento), o que mantém intervalos longos leves
 async fn get_stats_for_range(
     db: &DbConnection,
-    config: State<'_, Mutex<CategoryConfig>>,
+    config: State<'_, SharedConfig>,
     start: DateTime<Utc>,
     end: DateTime<Utc>,
+    options: StatsOptions,
 ) -> Result<DailyStats, String> {
-    let activities = database::get_activities_between(&db, start, end)
+    // Sem a lista bruta (semana/mês), os totais vêm dos resumos por hora, que
+    // não têm as linhas de fora do expediente
+    let from_rollups = !options.include_activities && !options.include_off_hours;
+    let totals = if from_rollups {
+        database::get_app_totals_from_rollups(db, start, end).await
+    } else {
+        database::get_app_totals_between(db, start, end, options.include_off_hours).await
+    }
+    .map_err(|e| e.to_string())?;
+    let activities = if options.include_activities {
+        let mut activities = database::get_activities_between(db, start, end)
+            .await
+            .map_err(|e| e.to_string())?;
+        activities.retain(|a| options.include_off_hours || !a.off_hours);
+        activities
+    } else {
+        Vec::new()
+    };
+
+    let calendar = config.lock().map_err(|e| e.to_string())?.calendar.clone();
+    let adjustments = database::get_adjustments_between(db, calendar.logical_date(start), calendar.logical_date(end))
+        .await
+        .map_err(|e| e.to_string())?;
+    let idle_breakdown = idle_breakdown(
+        database::get_idle_intervals(db, start, end, options.include_off_hours)
+            .await
+            .map_err(|e| e.to_string())?,
+    );
+    let mut overrides = database::get_override_totals(db, start, end, options.include_off_hours)
+        .await
+        .map_err(|e| e.to_string())?;
+    let profiles = database::get_profile_totals(db, start, end, options.include_off_hours)
         .await
         .map_err(|e| e.to_string())?;
 
     let config = config.lock().map_err(|e| e.to_string())?;
+    overrides.extend(profile_overrides(&config, &profiles));
 
-    // Agrupa atividades por aplicativo
-    let mut app_stats: std::collections::HashMap<String, Vec<WindowActivity>> = std::collections::HashMap::new();
-    for activity in activities.iter() {
-        app_stats.entry(activity.application.clone())
+    let mut activities_by_app: HashMap<&str, Vec<WindowActivity>> = HashMap::new();
+    for activity in &activities {
+        activities_by_app
+            .entry(activity.application.as_str())
             .or_default()
             .push(activity.clone());
     }
 
-    // Calcula estatísticas por aplicativo
-    let mut top_applications: Vec<ApplicationStats> = app_stats
+    let mut top_applications: Vec<ApplicationStats> = totals
         .into_iter()
-        .map(|(app, activities)| {
-            let total_duration = activities.iter()
-                .map(|a| (a.end_time - a.start_time).num_seconds())
-                .sum();
-            
-            let idle_duration = activities.iter()
-                .filter(|a| a.is_idle)
-                .map(|a| (a.end_time - a.start_time).num_seconds())
-                .sum();
-            
-            let category = config.get_category_for_app(&app).cloned();
-            
-            ApplicationStats {
-                application: app,
-                total_duration,
-                idle_duration,
-                activities,
-                category,
-            }
+        .map(|totals| ApplicationStats {
+            activities: activities_by_app.remove(totals.application.as_str()).unwrap_or_default(),
+            category: config.get_category_for_app(&totals.application).cloned(),
+            application: totals.application,
+            total_duration: totals.total_duration,
+            idle_duration: totals.idle_duration,
         })
         .collect();
 
@@ -495,7 +1636,8 @@ async fn get_stats_for_range(
     let productive_time: i64 = top_applications.iter()
         .filter(|app| app.category.as_ref().map_or(false, |c| c.is_productive))
         .map(|app| app.total_duration - app.idle_duration)
-        .sum();
+        .sum::<i64>()
+        + override_productive_delta(&config, &overrides);
 
     // Calcula a porcentagem da meta
     let productive_minutes = productive_time / 60;
@@ -505,12 +1647,2960 @@ async fn get_stats_for_range(
         0
     };
 
-    Ok(DailyStats {
+    let neutral_time = neutral_time(&top_applications);
+    let category_limits =
+        category_limit_progress(&config, &top_applications, &overrides, &adjustments, range_days(start, end));
+    let (top_applications, other_applications) =
+        split_top_applications(top_applications, options.top_n, start, end, from_rollups, options.include_off_hours);
+
+    let mut stats = DailyStats {
         total_time,
         productive_time,
         idle_time,
         goal_percentage,
-        top_applications: top_applications.into_iter().take(5).collect(),
+        prorated_goal_percentage: None,
+        neutral_time,
+        top_applications,
         activities,
+        weekly_goal: None,
+        groups: None,
+        other_applications,
+        idle_breakdown,
+        adjustments: Vec::new(),
+        adjustment_time: 0,
+        category_limits,
+    };
+    apply_adjustments(&mut stats, adjustments, &config);
+    Ok(stats)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+#[instrument(skip_all)]
+pub async fn import(
+    db: State<'_, DbConnection>,
+    path: String,
+    dry_run: Option<bool>,
+) -> Result<ImportPreview, String> {
+    // Por padrão apenas retorna a prévia, sem gravar nada
+    let dry_run = dry_run.unwrap_or(true);
+    let preview = importer::import_file(&db, Path::new(&path), dry_run)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Dados importados podem cair em qualquer período, então os resumos são refeitos
+    if !dry_run {
+        rollups::rebuild(&db).await.map_err(|e| e.to_string())?;
+    }
+    Ok(preview)
+}
+
+#[derive(Debug, Serialize)]
+pub struct StorageForecast {
+    pub size_bytes: i64,
+    pub rows: i64,
+    pub days_tracked: i64,
+    pub rows_per_day: f64,
+    pub bytes_per_day: f64,
+    pub projected_bytes_1y: i64,
+    pub projected_bytes_5y: i64,
+    pub suggestions: Vec<String>,
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_storage_forecast(
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<StorageForecast, String> {
+    let usage = database::get_storage_usage(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+    let calendar = config.lock().map_err(|e| e.to_string())?.calendar.clone();
+
+    // Considera o intervalo de calendário inteiro, inclusive dias sem uso
+    let days_tracked = match (usage.first_activity, usage.last_activity) {
+        (Some(first), Some(last)) => (calendar.logical_date(last) - calendar.logical_date(first)).num_days() + 1,
+        _ => 0,
+    };
+
+    let (rows_per_day, bytes_per_day) = if days_tracked > 0 {
+        (
+            usage.rows as f64 / days_tracked as f64,
+            usage.size_bytes as f64 / days_tracked as f64,
+        )
+    } else {
+        (0.0, 0.0)
+    };
+
+    let projected_bytes_1y = usage.size_bytes + (bytes_per_day * 365.0) as i64;
+    let projected_bytes_5y = usage.size_bytes + (bytes_per_day * 365.0 * 5.0) as i64;
+
+    let mut suggestions = Vec::new();
+    if rows_per_day > 2000.0 {
+        suggestions.push(format!(
+            "Averaging {:.0} rows/day: raising the merge threshold would combine more short activities",
+            rows_per_day
+        ));
+    }
+    if projected_bytes_5y > 1024 * 1024 * 1024 {
+        suggestions.push(
+            "Projected size exceeds 1 GB in 5 years: consider downsampling or a retention period for old raw activities"
+                .to_string(),
+        );
+    }
+    if days_tracked > 0 && days_tracked < 7 {
+        suggestions.push("Less than a week of data: the forecast will become more accurate over time".to_string());
+    }
+
+    info!(
+        "💾 Storage forecast | Size: {}B, Rows: {}, Days: {}, 1y: {}B, 5y: {}B",
+        usage.size_bytes,
+        usage.rows,
+        days_tracked,
+        projected_bytes_1y,
+        projected_bytes_5y
+    );
+
+    Ok(StorageForecast {
+        size_bytes: usage.size_bytes,
+        rows: usage.rows,
+        days_tracked,
+        rows_per_day,
+        bytes_per_day,
+        projected_bytes_1y,
+        projected_bytes_5y,
+        suggestions,
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub struct DesktopStats {
+    pub desktop: Option<String>,
+    pub total_duration: i64,
+    pub idle_duration: i64,
+    pub productive_duration: i64,
+    pub top_applications: Vec<(String, i64)>,
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_desktop_stats(
+    range: TimeRange,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<Vec<DesktopStats>, String> {
+    let mut activities = database::get_activities_between(&db, range.start, range.end)
+        .await
+        .map_err(|e| e.to_string())?;
+    activities.retain(|a| !a.off_hours);
+
+    let config = config.lock().map_err(|e| e.to_string())?;
+
+    // Agrupa atividades por área de trabalho virtual
+    let mut by_desktop: HashMap<Option<String>, Vec<&WindowActivity>> = HashMap::new();
+    for activity in activities.iter() {
+        by_desktop.entry(activity.desktop.clone()).or_default().push(activity);
+    }
+
+    let mut stats: Vec<DesktopStats> = by_desktop
+        .into_iter()
+        .map(|(desktop, activities)| {
+            let mut total_duration = 0;
+            let mut idle_duration = 0;
+            let mut productive_duration = 0;
+            let mut per_app: HashMap<String, i64> = HashMap::new();
+
+            for activity in activities {
+                let duration = (activity.end_time - activity.start_time).num_seconds();
+                total_duration += duration;
+                if activity.is_idle {
+                    idle_duration += duration;
+                    continue;
+                }
+
+                *per_app.entry(activity.application.clone()).or_default() += duration;
+                if config.get_category_for_app(&activity.application).map_or(false, |c| c.is_productive) {
+                    productive_duration += duration;
+                }
+            }
+
+            let mut top_applications: Vec<(String, i64)> = per_app.into_iter().collect();
+            top_applications.sort_by(|a, b| b.1.cmp(&a.1));
+            top_applications.truncate(5);
+
+            DesktopStats {
+                desktop,
+                total_duration,
+                idle_duration,
+                productive_duration,
+                top_applications,
+            }
+        })
+        .collect();
+
+    stats.sort_by(|a, b| b.total_duration.cmp(&a.total_duration));
+    Ok(stats)
+}
+
+#[derive(Debug, Serialize)]
+pub struct HourlyStats {
+    pub hour_start: DateTime<Utc>,
+    // Hora local (0-23); dias de horário de verão têm 23 ou 25 entradas
+    pub hour: u32,
+    pub total_duration: i64,
+    pub productive_duration: i64,
+    pub idle_duration: i64,
+    pub top_application: Option<String>,
+}
+
+// Totais por hora do dia para a linha do tempo. Atividades que cruzam a virada
+// da hora são divididas entre as duas
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_hourly_stats(
+    date: String,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<Vec<HourlyStats>, String> {
+    let date = DateTime::parse_from_rfc3339(&date)
+        .map_err(|e| e.to_string())?
+        .with_timezone(&Utc);
+
+    let calendar = config.lock().map_err(|e| e.to_string())?.calendar.clone();
+    let (start, end) = calendar.day_bounds(calendar.local_date(date));
+    let mut activities = database::get_activities_between(&db, start, end)
+        .await
+        .map_err(|e| e.to_string())?;
+    activities.retain(|a| !a.off_hours);
+
+    let config = config.lock().map_err(|e| e.to_string())?;
+    let tz = calendar.tz();
+    let mut hours = Vec::new();
+    let mut hour_start = start;
+    while hour_start <= end {
+        let hour_end = hour_start + Duration::hours(1);
+        let mut total_duration = 0;
+        let mut productive_duration = 0;
+        let mut idle_duration = 0;
+        let mut per_app: HashMap<&str, i64> = HashMap::new();
+
+        for activity in &activities {
+            let overlap = (activity.end_time.min(hour_end) - activity.start_time.max(hour_start)).num_seconds();
+            if overlap <= 0 {
+                continue;
+            }
+            total_duration += overlap;
+            if activity.is_idle {
+                idle_duration += overlap;
+                continue;
+            }
+
+            *per_app.entry(activity.application.as_str()).or_default() += overlap;
+            if config.category_for_activity(activity).map_or(false, |c| c.is_productive) {
+                productive_duration += overlap;
+            }
+        }
+
+        hours.push(HourlyStats {
+            hour_start,
+            hour: hour_start.with_timezone(&tz).hour(),
+            total_duration,
+            productive_duration,
+            idle_duration,
+            top_application: per_app
+                .into_iter()
+                .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+                .map(|(app, _)| app.to_string()),
+        });
+        hour_start = hour_end;
+    }
+
+    Ok(hours)
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProjectStats {
+    pub project: String,
+    pub total_duration: i64,
+    pub applications: Vec<(String, i64)>,
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_project_stats(
+    range: TimeRange,
+    db: State<'_, DbConnection>,
+) -> Result<Vec<ProjectStats>, String> {
+    let mut activities = database::get_activities_between(&db, range.start, range.end)
+        .await
+        .map_err(|e| e.to_string())?;
+    activities.retain(|a| !a.off_hours);
+
+    // Soma o tempo ativo por projeto e, dentro dele, por aplicativo
+    let mut by_project: HashMap<String, HashMap<String, i64>> = HashMap::new();
+    for activity in activities.iter().filter(|a| !a.is_idle) {
+        let Some(project) = activity_project(activity) else {
+            continue;
+        };
+        let duration = (activity.end_time - activity.start_time).num_seconds();
+        *by_project
+            .entry(project)
+            .or_default()
+            .entry(activity.application.clone())
+            .or_default() += duration;
+    }
+
+    let mut stats: Vec<ProjectStats> = by_project
+        .into_iter()
+        .map(|(project, per_app)| {
+            let mut applications: Vec<(String, i64)> = per_app.into_iter().collect();
+            applications.sort_by(|a, b| b.1.cmp(&a.1));
+
+            ProjectStats {
+                project,
+                total_duration: applications.iter().map(|(_, d)| d).sum(),
+                applications,
+            }
+        })
+        .collect();
+
+    stats.sort_by(|a, b| b.total_duration.cmp(&a.total_duration));
+    Ok(stats)
+}
+
+#[derive(Debug, Serialize)]
+pub struct DomainStats {
+    pub domain: String,
+    pub label: Option<String>,
+    pub total_duration: i64,
+    pub visits: usize,
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_domain_stats(
+    range: TimeRange,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<Vec<DomainStats>, String> {
+    let mut activities = database::get_activities_between(&db, range.start, range.end)
+        .await
+        .map_err(|e| e.to_string())?;
+    activities.retain(|a| !a.off_hours);
+
+    // Tempo ativo e número de visitas por domínio
+    let mut by_domain: HashMap<String, (i64, usize)> = HashMap::new();
+    for activity in activities.iter().filter(|a| !a.is_idle) {
+        let Some(domain) = activity.url.as_deref().and_then(crate::export::domain_of) else {
+            continue;
+        };
+        let entry = by_domain.entry(domain).or_default();
+        entry.0 += (activity.end_time - activity.start_time).num_seconds();
+        entry.1 += 1;
+    }
+
+    let config = config.lock().map_err(|e| e.to_string())?;
+    let mut stats: Vec<DomainStats> = by_domain
+        .into_iter()
+        .map(|(domain, (total_duration, visits))| DomainStats {
+            label: config.label_for_domain(&domain).map(str::to_string),
+            domain,
+            total_duration,
+            visits,
+        })
+        .collect();
+
+    stats.sort_by(|a, b| b.total_duration.cmp(&a.total_duration));
+    Ok(stats)
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebsiteStats {
+    pub domain: String,
+    pub label: Option<String>,
+    pub category: Option<Category>,
+    pub total_duration: i64,
+    pub visits: usize,
+    // Parcela do tempo ativo em sites no intervalo
+    pub percentage: f64,
+    pub browsers: Vec<String>,
+}
+
+// Tempo por domínio com categoria: a do domínio, se mapeada, senão a da atividade
+// (recategorização ou navegador). A categoria mais frequente no tempo prevalece
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_top_websites(
+    range: TimeRange,
+    limit: Option<usize>,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<Vec<WebsiteStats>, String> {
+    let mut activities = database::get_activities_between(&db, range.start, range.end)
+        .await
+        .map_err(|e| e.to_string())?;
+    activities.retain(|a| !a.off_hours);
+    let config = config.lock().map_err(|e| e.to_string())?;
+
+    struct DomainTotals<'a> {
+        total_duration: i64,
+        visits: usize,
+        by_category: HashMap<Option<&'a str>, i64>,
+        browsers: HashSet<&'a str>,
+    }
+
+    let mut by_domain: HashMap<String, DomainTotals> = HashMap::new();
+    for activity in activities.iter().filter(|a| !a.is_idle) {
+        let Some(domain) = activity.url.as_deref().and_then(crate::export::domain_of) else {
+            continue;
+        };
+        let duration = (activity.end_time - activity.start_time).num_seconds();
+        let category = activity
+            .profile
+            .as_deref()
+            .and_then(|profile| config.category_for_profile(profile, Some(&domain)))
+            .or_else(|| config.category_for_domain(&domain))
+            .or_else(|| config.category_for_activity(activity))
+            .map(|c| c.id.as_str());
+
+        let entry = by_domain.entry(domain).or_insert_with(|| DomainTotals {
+            total_duration: 0,
+            visits: 0,
+            by_category: HashMap::new(),
+            browsers: HashSet::new(),
+        });
+        entry.total_duration += duration;
+        entry.visits += 1;
+        *entry.by_category.entry(category).or_default() += duration;
+        entry.browsers.insert(activity.application.as_str());
+    }
+
+    let web_total: i64 = by_domain.values().map(|d| d.total_duration).sum();
+    let mut websites: Vec<WebsiteStats> = by_domain
+        .into_iter()
+        .map(|(domain, totals)| {
+            let category_id = totals
+                .by_category
+                .into_iter()
+                .max_by(|a, b| a.1.cmp(&b.1))
+                .and_then(|(category_id, _)| category_id);
+            let mut browsers: Vec<String> = totals.browsers.into_iter().map(str::to_string).collect();
+            browsers.sort();
+
+            WebsiteStats {
+                label: config.label_for_domain(&domain).map(str::to_string),
+                category: category_id.and_then(|id| config.categories.iter().find(|c| c.id == id)).cloned(),
+                percentage: if web_total > 0 {
+                    (totals.total_duration as f64 / web_total as f64 * 1000.0).round() / 10.0
+                } else {
+                    0.0
+                },
+                domain,
+                total_duration: totals.total_duration,
+                visits: totals.visits,
+                browsers,
+            }
+        })
+        .collect();
+
+    websites.sort_by(|a, b| b.total_duration.cmp(&a.total_duration).then_with(|| a.domain.cmp(&b.domain)));
+    if let Some(limit) = limit {
+        websites.truncate(limit);
+    }
+    Ok(websites)
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_domain_categories(
+    config: State<'_, SharedConfig>,
+) -> Result<HashMap<String, String>, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.domain_categories.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_domain_category(
+    config: State<'_, SharedConfig>,
+    domain: String,
+    category_id: Option<String>,
+) -> Result<(), String> {
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_domain_category(&domain, category_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_domain_labels(
+    config: State<'_, SharedConfig>,
+) -> Result<HashMap<String, String>, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.domain_labels.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_domain_label(
+    config: State<'_, SharedConfig>,
+    domain: String,
+    label: String,
+) -> Result<(), String> {
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_domain_label(&domain, &label).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Serialize)]
+pub struct BranchStats {
+    pub repository: String,
+    pub branch: String,
+    pub total_duration: i64,
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_branch_stats(
+    range: TimeRange,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<Vec<BranchStats>, String> {
+    let mut activities = database::get_activities_between(&db, range.start, range.end)
+        .await
+        .map_err(|e| e.to_string())?;
+    activities.retain(|a| !a.off_hours);
+    let intervals = database::get_branch_intervals_between(&db, range.start, range.end)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let repositories = config.lock().map_err(|e| e.to_string())?.git_branches.repositories.clone();
+
+    let mut totals: HashMap<(String, String), i64> = HashMap::new();
+    for activity in activities.iter().filter(|a| !a.is_idle) {
+        // Com vários repositórios, só atribui quando o projeto da atividade é um deles
+        let project = activity_project(activity);
+        let repository = match project {
+            Some(project) => repositories
+                .iter()
+                .find(|r| crate::project::project_for_directory(r).as_deref() == Some(project.as_str())),
+            None => None,
+        }
+        .or_else(|| (repositories.len() == 1).then(|| &repositories[0]));
+
+        let Some(repository) = repository else {
+            continue;
+        };
+
+        for interval in intervals.iter().filter(|i| &i.repository == repository) {
+            let overlap_start = activity.start_time.max(interval.start_time);
+            let overlap_end = activity.end_time.min(interval.end_time);
+            let seconds = (overlap_end - overlap_start).num_seconds();
+            if seconds > 0 {
+                *totals
+                    .entry((interval.repository.clone(), interval.branch.clone()))
+                    .or_default() += seconds;
+            }
+        }
+    }
+
+    let mut stats: Vec<BranchStats> = totals
+        .into_iter()
+        .map(|((repository, branch), total_duration)| BranchStats {
+            repository,
+            branch,
+            total_duration,
+        })
+        .collect();
+
+    stats.sort_by(|a, b| b.total_duration.cmp(&a.total_duration));
+    Ok(stats)
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigVersionSummary {
+    pub version: u64,
+    pub saved_at: DateTime<Utc>,
+    pub categories: usize,
+    pub app_rules: usize,
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_config_history() -> Result<Vec<ConfigVersionSummary>, String> {
+    let history = CategoryConfig::history().map_err(|e| e.to_string())?;
+    Ok(history
+        .into_iter()
+        .map(|v| ConfigVersionSummary {
+            version: v.version,
+            saved_at: v.saved_at,
+            categories: v.categories.len(),
+            app_rules: v.app_categories.len(),
+        })
+        .collect())
+}
+
+#[derive(Debug, Serialize)]
+pub struct HistoricalStats {
+    pub config_version: Option<u64>,
+    pub stats: DailyStats,
+}
+
+// Estatísticas do dia avaliadas com a categorização da época (ou de uma versão escolhida)
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_stats_as_of(
+    date: String,
+    config_version: Option<u64>,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<HistoricalStats, String> {
+    let date = DateTime::parse_from_rfc3339(&date)
+        .map_err(|e| e.to_string())?
+        .with_timezone(&Utc);
+
+    let calendar = config.lock().map_err(|e| e.to_string())?.calendar.clone();
+    let (start, end) = calendar.day_bounds(calendar.local_date(date));
+
+    let version = match config_version {
+        Some(version) => Some(
+            CategoryConfig::history()
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .find(|v| v.version == version)
+                .ok_or_else(|| format!("Unknown config version: {}", version))?,
+        ),
+        None => CategoryConfig::version_at(end).map_err(|e| e.to_string())?,
+    };
+
+    let mut activities = database::get_activities_between(&db, start, end)
+        .await
+        .map_err(|e| e.to_string())?;
+    activities.retain(|a| !a.off_hours);
+
+    let historical_config = match &version {
+        Some(version) => version.to_config(),
+        None => config.lock().map_err(|e| e.to_string())?.clone(),
+    };
+
+    Ok(HistoricalStats {
+        config_version: version.map(|v| v.version),
+        stats: compute_daily_stats(activities, &historical_config, None, start, end)?,
     })
-} 
\ No newline at end of file
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn export_org_clock(
+    range: TimeRange,
+    path: String,
+    exclusions: Option<ExportExclusions>,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<usize, String> {
+    let mut activities = database::get_activities_between(&db, range.start, range.end)
+        .await
+        .map_err(|e| e.to_string())?;
+    if let Some(exclusions) = &exclusions {
+        crate::export::redact(&mut activities, exclusions);
+    }
+
+    let (content, entries) = {
+        let config = config.lock().map_err(|e| e.to_string())?;
+        crate::export::org_clock(&activities, &config)
+    };
+
+    std::fs::write(&path, content).map_err(|e| e.to_string())?;
+    info!("Exported {} org clock entries to {}", entries, path);
+    Ok(entries)
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn export_csv(
+    range: TimeRange,
+    path: String,
+    columns: Option<Vec<String>>,
+    exclusions: Option<ExportExclusions>,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<usize, String> {
+    let mut activities = database::get_activities_between(&db, range.start, range.end)
+        .await
+        .map_err(|e| e.to_string())?;
+    if let Some(exclusions) = &exclusions {
+        crate::export::redact(&mut activities, exclusions);
+    }
+
+    let content = {
+        let config = config.lock().map_err(|e| e.to_string())?;
+        crate::export::csv(&activities, &config, columns)?
+    };
+
+    std::fs::write(&path, content).map_err(|e| e.to_string())?;
+    info!("Exported {} activities as CSV to {}", activities.len(), path);
+    Ok(activities.len())
+}
+
+// Lista para revisar antes de exportar; as escolhidas voltam como `exclusions`
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_export_review(
+    range: TimeRange,
+    db: State<'_, DbConnection>,
+) -> Result<Vec<ReviewItem>, String> {
+    let activities = database::get_activities_between(&db, range.start, range.end)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(crate::export::review_items(&activities))
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_export_columns() -> Result<Vec<String>, String> {
+    Ok(crate::export::CSV_COLUMNS.iter().map(|c| c.to_string()).collect())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_rule_suggestions(
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<Vec<crate::suggestions::RuleSuggestion>, String> {
+    let assignments = database::get_category_assignments(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+    let apps = database::get_unique_applications(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(crate::suggestions::suggest_rules(&assignments, &config, &apps))
+}
+
+// Agrupa o tempo sem categoria em blocos parecidos para categorização em lote
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_activity_clusters(
+    range: TimeRange,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<Vec<crate::clustering::ActivityCluster>, String> {
+    let activities = database::get_activities_between(&db, range.start, range.end)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(crate::clustering::cluster_uncategorized(&activities, &config))
+}
+
+// Ticks seguidos com falha antes de suspeitar de permissão negada
+const PERMISSION_FAILURE_TICKS: u32 = 3;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionState {
+    Granted,
+    TitlesUnavailable,
+    WindowAccessFailing,
+    Unknown,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrackerStatus {
+    pub running: bool,
+    pub last_tick: Option<DateTime<Utc>>,
+    pub last_successful_tick: Option<DateTime<Utc>>,
+    pub paused: bool,
+    pub started_at: Option<DateTime<Utc>>,
+    pub restarts: u32,
+    pub last_restart_reason: Option<String>,
+    pub database_path: String,
+    pub database_size_bytes: u64,
+    pub permission_state: PermissionState,
+    // Permissões do macOS ausentes (vazio em outros sistemas)
+    pub missing_permissions: Vec<Permission>,
+    pub errors_last_hour: usize,
+    pub errors_last_day: usize,
+    pub last_error: Option<TrackerErrorRecord>,
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_tracker_status(
+    health: State<'_, SharedTrackerHealth>,
+) -> Result<TrackerStatus, String> {
+    let health = health.lock().map_err(|e| e.to_string())?.clone();
+    let now = Utc::now();
+
+    let database_path = database::get_database_path().map_err(|e| e.to_string())?;
+    let database_size_bytes = std::fs::metadata(&database_path).map(|m| m.len()).unwrap_or(0);
+
+    // Sem acesso à janela ativa, ou só títulos vazios, o tracker "funciona" mas não registra nada útil
+    let permission_state = if health.consecutive_window_errors >= PERMISSION_FAILURE_TICKS {
+        PermissionState::WindowAccessFailing
+    } else if health.consecutive_empty_titles >= PERMISSION_FAILURE_TICKS {
+        PermissionState::TitlesUnavailable
+    } else if health.last_successful_tick.is_some() {
+        PermissionState::Granted
+    } else {
+        PermissionState::Unknown
+    };
+
+    Ok(TrackerStatus {
+        running: health
+            .last_tick
+            .map_or(false, |tick| (now - tick).num_seconds() <= 60),
+        last_tick: health.last_tick,
+        last_successful_tick: health.last_successful_tick,
+        paused: health.paused,
+        started_at: health.started_at,
+        restarts: health.restarts,
+        last_restart_reason: health.last_restart_reason,
+        database_path: database_path.to_string_lossy().to_string(),
+        database_size_bytes,
+        permission_state,
+        missing_permissions: permissions::missing_permissions(),
+        errors_last_hour: health.errors_since(now - Duration::hours(1)),
+        errors_last_day: health.errors_since(now - Duration::days(1)),
+        last_error: health.recent_errors.back().cloned(),
+    })
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_tracking_paused(
+    health: State<'_, SharedTrackerHealth>,
+    paused: bool,
+) -> Result<(), String> {
+    let mut health = health.lock().map_err(|e| e.to_string())?;
+    info!("Tracking {}", if paused { "paused" } else { "resumed" });
+    health.paused = paused;
+    Ok(())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn start_high_frequency_session(
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+    health: State<'_, SharedTrackerHealth>,
+    state: State<'_, SharedHighFrequency>,
+    duration_minutes: i64,
+    label: Option<String>,
+) -> Result<ActiveSession, String> {
+    if !(1..=high_frequency::MAX_SESSION_MINUTES).contains(&duration_minutes) {
+        return Err(format!(
+            "Duration must be between 1 and {} minutes",
+            high_frequency::MAX_SESSION_MINUTES
+        ));
+    }
+    if state.lock().map_err(|e| e.to_string())?.is_some() {
+        return Err("A high-frequency session is already running".to_string());
+    }
+    if health.lock().map_err(|e| e.to_string())?.paused {
+        return Err("Tracking is paused".to_string());
+    }
+
+    let started_at = Utc::now();
+    let ends_at = started_at + Duration::minutes(duration_minutes);
+    let id = database::create_high_frequency_session(&db, label.as_deref(), started_at, ends_at)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let session = ActiveSession { id, label, started_at, ends_at, finished: Default::default() };
+    *state.lock().map_err(|e| e.to_string())? = Some(session.clone());
+
+    let db = db.inner().clone();
+    let config = config.inner().clone();
+    let health = health.inner().clone();
+    let state = state.inner().clone();
+    let running = session.clone();
+    tauri::async_runtime::spawn(async move {
+        high_frequency::run_session(db, config, health, state, running).await;
+    });
+
+    Ok(session)
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn stop_high_frequency_session(
+    state: State<'_, SharedHighFrequency>,
+) -> Result<(), String> {
+    // O loop percebe a sessão removida, grava o que falta e fecha o registro
+    state.lock().map_err(|e| e.to_string())?.take();
+    Ok(())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_high_frequency_status(
+    state: State<'_, SharedHighFrequency>,
+) -> Result<Option<ActiveSession>, String> {
+    Ok(state.lock().map_err(|e| e.to_string())?.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_high_frequency_sessions(
+    db: State<'_, DbConnection>,
+) -> Result<Vec<HighFrequencySession>, String> {
+    database::get_high_frequency_sessions(&db)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_high_frequency_samples(
+    db: State<'_, DbConnection>,
+    session_id: i64,
+) -> Result<Vec<HighFrequencySample>, String> {
+    database::get_high_frequency_samples(&db, session_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn delete_high_frequency_session(
+    db: State<'_, DbConnection>,
+    state: State<'_, SharedHighFrequency>,
+    session_id: i64,
+) -> Result<usize, String> {
+    let is_running = state
+        .lock()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+        .map_or(false, |s| s.id == session_id);
+    if is_running {
+        return Err("Stop the session before deleting it".to_string());
+    }
+
+    database::delete_high_frequency_session(&db, session_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_permission_status() -> Result<Vec<PermissionInfo>, String> {
+    Ok(permissions::ALL_PERMISSIONS.iter().map(|p| p.info()).collect())
+}
+
+// Mostra o prompt do sistema e, se a permissão continuar negada, abre os Ajustes do Sistema
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn request_permission(permission: Permission) -> Result<PermissionInfo, String> {
+    let status = permissions::request(permission);
+    if status == PermissionStatus::Denied || status == PermissionStatus::NotDetermined {
+        info!("Opening system settings for {:?}", permission);
+        permissions::open_settings(permission).map_err(|e| e.to_string())?;
+    }
+    Ok(permission.info())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn open_permission_settings(permission: Permission) -> Result<(), String> {
+    permissions::open_settings(permission).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn add_adjustment(
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+    date: NaiveDate,
+    category: String,
+    minutes: i64,
+    note: String,
+) -> Result<Adjustment, String> {
+    if minutes == 0 {
+        return Err("Adjustment must add or remove at least one minute".to_string());
+    }
+    if note.trim().is_empty() {
+        return Err("Adjustments need a note explaining the correction".to_string());
+    }
+
+    let category_exists = config
+        .lock()
+        .map_err(|e| e.to_string())?
+        .categories
+        .iter()
+        .any(|c| c.id == category);
+    if !category_exists {
+        return Err(format!("Unknown category: {}", category));
+    }
+
+    database::add_adjustment(&db, date, &category, minutes, note.trim())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_adjustments(
+    db: State<'_, DbConnection>,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> Result<Vec<Adjustment>, String> {
+    database::get_adjustments_between(&db, start_date, end_date)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn delete_adjustment(
+    db: State<'_, DbConnection>,
+    id: i64,
+) -> Result<bool, String> {
+    database::delete_adjustment(&db, id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_tray_config(
+    config: State<'_, SharedConfig>,
+) -> Result<TrayConfig, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.tray.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_tray_config(
+    app: tauri::AppHandle,
+    config: State<'_, SharedConfig>,
+    tray: TrayConfig,
+) -> Result<(), String> {
+    if !(1..=30).contains(&tray.bar_length) {
+        return Err("Bar length must be between 1 and 30".to_string());
+    }
+
+    config.lock().map_err(|e| e.to_string())?
+        .set_tray(tray)
+        .map_err(|e| e.to_string())?;
+    crate::menu::update_tray_menu(&app).await
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_schedule_config(
+    config: State<'_, SharedConfig>,
+) -> Result<ScheduleConfig, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.schedule.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_schedule_config(
+    config: State<'_, SharedConfig>,
+    schedule: ScheduleConfig,
+) -> Result<(), String> {
+    if schedule.end <= schedule.start {
+        return Err("Schedule end must be after its start".to_string());
+    }
+
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_schedule(schedule)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_retention_config(
+    config: State<'_, SharedConfig>,
+) -> Result<RetentionConfig, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.retention.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_retention_config(
+    config: State<'_, SharedConfig>,
+    retention: RetentionConfig,
+) -> Result<(), String> {
+    if retention.activity_days == 0 {
+        return Err("Retention period must be at least one day".to_string());
+    }
+
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_retention(retention)
+        .map_err(|e| e.to_string())
+}
+
+// Execução manual usa o período configurado mesmo com a limpeza automática desligada
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn run_retention_purge(
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<database::PurgeResult, String> {
+    let retention = config.lock().map_err(|e| e.to_string())?.retention.clone();
+    retention::purge(&db, &retention)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// Limite de dias por consulta de cobertura
+const MAX_COVERAGE_DAYS: i64 = 366;
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_data_coverage(
+    range: TimeRange,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<Vec<DayCoverage>, String> {
+    let (calendar, schedule) = {
+        let config = config.lock().map_err(|e| e.to_string())?;
+        (config.calendar.clone(), config.schedule.clone())
+    };
+
+    let first_day = calendar.local_date(range.start);
+    let last_day = calendar.local_date(range.end);
+    if (last_day - first_day).num_days() > MAX_COVERAGE_DAYS {
+        return Err(format!("Coverage is limited to {} days per request", MAX_COVERAGE_DAYS));
+    }
+
+    let (start, _) = calendar.day_bounds(first_day);
+    let (_, end) = calendar.day_bounds(last_day);
+    let activities = database::get_activities_between(&db, start, end)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let now = Utc::now();
+    Ok(first_day
+        .iter_days()
+        .take_while(|day| *day <= last_day)
+        .map(|day| {
+            let (day_start, day_end) = calendar.day_bounds(day);
+            let day_activities: Vec<WindowActivity> = activities
+                .iter()
+                .filter(|a| a.start_time >= day_start && a.start_time <= day_end)
+                .cloned()
+                .collect();
+            coverage::day_coverage(day, &day_activities, &schedule, &calendar, now)
+        })
+        .collect())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_status_line(
+    app: tauri::AppHandle,
+    config: State<'_, SharedConfig>,
+) -> Result<String, String> {
+    let state = crate::menu::get_tray_state(&app).await;
+    let tray = config.lock().map_err(|e| e.to_string())?.tray.clone();
+    Ok(crate::status_line::status_line(&state, &tray))
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn start_focus_session(
+    app: tauri::AppHandle,
+    minutes: i64,
+    label: Option<String>,
+) -> Result<FocusSession, String> {
+    if !(1..=480).contains(&minutes) {
+        return Err("Focus sessions must last between 1 and 480 minutes".to_string());
+    }
+
+    let label = label.unwrap_or_else(|| "Focus".to_string());
+    let session = focus::start(&app, minutes, label, FocusOptions::default())?;
+    crate::menu::update_tray_menu(&app).await?;
+    Ok(session)
+}
+
+// Sessão de foco que avisa na hora ao abrir um app de categoria improdutiva;
+// duration em minutos
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn start_focus_mode(
+    app: tauri::AppHandle,
+    duration: i64,
+    label: Option<String>,
+    bring_to_front: Option<bool>,
+) -> Result<FocusSession, String> {
+    if !(1..=480).contains(&duration) {
+        return Err("Focus sessions must last between 1 and 480 minutes".to_string());
+    }
+
+    let options = FocusOptions {
+        distraction_alerts: true,
+        bring_to_front: bring_to_front.unwrap_or(false),
+    };
+    let session = focus::start(&app, duration, label.unwrap_or_else(|| "Focus".to_string()), options)?;
+    crate::menu::update_tray_menu(&app).await?;
+    Ok(session)
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn stop_focus_session(app: tauri::AppHandle) -> Result<Option<FocusSession>, String> {
+    let session = focus::stop(&app)?;
+    if let Some(session) = session.as_ref() {
+        focus::record_summary(&app, session, Utc::now()).await;
+    }
+    crate::menu::update_tray_menu(&app).await?;
+    Ok(session)
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_focus_session(app: tauri::AppHandle) -> Result<Option<FocusSession>, String> {
+    Ok(focus::current(&app))
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_focus_summaries(
+    db: State<'_, DbConnection>,
+    limit: Option<usize>,
+) -> Result<Vec<FocusSummary>, String> {
+    database::get_focus_summaries(&db, limit.unwrap_or(20).min(500))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_queued_notifications(
+    queue: State<'_, SharedNotificationQueue>,
+) -> Result<Vec<QueuedNotification>, String> {
+    Ok(queue.lock().map_err(|e| e.to_string())?.pending())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn backup_database(
+    db: State<'_, DbConnection>,
+    path: String,
+) -> Result<BackupInfo, String> {
+    backup::backup(&db, Path::new(&path))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn restore_database(
+    app: tauri::AppHandle,
+    db: State<'_, DbConnection>,
+    health: State<'_, SharedTrackerHealth>,
+    tracker: State<'_, TrackerHandle>,
+    path: String,
+) -> Result<RestoreInfo, String> {
+    let info = backup::restore(&db, &health, &tracker, Path::new(&path))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Resumos e estatísticas de hoje vêm do banco antigo
+    if let Err(e) = rollups::rebuild(&db).await {
+        error!("Failed to rebuild hourly rollups after restore: {}", e);
+    }
+    tokio::spawn(crate::stats_cache::backfill(app));
+
+    Ok(info)
+}
+
+#[derive(Debug, Serialize)]
+pub struct CategoryTime {
+    pub category_id: Option<String>,
+    pub name: String,
+    pub color: Option<String>,
+    pub is_productive: bool,
+    pub active_seconds: i64,
+}
+
+// Dias de histórico da sequência diária enviados ao widget
+const OVERVIEW_STREAK_DAYS: i64 = 7;
+
+// Tudo que o widget/popup da bandeja mostra, em uma chamada só
+#[derive(Debug, Serialize)]
+pub struct GoalsOverview {
+    pub date: NaiveDate,
+    pub tracked_seconds: i64,
+    pub productive_seconds: i64,
+    pub daily_goal_minutes: i64,
+    pub daily_percentage: i64,
+    pub weekly_goal: Option<WeeklyGoalProgress>,
+    pub category_limits: Vec<CategoryLimitProgress>,
+    pub streaks: crate::streaks::Streaks,
+    pub categories: Vec<CategoryTime>,
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_goals_overview(
+    app: tauri::AppHandle,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<GoalsOverview, String> {
+    // Metas diária e semanal e os limites por categoria já são calculados para a bandeja
+    let tray = crate::menu::get_tray_state(&app).await;
+
+    let config = config.lock().map_err(|e| e.to_string())?.clone();
+    let streaks = crate::streaks::read_streaks(&db, &config, OVERVIEW_STREAK_DAYS)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let calendar = &config.calendar;
+    let today = calendar.logical_date(Utc::now());
+    let (start, end) = calendar.day_bounds(today);
+    let totals = database::get_app_totals_between(&db, start, end, false)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut by_category: HashMap<Option<String>, CategoryTime> = HashMap::new();
+    for total in totals {
+        let category = config.get_category_for_app(&total.application);
+        by_category
+            .entry(category.map(|c| c.id.clone()))
+            .or_insert_with(|| CategoryTime {
+                category_id: category.map(|c| c.id.clone()),
+                name: category.map_or("Uncategorized".to_string(), |c| c.name.clone()),
+                color: category.map(|c| c.color.clone()),
+                is_productive: category.map_or(false, |c| c.is_productive),
+                active_seconds: 0,
+            })
+            .active_seconds += total.total_duration - total.idle_duration;
+    }
+
+    let mut categories: Vec<CategoryTime> = by_category.into_values().collect();
+    categories.sort_by(|a, b| b.active_seconds.cmp(&a.active_seconds));
+
+    Ok(GoalsOverview {
+        date: today,
+        tracked_seconds: tray.tracked_seconds,
+        productive_seconds: tray.productive_seconds,
+        daily_goal_minutes: tray.daily_goal_minutes,
+        daily_percentage: tray.goal_percentage,
+        weekly_goal: tray.weekly_goal,
+        category_limits: tray.category_limits,
+        streaks,
+        categories,
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub struct CategoryStats {
+    pub category_id: Option<String>,
+    pub name: String,
+    pub color: Option<String>,
+    pub is_productive: bool,
+    pub neutral: bool,
+    pub total_duration: i64,
+    pub idle_duration: i64,
+    // Tempo ativo, já com os ajustes manuais da categoria
+    pub active_duration: i64,
+    // Parcela do tempo ativo total do intervalo
+    pub percentage: f64,
+    pub application_count: usize,
+}
+
+// Tempo por categoria no intervalo, para gráficos de pizza. Usa os resumos por
+// hora, então serve para intervalos longos; apps sem categoria ficam juntos
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_category_stats(
+    range: TimeRange,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<Vec<CategoryStats>, String> {
+    let totals = database::get_app_totals_from_rollups(&db, range.start, range.end)
+        .await
+        .map_err(|e| e.to_string())?;
+    let calendar = config.lock().map_err(|e| e.to_string())?.calendar.clone();
+    let adjustments = database::get_adjustments_between(
+        &db,
+        calendar.logical_date(range.start),
+        calendar.logical_date(range.end),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    let mut overrides = database::get_override_totals(&db, range.start, range.end, false)
+        .await
+        .map_err(|e| e.to_string())?;
+    let profiles = database::get_profile_totals(&db, range.start, range.end, false)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let config = config.lock().map_err(|e| e.to_string())?;
+    overrides.extend(profile_overrides(&config, &profiles));
+    let new_entry = |category: Option<&Category>| CategoryStats {
+        category_id: category.map(|c| c.id.clone()),
+        name: category.map_or("Uncategorized".to_string(), |c| c.name.clone()),
+        color: category.map(|c| c.color.clone()),
+        is_productive: category.map_or(false, |c| c.is_productive),
+        neutral: category.map_or(false, |c| c.neutral),
+        total_duration: 0,
+        idle_duration: 0,
+        active_duration: 0,
+        percentage: 0.0,
+        application_count: 0,
+    };
+
+    let mut by_category: HashMap<Option<String>, CategoryStats> = HashMap::new();
+    for total in totals {
+        let category = config.get_category_for_app(&total.application);
+        let entry = by_category
+            .entry(category.map(|c| c.id.clone()))
+            .or_insert_with(|| new_entry(category));
+        entry.total_duration += total.total_duration;
+        entry.idle_duration += total.idle_duration;
+        entry.active_duration += total.total_duration - total.idle_duration;
+        entry.application_count += 1;
+    }
+
+    for adjustment in &adjustments {
+        let category = config.categories.iter().find(|c| c.id == adjustment.category_id);
+        let entry = by_category
+            .entry(category.map(|c| c.id.clone()))
+            .or_insert_with(|| new_entry(category));
+        entry.total_duration += adjustment.minutes * 60;
+        entry.active_duration += adjustment.minutes * 60;
+    }
+
+    // Tempo recategorizado sai da categoria do app e entra na do override
+    for moved in &overrides {
+        let from = config.get_category_for_app(&moved.application);
+        let to = config.categories.iter().find(|c| c.id == moved.category_id);
+        for (category, sign) in [(from, -1), (to, 1)] {
+            let entry = by_category
+                .entry(category.map(|c| c.id.clone()))
+                .or_insert_with(|| new_entry(category));
+            entry.total_duration += sign * moved.active_seconds;
+            entry.active_duration += sign * moved.active_seconds;
+        }
+    }
+
+    let mut categories: Vec<CategoryStats> = by_category
+        .into_values()
+        .filter(|c| c.total_duration > 0 || c.active_duration > 0)
+        .collect();
+    let active_total: i64 = categories.iter().map(|c| c.active_duration.max(0)).sum();
+    for category in &mut categories {
+        category.active_duration = category.active_duration.max(0);
+        category.percentage = if active_total > 0 {
+            (category.active_duration as f64 / active_total as f64 * 1000.0).round() / 10.0
+        } else {
+            0.0
+        };
+    }
+    categories.sort_by(|a, b| b.active_duration.cmp(&a.active_duration));
+
+    Ok(categories)
+}
+
+#[derive(Debug, Serialize)]
+pub struct PeriodSummary {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub total_time: i64,
+    pub productive_time: i64,
+    pub idle_time: i64,
+}
+
+// Tempo ativo de um app ou categoria nos dois períodos; delta = a - b.
+// Em apps, category_id é a categoria do app; percent_change é None quando b = 0
+#[derive(Debug, Serialize)]
+pub struct PeriodDelta {
+    pub name: String,
+    pub category_id: Option<String>,
+    pub a: i64,
+    pub b: i64,
+    pub delta: i64,
+    pub percent_change: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PeriodComparison {
+    pub a: PeriodSummary,
+    pub b: PeriodSummary,
+    pub total_delta: i64,
+    pub productive_delta: i64,
+    pub idle_delta: i64,
+    pub applications: Vec<PeriodDelta>,
+    pub categories: Vec<PeriodDelta>,
+}
+
+struct PeriodTotals {
+    summary: PeriodSummary,
+    applications: HashMap<String, i64>,
+    categories: HashMap<Option<String>, i64>,
+}
+
+// Mesmas fontes de get_category_stats: resumos por hora, ajustes e recategorizações
+async fn period_totals(db: &DbConnection, config: &CategoryConfig, range: &TimeRange) -> Result<PeriodTotals, String> {
+    let totals = database::get_app_totals_from_rollups(db, range.start, range.end)
+        .await
+        .map_err(|e| e.to_string())?;
+    let adjustments = database::get_adjustments_between(
+        db,
+        config.calendar.logical_date(range.start),
+        config.calendar.logical_date(range.end),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    let mut overrides = database::get_override_totals(db, range.start, range.end, false)
+        .await
+        .map_err(|e| e.to_string())?;
+    let profiles = database::get_profile_totals(db, range.start, range.end, false)
+        .await
+        .map_err(|e| e.to_string())?;
+    overrides.extend(profile_overrides(config, &profiles));
+
+    let is_productive = |category_id: Option<&str>| {
+        category_id.map_or(false, |id| config.categories.iter().any(|c| c.id == id && c.is_productive))
+    };
+    let mut period = PeriodTotals {
+        summary: PeriodSummary {
+            start: range.start,
+            end: range.end,
+            total_time: 0,
+            productive_time: 0,
+            idle_time: 0,
+        },
+        applications: HashMap::new(),
+        categories: HashMap::new(),
+    };
+
+    for total in &totals {
+        let active = total.total_duration - total.idle_duration;
+        let category_id = config.get_category_for_app(&total.application).map(|c| c.id.clone());
+        period.summary.total_time += total.total_duration;
+        period.summary.idle_time += total.idle_duration;
+        if is_productive(category_id.as_deref()) {
+            period.summary.productive_time += active;
+        }
+        *period.applications.entry(total.application.clone()).or_default() += active;
+        *period.categories.entry(category_id).or_default() += active;
+    }
+    for adjustment in &adjustments {
+        let seconds = adjustment.minutes * 60;
+        period.summary.total_time += seconds;
+        if is_productive(Some(&adjustment.category_id)) {
+            period.summary.productive_time += seconds;
+        }
+        *period.categories.entry(Some(adjustment.category_id.clone())).or_default() += seconds;
+    }
+    for moved in &overrides {
+        let from = config.get_category_for_app(&moved.application).map(|c| c.id.clone());
+        *period.categories.entry(from).or_default() -= moved.active_seconds;
+        *period.categories.entry(Some(moved.category_id.clone())).or_default() += moved.active_seconds;
+    }
+    period.summary.productive_time += override_productive_delta(config, &overrides);
+    period.summary.total_time = period.summary.total_time.max(0);
+    period.summary.productive_time = period.summary.productive_time.max(0);
+
+    Ok(period)
+}
+
+fn period_deltas<K: std::hash::Hash + Eq + Clone>(
+    a: &HashMap<K, i64>,
+    b: &HashMap<K, i64>,
+    describe: impl Fn(&K) -> (String, Option<String>),
+) -> Vec<PeriodDelta> {
+    let mut keys: Vec<&K> = a.keys().collect();
+    keys.extend(b.keys().filter(|key| !a.contains_key(*key)));
+
+    let mut deltas: Vec<PeriodDelta> = keys
+        .into_iter()
+        .map(|key| {
+            let (name, category_id) = describe(key);
+            let a = a.get(key).copied().unwrap_or(0).max(0);
+            let b = b.get(key).copied().unwrap_or(0).max(0);
+            PeriodDelta {
+                name,
+                category_id,
+                a,
+                b,
+                delta: a - b,
+                percent_change: (b > 0).then(|| ((a - b) as f64 / b as f64 * 1000.0).round() / 10.0),
+            }
+        })
+        .filter(|d| d.a > 0 || d.b > 0)
+        .collect();
+    // Maiores variações primeiro, para cima ou para baixo
+    deltas.sort_by(|x, y| y.delta.abs().cmp(&x.delta.abs()).then_with(|| x.name.cmp(&y.name)));
+    deltas
+}
+
+fn app_and_category_deltas(
+    config: &CategoryConfig,
+    a: &PeriodTotals,
+    b: &PeriodTotals,
+) -> (Vec<PeriodDelta>, Vec<PeriodDelta>) {
+    let applications = period_deltas(&a.applications, &b.applications, |app| {
+        (app.clone(), config.get_category_for_app(app).map(|c| c.id.clone()))
+    });
+    let categories = period_deltas(&a.categories, &b.categories, |category_id| {
+        let category = category_id
+            .as_deref()
+            .and_then(|id| config.categories.iter().find(|c| c.id == id));
+        (
+            category.map_or("Uncategorized".to_string(), |c| c.name.clone()),
+            category_id.clone(),
+        )
+    });
+    (applications, categories)
+}
+
+// Ex.: esta semana (a) contra a anterior (b): +2h Development, -1h Social Media
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn compare_periods(
+    range_a: TimeRange,
+    range_b: TimeRange,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<PeriodComparison, String> {
+    let config = config.lock().map_err(|e| e.to_string())?.clone();
+    let a = period_totals(&db, &config, &range_a).await?;
+    let b = period_totals(&db, &config, &range_b).await?;
+    let (applications, categories) = app_and_category_deltas(&config, &a, &b);
+
+    Ok(PeriodComparison {
+        total_delta: a.summary.total_time - b.summary.total_time,
+        productive_delta: a.summary.productive_time - b.summary.productive_time,
+        idle_delta: a.summary.idle_time - b.summary.idle_time,
+        a: a.summary,
+        b: b.summary,
+        applications,
+        categories,
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub struct AppDiff {
+    pub applications: Vec<PeriodDelta>,
+    pub categories: Vec<PeriodDelta>,
+}
+
+// "O que mudou desde a última sprint": só as variações por app e categoria,
+// ordenadas pela maior mudança absoluta (delta = a - b)
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_app_diff(
+    range_a: TimeRange,
+    range_b: TimeRange,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<AppDiff, String> {
+    let config = config.lock().map_err(|e| e.to_string())?.clone();
+    let a = period_totals(&db, &config, &range_a).await?;
+    let b = period_totals(&db, &config, &range_b).await?;
+    let (applications, categories) = app_and_category_deltas(&config, &a, &b);
+    Ok(AppDiff { applications, categories })
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn run_self_test(
+    app: tauri::AppHandle,
+    send_notification: Option<bool>,
+) -> Result<crate::self_test::SelfTestReport, String> {
+    Ok(crate::self_test::run(&app, send_notification.unwrap_or(true)).await)
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn export_archive(
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+    path: String,
+) -> Result<crate::archive::ArchiveExport, String> {
+    let config = config.lock().map_err(|e| e.to_string())?.clone();
+    crate::archive::export(&db, config, Path::new(&path))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+#[instrument(skip_all)]
+pub async fn import_archive(
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+    path: String,
+    replace_settings: Option<bool>,
+    dry_run: Option<bool>,
+) -> Result<crate::archive::ArchiveImport, String> {
+    // Como no import, por padrão só mostra o que seria feito
+    let dry_run = dry_run.unwrap_or(true);
+    let archive = crate::archive::read(Path::new(&path)).map_err(|e| e.to_string())?;
+
+    // A configuração é mesclada numa cópia e só substitui a atual depois de gravar as linhas
+    let mut merged = config.lock().map_err(|e| e.to_string())?.clone();
+    let summary = crate::archive::import(&db, &mut merged, archive, replace_settings.unwrap_or(false), dry_run)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !dry_run {
+        merged.save().map_err(|e| e.to_string())?;
+        *config.lock().map_err(|e| e.to_string())? = merged;
+        rollups::rebuild(&db).await.map_err(|e| e.to_string())?;
+    }
+    Ok(summary)
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_time_for_pattern(
+    db: State<'_, DbConnection>,
+    pattern: String,
+    range: TimeRange,
+) -> Result<database::PatternTime, String> {
+    if pattern.trim().is_empty() {
+        return Err("Pattern cannot be empty".to_string());
+    }
+
+    database::get_time_for_pattern(&db, pattern.trim(), range.start, range.end)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_screenshot_config(
+    config: State<'_, SharedConfig>,
+) -> Result<ScreenshotConfig, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.screenshots.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_screenshot_config(
+    config: State<'_, SharedConfig>,
+    screenshots: ScreenshotConfig,
+) -> Result<(), String> {
+    if screenshots.interval_minutes == 0 || screenshots.retention_days == 0 {
+        return Err("Interval and retention must be at least 1".to_string());
+    }
+    if !(64..=1280).contains(&screenshots.max_width) {
+        return Err("Thumbnail width must be between 64 and 1280 pixels".to_string());
+    }
+
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_screenshots(screenshots)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_screenshots(
+    range: TimeRange,
+    db: State<'_, DbConnection>,
+) -> Result<Vec<database::Screenshot>, String> {
+    database::get_screenshots_between(&db, range.start, range.end)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn delete_screenshot(
+    db: State<'_, DbConnection>,
+    id: i64,
+) -> Result<bool, String> {
+    let path = database::delete_screenshot(&db, id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if let Some(path) = &path {
+        if let Err(e) = std::fs::remove_file(path) {
+            error!("Failed to remove screenshot file {}: {}", path, e);
+        }
+    }
+    Ok(path.is_some())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_daily_review(
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+    date: NaiveDate,
+) -> Result<crate::review::DailyReview, String> {
+    let calendar = config.lock().map_err(|e| e.to_string())?.calendar.clone();
+    let (start, end) = calendar.day_bounds(date);
+    let activities = database::get_activities_between(&db, start, end)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(crate::review::build(date, start, end, &activities, &config))
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn submit_daily_review(
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+    date: NaiveDate,
+    resolutions: Vec<ReviewResolution>,
+) -> Result<ReviewResult, String> {
+    let mut categorize = Vec::new();
+    let mut labels = Vec::new();
+    let mut merges = Vec::new();
+    {
+        let config = config.lock().map_err(|e| e.to_string())?;
+        let (day_start, day_end) = config.calendar.day_bounds(date);
+        let category_exists = |id: &str| config.categories.iter().any(|c| c.id == id);
+
+        for resolution in resolutions {
+            match resolution {
+                ReviewResolution::CategorizeApp { application, category_id } => {
+                    if !category_exists(&category_id) {
+                        return Err(format!("Unknown category: {}", category_id));
+                    }
+                    categorize.push((application, category_id));
+                }
+                ReviewResolution::LabelIdle { start, end, category_id, note } => {
+                    if !category_exists(&category_id) {
+                        return Err(format!("Unknown category: {}", category_id));
+                    }
+                    let minutes = (end - start).num_minutes();
+                    if minutes > 0 {
+                        let note = match note.trim() {
+                            "" => "Daily review".to_string(),
+                            note => note.to_string(),
+                        };
+                        labels.push((category_id, minutes, note));
+                    }
+                }
+                ReviewResolution::Merge { start, end, application } => {
+                    if end <= start {
+                        return Err("Merge range must end after it starts".to_string());
+                    }
+                    if start < day_start || end > day_end {
+                        return Err(format!("Merge range is outside {}", date));
+                    }
+                    merges.push((start, end, application));
+                }
+            }
+        }
+    }
+
+    let merged_activities = database::apply_review(&db, date, &labels, &merges)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Some(since) = merges.iter().map(|(start, _, _)| start.timestamp()).min() {
+        database::refresh_hourly_rollups(&db, since, Utc::now())
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    // Categorias ficam no arquivo de configuração, fora da transação do banco: só
+    // depois do commit, e gravadas de uma vez
+    if !categorize.is_empty() {
+        let mut config = config.lock().map_err(|e| e.to_string())?;
+        for (application, category_id) in &categorize {
+            config.app_categories.insert(application.clone(), category_id.clone());
+        }
+        config.save().map_err(|e| e.to_string())?;
+    }
+
+    Ok(ReviewResult {
+        categorized_apps: categorize.len(),
+        labeled_minutes: labels.iter().map(|(_, minutes, _)| minutes).sum(),
+        merged_activities,
+    })
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_encryption_status() -> Result<crate::encryption::EncryptionStatus, String> {
+    Ok(crate::encryption::status())
+}
+
+// Converte o chronos.db atual para SQLCipher (ou de volta); o tracker fica pausado
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_database_encryption(
+    db: State<'_, DbConnection>,
+    health: State<'_, SharedTrackerHealth>,
+    enabled: bool,
+) -> Result<(), String> {
+    let was_paused = {
+        let mut health = health.lock().map_err(|e| e.to_string())?;
+        std::mem::replace(&mut health.paused, true)
+    };
+
+    let result = if enabled {
+        crate::encryption::enable(&db).await
+    } else {
+        crate::encryption::disable(&db).await
+    };
+
+    health.lock().map_err(|e| e.to_string())?.paused = was_paused;
+    result.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_database_location() -> Result<crate::db_location::DatabaseLocation, String> {
+    crate::db_location::current().map_err(|e| e.to_string())
+}
+
+// path = None volta para o local padrão
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_database_location(
+    app: tauri::AppHandle,
+    db: State<'_, DbConnection>,
+    health: State<'_, SharedTrackerHealth>,
+    tracker: State<'_, TrackerHandle>,
+    path: Option<String>,
+    mode: crate::db_location::RelocateMode,
+) -> Result<crate::db_location::DatabaseLocation, String> {
+    let location = crate::db_location::relocate(&db, &health, &tracker, path.as_deref().map(Path::new), mode)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Um banco que já existia tem outros dados: resumos e estatísticas de hoje vêm do anterior
+    if mode == crate::db_location::RelocateMode::UseExisting {
+        if let Err(e) = rollups::rebuild(&db).await {
+            error!("Failed to rebuild hourly rollups after switching database: {}", e);
+        }
+        tokio::spawn(crate::stats_cache::backfill(app));
+    }
+
+    Ok(location)
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_allocation_config(
+    config: State<'_, SharedConfig>,
+) -> Result<AllocationConfig, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.allocation.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_allocation_config(
+    config: State<'_, SharedConfig>,
+    allocation: AllocationConfig,
+) -> Result<(), String> {
+    if allocation.weekly_targets.values().any(|minutes| *minutes <= 0) {
+        return Err("Project targets must be positive".to_string());
+    }
+
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_allocation(allocation)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_project_allocation(
+    week: DateTime<Utc>,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<crate::allocation::ProjectAllocation, String> {
+    crate::allocation::allocation(&db, config.inner(), week)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_category_limits(
+    config: State<'_, SharedConfig>,
+) -> Result<Vec<CategoryLimit>, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.category_limits.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_category_limits(
+    config: State<'_, SharedConfig>,
+    limits: Vec<CategoryLimit>,
+) -> Result<(), String> {
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_category_limits(limits)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_project_patterns(
+    config: State<'_, SharedConfig>,
+) -> Result<Vec<ProjectPattern>, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.project_patterns.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_project_patterns(
+    config: State<'_, SharedConfig>,
+    patterns: Vec<ProjectPattern>,
+) -> Result<(), String> {
+    if patterns.iter().any(|p| p.prefix.trim().is_empty() || p.project.trim().is_empty()) {
+        return Err("Patterns need both a directory prefix and a project name".to_string());
+    }
+
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_project_patterns(patterns)
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeleteActivitiesResult {
+    pub matched: usize,
+    pub deleted: bool,
+}
+
+// Por padrão só conta o que seria apagado; a exclusão exige dry_run = false
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn delete_activities(
+    app: tauri::AppHandle,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+    tracker: State<'_, TrackerHandle>,
+    range: Option<TimeRange>,
+    application: Option<String>,
+    dry_run: Option<bool>,
+) -> Result<DeleteActivitiesResult, String> {
+    let application = application.map(|a| a.trim().to_string()).filter(|a| !a.is_empty());
+    if range.is_none() && application.is_none() {
+        return Err("Provide a time range, an application or both".to_string());
+    }
+    if let Some(range) = &range {
+        if range.end <= range.start {
+            return Err("End time must be after start time".to_string());
+        }
+    }
+
+    let dry_run = dry_run.unwrap_or(true);
+    let (start, end) = (range.as_ref().map(|r| r.start), range.as_ref().map(|r| r.end));
+    if !dry_run {
+        // O buffer e o evento em andamento vão para o banco e entram na exclusão, em
+        // vez de serem gravados de novo depois dela
+        tracker.reset().await;
+    }
+    let result = database::delete_activities(&db, start, end, application.as_deref(), dry_run)
+        .await
+        .map_err(|e| e.to_string())?;
+    let matched = result.matched;
+
+    if !dry_run {
+        for path in &result.screenshot_paths {
+            if let Err(e) = std::fs::remove_file(path) {
+                error!("Failed to remove screenshot {}: {}", path, e);
+            }
+        }
+        // Também com o diário desligado: arquivos antigos podem ter o que foi apagado
+        let config = config.lock().map_err(|e| e.to_string())?.clone();
+        match crate::journal::purge(&config, start, end, application.as_deref()) {
+            Ok(removed) if removed > 0 => info!("Removed {} deleted activities from the journal", removed),
+            Ok(_) => {}
+            Err(e) => error!("Failed to remove deleted activities from the journal: {}", e),
+        }
+    }
+
+    let deleted = !dry_run && matched > 0;
+    if deleted {
+        info!("🗑️ Deleted {} activities", matched);
+        if let Some(since) = result.earliest {
+            if let Err(e) = database::refresh_hourly_rollups(&db, since, Utc::now()).await {
+                error!("Failed to refresh hourly rollups after delete: {}", e);
+            }
+        }
+        tokio::spawn(crate::stats_cache::backfill(app));
+    }
+
+    Ok(DeleteActivitiesResult { matched, deleted })
+}
+
+// Correção manual de uma atividade gravada (horários, título ou ociosidade)
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn update_activity(
+    app: tauri::AppHandle,
+    db: State<'_, DbConnection>,
+    id: i64,
+    update: database::ActivityUpdate,
+) -> Result<WindowActivity, String> {
+    let (before, after) = database::update_activity(&db, id, &update)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Activity {} not found", id))?;
+
+    // Os resumos são por hora de início; recalcula a partir da mais antiga das duas
+    let since = before.start_time.min(after.start_time).timestamp();
+    if let Err(e) = database::refresh_hourly_rollups(&db, since, Utc::now()).await {
+        error!("Failed to refresh hourly rollups after update: {}", e);
+    }
+    tokio::spawn(crate::stats_cache::backfill(app));
+
+    Ok(after)
+}
+
+// Tempo fora do computador (reunião, ligação, quadro branco) lançado à mão,
+// com o aplicativo informado ou "Manual" e, opcionalmente, uma categoria
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn add_manual_activity(
+    app: tauri::AppHandle,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+    title: String,
+    application: Option<String>,
+    category_id: Option<String>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<WindowActivity, String> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err("Title cannot be empty".to_string());
+    }
+    if end <= start {
+        return Err("End time must be after start time".to_string());
+    }
+    if end > Utc::now() {
+        return Err("Manual entries cannot end in the future".to_string());
+    }
+    if let Some(category_id) = &category_id {
+        let config = config.lock().map_err(|e| e.to_string())?;
+        if !config.categories.iter().any(|c| &c.id == category_id) {
+            return Err(format!("Category not found: {}", category_id));
+        }
+    }
+
+    let application = application
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| database::MANUAL_APPLICATION.to_string());
+    let mut activity = WindowActivity {
+        title: title.to_string(),
+        application,
+        start_time: start,
+        end_time: end,
+        manual: true,
+        category_override: category_id.clone(),
+        ..Default::default()
+    };
+    let (id, since) = database::add_manual_activity(&db, &activity, category_id.as_deref())
+        .await
+        .map_err(|e| e.to_string())?;
+    activity.id = Some(id);
+    info!("Added manual activity {} ({} - {})", activity.application, start, end);
+
+    if let Err(e) = database::refresh_hourly_rollups(&db, since, Utc::now()).await {
+        error!("Failed to refresh hourly rollups after manual entry: {}", e);
+    }
+    tokio::spawn(crate::stats_cache::backfill(app));
+
+    Ok(activity)
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_blackout_config(
+    config: State<'_, SharedConfig>,
+) -> Result<BlackoutConfig, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.blackout.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_blackout_config(
+    config: State<'_, SharedConfig>,
+    blackout: BlackoutConfig,
+) -> Result<(), String> {
+    if blackout.windows.iter().any(|w| w.start == w.end) {
+        return Err("Blackout windows need different start and end times".to_string());
+    }
+
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_blackout(blackout)
+        .map_err(|e| e.to_string())
+}
+
+// Por padrão só relata; com dry_run = false aplica e recalcula os resumos
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn repair_activity_overlaps(
+    app: tauri::AppHandle,
+    db: State<'_, DbConnection>,
+    dry_run: Option<bool>,
+) -> Result<crate::maintenance::OverlapReport, String> {
+    let report = crate::maintenance::repair_overlaps(&db, dry_run.unwrap_or(true))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if report.applied {
+        info!(
+            "🧹 Repaired activities: {} duplicates, {} merged, {} trimmed, {} contained",
+            report.removed_duplicates, report.merged, report.trimmed, report.removed_contained
+        );
+        if let Err(e) = rollups::rebuild(&db).await {
+            error!("Failed to rebuild hourly rollups after repair: {}", e);
+        }
+        tokio::spawn(crate::stats_cache::backfill(app));
+    }
+
+    Ok(report)
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn maintain_database(
+    app: tauri::AppHandle,
+) -> Result<crate::maintenance::MaintenanceReport, String> {
+    crate::maintenance::maintain_and_notify(&app)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_maintenance_config(
+    config: State<'_, SharedConfig>,
+) -> Result<MaintenanceConfig, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.maintenance.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_maintenance_config(
+    config: State<'_, SharedConfig>,
+    maintenance: MaintenanceConfig,
+) -> Result<(), String> {
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_maintenance(maintenance)
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Serialize)]
+pub struct IndexStatus {
+    pub name: String,
+    pub present: bool,
+}
+
+// Painel de armazenamento/saúde: tamanho em disco (com o WAL), volume e índices
+#[derive(Debug, Serialize)]
+pub struct DatabaseInfo {
+    pub path: String,
+    pub file_size_bytes: u64,
+    pub wal_size_bytes: u64,
+    pub rows: i64,
+    pub first_activity: Option<DateTime<Utc>>,
+    pub last_activity: Option<DateTime<Utc>>,
+    pub days_tracked: i64,
+    pub rows_per_day: f64,
+    pub schema_version: i64,
+    pub latest_schema_version: i64,
+    pub indexes: Vec<IndexStatus>,
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_database_info(
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<DatabaseInfo, String> {
+    let calendar = config.lock().map_err(|e| e.to_string())?.calendar.clone();
+    let path = database::get_database_path().map_err(|e| e.to_string())?;
+    let file_size = |path: &Path| std::fs::metadata(path).map_or(0, |m| m.len());
+    let mut wal_path = path.clone().into_os_string();
+    wal_path.push("-wal");
+
+    let usage = database::get_storage_usage(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+    let schema_version = database::get_schema_version(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+    let index_names = database::get_index_names(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Mesmo critério da previsão de armazenamento: dias de calendário entre a primeira e a última
+    let days_tracked = match (usage.first_activity, usage.last_activity) {
+        (Some(first), Some(last)) => (calendar.logical_date(last) - calendar.logical_date(first)).num_days() + 1,
+        _ => 0,
+    };
+
+    Ok(DatabaseInfo {
+        file_size_bytes: file_size(&path),
+        wal_size_bytes: file_size(Path::new(&wal_path)),
+        path: path.to_string_lossy().into_owned(),
+        rows: usage.rows,
+        first_activity: usage.first_activity,
+        last_activity: usage.last_activity,
+        days_tracked,
+        rows_per_day: if days_tracked > 0 { usage.rows as f64 / days_tracked as f64 } else { 0.0 },
+        schema_version,
+        latest_schema_version: crate::migrations::latest_version(),
+        indexes: database::EXPECTED_INDEXES
+            .iter()
+            .map(|name| IndexStatus {
+                name: name.to_string(),
+                present: index_names.iter().any(|n| n == name),
+            })
+            .collect(),
+    })
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_sync_feed_config(
+    config: State<'_, SharedConfig>,
+) -> Result<SyncFeedConfig, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.sync_feed.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_sync_feed_config(
+    app: tauri::AppHandle,
+    config: State<'_, SharedConfig>,
+    sync_feed: SyncFeedConfig,
+) -> Result<(), String> {
+    {
+        let mut config = config.lock().map_err(|e| e.to_string())?;
+        if sync_feed.enabled && config.sync_feed.token.is_none() {
+            config.regenerate_sync_feed_token().map_err(|e| e.to_string())?;
+        }
+        config.set_sync_feed(sync_feed).map_err(|e| e.to_string())?;
+    }
+    crate::sync_feed::restart(&app).await.map_err(|e| e.to_string())
+}
+
+// Invalida o token atual; o celular precisa ser pareado de novo
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn regenerate_sync_feed_token(
+    app: tauri::AppHandle,
+    config: State<'_, SharedConfig>,
+) -> Result<String, String> {
+    let token = config
+        .lock()
+        .map_err(|e| e.to_string())?
+        .regenerate_sync_feed_token()
+        .map_err(|e| e.to_string())?;
+    crate::sync_feed::restart(&app).await.map_err(|e| e.to_string())?;
+    Ok(token)
+}
+
+// O token só aparece aqui e em get_sync_feed_config
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn add_api_client(
+    app: tauri::AppHandle,
+    config: State<'_, SharedConfig>,
+    name: String,
+    permissions: Vec<ApiPermission>,
+) -> Result<ApiClient, String> {
+    let client = config
+        .lock()
+        .map_err(|e| e.to_string())?
+        .add_api_client(&name, permissions)
+        .map_err(|e| e.to_string())?;
+    crate::sync_feed::restart(&app).await.map_err(|e| e.to_string())?;
+    Ok(client)
+}
+
+// Vale já na próxima requisição, sem reiniciar o servidor
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_api_client_permissions(
+    config: State<'_, SharedConfig>,
+    id: String,
+    permissions: Vec<ApiPermission>,
+) -> Result<(), String> {
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_api_client_permissions(&id, permissions)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn remove_api_client(
+    app: tauri::AppHandle,
+    config: State<'_, SharedConfig>,
+    id: String,
+) -> Result<(), String> {
+    config
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove_api_client(&id)
+        .map_err(|e| e.to_string())?;
+    crate::sync_feed::restart(&app).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_sync_config(
+    config: State<'_, SharedConfig>,
+) -> Result<SyncConfig, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.sync.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_sync_config(
+    config: State<'_, SharedConfig>,
+    sync: SyncConfig,
+) -> Result<SyncConfig, String> {
+    if sync.enabled && sync.endpoint.trim().is_empty() {
+        return Err("Sync endpoint is required".to_string());
+    }
+
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_sync(sync).map_err(|e| e.to_string())?;
+    Ok(config.sync.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn sync_now(
+    app: tauri::AppHandle,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<crate::sync::SyncReport, String> {
+    let sync = config.lock().map_err(|e| e.to_string())?.sync.clone();
+    if sync.device_id.is_empty() {
+        return Err("Sync is not configured".to_string());
+    }
+
+    let report = crate::sync::sync_now(&db, &sync)
+        .await
+        .map_err(|e| e.to_string())?;
+    if report.pulled > 0 {
+        tokio::spawn(crate::stats_cache::backfill(app));
+    }
+    Ok(report)
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_cloud_backup_config(
+    config: State<'_, SharedConfig>,
+) -> Result<CloudBackupConfig, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.cloud_backup.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_cloud_backup_config(
+    config: State<'_, SharedConfig>,
+    cloud_backup: CloudBackupConfig,
+) -> Result<(), String> {
+    if cloud_backup.enabled && cloud_backup.target.is_none() {
+        return Err("Choose a backup target before enabling cloud backups".to_string());
+    }
+
+    // O segredo do destino vai para o chaveiro, não para o arquivo de configuração
+    let mut cloud_backup = cloud_backup;
+    if let Some(target) = cloud_backup.target.as_mut() {
+        crate::cloud_backup::store_target_secret(target).map_err(|e| e.to_string())?;
+    }
+
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_cloud_backup(cloud_backup)
+        .map_err(|e| e.to_string())
+}
+
+// Envio imediato, mesmo com o agendamento desligado
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn run_cloud_backup(
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<crate::cloud_backup::CloudBackupResult, String> {
+    let cloud_backup = config.lock().map_err(|e| e.to_string())?.cloud_backup.clone();
+    let result = crate::cloud_backup::run(&db, &cloud_backup)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    config
+        .lock()
+        .map_err(|e| e.to_string())?
+        .record_cloud_backup(Utc::now())
+        .map_err(|e| e.to_string())?;
+    Ok(result)
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn list_cloud_backups(
+    config: State<'_, SharedConfig>,
+) -> Result<Vec<String>, String> {
+    let cloud_backup = config.lock().map_err(|e| e.to_string())?.cloud_backup.clone();
+    crate::cloud_backup::list(&cloud_backup)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_journal_config(
+    config: State<'_, SharedConfig>,
+) -> Result<JournalConfig, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.journal.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_journal_config(
+    config: State<'_, SharedConfig>,
+    journal: JournalConfig,
+) -> Result<String, String> {
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    // Confere se a pasta pode ser criada antes de salvar
+    let mut candidate = config.clone();
+    candidate.journal = journal.clone();
+    let dir = crate::journal::journal_dir(&candidate).map_err(|e| e.to_string())?;
+
+    config.set_journal(journal)
+        .map_err(|e| e.to_string())?;
+    Ok(dir.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_performance_metrics() -> Result<crate::perf::PerformanceMetrics, String> {
+    Ok(crate::perf::snapshot())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn reset_performance_metrics() -> Result<(), String> {
+    crate::perf::reset();
+    Ok(())
+}
+
+// Recategoriza o histórico: toda atividade do intervalo cujo título ou URL contém
+// o padrão passa a contar na categoria escolhida. Por padrão só mostra a prévia
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn recategorize_by_pattern(
+    app: tauri::AppHandle,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+    pattern: String,
+    category_id: String,
+    range: TimeRange,
+    dry_run: Option<bool>,
+) -> Result<database::RecategorizeResult, String> {
+    let pattern = pattern.trim().to_string();
+    if pattern.is_empty() {
+        return Err("Pattern cannot be empty".to_string());
+    }
+    if range.end <= range.start {
+        return Err("End time must be after start time".to_string());
+    }
+    if !config.lock().map_err(|e| e.to_string())?.categories.iter().any(|c| c.id == category_id) {
+        return Err(format!("Unknown category: {}", category_id));
+    }
+
+    let result = database::recategorize_by_pattern(
+        &db,
+        &pattern,
+        &category_id,
+        range.start,
+        range.end,
+        dry_run.unwrap_or(true),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if result.applied && result.matched > 0 {
+        info!("🏷️ Recategorized {} activities matching {:?} as {}", result.matched, pattern, category_id);
+        tokio::spawn(crate::stats_cache::backfill(app));
+    }
+    Ok(result)
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn clear_category_overrides(
+    app: tauri::AppHandle,
+    db: State<'_, DbConnection>,
+    range: TimeRange,
+    pattern: Option<String>,
+) -> Result<usize, String> {
+    let removed = database::clear_category_overrides(&db, pattern.as_deref(), range.start, range.end)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if removed > 0 {
+        tokio::spawn(crate::stats_cache::backfill(app));
+    }
+    Ok(removed)
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn restore_from_cloud(
+    app: tauri::AppHandle,
+    db: State<'_, DbConnection>,
+    health: State<'_, SharedTrackerHealth>,
+    tracker: State<'_, TrackerHandle>,
+    config: State<'_, SharedConfig>,
+    backup_id: String,
+    key: Option<String>,
+) -> Result<RestoreInfo, String> {
+    let cloud_backup = config.lock().map_err(|e| e.to_string())?.cloud_backup.clone();
+    let info = crate::cloud_backup::restore(&db, &health, &tracker, &cloud_backup, &backup_id, key.as_deref())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Err(e) = rollups::rebuild(&db).await {
+        error!("Failed to rebuild hourly rollups after restore: {}", e);
+    }
+    tokio::spawn(crate::stats_cache::backfill(app));
+
+    Ok(info)
+}
+
+// Chave dos backups cifrados, para guardar fora do computador (restauração em
+// outra máquina). Gerada aqui se ainda não existir
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_cloud_backup_key() -> Result<String, String> {
+    crate::cloud_backup::encryption_key().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_productivity_score(
+    range: TimeRange,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<crate::score::ProductivityScore, String> {
+    let mut activities = database::get_activities_between(&db, range.start, range.end)
+        .await
+        .map_err(|e| e.to_string())?;
+    activities.retain(|a| !a.off_hours);
+
+    let config = config.lock().map_err(|e| e.to_string())?;
+    let days = (config.calendar.logical_date(range.end) - config.calendar.logical_date(range.start)).num_days() + 1;
+    Ok(crate::score::compute(&activities, &config, days))
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_score_weights(
+    config: State<'_, SharedConfig>,
+) -> Result<ScoreWeights, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.score_weights.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_score_weights(
+    config: State<'_, SharedConfig>,
+    score_weights: ScoreWeights,
+) -> Result<(), String> {
+    let weights = [score_weights.productive, score_weights.idle, score_weights.focus, score_weights.goal];
+    if weights.iter().any(|w| !w.is_finite() || *w < 0.0) || weights.iter().sum::<f64>() <= 0.0 {
+        return Err("Weights must be non-negative and not all zero".to_string());
+    }
+    if !(score_weights.switches_per_hour_ceiling > 0.0) {
+        return Err("Switches per hour ceiling must be positive".to_string());
+    }
+
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_score_weights(score_weights)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_browser_profiles(
+    config: State<'_, SharedConfig>,
+) -> Result<BrowserProfileConfig, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.browser_profiles.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_browser_profiles(
+    config: State<'_, SharedConfig>,
+    browser_profiles: BrowserProfileConfig,
+) -> Result<(), String> {
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_browser_profiles(browser_profiles)
+        .map_err(|e| e.to_string())
+}
+
+// history_days: quantos dias de histórico devolver (padrão 30)
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_streaks(
+    history_days: Option<i64>,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<crate::streaks::Streaks, String> {
+    let config = config.lock().map_err(|e| e.to_string())?.clone();
+    crate::streaks::get_streaks(&db, &config, history_days.unwrap_or(30).max(0))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_timesheet_config(
+    config: State<'_, SharedConfig>,
+) -> Result<TimesheetConfig, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.timesheet.clone())
+}
+
+// Devolve a pasta onde os rascunhos serão gravados
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_timesheet_config(
+    config: State<'_, SharedConfig>,
+    timesheet: TimesheetConfig,
+) -> Result<String, String> {
+    if timesheet.hour > 23 {
+        return Err("Hour must be between 0 and 23".to_string());
+    }
+    if timesheet.increment_minutes <= 0 {
+        return Err("Rounding increment must be positive".to_string());
+    }
+
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    let mut candidate = config.clone();
+    candidate.timesheet = timesheet.clone();
+    let dir = crate::timesheet::timesheet_dir(&candidate).map_err(|e| e.to_string())?;
+
+    config.set_timesheet(timesheet)
+        .map_err(|e| e.to_string())?;
+    Ok(dir.to_string_lossy().to_string())
+}
+
+// Rascunho da semana que contém date (padrão: a atual), gravado também em arquivo
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn generate_timesheet_draft(
+    date: Option<NaiveDate>,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<crate::timesheet::TimesheetDraft, String> {
+    let config = config.lock().map_err(|e| e.to_string())?.clone();
+    let day = date.unwrap_or_else(|| config.calendar.logical_date(Utc::now()));
+    crate::timesheet::generate(&db, &config, day)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_break_reminder_config(
+    config: State<'_, SharedConfig>,
+) -> Result<BreakReminderConfig, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.break_reminders.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_break_reminder_config(
+    config: State<'_, SharedConfig>,
+    break_reminders: BreakReminderConfig,
+) -> Result<(), String> {
+    let durations = [break_reminders.interval_minutes, break_reminders.break_minutes, break_reminders.snooze_minutes];
+    if durations.iter().any(|minutes| *minutes <= 0) {
+        return Err("Break reminder durations must be positive".to_string());
+    }
+
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_break_reminders(break_reminders)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_break_status(
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+    state: State<'_, SharedBreakReminder>,
+) -> Result<BreakStatus, String> {
+    let config = config.lock().map_err(|e| e.to_string())?.clone();
+    crate::breaks::status(&db, &config, state.inner(), Utc::now())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// Adia o próximo lembrete (padrão: snooze_minutes da configuração)
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn snooze_break_reminder(
+    minutes: Option<i64>,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+    state: State<'_, SharedBreakReminder>,
+) -> Result<BreakStatus, String> {
+    let config = config.lock().map_err(|e| e.to_string())?.clone();
+    let minutes = minutes.unwrap_or(config.break_reminders.snooze_minutes).max(1);
+    let now = Utc::now();
+    state
+        .lock()
+        .map_err(|e| e.to_string())?
+        .snooze(now + Duration::minutes(minutes));
+    info!("Break reminder snoozed for {} minutes", minutes);
+
+    crate::breaks::status(&db, &config, state.inner(), now)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// Ferramenta de depuração: reproduz um diário num banco temporário, sem tocar no banco real.
+// Só existe em builds de debug; no release o comando registrado apenas recusa
+#[cfg(debug_assertions)]
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn replay_from_journal(
+    path: String,
+    speed: Option<f64>,
+    config: State<'_, SharedConfig>,
+) -> Result<crate::replay::ReplayReport, String> {
+    let config = config.lock().map_err(|e| e.to_string())?.clone();
+    crate::replay::replay_from_journal(&config, std::path::Path::new(&path), speed.unwrap_or(0.0))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(debug_assertions))]
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn replay_from_journal(_path: String, _speed: Option<f64>) -> Result<(), String> {
+    Err("Journal replay is only available in debug builds".to_string())
+}
+
+// Dia em blocos contínuos (atividade, ocioso, sem registro); hoje termina em agora
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_timeline(
+    date: String,
+    min_gap_seconds: Option<i64>,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<Vec<crate::timeline::TimelineBlock>, String> {
+    let date = DateTime::parse_from_rfc3339(&date)
+        .map_err(|e| e.to_string())?
+        .with_timezone(&Utc);
+
+    let config = config.lock().map_err(|e| e.to_string())?.clone();
+    let (start, end) = config.calendar.day_bounds(config.calendar.local_date(date));
+    let end = end.min(Utc::now());
+    if end <= start {
+        return Ok(Vec::new());
+    }
+    let activities = database::get_activities_between(&db, start, end)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let min_gap = min_gap_seconds.unwrap_or(crate::timeline::DEFAULT_MIN_GAP_SECONDS).max(1);
+    Ok(crate::timeline::build(&activities, &config, start, end, min_gap))
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_context_switch_metrics(
+    range: TimeRange,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<crate::score::ContextSwitchMetrics, String> {
+    let tz = config.lock().map_err(|e| e.to_string())?.calendar.tz();
+    let activities = database::get_activities_between(&db, range.start, range.end)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(crate::score::context_switch_metrics(&activities, tz))
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_pomodoro_config(config: State<'_, SharedConfig>) -> Result<PomodoroConfig, String> {
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.pomodoro.clone())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn set_pomodoro_config(
+    config: State<'_, SharedConfig>,
+    pomodoro: PomodoroConfig,
+) -> Result<(), String> {
+    let durations = [pomodoro.work_minutes, pomodoro.short_break_minutes, pomodoro.long_break_minutes];
+    if durations.iter().any(|minutes| *minutes <= 0) || pomodoro.long_break_every == 0 {
+        return Err("Pomodoro durations and long break interval must be positive".to_string());
+    }
+
+    let mut config = config.lock().map_err(|e| e.to_string())?;
+    config.set_pomodoro(pomodoro)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn start_pomodoro(
+    app: tauri::AppHandle,
+    label: Option<String>,
+) -> Result<PomodoroTimer, String> {
+    let timer = pomodoro::start(&app, label.unwrap_or_else(|| "Pomodoro".to_string()))?;
+    crate::menu::update_tray_menu(&app).await?;
+    Ok(timer)
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn stop_pomodoro(app: tauri::AppHandle) -> Result<Option<PomodoroTimer>, String> {
+    let timer = pomodoro::stop(&app)?;
+    crate::menu::update_tray_menu(&app).await?;
+    Ok(timer)
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn skip_pomodoro(app: tauri::AppHandle) -> Result<Option<PomodoroTimer>, String> {
+    let timer = pomodoro::skip(&app)?;
+    crate::menu::update_tray_menu(&app).await?;
+    Ok(timer)
+}
+
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_pomodoro(app: tauri::AppHandle) -> Result<Option<PomodoroTimer>, String> {
+    Ok(pomodoro::current(&app))
+}
+
+// Pomodoros concluídos no intervalo, cada um com os apps usados durante ele
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn get_pomodoro_stats(
+    range: TimeRange,
+    db: State<'_, DbConnection>,
+    config: State<'_, SharedConfig>,
+) -> Result<PomodoroStats, String> {
+    let config = config.lock().map_err(|e| e.to_string())?.clone();
+    pomodoro::stats(&db, &config, range.start, range.end)
+        .await
+        .map_err(|e| e.to_string())
+}
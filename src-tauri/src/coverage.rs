@@ -0,0 +1,115 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::category::{CalendarConfig, ScheduleConfig};
+use crate::tracker::WindowActivity;
+
+// Abaixo dessa fração do horário esperado o dia é considerado esparso
+const SPARSE_RATIO: f64 = 0.5;
+// Buracos dentro do horário de trabalho a partir desse tamanho são sinalizados
+const GAP_THRESHOLD_SECONDS: i64 = 60 * 60;
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CoverageStatus {
+    Complete,
+    Sparse,
+    Missing,
+    // Fora do horário de trabalho e sem nada registrado
+    OffDay,
+    Future,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DataGap {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub duration: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DayCoverage {
+    pub date: NaiveDate,
+    pub tracked_seconds: i64,
+    pub idle_seconds: i64,
+    pub expected_seconds: i64,
+    pub coverage: f64,
+    pub status: CoverageStatus,
+    pub gaps: Vec<DataGap>,
+}
+
+// Compara o que foi registrado no dia (ocioso conta como "dado presente", só não
+// como trabalho) com o horário esperado e lista os buracos dentro desse horário
+pub fn day_coverage(
+    date: NaiveDate,
+    activities: &[WindowActivity],
+    schedule: &ScheduleConfig,
+    calendar: &CalendarConfig,
+    now: DateTime<Utc>,
+) -> DayCoverage {
+    let tracked_seconds: i64 = activities
+        .iter()
+        .filter(|a| !a.is_idle)
+        .map(|a| (a.end_time - a.start_time).num_seconds())
+        .sum();
+    let idle_seconds: i64 = activities
+        .iter()
+        .filter(|a| a.is_idle)
+        .map(|a| (a.end_time - a.start_time).num_seconds())
+        .sum();
+
+    let window = schedule.window(calendar, date);
+    let mut gaps = Vec::new();
+    let mut expected_seconds = 0;
+
+    if let Some((start, end)) = window {
+        // Hoje só conta o horário que já passou
+        let end = end.min(now);
+        expected_seconds = (end - start).num_seconds().max(0);
+
+        let mut intervals: Vec<(DateTime<Utc>, DateTime<Utc>)> = activities
+            .iter()
+            .map(|a| (a.start_time.max(start), a.end_time.min(end)))
+            .filter(|(s, e)| s < e)
+            .collect();
+        intervals.sort();
+
+        let mut cursor = start;
+        for (interval_start, interval_end) in intervals.into_iter().chain(std::iter::once((end, end))) {
+            if (interval_start - cursor).num_seconds() >= GAP_THRESHOLD_SECONDS {
+                gaps.push(DataGap {
+                    start: cursor,
+                    end: interval_start,
+                    duration: (interval_start - cursor).num_seconds(),
+                });
+            }
+            cursor = cursor.max(interval_end);
+        }
+    }
+
+    let recorded = tracked_seconds + idle_seconds;
+    let coverage = if expected_seconds > 0 {
+        (recorded as f64 / expected_seconds as f64).min(1.0)
+    } else {
+        0.0
+    };
+
+    let status = match window {
+        Some((start, _)) if start > now => CoverageStatus::Future,
+        Some(_) if recorded == 0 => CoverageStatus::Missing,
+        Some(_) if coverage < SPARSE_RATIO || !gaps.is_empty() => CoverageStatus::Sparse,
+        Some(_) => CoverageStatus::Complete,
+        None if recorded == 0 => CoverageStatus::OffDay,
+        None => CoverageStatus::Complete,
+    };
+
+    DayCoverage {
+        date,
+        tracked_seconds,
+        idle_seconds,
+        expected_seconds,
+        coverage,
+        status,
+        gaps,
+    }
+}
@@ -1,17 +1,75 @@
 use anyhow::Result;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use rusqlite::{params, Connection, OptionalExtension};
 use rusqlite::types::ToSql;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tracing::{debug, info};
-use std::path::PathBuf;
+use tracing::{debug, info, instrument};
+use std::path::{Path, PathBuf};
 
+use crate::db_location;
+use crate::encryption;
+use crate::migrations;
 use crate::tracker::WindowActivity;
 
 pub type DbConnection = Arc<Mutex<Connection>>;
 
-fn get_database_path() -> Result<PathBuf> {
+// Colunas lidas por activity_from_row, na mesma ordem
+const ACTIVITY_COLUMNS: &str =
+    "title, application, start_time, end_time, is_browser, url, is_idle, desktop, network, project, cwd, id, device_id, profile, off_hours, manual,
+     (SELECT category_id FROM category_overrides WHERE activity_id = activities.id), source_id";
+
+fn parse_timestamp(value: &str, column: usize) -> rusqlite::Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(
+            column,
+            rusqlite::types::Type::Text,
+            Box::new(e),
+        ))
+}
+
+fn activity_from_row(row: &rusqlite::Row) -> rusqlite::Result<WindowActivity> {
+    let start_time: String = row.get(2)?;
+    let end_time: String = row.get(3)?;
+
+    Ok(WindowActivity {
+        title: row.get(0)?,
+        application: row.get(1)?,
+        start_time: parse_timestamp(&start_time, 2)?,
+        end_time: parse_timestamp(&end_time, 3)?,
+        is_browser: row.get(4)?,
+        url: row.get(5)?,
+        is_idle: row.get(6).unwrap_or(false),
+        desktop: row.get(7)?,
+        network: row.get(8)?,
+        project: row.get(9)?,
+        cwd: row.get(10)?,
+        id: row.get(11)?,
+        device_id: row.get(12)?,
+        profile: row.get(13)?,
+        off_hours: row.get(14)?,
+        manual: row.get(15)?,
+        category_override: row.get(16)?,
+        source_id: row.get(17)?,
+    })
+}
+
+// Local escolhido nas configurações ou o padrão do sistema
+pub fn get_database_path() -> Result<PathBuf> {
+    match db_location::custom_path() {
+        Some(path) => {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            Ok(path)
+        }
+        None => default_database_path(),
+    }
+}
+
+pub fn default_database_path() -> Result<PathBuf> {
     let app_support = if cfg!(target_os = "macos") {
         dirs::home_dir()
             .ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?
@@ -32,59 +90,46 @@ fn get_database_path() -> Result<PathBuf> {
     Ok(app_support.join("chronos.db"))
 }
 
+#[instrument(skip_all)]
 pub async fn init_database() -> Result<DbConnection> {
     info!("Initializing database");
     let db_path = get_database_path()?;
     info!("Database path: {:?}", db_path);
     
-    let conn = Connection::open(db_path)?;
-    
-    // Habilita chaves estrangeiras e usa o modo DELETE para o journal
+    let conn = open_connection(&db_path)?;
+
+    info!("Database initialized successfully");
+    Ok(Arc::new(Mutex::new(conn)))
+}
+
+// Abre (e migra) o banco; também usado para reabrir a conexão em tempo de execução
+pub fn open_connection(path: &Path) -> Result<Connection> {
+    open_connection_with_key(path, encryption::stored_key().as_deref())
+}
+
+// Com uma chave que ainda não está no chaveiro (troca da criptografia)
+pub fn open_connection_with_key(path: &Path, key: Option<&str>) -> Result<Connection> {
+    let mut conn = Connection::open(path)?;
+    encryption::apply_key(&conn, key)?;
+
+    // WAL permite leituras (stats) concorrentes com a escrita do tracker e reduz fsyncs;
+    // com WAL, synchronous = NORMAL é seguro contra corrupção
     conn.execute_batch(
         "PRAGMA foreign_keys = ON;
-         PRAGMA journal_mode = DELETE;"
-    )?;
-    
-    info!("Creating table");
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS activities (
-            id INTEGER PRIMARY KEY,
-            title TEXT NOT NULL,
-            application TEXT NOT NULL,
-            start_time TEXT NOT NULL,
-            end_time TEXT NOT NULL,
-            is_browser BOOLEAN NOT NULL,
-            url TEXT,
-            is_idle BOOLEAN NOT NULL DEFAULT 0
-        )",
-        [],
+         PRAGMA journal_mode = WAL;
+         PRAGMA synchronous = NORMAL;"
     )?;
 
-    // Verifica se a coluna is_idle existe
-    let columns: Vec<String> = conn
-        .prepare("SELECT sql FROM sqlite_master WHERE type='table' AND name='activities'")?
-        .query_map([], |row| row.get(0))?
-        .collect::<Result<Vec<String>, _>>()?;
-
-    if let Some(create_sql) = columns.first() {
-        if !create_sql.contains("is_idle") {
-            info!("Adding is_idle column");
-            conn.execute(
-                "ALTER TABLE activities ADD COLUMN is_idle BOOLEAN NOT NULL DEFAULT 0",
-                [],
-            )?;
-        }
-    }
-
-    info!("Database initialized successfully");
-    Ok(Arc::new(Mutex::new(conn)))
+    migrations::run(&mut conn)?;
+    Ok(conn)
 }
 
+#[instrument(skip_all)]
 pub async fn save_activity(conn: &DbConnection, activity: &WindowActivity) -> Result<i64> {
     let conn = conn.lock().await;
     let mut stmt = conn.prepare(
-        "INSERT INTO activities (title, application, start_time, end_time, is_browser, url, is_idle)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        "INSERT INTO activities (title, application, start_time, end_time, is_browser, url, is_idle, desktop, network, project, cwd, profile, off_hours, manual)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
     )?;
     
     let id = stmt.insert([
@@ -95,11 +140,19 @@ pub async fn save_activity(conn: &DbConnection, activity: &WindowActivity) -> Re
         &activity.is_browser,
         &activity.url,
         &activity.is_idle,
+        &activity.desktop,
+        &activity.network,
+        &activity.project,
+        &activity.cwd,
+        &activity.profile,
+        &activity.off_hours,
+        &activity.manual,
     ])?;
     
     Ok(id)
 }
 
+#[instrument(skip_all)]
 pub async fn get_activities_between(
     conn: &DbConnection,
     start: DateTime<Utc>,
@@ -108,14 +161,15 @@ pub async fn get_activities_between(
     let conn = conn.lock().await;
     debug!("Getting activities between {} and {}", start, end);
     
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare(&format!(
         r#"
-        SELECT title, application, start_time, end_time, is_browser, url, is_idle
+        SELECT {}
         FROM activities
         WHERE start_time >= ? AND end_time <= ?
         ORDER BY start_time DESC
         "#,
-    )?;
+        ACTIVITY_COLUMNS
+    ))?;
 
     let activities = stmt
         .query_map(
@@ -123,30 +177,7 @@ pub async fn get_activities_between(
                 start.to_rfc3339(),
                 end.to_rfc3339(),
             ],
-            |row| {
-                let start_time: String = row.get(2)?;
-                let end_time: String = row.get(3)?;
-                
-                Ok(WindowActivity {
-                    title: row.get(0)?,
-                    application: row.get(1)?,
-                    start_time: DateTime::parse_from_rfc3339(&start_time)
-                        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(
-                            0,
-                            rusqlite::types::Type::Text,
-                            Box::new(e),
-                        ))?.with_timezone(&Utc),
-                    end_time: DateTime::parse_from_rfc3339(&end_time)
-                        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(
-                            0,
-                            rusqlite::types::Type::Text,
-                            Box::new(e),
-                        ))?.with_timezone(&Utc),
-                    is_browser: row.get(4)?,
-                    url: row.get(5)?,
-                    is_idle: row.get(6).unwrap_or(false),
-                })
-            },
+            activity_from_row,
         )?
         .collect::<Result<Vec<_>, _>>()?;
 
@@ -154,13 +185,90 @@ pub async fn get_activities_between(
     Ok(activities)
 }
 
-pub async fn merge_activity(
+// Filtros opcionais da listagem; None = sem restrição
+#[derive(Debug, Clone, Default)]
+pub struct ActivityFilter {
+    pub applications: Option<Vec<String>>,
+    pub is_idle: Option<bool>,
+    pub is_browser: Option<bool>,
+    pub title_contains: Option<String>,
+}
+
+impl ActivityFilter {
+    fn where_clause(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> (String, Vec<Box<dyn ToSql>>) {
+        let mut conditions = vec!["start_time >= ?".to_string(), "end_time <= ?".to_string()];
+        let mut values: Vec<Box<dyn ToSql>> = vec![Box::new(start.to_rfc3339()), Box::new(end.to_rfc3339())];
+
+        if let Some(applications) = &self.applications {
+            if applications.is_empty() {
+                conditions.push("0".to_string());
+            } else {
+                conditions.push(format!("application IN ({})", vec!["?"; applications.len()].join(", ")));
+                values.extend(applications.iter().map(|app| Box::new(app.clone()) as Box<dyn ToSql>));
+            }
+        }
+        if let Some(is_idle) = self.is_idle {
+            conditions.push("is_idle = ?".to_string());
+            values.push(Box::new(is_idle));
+        }
+        if let Some(is_browser) = self.is_browser {
+            conditions.push("is_browser = ?".to_string());
+            values.push(Box::new(is_browser));
+        }
+        if let Some(title) = self.title_contains.as_deref().filter(|t| !t.is_empty()) {
+            // instr() evita ter que escapar % e _ do LIKE; lower() para ignorar maiúsculas
+            conditions.push("instr(lower(title), lower(?)) > 0".to_string());
+            values.push(Box::new(title.to_string()));
+        }
+
+        (conditions.join(" AND "), values)
+    }
+}
+
+// Uma página do intervalo (mais recentes primeiro) e o total de atividades nele
+#[instrument(skip_all)]
+pub async fn get_activities_page(
     conn: &DbConnection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    filter: &ActivityFilter,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<WindowActivity>, i64)> {
+    let conn = conn.lock().await;
+    let (where_clause, mut values) = filter.where_clause(start, end);
+
+    let total: i64 = conn.query_row(
+        &format!("SELECT COUNT(*) FROM activities WHERE {}", where_clause),
+        rusqlite::params_from_iter(values.iter()),
+        |row| row.get(0),
+    )?;
+
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT {}
+        FROM activities
+        WHERE {}
+        ORDER BY start_time DESC
+        LIMIT ? OFFSET ?
+        "#,
+        ACTIVITY_COLUMNS, where_clause
+    ))?;
+
+    values.push(Box::new(limit));
+    values.push(Box::new(offset));
+    let activities = stmt
+        .query_map(rusqlite::params_from_iter(values.iter()), activity_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((activities, total))
+}
+
+fn merge_activity(
+    conn: &Connection,
     activity: &WindowActivity,
     threshold_seconds: i64,
-) -> Result<()> {
-    let conn = conn.lock().await;
-    
+) -> Result<i64> {
     info!(
         "🔍 Merging activity: {} - {} | Idle: {} | {} -> {}",
         activity.application,
@@ -180,6 +288,13 @@ pub async fn merge_activity(
               AND title = ?
               AND is_browser = ?
               AND is_idle = ?  -- Só mescla se o estado de idle for o mesmo
+              AND desktop IS ?
+              AND network IS ?
+              AND cwd IS ?
+              AND profile IS ?
+              AND off_hours = ?
+              AND NOT manual  -- lançamentos manuais nunca são estendidos pelo tracker
+              AND device_id IS NULL  -- linhas de outros dispositivos nunca são estendidas aqui
               AND date(start_time) = date(?)
               AND (strftime('%s', ?) - strftime('%s', end_time)) <= ?
             ORDER BY end_time DESC
@@ -190,6 +305,11 @@ pub async fn merge_activity(
                 activity.title,
                 activity.is_browser,
                 activity.is_idle,
+                activity.desktop,
+                activity.network,
+                activity.cwd,
+                activity.profile,
+                activity.off_hours,
                 activity.start_time.to_rfc3339(),
                 activity.start_time.to_rfc3339(),
                 threshold_seconds,
@@ -224,6 +344,7 @@ pub async fn merge_activity(
             "UPDATE activities SET end_time = ? WHERE id = ?",
             params![activity.end_time.to_rfc3339(), id],
         )?;
+        Ok(id)
     } else {
         info!(
             "➕ New activity | Idle: {} | {} -> {}",
@@ -236,9 +357,9 @@ pub async fn merge_activity(
             r#"
             INSERT INTO activities (
                 title, application, start_time, end_time, 
-                is_browser, url, is_idle
+                is_browser, url, is_idle, desktop, network, project, cwd, profile, off_hours, manual
             )
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
             params![
                 activity.title,
@@ -248,13 +369,63 @@ pub async fn merge_activity(
                 activity.is_browser,
                 activity.url,
                 activity.is_idle,
+                activity.desktop,
+                activity.network,
+                activity.project,
+                activity.cwd,
+                activity.profile,
+                activity.off_hours,
+                activity.manual,
             ],
         )?;
+        Ok(conn.last_insert_rowid())
     }
+}
 
-    Ok(())
+// Evento já gravado antes só tem o end_time atualizado; evento novo passa pelo merge
+fn write_activity(
+    conn: &Connection,
+    activity: &WindowActivity,
+    row_id: Option<i64>,
+    threshold_seconds: i64,
+) -> Result<i64> {
+    match row_id {
+        Some(id) => {
+            conn.execute(
+                "UPDATE activities SET end_time = ? WHERE id = ?",
+                params![activity.end_time.to_rfc3339(), id],
+            )?;
+            Ok(id)
+        }
+        None => merge_activity(conn, activity, threshold_seconds),
+    }
+}
+
+// Grava em uma única transação os eventos encerrados desde o último flush e o
+// estado do evento atual. Retorna a linha do evento atual
+#[instrument(skip_all)]
+pub async fn write_activity_batch(
+    conn: &DbConnection,
+    completed: &[(WindowActivity, Option<i64>)],
+    current: Option<(&WindowActivity, Option<i64>)>,
+    threshold_seconds: i64,
+) -> Result<Option<i64>> {
+    let mut conn = conn.lock().await;
+    let tx = conn.transaction()?;
+
+    for (activity, row_id) in completed {
+        write_activity(&tx, activity, *row_id, threshold_seconds)?;
+    }
+    let current_id = current
+        .map(|(activity, row_id)| write_activity(&tx, activity, row_id, threshold_seconds))
+        .transpose()?;
+
+    tx.commit()?;
+    debug!("Wrote {} completed activities in one transaction", completed.len());
+    Ok(current_id)
 }
 
+#[instrument(skip_all)]
 pub async fn get_activities_for_day(
     conn: &DbConnection,
     date: DateTime<Utc>,
@@ -262,42 +433,20 @@ pub async fn get_activities_for_day(
     let conn = conn.lock().await;
     debug!("Getting activities for day {}", date.date_naive());
     
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare(&format!(
         r#"
-        SELECT title, application, start_time, end_time, is_browser, url, is_idle
+        SELECT {}
         FROM activities
         WHERE date(start_time) = date(?)
         ORDER BY start_time DESC
         "#,
-    )?;
+        ACTIVITY_COLUMNS
+    ))?;
 
     let activities = stmt
         .query_map(
             params![date.to_rfc3339()],
-            |row| {
-                let start_time: String = row.get(2)?;
-                let end_time: String = row.get(3)?;
-                
-                Ok(WindowActivity {
-                    title: row.get(0)?,
-                    application: row.get(1)?,
-                    start_time: DateTime::parse_from_rfc3339(&start_time)
-                        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(
-                            0,
-                            rusqlite::types::Type::Text,
-                            Box::new(e),
-                        ))?.with_timezone(&Utc),
-                    end_time: DateTime::parse_from_rfc3339(&end_time)
-                        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(
-                            0,
-                            rusqlite::types::Type::Text,
-                            Box::new(e),
-                        ))?.with_timezone(&Utc),
-                    is_browser: row.get(4)?,
-                    url: row.get(5)?,
-                    is_idle: row.get(6).unwrap_or(false),
-                })
-            },
+            activity_from_row,
         )?
         .collect::<Result<Vec<_>, _>>()?;
 
@@ -305,6 +454,251 @@ pub async fn get_activities_for_day(
     Ok(activities)
 }
 
+#[instrument(skip_all)]
+pub async fn insert_activities(conn: &DbConnection, activities: &[WindowActivity]) -> Result<usize> {
+    let mut conn = conn.lock().await;
+    let tx = conn.transaction()?;
+
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO activities (title, application, start_time, end_time, is_browser, url, is_idle, desktop, network, project, cwd, profile, off_hours, manual)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+        )?;
+
+        for activity in activities {
+            stmt.execute(params![
+                activity.title,
+                activity.application,
+                activity.start_time.to_rfc3339(),
+                activity.end_time.to_rfc3339(),
+                activity.is_browser,
+                activity.url,
+                activity.is_idle,
+                activity.desktop,
+                activity.network,
+                activity.project,
+                activity.cwd,
+                activity.profile,
+                activity.off_hours,
+                activity.manual,
+            ])?;
+        }
+    }
+
+    tx.commit()?;
+    info!("Inserted {} activities", activities.len());
+    Ok(activities.len())
+}
+
+#[instrument(skip_all)]
+pub async fn count_overlapping(conn: &DbConnection, activities: &[WindowActivity]) -> Result<usize> {
+    let conn = conn.lock().await;
+    let mut stmt = conn.prepare(
+        "SELECT EXISTS(SELECT 1 FROM activities WHERE start_time < ?1 AND end_time > ?2)",
+    )?;
+
+    let mut conflicts = 0;
+    for activity in activities {
+        let overlaps: bool = stmt.query_row(
+            params![activity.end_time.to_rfc3339(), activity.start_time.to_rfc3339()],
+            |row| row.get(0),
+        )?;
+        if overlaps {
+            conflicts += 1;
+        }
+    }
+
+    Ok(conflicts)
+}
+
+#[instrument(skip_all)]
+pub async fn save_window_snapshot(
+    conn: &DbConnection,
+    taken_at: DateTime<Utc>,
+    windows: &[(String, String)],
+) -> Result<()> {
+    let mut conn = conn.lock().await;
+    let tx = conn.transaction()?;
+
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO window_snapshots (taken_at, application, title) VALUES (?1, ?2, ?3)",
+        )?;
+        for (application, title) in windows {
+            stmt.execute(params![taken_at.to_rfc3339(), application, title])?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(())
+}
+
+#[instrument(skip_all)]
+pub async fn get_window_snapshots_between(
+    conn: &DbConnection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<(DateTime<Utc>, String, String)>> {
+    let conn = conn.lock().await;
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT taken_at, application, title
+        FROM window_snapshots
+        WHERE taken_at >= ? AND taken_at <= ?
+        ORDER BY taken_at DESC
+        "#,
+    )?;
+
+    let snapshots = stmt
+        .query_map(params![start.to_rfc3339(), end.to_rfc3339()], |row| {
+            let taken_at: String = row.get(0)?;
+            Ok((parse_timestamp(&taken_at, 0)?, row.get(1)?, row.get(2)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(snapshots)
+}
+
+#[derive(Debug, Clone)]
+pub struct BranchInterval {
+    pub repository: String,
+    pub branch: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+}
+
+// Estende o último intervalo do repositório se o branch é o mesmo, senão abre um novo
+#[instrument(skip_all)]
+pub async fn record_branch(
+    conn: &DbConnection,
+    repository: &str,
+    branch: &str,
+    now: DateTime<Utc>,
+    max_gap_seconds: i64,
+) -> Result<()> {
+    let conn = conn.lock().await;
+
+    let last: Option<(i64, String, String)> = conn
+        .query_row(
+            "SELECT id, branch, end_time FROM branch_history
+             WHERE repository = ?
+             ORDER BY end_time DESC
+             LIMIT 1",
+            params![repository],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?;
+
+    if let Some((id, last_branch, end_time)) = last {
+        let end_time = DateTime::parse_from_rfc3339(&end_time)?.with_timezone(&Utc);
+        if last_branch == branch && (now - end_time).num_seconds() <= max_gap_seconds {
+            conn.execute(
+                "UPDATE branch_history SET end_time = ? WHERE id = ?",
+                params![now.to_rfc3339(), id],
+            )?;
+            return Ok(());
+        }
+    }
+
+    debug!("Branch change in {}: {}", repository, branch);
+    conn.execute(
+        "INSERT INTO branch_history (repository, branch, start_time, end_time) VALUES (?1, ?2, ?3, ?3)",
+        params![repository, branch, now.to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+#[instrument(skip_all)]
+pub async fn get_branch_intervals_between(
+    conn: &DbConnection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<BranchInterval>> {
+    let conn = conn.lock().await;
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT repository, branch, start_time, end_time
+        FROM branch_history
+        WHERE end_time >= ? AND start_time <= ?
+        ORDER BY start_time
+        "#,
+    )?;
+
+    let intervals = stmt
+        .query_map(params![start.to_rfc3339(), end.to_rfc3339()], |row| {
+            let start_time: String = row.get(2)?;
+            let end_time: String = row.get(3)?;
+            Ok(BranchInterval {
+                repository: row.get(0)?,
+                branch: row.get(1)?,
+                start_time: parse_timestamp(&start_time, 2)?,
+                end_time: parse_timestamp(&end_time, 3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(intervals)
+}
+
+pub struct StorageUsage {
+    pub size_bytes: i64,
+    pub rows: i64,
+    pub first_activity: Option<DateTime<Utc>>,
+    pub last_activity: Option<DateTime<Utc>>,
+}
+
+#[instrument(skip_all)]
+pub async fn get_storage_usage(conn: &DbConnection) -> Result<StorageUsage> {
+    let conn = conn.lock().await;
+
+    let page_count: i64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+    let page_size: i64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+
+    let (rows, first, last): (i64, Option<String>, Option<String>) = conn.query_row(
+        "SELECT COUNT(*), MIN(start_time), MAX(end_time) FROM activities",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )?;
+
+    Ok(StorageUsage {
+        size_bytes: page_count * page_size,
+        rows,
+        first_activity: first.map(|t| parse_timestamp(&t, 1)).transpose()?,
+        last_activity: last.map(|t| parse_timestamp(&t, 2)).transpose()?,
+    })
+}
+
+// Índices criados pelas migrações; a ausência de algum indica banco antigo ou restaurado pela metade
+pub const EXPECTED_INDEXES: &[&str] = &[
+    "idx_activities_start_time",
+    "idx_activities_application",
+    "idx_activities_day",
+    "idx_activities_start_epoch",
+    "idx_adjustments_date",
+    "idx_window_snapshots_taken_at",
+    "idx_branch_history_repository",
+    "idx_high_frequency_samples_session",
+    "idx_screenshots_taken_at",
+    "idx_activities_device_source",
+];
+
+#[instrument(skip_all)]
+pub async fn get_index_names(conn: &DbConnection) -> Result<Vec<String>> {
+    let conn = conn.lock().await;
+    let mut stmt = conn.prepare("SELECT name FROM sqlite_master WHERE type = 'index' AND name NOT LIKE 'sqlite_%'")?;
+    let names = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    Ok(names)
+}
+
+#[instrument(skip_all)]
+pub async fn get_schema_version(conn: &DbConnection) -> Result<i64> {
+    let conn = conn.lock().await;
+    migrations::current_version(&conn)
+}
+
+#[instrument(skip_all)]
 pub async fn get_unique_applications(conn: &DbConnection) -> Result<Vec<String>> {
     let conn = conn.lock().await;
     let mut stmt = conn.prepare("SELECT DISTINCT application FROM activities")?;
@@ -312,4 +706,1780 @@ pub async fn get_unique_applications(conn: &DbConnection) -> Result<Vec<String>>
         .query_map([], |row| row.get(0))?
         .collect::<Result<Vec<String>, _>>()?;
     Ok(apps)
-} 
\ No newline at end of file
+}
+
+// Correção manual de categoria, com o título mais recente do app como contexto
+#[derive(Debug, Clone)]
+pub struct CategoryAssignment {
+    pub assigned_at: DateTime<Utc>,
+    pub application: String,
+    pub title: Option<String>,
+    pub category_id: String,
+}
+
+#[instrument(skip_all)]
+pub async fn record_category_assignment(
+    conn: &DbConnection,
+    application: &str,
+    category_id: &str,
+) -> Result<()> {
+    let conn = conn.lock().await;
+
+    let title: Option<String> = conn
+        .query_row(
+            "SELECT title FROM activities WHERE application = ? ORDER BY end_time DESC LIMIT 1",
+            params![application],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    conn.execute(
+        "INSERT INTO category_assignments (assigned_at, application, title, category_id) VALUES (?1, ?2, ?3, ?4)",
+        params![Utc::now().to_rfc3339(), application, title, category_id],
+    )?;
+    Ok(())
+}
+
+#[instrument(skip_all)]
+pub async fn get_category_assignments(conn: &DbConnection) -> Result<Vec<CategoryAssignment>> {
+    let conn = conn.lock().await;
+    let mut stmt = conn.prepare(
+        "SELECT assigned_at, application, title, category_id FROM category_assignments ORDER BY assigned_at",
+    )?;
+
+    let assignments = stmt
+        .query_map([], |row| {
+            let assigned_at: String = row.get(0)?;
+            Ok(CategoryAssignment {
+                assigned_at: parse_timestamp(&assigned_at, 0)?,
+                application: row.get(1)?,
+                title: row.get(2)?,
+                category_id: row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(assignments)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HighFrequencySession {
+    pub id: i64,
+    pub label: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub stopped_at: Option<DateTime<Utc>>,
+    pub sample_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HighFrequencySample {
+    pub sampled_at: DateTime<Utc>,
+    pub application: String,
+    pub title: String,
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    pub width: Option<f64>,
+    pub height: Option<f64>,
+    pub clicks: u32,
+    pub key_presses: u32,
+    pub scrolls: u32,
+}
+
+#[instrument(skip_all)]
+pub async fn create_high_frequency_session(
+    conn: &DbConnection,
+    label: Option<&str>,
+    started_at: DateTime<Utc>,
+    ends_at: DateTime<Utc>,
+) -> Result<i64> {
+    let conn = conn.lock().await;
+    conn.execute(
+        "INSERT INTO high_frequency_sessions (label, started_at, ends_at) VALUES (?1, ?2, ?3)",
+        params![label, started_at.to_rfc3339(), ends_at.to_rfc3339()],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+#[instrument(skip_all)]
+pub async fn finish_high_frequency_session(
+    conn: &DbConnection,
+    id: i64,
+    stopped_at: DateTime<Utc>,
+) -> Result<()> {
+    let conn = conn.lock().await;
+    conn.execute(
+        "UPDATE high_frequency_sessions SET stopped_at = ? WHERE id = ? AND stopped_at IS NULL",
+        params![stopped_at.to_rfc3339(), id],
+    )?;
+    Ok(())
+}
+
+// Sessões que ficaram abertas quando o app foi encerrado à força: fecham na última amostra
+#[instrument(skip_all)]
+pub async fn close_interrupted_high_frequency_sessions(conn: &DbConnection) -> Result<usize> {
+    let conn = conn.lock().await;
+    let closed = conn.execute(
+        "UPDATE high_frequency_sessions
+         SET stopped_at = COALESCE(
+             (SELECT MAX(sampled_at) FROM high_frequency_samples WHERE session_id = high_frequency_sessions.id),
+             started_at
+         )
+         WHERE stopped_at IS NULL",
+        [],
+    )?;
+    Ok(closed)
+}
+
+#[instrument(skip_all)]
+pub async fn save_high_frequency_samples(
+    conn: &DbConnection,
+    session_id: i64,
+    samples: &[HighFrequencySample],
+) -> Result<()> {
+    let mut conn = conn.lock().await;
+    let tx = conn.transaction()?;
+
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO high_frequency_samples
+                (session_id, sampled_at, application, title, x, y, width, height, clicks, key_presses, scrolls)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        )?;
+        for sample in samples {
+            stmt.execute(params![
+                session_id,
+                sample.sampled_at.to_rfc3339(),
+                sample.application,
+                sample.title,
+                sample.x,
+                sample.y,
+                sample.width,
+                sample.height,
+                sample.clicks,
+                sample.key_presses,
+                sample.scrolls,
+            ])?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(())
+}
+
+#[instrument(skip_all)]
+pub async fn get_high_frequency_sessions(conn: &DbConnection) -> Result<Vec<HighFrequencySession>> {
+    let conn = conn.lock().await;
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT s.id, s.label, s.started_at, s.ends_at, s.stopped_at,
+               (SELECT COUNT(*) FROM high_frequency_samples WHERE session_id = s.id)
+        FROM high_frequency_sessions s
+        ORDER BY s.started_at DESC
+        "#,
+    )?;
+
+    let sessions = stmt
+        .query_map([], |row| {
+            let started_at: String = row.get(2)?;
+            let ends_at: String = row.get(3)?;
+            let stopped_at: Option<String> = row.get(4)?;
+            Ok(HighFrequencySession {
+                id: row.get(0)?,
+                label: row.get(1)?,
+                started_at: parse_timestamp(&started_at, 2)?,
+                ends_at: parse_timestamp(&ends_at, 3)?,
+                stopped_at: stopped_at.as_deref().map(|s| parse_timestamp(s, 4)).transpose()?,
+                sample_count: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(sessions)
+}
+
+#[instrument(skip_all)]
+pub async fn get_high_frequency_samples(
+    conn: &DbConnection,
+    session_id: i64,
+) -> Result<Vec<HighFrequencySample>> {
+    let conn = conn.lock().await;
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT sampled_at, application, title, x, y, width, height, clicks, key_presses, scrolls
+        FROM high_frequency_samples
+        WHERE session_id = ?
+        ORDER BY sampled_at
+        "#,
+    )?;
+
+    let samples = stmt
+        .query_map(params![session_id], |row| {
+            let sampled_at: String = row.get(0)?;
+            Ok(HighFrequencySample {
+                sampled_at: parse_timestamp(&sampled_at, 0)?,
+                application: row.get(1)?,
+                title: row.get(2)?,
+                x: row.get(3)?,
+                y: row.get(4)?,
+                width: row.get(5)?,
+                height: row.get(6)?,
+                clicks: row.get(7)?,
+                key_presses: row.get(8)?,
+                scrolls: row.get(9)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(samples)
+}
+
+#[instrument(skip_all)]
+pub async fn delete_high_frequency_session(conn: &DbConnection, id: i64) -> Result<usize> {
+    let mut conn = conn.lock().await;
+    let tx = conn.transaction()?;
+    let samples = tx.execute("DELETE FROM high_frequency_samples WHERE session_id = ?", params![id])?;
+    tx.execute("DELETE FROM high_frequency_sessions WHERE id = ?", params![id])?;
+    tx.commit()?;
+    Ok(samples)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Adjustment {
+    pub id: i64,
+    pub date: NaiveDate,
+    pub category_id: String,
+    pub minutes: i64,
+    pub note: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[instrument(skip_all)]
+pub async fn add_adjustment(
+    conn: &DbConnection,
+    date: NaiveDate,
+    category_id: &str,
+    minutes: i64,
+    note: &str,
+) -> Result<Adjustment> {
+    let conn = conn.lock().await;
+    let created_at = Utc::now();
+    conn.execute(
+        "INSERT INTO adjustments (date, category_id, minutes, note, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![date.to_string(), category_id, minutes, note, created_at.to_rfc3339()],
+    )?;
+
+    Ok(Adjustment {
+        id: conn.last_insert_rowid(),
+        date,
+        category_id: category_id.to_string(),
+        minutes,
+        note: note.to_string(),
+        created_at,
+    })
+}
+
+// Ajustes dos dias entre as duas datas, inclusive
+#[instrument(skip_all)]
+pub async fn get_adjustments_between(
+    conn: &DbConnection,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<Adjustment>> {
+    let conn = conn.lock().await;
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT id, date, category_id, minutes, note, created_at
+        FROM adjustments
+        WHERE date >= ? AND date <= ?
+        ORDER BY date, id
+        "#,
+    )?;
+
+    let adjustments = stmt
+        .query_map(params![start.to_string(), end.to_string()], adjustment_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(adjustments)
+}
+
+fn adjustment_from_row(row: &rusqlite::Row) -> rusqlite::Result<Adjustment> {
+    let date: String = row.get(1)?;
+    let created_at: String = row.get(5)?;
+    Ok(Adjustment {
+        id: row.get(0)?,
+        date: date.parse().map_err(|e| rusqlite::Error::FromSqlConversionFailure(
+            1,
+            rusqlite::types::Type::Text,
+            Box::new(e),
+        ))?,
+        category_id: row.get(2)?,
+        minutes: row.get(3)?,
+        note: row.get(4)?,
+        created_at: parse_timestamp(&created_at, 5)?,
+    })
+}
+
+#[instrument(skip_all)]
+pub async fn delete_adjustment(conn: &DbConnection, id: i64) -> Result<bool> {
+    let conn = conn.lock().await;
+    let deleted = conn.execute("DELETE FROM adjustments WHERE id = ?", params![id])?;
+    Ok(deleted > 0)
+}
+
+#[derive(Debug, Clone)]
+pub struct AppTotals {
+    pub application: String,
+    pub total_duration: i64,
+    pub idle_duration: i64,
+}
+
+// Só os horários dos trechos ociosos, para classificar as pausas sem carregar as linhas
+#[instrument(skip_all)]
+pub async fn get_idle_intervals(
+    conn: &DbConnection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    include_off_hours: bool,
+) -> Result<Vec<(DateTime<Utc>, DateTime<Utc>)>> {
+    let conn = conn.lock().await;
+    let mut stmt = conn.prepare(
+        "SELECT start_time, end_time FROM activities
+         WHERE is_idle AND start_epoch >= ?1 AND end_epoch <= ?2 AND (?3 OR NOT off_hours)
+         ORDER BY start_time",
+    )?;
+
+    let intervals = stmt
+        .query_map(params![start.timestamp(), end.timestamp(), include_off_hours], |row| {
+            let start_time: String = row.get(0)?;
+            let end_time: String = row.get(1)?;
+            Ok((parse_timestamp(&start_time, 0)?, parse_timestamp(&end_time, 1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(intervals)
+}
+
+// Soma por app direto no SQLite, sem carregar as linhas
+#[instrument(skip_all)]
+pub async fn get_app_totals_between(
+    conn: &DbConnection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    include_off_hours: bool,
+) -> Result<Vec<AppTotals>> {
+    let conn = conn.lock().await;
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT application,
+               SUM(duration_seconds),
+               SUM(CASE WHEN is_idle THEN duration_seconds ELSE 0 END)
+        FROM activities
+        WHERE start_epoch >= ?1 AND end_epoch <= ?2 AND (?3 OR NOT off_hours)
+        GROUP BY application
+        ORDER BY SUM(duration_seconds) DESC
+        "#,
+    )?;
+
+    let totals = stmt
+        .query_map(params![start.timestamp(), end.timestamp(), include_off_hours], |row| {
+            Ok(AppTotals {
+                application: row.get(0)?,
+                total_duration: row.get(1)?,
+                idle_duration: row.get(2)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(totals)
+}
+
+// Recalcula os resumos por hora/app a partir de `since` (epoch). Atividades são
+// atribuídas à hora em que começaram; as de fora do expediente (off_hours) ficam
+// de fora, como nas demais estatísticas. Retorna o número de linhas geradas
+#[instrument(skip_all)]
+pub async fn refresh_hourly_rollups(conn: &DbConnection, since: i64, now: DateTime<Utc>) -> Result<usize> {
+    let mut conn = conn.lock().await;
+    let since = since - since.rem_euclid(3600);
+    let current_hour = now.timestamp() - now.timestamp().rem_euclid(3600);
+
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM hourly_rollups WHERE hour_start >= ?", params![since])?;
+    let rows = tx.execute(
+        r#"
+        INSERT INTO hourly_rollups (hour_start, application, total_seconds, idle_seconds, activity_count)
+        SELECT start_epoch - (start_epoch % 3600),
+               application,
+               SUM(duration_seconds),
+               SUM(CASE WHEN is_idle THEN duration_seconds ELSE 0 END),
+               COUNT(*)
+        FROM activities
+        WHERE start_epoch >= ? AND start_epoch < ? AND NOT off_hours
+        GROUP BY start_epoch - (start_epoch % 3600), application
+        "#,
+        params![since, current_hour],
+    )?;
+    // Horas anteriores à atual estão cobertas pelos resumos
+    tx.execute(
+        "INSERT INTO rollup_state (id, refreshed_through) VALUES (1, ?1)
+         ON CONFLICT(id) DO UPDATE SET refreshed_through = ?1",
+        params![current_hour],
+    )?;
+    tx.commit()?;
+
+    Ok(rows)
+}
+
+#[instrument(skip_all)]
+pub async fn get_rollups_refreshed_through(conn: &DbConnection) -> Result<Option<i64>> {
+    let conn = conn.lock().await;
+    let refreshed = conn
+        .query_row("SELECT refreshed_through FROM rollup_state WHERE id = 1", [], |row| row.get(0))
+        .optional()?;
+    Ok(refreshed)
+}
+
+#[instrument(skip_all)]
+pub async fn get_first_activity_epoch(conn: &DbConnection) -> Result<Option<i64>> {
+    let conn = conn.lock().await;
+    let first = conn.query_row("SELECT MIN(start_epoch) FROM activities", [], |row| row.get(0))?;
+    Ok(first)
+}
+
+// Início do primeiro registro no intervalo, usado na proporção da meta diária
+#[instrument(skip_all)]
+pub async fn get_first_activity_between(
+    conn: &DbConnection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Option<DateTime<Utc>>> {
+    let conn = conn.lock().await;
+    let first = conn
+        .query_row(
+            "SELECT start_time FROM activities
+             WHERE start_epoch >= ? AND start_epoch <= ?
+             ORDER BY start_epoch LIMIT 1",
+            params![start.timestamp(), end.timestamp()],
+            |row| parse_timestamp(&row.get::<_, String>(0)?, 0),
+        )
+        .optional()?;
+    Ok(first)
+}
+
+// Mesma atribuição dos resumos (pelo início da atividade), para as bordas do intervalo
+async fn get_app_totals_started_between(conn: &DbConnection, from: i64, until: i64) -> Result<Vec<AppTotals>> {
+    let conn = conn.lock().await;
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT application,
+               SUM(duration_seconds),
+               SUM(CASE WHEN is_idle THEN duration_seconds ELSE 0 END)
+        FROM activities
+        WHERE start_epoch >= ? AND start_epoch < ? AND NOT off_hours
+        GROUP BY application
+        "#,
+    )?;
+
+    let totals = stmt
+        .query_map(params![from, until], |row| {
+            Ok(AppTotals {
+                application: row.get(0)?,
+                total_duration: row.get(1)?,
+                idle_duration: row.get(2)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(totals)
+}
+
+// Totais por app: resumos por hora até onde já foram calculados e agregação direta
+// das linhas para o restante do intervalo (normalmente só a hora atual). Limites de
+// dia fora da hora cheia (fusos com meia hora) também vêm das linhas
+#[instrument(skip_all)]
+pub async fn get_app_totals_from_rollups(
+    conn: &DbConnection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<AppTotals>> {
+    let refreshed_through = get_rollups_refreshed_through(conn).await?.unwrap_or(i64::MIN);
+    let until = end.timestamp() + 1;
+    let first_hour = start.timestamp() + (3600 - start.timestamp().rem_euclid(3600)) % 3600;
+    let last_hour = until - until.rem_euclid(3600);
+    let rollup_start = first_hour.min(until);
+    let split = refreshed_through.min(last_hour).clamp(rollup_start, until);
+
+    let mut totals: Vec<AppTotals> = {
+        let conn = conn.lock().await;
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT application, SUM(total_seconds), SUM(idle_seconds)
+            FROM hourly_rollups
+            WHERE hour_start >= ? AND hour_start < ?
+            GROUP BY application
+            "#,
+        )?;
+        let rows = stmt
+            .query_map(params![rollup_start, split], |row| {
+                Ok(AppTotals {
+                    application: row.get(0)?,
+                    total_duration: row.get(1)?,
+                    idle_duration: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows
+    };
+
+    let edges = [(start.timestamp(), rollup_start), (split, until)];
+    for (from, to) in edges.into_iter().filter(|(from, to)| from < to) {
+        for raw in get_app_totals_started_between(conn, from, to).await? {
+            match totals.iter_mut().find(|t| t.application == raw.application) {
+                Some(existing) => {
+                    existing.total_duration += raw.total_duration;
+                    existing.idle_duration += raw.idle_duration;
+                }
+                None => totals.push(raw),
+            }
+        }
+    }
+
+    totals.sort_by(|a, b| b.total_duration.cmp(&a.total_duration));
+    Ok(totals)
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct PurgeResult {
+    pub activities: usize,
+    pub window_snapshots: usize,
+    pub branch_history: usize,
+    pub high_frequency_sessions: usize,
+    pub high_frequency_samples: usize,
+}
+
+// Apaga dados brutos anteriores a cutoff; hourly_rollups não é tocado
+#[instrument(skip_all)]
+pub async fn purge_before(conn: &DbConnection, cutoff: DateTime<Utc>) -> Result<PurgeResult> {
+    let mut conn = conn.lock().await;
+    let cutoff_text = cutoff.to_rfc3339();
+
+    let tx = conn.transaction()?;
+    let result = PurgeResult {
+        activities: tx.execute("DELETE FROM activities WHERE start_epoch < ?", params![cutoff.timestamp()])?,
+        window_snapshots: tx.execute("DELETE FROM window_snapshots WHERE taken_at < ?", params![cutoff_text])?,
+        branch_history: tx.execute("DELETE FROM branch_history WHERE end_time < ?", params![cutoff_text])?,
+        high_frequency_samples: tx.execute(
+            "DELETE FROM high_frequency_samples WHERE session_id IN
+                (SELECT id FROM high_frequency_sessions WHERE COALESCE(stopped_at, ends_at) < ?)",
+            params![cutoff_text],
+        )?,
+        high_frequency_sessions: tx.execute(
+            "DELETE FROM high_frequency_sessions WHERE COALESCE(stopped_at, ends_at) < ?",
+            params![cutoff_text],
+        )?,
+    };
+    // A retenção é local: o que já foi enviado continua nos outros dispositivos
+    tx.execute("DELETE FROM sync_tombstones WHERE start_epoch < ?", params![cutoff.timestamp()])?;
+    tx.commit()?;
+
+    Ok(result)
+}
+
+#[derive(Debug, Default)]
+pub struct DeletedActivities {
+    pub matched: usize,
+    // Início mais antigo afetado, para recalcular os resumos dali em diante
+    pub earliest: Option<i64>,
+    // Arquivos das capturas de tela removidas, para apagar do disco
+    pub screenshot_paths: Vec<String>,
+}
+
+// Condições do intervalo e/ou app para uma tabela: `time` é a coluna do instante e
+// `epoch` diz se ela é numérica (start_epoch) ou texto RFC 3339
+fn deletion_filter(
+    time: &str,
+    epoch: bool,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+    application: Option<&str>,
+) -> (String, Vec<Box<dyn ToSql>>) {
+    let bound = |at: DateTime<Utc>| -> Box<dyn ToSql> {
+        if epoch {
+            Box::new(at.timestamp())
+        } else {
+            Box::new(at.to_rfc3339())
+        }
+    };
+    let mut conditions = Vec::new();
+    let mut values: Vec<Box<dyn ToSql>> = Vec::new();
+    if let Some(start) = start {
+        conditions.push(format!("{} >= ?", time));
+        values.push(bound(start));
+    }
+    if let Some(end) = end {
+        conditions.push(format!("{} < ?", time));
+        values.push(bound(end));
+    }
+    if let Some(application) = application {
+        conditions.push("application = ?".to_string());
+        values.push(Box::new(application.to_string()));
+    }
+    (conditions.join(" AND "), values)
+}
+
+// Remove atividades que começam no intervalo e/ou de um app; com `dry_run` só conta.
+// Leva junto o que foi registrado das mesmas janelas: snapshots, capturas de tela e
+// categorizações manuais. Linhas já sincronizadas viram tombstones (migração 24)
+#[instrument(skip_all)]
+pub async fn delete_activities(
+    conn: &DbConnection,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+    application: Option<&str>,
+    dry_run: bool,
+) -> Result<DeletedActivities> {
+    if start.is_none() && end.is_none() && application.is_none() {
+        return Err(anyhow::anyhow!("Refusing to delete activities without a range or application"));
+    }
+    let (where_clause, values) = deletion_filter("start_epoch", true, start, end, application);
+
+    let mut conn = conn.lock().await;
+    let tx = conn.transaction()?;
+    let (count, earliest): (i64, Option<i64>) = tx.query_row(
+        &format!("SELECT COUNT(*), MIN(start_epoch) FROM activities WHERE {}", where_clause),
+        rusqlite::params_from_iter(values.iter()),
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let mut deleted = DeletedActivities {
+        matched: count as usize,
+        earliest,
+        screenshot_paths: Vec::new(),
+    };
+    if dry_run {
+        return Ok(deleted);
+    }
+
+    tx.execute(
+        &format!("DELETE FROM activities WHERE {}", where_clause),
+        rusqlite::params_from_iter(values.iter()),
+    )?;
+
+    let (where_clause, values) = deletion_filter("taken_at", false, start, end, application);
+    deleted.screenshot_paths = {
+        let mut stmt = tx.prepare(&format!("SELECT path FROM screenshots WHERE {}", where_clause))?;
+        let paths = stmt
+            .query_map(rusqlite::params_from_iter(values.iter()), |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        paths
+    };
+    for table in ["screenshots", "window_snapshots"] {
+        tx.execute(
+            &format!("DELETE FROM {} WHERE {}", table, where_clause),
+            rusqlite::params_from_iter(values.iter()),
+        )?;
+    }
+
+    let (where_clause, values) = deletion_filter("assigned_at", false, start, end, application);
+    tx.execute(
+        &format!("DELETE FROM category_assignments WHERE {}", where_clause),
+        rusqlite::params_from_iter(values.iter()),
+    )?;
+    tx.commit()?;
+
+    Ok(deleted)
+}
+
+// Aplicativo das atividades criadas ao rotular uma ausência (ver IdlePromptConfig)
+pub const AWAY_APPLICATION: &str = "Away";
+
+// Converte os trechos ociosos locais do intervalo numa atividade "Away" com o rótulo
+// escolhido. Linhas que passam dos limites são divididas: só o que está dentro do
+// intervalo é rotulado. A linha original fica com o fim, que o tracker ainda pode
+// estender pelo id
+#[instrument(skip_all)]
+pub async fn label_idle_period(
+    conn: &DbConnection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    label: &str,
+    category_id: Option<&str>,
+) -> Result<usize> {
+    let mut conn = conn.lock().await;
+    let tx = conn.transaction()?;
+
+    let rows: Vec<(i64, String, String)> = {
+        let mut stmt = tx.prepare(
+            "SELECT id, start_time, end_time FROM activities
+             WHERE is_idle = 1 AND device_id IS NULL AND start_epoch < ?2 AND end_epoch > ?1",
+        )?;
+        let rows = stmt
+            .query_map(params![start.timestamp(), end.timestamp()], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows
+    };
+
+    // Cópia da linha com outro intervalo; retorna o id novo
+    let copy = |id: i64, from: DateTime<Utc>, to: DateTime<Utc>| -> Result<i64> {
+        tx.execute(
+            "INSERT INTO activities (title, application, start_time, end_time, is_browser, url, is_idle, desktop, network, project, cwd, profile, off_hours, manual)
+             SELECT title, application, ?2, ?3, is_browser, url, is_idle, desktop, network, project, cwd, profile, off_hours, manual
+             FROM activities WHERE id = ?1",
+            params![id, from.to_rfc3339(), to.to_rfc3339()],
+        )?;
+        Ok(tx.last_insert_rowid())
+    };
+
+    let mut ids = Vec::new();
+    for (id, row_start, row_end) in &rows {
+        let (row_start, row_end) = (parse_timestamp(row_start, 1)?, parse_timestamp(row_end, 2)?);
+        if row_start < start {
+            copy(*id, row_start, start)?;
+        }
+        let from = row_start.max(start);
+        if row_end > end {
+            ids.push(copy(*id, from, end)?);
+            tx.execute("UPDATE activities SET start_time = ?1 WHERE id = ?2", params![end.to_rfc3339(), id])?;
+        } else {
+            tx.execute("UPDATE activities SET start_time = ?1 WHERE id = ?2", params![from.to_rfc3339(), id])?;
+            ids.push(*id);
+        }
+    }
+
+    let created_at = Utc::now().to_rfc3339();
+    for id in &ids {
+        tx.execute(
+            "UPDATE activities SET application = ?1, title = ?2, is_idle = 0, is_browser = 0, url = NULL
+             WHERE id = ?3",
+            params![AWAY_APPLICATION, label, id],
+        )?;
+        if let Some(category_id) = category_id {
+            tx.execute(
+                "INSERT INTO category_overrides (activity_id, category_id, pattern, created_at)
+                 VALUES (?1, ?2, NULL, ?3)
+                 ON CONFLICT(activity_id) DO UPDATE SET
+                    category_id = excluded.category_id,
+                    pattern = excluded.pattern,
+                    created_at = excluded.created_at",
+                params![id, category_id, created_at],
+            )?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(ids.len())
+}
+
+// Aplicativo dos lançamentos manuais sem aplicativo informado
+pub const MANUAL_APPLICATION: &str = "Manual";
+
+// Grava um lançamento manual (tempo longe do computador). O ocioso local que ele
+// cobre é cortado para não contar duas vezes; atividade registrada no intervalo
+// impede o lançamento. Retorna o id e o início mais antigo afetado (epoch)
+#[instrument(skip_all)]
+pub async fn add_manual_activity(
+    conn: &DbConnection,
+    activity: &WindowActivity,
+    category_id: Option<&str>,
+) -> Result<(i64, i64)> {
+    let mut conn = conn.lock().await;
+    let tx = conn.transaction()?;
+    let (start, end) = (activity.start_time, activity.end_time);
+    let (start_epoch, end_epoch) = (start.timestamp(), end.timestamp());
+
+    let overlapping: i64 = tx.query_row(
+        "SELECT COUNT(*) FROM activities
+         WHERE NOT is_idle AND device_id IS NULL AND start_epoch < ?2 AND end_epoch > ?1",
+        params![start_epoch, end_epoch],
+        |row| row.get(0),
+    )?;
+    if overlapping > 0 {
+        return Err(anyhow::anyhow!("The period overlaps {} recorded activities", overlapping));
+    }
+    let since: Option<i64> = tx.query_row(
+        "SELECT MIN(start_epoch) FROM activities
+         WHERE is_idle AND device_id IS NULL AND start_epoch < ?2 AND end_epoch > ?1",
+        params![start_epoch, end_epoch],
+        |row| row.get(0),
+    )?;
+
+    // Ocioso que cobre o intervalo inteiro vira dois trechos: antes e depois dele
+    tx.execute(
+        "INSERT INTO activities (title, application, start_time, end_time, is_browser, url, is_idle, desktop, network, project, cwd, profile, off_hours, manual)
+         SELECT title, application, ?3, end_time, is_browser, url, is_idle, desktop, network, project, cwd, profile, off_hours, manual
+         FROM activities
+         WHERE is_idle AND device_id IS NULL AND start_epoch < ?1 AND end_epoch > ?2",
+        params![start_epoch, end_epoch, end.to_rfc3339()],
+    )?;
+    tx.execute(
+        "UPDATE activities SET end_time = ?2
+         WHERE is_idle AND device_id IS NULL AND start_epoch < ?1 AND end_epoch > ?1",
+        params![start_epoch, start.to_rfc3339()],
+    )?;
+    tx.execute(
+        "UPDATE activities SET start_time = ?2
+         WHERE is_idle AND device_id IS NULL AND start_epoch < ?1 AND end_epoch > ?1",
+        params![end_epoch, end.to_rfc3339()],
+    )?;
+    tx.execute(
+        "DELETE FROM activities
+         WHERE is_idle AND device_id IS NULL AND start_epoch >= ?1 AND end_epoch <= ?2",
+        params![start_epoch, end_epoch],
+    )?;
+
+    tx.execute(
+        "INSERT INTO activities (title, application, start_time, end_time, is_browser, url, is_idle, desktop, network, project, cwd, profile, off_hours, manual)
+         VALUES (?1, ?2, ?3, ?4, 0, NULL, 0, NULL, NULL, ?5, NULL, NULL, 0, 1)",
+        params![
+            activity.title,
+            activity.application,
+            start.to_rfc3339(),
+            end.to_rfc3339(),
+            activity.project,
+        ],
+    )?;
+    let id = tx.last_insert_rowid();
+    if let Some(category_id) = category_id {
+        tx.execute(
+            "INSERT INTO category_overrides (activity_id, category_id, pattern, created_at)
+             VALUES (?1, ?2, NULL, ?3)",
+            params![id, category_id, Utc::now().to_rfc3339()],
+        )?;
+    }
+
+    tx.commit()?;
+    Ok((id, since.map_or(start_epoch, |since| since.min(start_epoch))))
+}
+
+// Campos corrigíveis de uma atividade; None mantém o valor atual
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ActivityUpdate {
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub title: Option<String>,
+    pub is_idle: Option<bool>,
+}
+
+// Retorna a atividade como estava e como ficou, ou None se o id não existe
+#[instrument(skip_all)]
+pub async fn update_activity(
+    conn: &DbConnection,
+    id: i64,
+    update: &ActivityUpdate,
+) -> Result<Option<(WindowActivity, WindowActivity)>> {
+    let mut conn = conn.lock().await;
+    let tx = conn.transaction()?;
+
+    let select = format!("SELECT {} FROM activities WHERE id = ?", ACTIVITY_COLUMNS);
+    let Some(before) = tx.query_row(&select, params![id], activity_from_row).optional()? else {
+        return Ok(None);
+    };
+
+    let start_time = update.start_time.unwrap_or(before.start_time);
+    let end_time = update.end_time.unwrap_or(before.end_time);
+    if end_time <= start_time {
+        return Err(anyhow::anyhow!("End time must be after start time"));
+    }
+
+    tx.execute(
+        "UPDATE activities SET start_time = ?, end_time = ?, title = ?, is_idle = ? WHERE id = ?",
+        params![
+            start_time.to_rfc3339(),
+            end_time.to_rfc3339(),
+            update.title.as_deref().unwrap_or(&before.title),
+            update.is_idle.unwrap_or(before.is_idle),
+            id,
+        ],
+    )?;
+    let after = tx.query_row(&select, params![id], activity_from_row)?;
+    tx.commit()?;
+
+    Ok(Some((before, after)))
+}
+
+// Correções de sobreposição: exclusões e novos intervalos por id, numa transação.
+// Só linhas locais; as sincronizadas pertencem ao dispositivo de origem
+#[instrument(skip_all)]
+pub async fn apply_activity_repairs(
+    conn: &DbConnection,
+    deletes: &[i64],
+    updates: &[(i64, DateTime<Utc>, DateTime<Utc>)],
+) -> Result<()> {
+    let mut conn = conn.lock().await;
+    let tx = conn.transaction()?;
+    for id in deletes {
+        tx.execute("DELETE FROM activities WHERE id = ? AND device_id IS NULL", params![id])?;
+    }
+    for (id, start, end) in updates {
+        tx.execute(
+            "UPDATE activities SET start_time = ?, end_time = ? WHERE id = ? AND device_id IS NULL",
+            params![start.to_rfc3339(), end.to_rfc3339(), id],
+        )?;
+    }
+    tx.commit()?;
+
+    Ok(())
+}
+
+// Atividades locais novas ou alteradas desde o último envio, já encerradas (não serão
+// mais estendidas), com a revisão lida
+#[instrument(skip_all)]
+pub async fn get_unsynced_activities(
+    conn: &DbConnection,
+    settled_before: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<(WindowActivity, i64)>> {
+    let conn = conn.lock().await;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}, revision FROM activities
+         WHERE device_id IS NULL AND revision > pushed_revision AND end_epoch < ?
+         ORDER BY id
+         LIMIT ?",
+        ACTIVITY_COLUMNS
+    ))?;
+
+    let activities = stmt
+        .query_map(params![settled_before.timestamp(), limit], |row| {
+            Ok((activity_from_row(row)?, row.get(18)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(activities)
+}
+
+// Só a revisão enviada: uma alteração feita durante o envio continua pendente
+#[instrument(skip_all)]
+pub async fn mark_activities_pushed(conn: &DbConnection, pushed: &[(i64, i64)]) -> Result<()> {
+    let mut conn = conn.lock().await;
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare("UPDATE activities SET pushed_revision = ?2 WHERE id = ?1")?;
+        for (id, revision) in pushed {
+            stmt.execute(params![id, revision])?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+// ids de linhas já enviadas que foram apagadas aqui
+#[instrument(skip_all)]
+pub async fn get_sync_tombstones(conn: &DbConnection, limit: i64) -> Result<Vec<i64>> {
+    let conn = conn.lock().await;
+    let mut stmt = conn.prepare("SELECT source_id FROM sync_tombstones ORDER BY source_id LIMIT ?")?;
+    let ids = stmt.query_map(params![limit], |row| row.get(0))?.collect::<Result<Vec<_>, _>>()?;
+    Ok(ids)
+}
+
+#[instrument(skip_all)]
+pub async fn clear_sync_tombstones(conn: &DbConnection, ids: &[i64]) -> Result<()> {
+    let mut conn = conn.lock().await;
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare("DELETE FROM sync_tombstones WHERE source_id = ?")?;
+        for id in ids {
+            stmt.execute(params![id])?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+#[instrument(skip_all)]
+pub async fn get_pull_cursor(conn: &DbConnection) -> Result<Option<String>> {
+    let conn = conn.lock().await;
+    let cursor = conn
+        .query_row("SELECT pull_cursor FROM sync_state WHERE id = 1", [], |row| row.get(0))
+        .optional()?;
+    Ok(cursor.flatten())
+}
+
+#[instrument(skip_all)]
+pub async fn set_pull_cursor(conn: &DbConnection, pull_cursor: Option<&str>) -> Result<()> {
+    let conn = conn.lock().await;
+    conn.execute(
+        "INSERT INTO sync_state (id, pull_cursor) VALUES (1, ?1)
+         ON CONFLICT(id) DO UPDATE SET pull_cursor = ?1",
+        params![pull_cursor],
+    )?;
+    Ok(())
+}
+
+// Cada linha pertence a um único dispositivo, identificada por (device_id, source_id):
+// reenvios da origem substituem a cópia, sem conflito com o que foi gravado aqui.
+// Retorna quantas linhas mudaram e o início mais antigo recebido
+#[instrument(skip_all)]
+pub async fn upsert_remote_activities(
+    conn: &DbConnection,
+    activities: &[(String, i64, WindowActivity)],
+) -> Result<(usize, Option<i64>)> {
+    let mut conn = conn.lock().await;
+    let tx = conn.transaction()?;
+
+    let mut changed = 0;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO activities (title, application, start_time, end_time, is_browser, url, is_idle, desktop, network, project, cwd, device_id, source_id, profile, off_hours, manual)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)
+             ON CONFLICT(device_id, source_id) WHERE device_id IS NOT NULL DO UPDATE SET
+                title = excluded.title,
+                application = excluded.application,
+                start_time = excluded.start_time,
+                end_time = excluded.end_time,
+                is_browser = excluded.is_browser,
+                is_idle = excluded.is_idle,
+                url = excluded.url,
+                desktop = excluded.desktop,
+                network = excluded.network,
+                project = excluded.project,
+                cwd = excluded.cwd,
+                profile = excluded.profile,
+                off_hours = excluded.off_hours,
+                manual = excluded.manual",
+        )?;
+        for (device_id, source_id, activity) in activities {
+            changed += stmt.execute(params![
+                activity.title,
+                activity.application,
+                activity.start_time.to_rfc3339(),
+                activity.end_time.to_rfc3339(),
+                activity.is_browser,
+                activity.url,
+                activity.is_idle,
+                activity.desktop,
+                activity.network,
+                activity.project,
+                activity.cwd,
+                device_id,
+                source_id,
+                activity.profile,
+                activity.off_hours,
+                activity.manual,
+            ])?;
+        }
+    }
+    tx.commit()?;
+
+    let earliest = activities.iter().map(|(_, _, a)| a.start_time.timestamp()).min();
+    Ok((changed, earliest))
+}
+
+// Linhas apagadas na origem. Retorna quantas foram removidas e o início mais antigo
+#[instrument(skip_all)]
+pub async fn delete_remote_activities(conn: &DbConnection, deleted: &[(String, i64)]) -> Result<(usize, Option<i64>)> {
+    let mut conn = conn.lock().await;
+    let tx = conn.transaction()?;
+
+    let mut removed = 0;
+    let mut earliest: Option<i64> = None;
+    {
+        let mut stmt = tx.prepare(
+            "DELETE FROM activities WHERE device_id = ?1 AND source_id = ?2 RETURNING start_epoch",
+        )?;
+        for (device_id, source_id) in deleted {
+            let start: Option<i64> = stmt.query_row(params![device_id, source_id], |row| row.get(0)).optional()?;
+            if let Some(start) = start {
+                removed += 1;
+                earliest = Some(earliest.map_or(start, |e| e.min(start)));
+            }
+        }
+    }
+    tx.commit()?;
+
+    Ok((removed, earliest))
+}
+
+#[instrument(skip_all)]
+pub async fn get_all_activities(conn: &DbConnection) -> Result<Vec<WindowActivity>> {
+    let conn = conn.lock().await;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM activities ORDER BY start_time",
+        ACTIVITY_COLUMNS
+    ))?;
+
+    let activities = stmt
+        .query_map([], activity_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(activities)
+}
+
+#[instrument(skip_all)]
+pub async fn get_all_adjustments(conn: &DbConnection) -> Result<Vec<Adjustment>> {
+    let conn = conn.lock().await;
+    let mut stmt = conn.prepare(
+        "SELECT id, date, category_id, minutes, note, created_at FROM adjustments ORDER BY date, id",
+    )?;
+
+    let adjustments = stmt
+        .query_map([], adjustment_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(adjustments)
+}
+
+// Insere só o que ainda não existe: atividades pelo início + app + título e ajustes
+// pelo dia + categoria + minutos + criação. Com commit = false tudo é desfeito
+// e só as contagens são retornadas
+#[instrument(skip_all)]
+pub async fn merge_archive_rows(
+    conn: &DbConnection,
+    activities: &[WindowActivity],
+    adjustments: &[Adjustment],
+    commit: bool,
+) -> Result<(usize, usize)> {
+    let mut conn = conn.lock().await;
+    let tx = conn.transaction()?;
+
+    let mut new_activities = 0;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO activities (title, application, start_time, end_time, is_browser, url, is_idle, desktop, network, project, cwd, profile, off_hours, manual, device_id, source_id)
+             SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16
+             WHERE NOT EXISTS (
+                 SELECT 1 FROM activities
+                 WHERE (start_time = ?3 AND application = ?2 AND title = ?1)
+                    OR (device_id = ?15 AND source_id = ?16)
+             )",
+        )?;
+        let mut override_stmt = tx.prepare(
+            "INSERT INTO category_overrides (activity_id, category_id, pattern, created_at)
+             VALUES (?1, ?2, NULL, ?3)",
+        )?;
+        let created_at = Utc::now().to_rfc3339();
+        for activity in activities {
+            // Sem source_id a sincronização não reconheceria a cópia e a duplicaria
+            let (device_id, source_id) = match (&activity.device_id, activity.source_id) {
+                (Some(device_id), Some(source_id)) => (Some(device_id), Some(source_id)),
+                _ => (None, None),
+            };
+            let inserted = stmt.execute(params![
+                activity.title,
+                activity.application,
+                activity.start_time.to_rfc3339(),
+                activity.end_time.to_rfc3339(),
+                activity.is_browser,
+                activity.url,
+                activity.is_idle,
+                activity.desktop,
+                activity.network,
+                activity.project,
+                activity.cwd,
+                activity.profile,
+                activity.off_hours,
+                activity.manual,
+                device_id,
+                source_id,
+            ])?;
+            if inserted > 0 {
+                if let Some(category_id) = &activity.category_override {
+                    override_stmt.execute(params![tx.last_insert_rowid(), category_id, created_at])?;
+                }
+            }
+            new_activities += inserted;
+        }
+    }
+
+    let mut new_adjustments = 0;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO adjustments (date, category_id, minutes, note, created_at)
+             SELECT ?1, ?2, ?3, ?4, ?5
+             WHERE NOT EXISTS (
+                 SELECT 1 FROM adjustments
+                 WHERE date = ?1 AND category_id = ?2 AND minutes = ?3 AND created_at = ?5
+             )",
+        )?;
+        for adjustment in adjustments {
+            new_adjustments += stmt.execute(params![
+                adjustment.date.to_string(),
+                adjustment.category_id,
+                adjustment.minutes,
+                adjustment.note,
+                adjustment.created_at.to_rfc3339(),
+            ])?;
+        }
+    }
+
+    if commit {
+        tx.commit()?;
+        info!("Merged {} activities and {} adjustments", new_activities, new_adjustments);
+    }
+    Ok((new_activities, new_adjustments))
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecategorizeResult {
+    pub matched: i64,
+    pub total_seconds: i64,
+    pub applications: Vec<(String, i64)>,
+    pub applied: bool,
+}
+
+// Mesmo critério de get_time_for_pattern (título ou URL, sem diferenciar
+// maiúsculas), só trechos ativos
+const PATTERN_FILTER: &str = "start_epoch >= ?1 AND end_epoch <= ?2
+    AND NOT is_idle
+    AND (instr(lower(title), lower(?3)) > 0 OR instr(lower(COALESCE(url, '')), lower(?3)) > 0)";
+
+// Grava a categoria em todas as atividades do intervalo que casam com o padrão,
+// substituindo overrides anteriores. Com dry_run só conta o que seria alterado
+#[instrument(skip_all)]
+pub async fn recategorize_by_pattern(
+    conn: &DbConnection,
+    pattern: &str,
+    category_id: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    dry_run: bool,
+) -> Result<RecategorizeResult> {
+    let mut conn = conn.lock().await;
+    let tx = conn.transaction()?;
+
+    let applications: Vec<(String, i64, i64)> = {
+        let mut stmt = tx.prepare(&format!(
+            "SELECT application, SUM(duration_seconds), COUNT(*) FROM activities
+             WHERE {}
+             GROUP BY application
+             ORDER BY SUM(duration_seconds) DESC",
+            PATTERN_FILTER
+        ))?;
+        let rows = stmt
+            .query_map(params![start.timestamp(), end.timestamp(), pattern], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows
+    };
+
+    if !dry_run {
+        tx.execute(
+            &format!(
+                "INSERT INTO category_overrides (activity_id, category_id, pattern, created_at)
+                 SELECT id, ?4, ?3, ?5 FROM activities WHERE {}
+                 ON CONFLICT(activity_id) DO UPDATE SET
+                    category_id = excluded.category_id,
+                    pattern = excluded.pattern,
+                    created_at = excluded.created_at",
+                PATTERN_FILTER
+            ),
+            params![start.timestamp(), end.timestamp(), pattern, category_id, Utc::now().to_rfc3339()],
+        )?;
+        tx.commit()?;
+    }
+
+    Ok(RecategorizeResult {
+        matched: applications.iter().map(|(_, _, count)| count).sum(),
+        total_seconds: applications.iter().map(|(_, seconds, _)| seconds).sum(),
+        applications: applications.into_iter().map(|(app, seconds, _)| (app, seconds)).collect(),
+        applied: !dry_run,
+    })
+}
+
+// Desfaz overrides do intervalo; com pattern, só os gravados por aquele padrão
+#[instrument(skip_all)]
+pub async fn clear_category_overrides(
+    conn: &DbConnection,
+    pattern: Option<&str>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<usize> {
+    let conn = conn.lock().await;
+    let removed = conn.execute(
+        "DELETE FROM category_overrides
+         WHERE (?3 IS NULL OR pattern = ?3)
+           AND activity_id IN (SELECT id FROM activities WHERE start_epoch >= ?1 AND end_epoch <= ?2)",
+        params![start.timestamp(), end.timestamp(), pattern],
+    )?;
+    Ok(removed)
+}
+
+#[derive(Debug, Clone)]
+pub struct OverrideTotals {
+    pub application: String,
+    pub category_id: String,
+    pub active_seconds: i64,
+}
+
+// Tempo ativo com categoria sobrescrita, por app e categoria de destino
+#[instrument(skip_all)]
+pub async fn get_override_totals(
+    conn: &DbConnection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    include_off_hours: bool,
+) -> Result<Vec<OverrideTotals>> {
+    let conn = conn.lock().await;
+    let mut stmt = conn.prepare(
+        "SELECT a.application, o.category_id, SUM(a.duration_seconds)
+         FROM category_overrides o
+         JOIN activities a ON a.id = o.activity_id
+         WHERE a.start_epoch >= ?1 AND a.end_epoch <= ?2 AND NOT a.is_idle AND (?3 OR NOT a.off_hours)
+         GROUP BY a.application, o.category_id",
+    )?;
+
+    let totals = stmt
+        .query_map(params![start.timestamp(), end.timestamp(), include_off_hours], |row| {
+            Ok(OverrideTotals {
+                application: row.get(0)?,
+                category_id: row.get(1)?,
+                active_seconds: row.get(2)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(totals)
+}
+
+// Tempo ativo em perfis do navegador, sem recategorização, por app, perfil e URL: a
+// regra do perfil (category.rs) depende do domínio e é resolvida com a configuração
+#[derive(Debug, Clone)]
+pub struct ProfileTotals {
+    pub application: String,
+    pub profile: String,
+    pub url: Option<String>,
+    pub active_seconds: i64,
+}
+
+#[instrument(skip_all)]
+pub async fn get_profile_totals(
+    conn: &DbConnection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    include_off_hours: bool,
+) -> Result<Vec<ProfileTotals>> {
+    let conn = conn.lock().await;
+    let mut stmt = conn.prepare(
+        "SELECT a.application, a.profile, a.url, SUM(a.duration_seconds)
+         FROM activities a
+         WHERE a.profile IS NOT NULL AND a.start_epoch >= ?1 AND a.end_epoch <= ?2 AND NOT a.is_idle
+         AND (?3 OR NOT a.off_hours)
+         AND NOT EXISTS (SELECT 1 FROM category_overrides o WHERE o.activity_id = a.id)
+         GROUP BY a.application, a.profile, a.url",
+    )?;
+
+    let totals = stmt
+        .query_map(params![start.timestamp(), end.timestamp(), include_off_hours], |row| {
+            Ok(ProfileTotals {
+                application: row.get(0)?,
+                profile: row.get(1)?,
+                url: row.get(2)?,
+                active_seconds: row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(totals)
+}
+
+#[derive(Debug, Serialize)]
+pub struct PatternTime {
+    pub pattern: String,
+    pub total_seconds: i64,
+    pub activity_count: i64,
+    pub first_seen: Option<DateTime<Utc>>,
+    pub last_seen: Option<DateTime<Utc>>,
+    pub applications: Vec<(String, i64)>,
+}
+
+// Tempo ativo em atividades cujo título ou URL contém o padrão (sem diferenciar
+// maiúsculas), ex.: o ID de um ticket
+#[instrument(skip_all)]
+pub async fn get_time_for_pattern(
+    conn: &DbConnection,
+    pattern: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<PatternTime> {
+    let conn = conn.lock().await;
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT application, SUM(duration_seconds), COUNT(*), MIN(start_time), MAX(end_time)
+        FROM activities
+        WHERE start_epoch >= ?1 AND end_epoch <= ?2
+          AND NOT is_idle
+          AND (instr(lower(title), lower(?3)) > 0 OR instr(lower(COALESCE(url, '')), lower(?3)) > 0)
+        GROUP BY application
+        ORDER BY SUM(duration_seconds) DESC
+        "#,
+    )?;
+
+    let rows = stmt
+        .query_map(params![start.timestamp(), end.timestamp(), pattern], |row| {
+            let first: String = row.get(3)?;
+            let last: String = row.get(4)?;
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, i64>(2)?,
+                parse_timestamp(&first, 3)?,
+                parse_timestamp(&last, 4)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(PatternTime {
+        pattern: pattern.to_string(),
+        total_seconds: rows.iter().map(|r| r.1).sum(),
+        activity_count: rows.iter().map(|r| r.2).sum(),
+        first_seen: rows.iter().map(|r| r.3).min(),
+        last_seen: rows.iter().map(|r| r.4).max(),
+        applications: rows.into_iter().map(|r| (r.0, r.1)).collect(),
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub struct Screenshot {
+    pub id: i64,
+    pub taken_at: DateTime<Utc>,
+    pub path: String,
+    // Janela em foco no momento da captura, para ligar à atividade
+    pub application: String,
+    pub title: String,
+}
+
+#[instrument(skip_all)]
+pub async fn save_screenshot(
+    conn: &DbConnection,
+    taken_at: DateTime<Utc>,
+    path: &str,
+    application: &str,
+    title: &str,
+) -> Result<i64> {
+    let conn = conn.lock().await;
+    conn.execute(
+        "INSERT INTO screenshots (taken_at, path, application, title) VALUES (?1, ?2, ?3, ?4)",
+        params![taken_at.to_rfc3339(), path, application, title],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+#[instrument(skip_all)]
+pub async fn get_screenshots_between(
+    conn: &DbConnection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<Screenshot>> {
+    let conn = conn.lock().await;
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT id, taken_at, path, application, title
+        FROM screenshots
+        WHERE taken_at >= ? AND taken_at <= ?
+        ORDER BY taken_at
+        "#,
+    )?;
+
+    let screenshots = stmt
+        .query_map(params![start.to_rfc3339(), end.to_rfc3339()], |row| {
+            let taken_at: String = row.get(1)?;
+            Ok(Screenshot {
+                id: row.get(0)?,
+                taken_at: parse_timestamp(&taken_at, 1)?,
+                path: row.get(2)?,
+                application: row.get(3)?,
+                title: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(screenshots)
+}
+
+// Retorna os caminhos dos arquivos para que sejam apagados do disco
+#[instrument(skip_all)]
+pub async fn delete_screenshot(conn: &DbConnection, id: i64) -> Result<Option<String>> {
+    let conn = conn.lock().await;
+    let path: Option<String> = conn
+        .query_row("SELECT path FROM screenshots WHERE id = ?", params![id], |row| row.get(0))
+        .optional()?;
+    conn.execute("DELETE FROM screenshots WHERE id = ?", params![id])?;
+    Ok(path)
+}
+
+#[instrument(skip_all)]
+pub async fn delete_screenshots_before(conn: &DbConnection, cutoff: DateTime<Utc>) -> Result<Vec<String>> {
+    let mut conn = conn.lock().await;
+    let tx = conn.transaction()?;
+    let paths = {
+        let mut stmt = tx.prepare("SELECT path FROM screenshots WHERE taken_at < ?")?;
+        let paths = stmt
+            .query_map(params![cutoff.to_rfc3339()], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        paths
+    };
+    tx.execute("DELETE FROM screenshots WHERE taken_at < ?", params![cutoff.to_rfc3339()])?;
+    tx.commit()?;
+    Ok(paths)
+}
+
+// Aplica as correções da revisão diária em uma única transação: ajustes para os
+// intervalos rotulados e mescla das atividades do app em cada intervalo em uma só.
+// Só linhas locais e não ociosas do app entram na mescla; o resto do intervalo fica
+#[instrument(skip_all)]
+pub async fn apply_review(
+    conn: &DbConnection,
+    date: NaiveDate,
+    labels: &[(String, i64, String)],
+    merges: &[(DateTime<Utc>, DateTime<Utc>, String)],
+) -> Result<usize> {
+    let mut conn = conn.lock().await;
+    let tx = conn.transaction()?;
+    let created_at = Utc::now().to_rfc3339();
+
+    for (category_id, minutes, note) in labels {
+        tx.execute(
+            "INSERT INTO adjustments (date, category_id, minutes, note, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![date.to_string(), category_id, minutes, note, created_at],
+        )?;
+    }
+
+    let mut merged = 0;
+    for (start, end, application) in merges {
+        let activities = {
+            let mut stmt = tx.prepare(&format!(
+                "SELECT {} FROM activities
+                 WHERE start_epoch >= ? AND end_epoch <= ? AND application = ?
+                 AND NOT is_idle AND device_id IS NULL
+                 ORDER BY start_time",
+                ACTIVITY_COLUMNS
+            ))?;
+            let activities = stmt
+                .query_map(params![start.timestamp(), end.timestamp(), application], activity_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            activities
+        };
+        let Some(base) = activities.first() else {
+            continue;
+        };
+        if activities.len() < 2 {
+            continue;
+        }
+
+        let merged_start = activities.iter().map(|a| a.start_time).min().unwrap_or(*start);
+        let merged_end = activities.iter().map(|a| a.end_time).max().unwrap_or(*end);
+        for id in activities.iter().filter_map(|a| a.id) {
+            tx.execute("DELETE FROM activities WHERE id = ?", params![id])?;
+        }
+        tx.execute(
+            "INSERT INTO activities (title, application, start_time, end_time, is_browser, url, is_idle, desktop, network, project, cwd, profile, off_hours, manual)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            params![
+                base.title,
+                base.application,
+                merged_start.to_rfc3339(),
+                merged_end.to_rfc3339(),
+                base.is_browser,
+                base.url,
+                base.desktop,
+                base.network,
+                base.project,
+                base.cwd,
+                base.profile,
+                base.off_hours,
+                base.manual,
+            ],
+        )?;
+        merged += activities.len();
+    }
+
+    tx.commit()?;
+    Ok(merged)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GoalOutcome {
+    pub date: NaiveDate,
+    pub productive_seconds: i64,
+    pub goal_minutes: i64,
+    pub met: bool,
+    pub workday: bool,
+}
+
+#[instrument(skip_all)]
+pub async fn get_last_goal_outcome_date(conn: &DbConnection) -> Result<Option<NaiveDate>> {
+    let conn = conn.lock().await;
+    let date: Option<String> = conn.query_row("SELECT MAX(date) FROM daily_goal_outcomes", [], |row| row.get(0))?;
+    Ok(date.and_then(|d| d.parse().ok()))
+}
+
+// Regrava os dias informados; dias já encerrados podem mudar com sincronização ou importação
+#[instrument(skip_all)]
+pub async fn save_goal_outcomes(conn: &DbConnection, outcomes: &[GoalOutcome]) -> Result<()> {
+    let mut conn = conn.lock().await;
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT OR REPLACE INTO daily_goal_outcomes (date, productive_seconds, goal_minutes, met, workday, recorded_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        let recorded_at = Utc::now().to_rfc3339();
+        for outcome in outcomes {
+            stmt.execute(params![
+                outcome.date.to_string(),
+                outcome.productive_seconds,
+                outcome.goal_minutes,
+                outcome.met,
+                outcome.workday,
+                recorded_at,
+            ])?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+// Em ordem cronológica
+#[instrument(skip_all)]
+pub async fn get_goal_outcomes(conn: &DbConnection, since: Option<NaiveDate>) -> Result<Vec<GoalOutcome>> {
+    let conn = conn.lock().await;
+    let mut stmt = conn.prepare(
+        "SELECT date, productive_seconds, goal_minutes, met, workday
+         FROM daily_goal_outcomes
+         WHERE date >= ?
+         ORDER BY date",
+    )?;
+    let since = since.map(|d| d.to_string()).unwrap_or_default();
+    let outcomes = stmt
+        .query_map(params![since], |row| {
+            let date: String = row.get(0)?;
+            Ok(GoalOutcome {
+                date: date.parse().map_err(|e| rusqlite::Error::FromSqlConversionFailure(
+                    0,
+                    rusqlite::types::Type::Text,
+                    Box::new(e),
+                ))?,
+                productive_seconds: row.get(1)?,
+                goal_minutes: row.get(2)?,
+                met: row.get(3)?,
+                workday: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(outcomes)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FocusSummary {
+    pub id: i64,
+    pub label: String,
+    pub started_at: DateTime<Utc>,
+    pub planned_end: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    // false = interrompida antes do fim planejado
+    pub completed: bool,
+    pub productive_seconds: i64,
+    pub unproductive_seconds: i64,
+    pub distraction_count: i64,
+    pub distracting_apps: Vec<String>,
+}
+
+#[instrument(skip_all)]
+pub async fn save_focus_summary(conn: &DbConnection, summary: &FocusSummary) -> Result<i64> {
+    let conn = conn.lock().await;
+    conn.execute(
+        "INSERT INTO focus_sessions (label, started_at, planned_end, ended_at, completed, productive_seconds,
+                                     unproductive_seconds, distraction_count, distracting_apps)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            summary.label,
+            summary.started_at.to_rfc3339(),
+            summary.planned_end.to_rfc3339(),
+            summary.ended_at.to_rfc3339(),
+            summary.completed,
+            summary.productive_seconds,
+            summary.unproductive_seconds,
+            summary.distraction_count,
+            serde_json::to_string(&summary.distracting_apps)?,
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+// Mais recentes primeiro
+#[instrument(skip_all)]
+pub async fn get_focus_summaries(conn: &DbConnection, limit: usize) -> Result<Vec<FocusSummary>> {
+    let conn = conn.lock().await;
+    let mut stmt = conn.prepare(
+        "SELECT id, label, started_at, planned_end, ended_at, completed, productive_seconds,
+                unproductive_seconds, distraction_count, distracting_apps
+         FROM focus_sessions
+         ORDER BY started_at DESC
+         LIMIT ?",
+    )?;
+    let summaries = stmt
+        .query_map(params![limit as i64], |row| {
+            let started_at: String = row.get(2)?;
+            let planned_end: String = row.get(3)?;
+            let ended_at: String = row.get(4)?;
+            let distracting_apps: String = row.get(9)?;
+            Ok(FocusSummary {
+                id: row.get(0)?,
+                label: row.get(1)?,
+                started_at: parse_timestamp(&started_at, 2)?,
+                planned_end: parse_timestamp(&planned_end, 3)?,
+                ended_at: parse_timestamp(&ended_at, 4)?,
+                completed: row.get(5)?,
+                productive_seconds: row.get(6)?,
+                unproductive_seconds: row.get(7)?,
+                distraction_count: row.get(8)?,
+                distracting_apps: serde_json::from_str(&distracting_apps).unwrap_or_default(),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(summaries)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Pomodoro {
+    pub id: i64,
+    pub label: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+}
+
+#[instrument(skip_all)]
+pub async fn save_pomodoro(
+    conn: &DbConnection,
+    label: &str,
+    started_at: DateTime<Utc>,
+    ended_at: DateTime<Utc>,
+) -> Result<i64> {
+    let conn = conn.lock().await;
+    conn.execute(
+        "INSERT INTO pomodoros (label, started_at, ended_at) VALUES (?1, ?2, ?3)",
+        params![label, started_at.to_rfc3339(), ended_at.to_rfc3339()],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+// Pomodoros iniciados no intervalo, em ordem cronológica
+#[instrument(skip_all)]
+pub async fn get_pomodoros_between(
+    conn: &DbConnection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<Pomodoro>> {
+    let conn = conn.lock().await;
+    let mut stmt = conn.prepare(
+        "SELECT id, label, started_at, ended_at
+         FROM pomodoros
+         WHERE started_at >= ? AND started_at < ?
+         ORDER BY started_at",
+    )?;
+    let pomodoros = stmt
+        .query_map(params![start.to_rfc3339(), end.to_rfc3339()], |row| {
+            let started_at: String = row.get(2)?;
+            let ended_at: String = row.get(3)?;
+            Ok(Pomodoro {
+                id: row.get(0)?,
+                label: row.get(1)?,
+                started_at: parse_timestamp(&started_at, 2)?,
+                ended_at: parse_timestamp(&ended_at, 3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(pomodoros)
+}
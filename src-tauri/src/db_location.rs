@@ -0,0 +1,161 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::api::path::config_dir;
+use tracing::{info, warn};
+
+use crate::database::{self, DbConnection};
+use crate::tracker::{SharedTrackerHealth, TrackerHandle};
+
+// Guardado fora do banco e da configuração de categorias: precisa ser lido antes de abrir o banco
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LocationFile {
+    path: Option<PathBuf>,
+}
+
+fn location_file() -> Result<PathBuf> {
+    let mut path = config_dir().ok_or_else(|| anyhow::anyhow!("Failed to get config directory"))?;
+    path.push("chronos-track");
+    path.push("database_location.json");
+    Ok(path)
+}
+
+pub fn custom_path() -> Option<PathBuf> {
+    let content = fs::read_to_string(location_file().ok()?).ok()?;
+    serde_json::from_str::<LocationFile>(&content).ok()?.path
+}
+
+fn save(path: Option<&Path>) -> Result<()> {
+    let file = location_file()?;
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let location = LocationFile { path: path.map(Path::to_path_buf) };
+    fs::write(file, serde_json::to_string_pretty(&location)?)?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RelocateMode {
+    // Copia para o novo local e apaga o arquivo antigo
+    Move,
+    // Copia e mantém o antigo
+    Copy,
+    // Passa a usar um chronos.db que já existe no destino (ex.: pasta sincronizada)
+    UseExisting,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DatabaseLocation {
+    pub path: PathBuf,
+    pub is_default: bool,
+}
+
+pub fn current() -> Result<DatabaseLocation> {
+    Ok(DatabaseLocation {
+        path: database::get_database_path()?,
+        is_default: custom_path().is_none(),
+    })
+}
+
+// Aceita uma pasta (usa chronos.db dentro dela) ou o caminho do arquivo
+fn resolve_target(target: &Path) -> PathBuf {
+    if target.is_dir() || target.extension().is_none() {
+        target.join("chronos.db")
+    } else {
+        target.to_path_buf()
+    }
+}
+
+// Arquivos auxiliares do SQLite: o sufixo vai depois do nome inteiro (dados.sqlite-wal)
+pub fn sidecar(path: &Path, suffix: &str) -> PathBuf {
+    let mut file = path.as_os_str().to_owned();
+    file.push(suffix);
+    PathBuf::from(file)
+}
+
+fn remove_database_files(path: &Path) {
+    for file in [path.to_path_buf(), sidecar(path, "-wal"), sidecar(path, "-shm")] {
+        if let Err(e) = fs::remove_file(&file) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                warn!("Failed to remove {:?}: {}", file, e);
+            }
+        }
+    }
+}
+
+// Troca o arquivo do banco em tempo de execução. O tracker fica pausado e a conexão
+// travada durante a cópia; a conexão é reaberta no novo local. None = local padrão
+pub async fn relocate(
+    db: &DbConnection,
+    health: &SharedTrackerHealth,
+    tracker: &TrackerHandle,
+    target: Option<&Path>,
+    mode: RelocateMode,
+) -> Result<DatabaseLocation> {
+    let current_path = database::get_database_path()?;
+    let target_path = match target {
+        Some(target) => resolve_target(target),
+        None => database::default_database_path()?,
+    };
+    if target_path == current_path {
+        return current();
+    }
+
+    let target_exists = target_path.exists();
+    if mode == RelocateMode::UseExisting && !target_exists {
+        return Err(anyhow::anyhow!("No database found at {:?}", target_path));
+    }
+    if mode != RelocateMode::UseExisting && target_exists {
+        return Err(anyhow::anyhow!("A database already exists at {:?}", target_path));
+    }
+    if let Some(parent) = target_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let was_paused = {
+        let mut health = health.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        std::mem::replace(&mut health.paused, true)
+    };
+    // Grava o evento em andamento no banco atual (vai junto na cópia) e esquece a
+    // linha: com UseExisting o id não existe, ou é de outro registro, no novo banco
+    tracker.reset().await;
+
+    let result = async {
+        let mut conn = db.lock().await;
+
+        if mode != RelocateMode::UseExisting {
+            // Com o WAL esvaziado o arquivo principal tem tudo (e continua criptografado, se for o caso)
+            conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);")?;
+            fs::copy(&current_path, &target_path)?;
+        }
+
+        let new_conn = match database::open_connection(&target_path) {
+            Ok(new_conn) => new_conn,
+            Err(e) => {
+                if mode != RelocateMode::UseExisting {
+                    remove_database_files(&target_path);
+                }
+                return Err(e);
+            }
+        };
+        drop(std::mem::replace(&mut *conn, new_conn));
+
+        save(target.map(|_| target_path.as_path()))?;
+        if mode == RelocateMode::Move {
+            remove_database_files(&current_path);
+        }
+        Ok(())
+    }
+    .await;
+
+    if let Ok(mut health) = health.lock() {
+        health.paused = was_paused;
+    }
+    result?;
+
+    info!("Database moved from {:?} to {:?} ({:?})", current_path, target_path, mode);
+    current()
+}
@@ -0,0 +1,175 @@
+use anyhow::Result;
+use rusqlite::{Connection, DatabaseName};
+use serde::Serialize;
+use std::path::Path;
+#[cfg(feature = "encryption")]
+use tracing::info;
+
+use crate::database::DbConnection;
+#[cfg(feature = "encryption")]
+use crate::db_location::sidecar;
+
+// A chave do SQLCipher fica no chaveiro do sistema, nunca em disco
+#[cfg(feature = "encryption")]
+mod keychain {
+    use anyhow::Result;
+
+    use crate::secrets;
+
+    const ACCOUNT: &str = "database-key";
+
+    pub fn get() -> Option<String> {
+        secrets::get(ACCOUNT)
+    }
+
+    pub fn set(key: &str) -> Result<()> {
+        secrets::set(ACCOUNT, key)
+    }
+
+    pub fn delete() -> Result<()> {
+        secrets::delete(ACCOUNT)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct EncryptionStatus {
+    // Compilado com SQLCipher (feature "encryption")
+    pub available: bool,
+    pub enabled: bool,
+}
+
+pub fn status() -> EncryptionStatus {
+    EncryptionStatus {
+        available: cfg!(feature = "encryption"),
+        enabled: stored_key().is_some(),
+    }
+}
+
+#[cfg(feature = "encryption")]
+pub fn stored_key() -> Option<String> {
+    keychain::get()
+}
+
+#[cfg(not(feature = "encryption"))]
+pub fn stored_key() -> Option<String> {
+    None
+}
+
+// Precisa ser a primeira instrução depois de abrir a conexão
+pub fn apply_key(conn: &Connection, key: Option<&str>) -> Result<()> {
+    if let Some(key) = key {
+        conn.pragma_update(None, "key", key)?;
+    }
+    Ok(())
+}
+
+// Grava uma cópia do banco aberto em `target` com a chave indicada (vazia = sem criptografia)
+#[cfg(feature = "encryption")]
+fn export(conn: &Connection, target: &Path, key: &str) -> rusqlite::Result<()> {
+    let exported = conn
+        .execute(
+            "ATTACH DATABASE ?1 AS converted KEY ?2",
+            rusqlite::params![target.to_string_lossy(), key],
+        )
+        .and_then(|_| conn.query_row("SELECT sqlcipher_export('converted')", [], |_| Ok(())));
+    let _ = conn.execute_batch("DETACH DATABASE converted");
+    exported
+}
+
+// Cópia consistente do banco aberto, com a mesma chave. O backup online do SQLite não
+// copia de um banco com chave para um sem, então com SQLCipher a cópia sai pelo export
+pub fn copy_to(conn: &Connection, target: &Path) -> Result<()> {
+    #[cfg(feature = "encryption")]
+    if let Some(key) = stored_key() {
+        let _ = std::fs::remove_file(target);
+        export(conn, target, &key)?;
+        return Ok(());
+    }
+    conn.backup(DatabaseName::Main, target, None)?;
+    Ok(())
+}
+
+// Copia o banco aberto para `target` com a chave indicada (vazia = sem criptografia)
+// e troca o arquivo atual por ele, reabrindo a conexão. O chaveiro só muda depois
+// que o banco novo abriu; em qualquer falha o arquivo e a conexão originais voltam
+#[cfg(feature = "encryption")]
+async fn reencrypt(db: &DbConnection, new_key: Option<&str>) -> Result<()> {
+    let path = crate::database::get_database_path()?;
+    let target = sidecar(&path, ".converting");
+    let previous = sidecar(&path, ".previous");
+    let _ = std::fs::remove_file(&target);
+    let old_key = stored_key();
+
+    let mut conn = db.lock().await;
+    if let Err(e) = export(&conn, &target, new_key.unwrap_or("")) {
+        let _ = std::fs::remove_file(&target);
+        return Err(e.into());
+    }
+
+    // Fecha a conexão antiga (checkpoint do WAL) antes de trocar os arquivos
+    drop(std::mem::replace(&mut *conn, Connection::open_in_memory()?));
+    for suffix in ["-wal", "-shm"] {
+        let _ = std::fs::remove_file(sidecar(&path, suffix));
+    }
+
+    let swapped = (|| -> Result<Connection> {
+        std::fs::rename(&path, &previous)?;
+        std::fs::rename(&target, &path)?;
+        let new_conn = crate::database::open_connection_with_key(&path, new_key)?;
+        match new_key {
+            Some(key) => keychain::set(key)?,
+            None => keychain::delete()?,
+        }
+        Ok(new_conn)
+    })();
+
+    match swapped {
+        Ok(new_conn) => {
+            *conn = new_conn;
+            let _ = std::fs::remove_file(&previous);
+            Ok(())
+        }
+        Err(e) => {
+            if previous.exists() {
+                let _ = std::fs::remove_file(&path);
+                std::fs::rename(&previous, &path)?;
+            }
+            let _ = std::fs::remove_file(&target);
+            *conn = crate::database::open_connection_with_key(&path, old_key.as_deref())?;
+            Err(e)
+        }
+    }
+}
+
+#[cfg(feature = "encryption")]
+pub async fn enable(db: &DbConnection) -> Result<()> {
+    if stored_key().is_some() {
+        return Err(anyhow::anyhow!("Database is already encrypted"));
+    }
+
+    let key = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+    reencrypt(db, Some(&key)).await?;
+    info!("Database encrypted with a key stored in the system keychain");
+    Ok(())
+}
+
+#[cfg(feature = "encryption")]
+pub async fn disable(db: &DbConnection) -> Result<()> {
+    if stored_key().is_none() {
+        return Err(anyhow::anyhow!("Database is not encrypted"));
+    }
+
+    reencrypt(db, None).await?;
+    info!("Database decrypted");
+    Ok(())
+}
+
+#[cfg(not(feature = "encryption"))]
+pub async fn enable(_db: &DbConnection) -> Result<()> {
+    Err(anyhow::anyhow!("This build does not include database encryption support"))
+}
+
+#[cfg(not(feature = "encryption"))]
+pub async fn disable(_db: &DbConnection) -> Result<()> {
+    Err(anyhow::anyhow!("This build does not include database encryption support"))
+}
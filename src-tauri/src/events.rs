@@ -0,0 +1,265 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, error};
+
+use crate::category::{ApiPermission, SharedConfig};
+use crate::database::{self, DbConnection};
+use crate::tracker::WindowActivity;
+
+pub const STATS_DELTA_EVENT: &str = "stats-delta";
+pub const ACTIVITY_CHANGED_EVENT: &str = "activity-changed";
+pub const IDLE_STARTED_EVENT: &str = "idle-started";
+pub const IDLE_ENDED_EVENT: &str = "idle-ended";
+pub const IDLE_RETURN_PROMPT_EVENT: &str = "idle-return-prompt";
+// Clientes lentos perdem os eventos mais antigos (e são desconectados, sync_feed.rs)
+const FEED_EVENT_CAPACITY: usize = 256;
+
+// Eventos produzidos pelo tracker, que roda antes do AppHandle existir
+#[derive(Debug, Clone)]
+pub enum TrackerEvent {
+    ActivityChanged(WindowActivity),
+    IdleStarted {
+        started_at: DateTime<Utc>,
+    },
+    IdleEnded {
+        started_at: DateTime<Utc>,
+        ended_at: DateTime<Utc>,
+    },
+}
+
+pub type TrackerEventSender = mpsc::UnboundedSender<TrackerEvent>;
+pub type TrackerEventReceiver = mpsc::UnboundedReceiver<TrackerEvent>;
+
+#[derive(Debug, Serialize, Clone)]
+struct IdlePayload {
+    started_at: DateTime<Utc>,
+    ended_at: Option<DateTime<Utc>>,
+    duration_seconds: Option<i64>,
+}
+
+// Oferta para rotular a ausência; a resposta vai para label_idle_period
+#[derive(Debug, Serialize, Clone)]
+struct IdleReturnPrompt {
+    started_at: DateTime<Utc>,
+    ended_at: DateTime<Utc>,
+    duration_seconds: i64,
+    labels: Vec<String>,
+}
+
+pub fn tracker_channel() -> (TrackerEventSender, TrackerEventReceiver) {
+    mpsc::unbounded_channel()
+}
+
+// Cópia dos eventos para clientes externos, pelo WebSocket do sync_feed
+#[derive(Debug, Clone, Serialize)]
+pub struct FeedEvent {
+    pub event: &'static str,
+    pub payload: serde_json::Value,
+    // Atividades trazem títulos e URLs; o resto só precisa dos agregados
+    #[serde(skip)]
+    pub permission: ApiPermission,
+}
+
+pub type FeedEventSender = broadcast::Sender<FeedEvent>;
+
+pub fn feed_channel() -> FeedEventSender {
+    broadcast::channel(FEED_EVENT_CAPACITY).0
+}
+
+// Emite para o frontend e repassa aos clientes do WebSocket, se houver algum conectado
+fn emit<S: Serialize + Clone>(
+    app: &AppHandle,
+    event: &'static str,
+    permission: ApiPermission,
+    payload: S,
+) -> tauri::Result<()> {
+    let feed = app.state::<FeedEventSender>();
+    if feed.receiver_count() > 0 {
+        match serde_json::to_value(&payload) {
+            Ok(payload) => {
+                // Só falha se o último cliente acabou de sair
+                let _ = feed.send(FeedEvent { event, payload, permission });
+            }
+            Err(e) => error!("Failed to serialize {} for the sync feed: {}", event, e),
+        }
+    }
+    app.emit_all(event, payload)
+}
+
+// Repassa os eventos do tracker para o frontend
+pub async fn forward_tracker_events(app: AppHandle, mut receiver: TrackerEventReceiver) {
+    while let Some(event) = receiver.recv().await {
+        let result = match event {
+            TrackerEvent::ActivityChanged(activity) => {
+                crate::focus::on_activity_changed(&app, &activity);
+                emit(&app, ACTIVITY_CHANGED_EVENT, ApiPermission::ReadActivities, activity)
+            }
+            TrackerEvent::IdleStarted { started_at } => emit(
+                &app,
+                IDLE_STARTED_EVENT,
+                ApiPermission::ReadAggregates,
+                IdlePayload {
+                    started_at,
+                    ended_at: None,
+                    duration_seconds: None,
+                },
+            ),
+            TrackerEvent::IdleEnded { started_at, ended_at } => {
+                prompt_idle_return(&app, started_at, ended_at);
+                emit(
+                    &app,
+                    IDLE_ENDED_EVENT,
+                    ApiPermission::ReadAggregates,
+                    IdlePayload {
+                        started_at,
+                        ended_at: Some(ended_at),
+                        duration_seconds: Some((ended_at - started_at).num_seconds()),
+                    },
+                )
+            }
+        };
+
+        if let Err(e) = result {
+            error!("Failed to emit tracker event: {}", e);
+        }
+    }
+}
+
+// Ausências longas viram um convite para rotular o intervalo: evento para o
+// dashboard e notificação para quem não está com a janela aberta
+fn prompt_idle_return(app: &AppHandle, started_at: DateTime<Utc>, ended_at: DateTime<Utc>) {
+    let prompt = match app.state::<SharedConfig>().lock() {
+        Ok(config) => config.idle_prompt.clone(),
+        Err(e) => {
+            error!("Failed to lock config: {}", e);
+            return;
+        }
+    };
+    let duration_seconds = (ended_at - started_at).num_seconds();
+    if !prompt.enabled || duration_seconds < prompt.after_minutes.max(1) * 60 {
+        return;
+    }
+
+    let payload = IdleReturnPrompt {
+        started_at,
+        ended_at,
+        duration_seconds,
+        labels: prompt.labels,
+    };
+    if let Err(e) = app.emit_all(IDLE_RETURN_PROMPT_EVENT, payload) {
+        error!("Failed to emit idle return prompt: {}", e);
+    }
+
+    let app = app.clone();
+    tokio::spawn(async move {
+        let body = format!(
+            "You were away for {}. Open Chronos Track to label it as a meeting, lunch or break.",
+            crate::menu::format_duration(duration_seconds),
+        );
+        if let Err(e) = crate::notifications::notify(&app, "Welcome back", &body).await {
+            error!("Failed to send notification: {}", e);
+        }
+    });
+}
+
+// Segundos acumulados no dia por aplicativo e por categoria
+#[derive(Debug, Default, Clone, PartialEq)]
+struct DayTotals {
+    date: Option<NaiveDate>,
+    apps: HashMap<String, i64>,
+    categories: HashMap<String, i64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct StatsDelta {
+    pub date: NaiveDate,
+    // true quando o dia virou e o frontend deve zerar os totais antes de aplicar
+    pub reset: bool,
+    pub apps: HashMap<String, i64>,
+    pub categories: HashMap<String, i64>,
+}
+
+async fn compute_today_totals(app: &AppHandle) -> Result<DayTotals, String> {
+    let db = app.state::<DbConnection>();
+    let config = app.state::<SharedConfig>();
+
+    let calendar = config.lock().map_err(|e| e.to_string())?.calendar.clone();
+    let today = calendar.logical_date(Utc::now());
+    let (start, end) = calendar.day_bounds(today);
+
+    let activities = database::get_activities_between(&db, start, end)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let config = config.lock().map_err(|e| e.to_string())?;
+
+    let mut totals = DayTotals {
+        date: Some(today),
+        ..Default::default()
+    };
+    for activity in activities.iter().filter(|a| !a.is_idle && !a.off_hours) {
+        let seconds = (activity.end_time - activity.start_time).num_seconds();
+        let category = config
+            .category_for_activity(activity)
+            .map_or("uncategorized".to_string(), |c| c.id.clone());
+
+        *totals.apps.entry(activity.application.clone()).or_default() += seconds;
+        *totals.categories.entry(category).or_default() += seconds;
+    }
+
+    Ok(totals)
+}
+
+fn diff(previous: &HashMap<String, i64>, current: &HashMap<String, i64>) -> HashMap<String, i64> {
+    current
+        .iter()
+        .filter_map(|(key, seconds)| {
+            let delta = seconds - previous.get(key).copied().unwrap_or(0);
+            (delta != 0).then(|| (key.clone(), delta))
+        })
+        .collect()
+}
+
+// Emite apenas o que mudou desde a última emissão, para o dashboard e o widget
+// atualizarem incrementalmente em vez de buscar o dia inteiro
+pub async fn run_stats_delta_loop(app: AppHandle) {
+    let mut previous = DayTotals::default();
+    let mut interval = tokio::time::interval(Duration::from_secs(5));
+
+    loop {
+        interval.tick().await;
+
+        let current = match compute_today_totals(&app).await {
+            Ok(totals) => totals,
+            Err(e) => {
+                error!("Failed to compute stats delta: {}", e);
+                continue;
+            }
+        };
+
+        if current == previous {
+            continue;
+        }
+
+        let reset = current.date != previous.date;
+        let baseline = if reset { DayTotals::default() } else { previous };
+
+        let delta = StatsDelta {
+            date: current.date.unwrap_or_else(|| Utc::now().date_naive()),
+            reset,
+            apps: diff(&baseline.apps, &current.apps),
+            categories: diff(&baseline.categories, &current.categories),
+        };
+
+        debug!("Emitting stats delta: {} apps, {} categories", delta.apps.len(), delta.categories.len());
+        if let Err(e) = emit(&app, STATS_DELTA_EVENT, ApiPermission::ReadAggregates, delta) {
+            error!("Failed to emit stats delta: {}", e);
+        }
+
+        previous = current;
+    }
+}
@@ -0,0 +1,282 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::category::CategoryConfig;
+use crate::project::activity_project;
+use crate::tracker::WindowActivity;
+
+// Intervalos do mesmo grupo separados por menos que isso viram um único CLOCK
+const CLOCK_MERGE_GAP_SECONDS: i64 = 60;
+
+fn org_timestamp(time: DateTime<Utc>, tz: &Tz) -> String {
+    time.with_timezone(tz).format("[%Y-%m-%d %a %H:%M]").to_string()
+}
+
+fn org_duration(start: DateTime<Utc>, end: DateTime<Utc>) -> String {
+    let minutes = (end - start).num_minutes();
+    format!("{:>2}:{:02}", minutes / 60, minutes % 60)
+}
+
+// Entradas CLOCK do org-mode agrupadas por categoria e projeto:
+//
+// * Development
+// ** chronos-track
+//    :LOGBOOK:
+//    CLOCK: [2024-05-02 Thu 09:00]--[2024-05-02 Thu 10:30] =>  1:30
+//    :END:
+pub fn org_clock(activities: &[WindowActivity], config: &CategoryConfig) -> (String, usize) {
+    let mut groups: BTreeMap<String, BTreeMap<String, Vec<(DateTime<Utc>, DateTime<Utc>)>>> = BTreeMap::new();
+    let tz = config.calendar.tz();
+
+    let mut sorted: Vec<&WindowActivity> = activities.iter().filter(|a| !a.is_idle).collect();
+    sorted.sort_by_key(|a| a.start_time);
+
+    for activity in sorted {
+        let category = config
+            .category_for_activity(activity)
+            .map_or("Uncategorized".to_string(), |c| c.name.clone());
+        let project = activity_project(activity).unwrap_or_else(|| activity.application.clone());
+
+        let clocks = groups.entry(category).or_default().entry(project).or_default();
+        match clocks.last_mut() {
+            Some((_, end)) if (activity.start_time - *end).num_seconds() <= CLOCK_MERGE_GAP_SECONDS => {
+                *end = (*end).max(activity.end_time);
+            }
+            _ => clocks.push((activity.start_time, activity.end_time)),
+        }
+    }
+
+    let mut output = String::from("#+TITLE: Chronos Track\n\n");
+    let mut entries = 0;
+    for (category, projects) in groups {
+        output.push_str(&format!("* {}\n", category));
+        for (project, clocks) in projects {
+            output.push_str(&format!("** {}\n   :LOGBOOK:\n", project));
+            // O org-mode lista os CLOCKs mais recentes primeiro
+            for (start, end) in clocks.iter().rev().filter(|(s, e)| (*e - *s).num_minutes() > 0) {
+                output.push_str(&format!(
+                    "   CLOCK: {}--{} => {}\n",
+                    org_timestamp(*start, &tz),
+                    org_timestamp(*end, &tz),
+                    org_duration(*start, *end)
+                ));
+                entries += 1;
+            }
+            output.push_str("   :END:\n");
+        }
+    }
+
+    (output, entries)
+}
+
+pub const CSV_COLUMNS: &[&str] = &[
+    "start_time",
+    "end_time",
+    "application",
+    "title",
+    "url",
+    "domain",
+    "domain_label",
+    "is_browser",
+    "is_idle",
+    "duration_seconds",
+    "duration_minutes",
+    "category_id",
+    "category_name",
+    "is_productive",
+    "project",
+    "cwd",
+    "desktop",
+    "network",
+];
+
+pub const DEFAULT_CSV_COLUMNS: &[&str] = &[
+    "start_time",
+    "end_time",
+    "application",
+    "title",
+    "duration_minutes",
+    "category_name",
+    "is_idle",
+];
+
+// Host da URL sem "www.", usado como coluna derivada
+pub fn domain_of(url: &str) -> Option<String> {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let host = without_scheme
+        .split(['/', '?', '#'])
+        .next()?
+        .rsplit('@')
+        .next()?
+        .split(':')
+        .next()?;
+
+    (!host.is_empty()).then(|| host.trim_start_matches("www.").to_lowercase())
+}
+
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn csv_value(activity: &WindowActivity, config: &CategoryConfig, column: &str) -> String {
+    let category = config.category_for_activity(activity);
+    let duration = (activity.end_time - activity.start_time).num_seconds();
+
+    match column {
+        "start_time" => activity.start_time.to_rfc3339(),
+        "end_time" => activity.end_time.to_rfc3339(),
+        "application" => activity.application.clone(),
+        "title" => activity.title.clone(),
+        "url" => activity.url.clone().unwrap_or_default(),
+        "domain" => activity.url.as_deref().and_then(domain_of).unwrap_or_default(),
+        "domain_label" => activity.url.as_deref()
+            .and_then(domain_of)
+            .and_then(|domain| config.label_for_domain(&domain).map(str::to_string))
+            .unwrap_or_default(),
+        "is_browser" => activity.is_browser.to_string(),
+        "is_idle" => activity.is_idle.to_string(),
+        "duration_seconds" => duration.to_string(),
+        "duration_minutes" => format!("{:.2}", duration as f64 / 60.0),
+        "category_id" => category.map(|c| c.id.clone()).unwrap_or_default(),
+        "category_name" => category.map_or("Uncategorized".to_string(), |c| c.name.clone()),
+        "is_productive" => category.map_or(false, |c| c.is_productive).to_string(),
+        "project" => activity_project(activity).unwrap_or_default(),
+        "cwd" => activity.cwd.clone().unwrap_or_default(),
+        "desktop" => activity.desktop.clone().unwrap_or_default(),
+        "network" => activity.network.clone().unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+// CSV com as colunas na ordem pedida (ou as padrão)
+pub fn csv(
+    activities: &[WindowActivity],
+    config: &CategoryConfig,
+    columns: Option<Vec<String>>,
+) -> Result<String, String> {
+    let columns = columns
+        .filter(|c| !c.is_empty())
+        .unwrap_or_else(|| DEFAULT_CSV_COLUMNS.iter().map(|c| c.to_string()).collect());
+
+    if let Some(unknown) = columns.iter().find(|c| !CSV_COLUMNS.contains(&c.as_str())) {
+        return Err(format!(
+            "Unknown export column: {} (available: {})",
+            unknown,
+            CSV_COLUMNS.join(", ")
+        ));
+    }
+
+    let mut sorted: Vec<&WindowActivity> = activities.iter().collect();
+    sorted.sort_by_key(|a| a.start_time);
+
+    let mut output = columns.join(",");
+    output.push('\n');
+    for activity in sorted {
+        let row: Vec<String> = columns
+            .iter()
+            .map(|column| csv_field(&csv_value(activity, config, column)))
+            .collect();
+        output.push_str(&row.join(","));
+        output.push('\n');
+    }
+
+    Ok(output)
+}
+
+// Revisão antes de compartilhar: cada título/URL/diretório/projeto/desktop distinto que iria
+// para o arquivo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewKind {
+    Title,
+    Url,
+    Cwd,
+    Project,
+    Desktop,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReviewItem {
+    pub kind: ReviewKind,
+    pub value: String,
+    pub applications: Vec<String>,
+    pub occurrences: usize,
+    pub total_seconds: i64,
+}
+
+pub fn review_items(activities: &[WindowActivity]) -> Vec<ReviewItem> {
+    let mut items: HashMap<(ReviewKind, String), (HashSet<String>, usize, i64)> = HashMap::new();
+
+    for activity in activities {
+        let duration = (activity.end_time - activity.start_time).num_seconds();
+        let values = [
+            Some((ReviewKind::Title, activity.title.clone())).filter(|(_, title)| !title.is_empty()),
+            activity.url.clone().map(|url| (ReviewKind::Url, url)),
+            activity.cwd.clone().map(|cwd| (ReviewKind::Cwd, cwd)),
+            activity_project(activity).map(|project| (ReviewKind::Project, project)),
+            activity.desktop.clone().map(|desktop| (ReviewKind::Desktop, desktop)),
+        ];
+        for key in values.into_iter().flatten() {
+            let entry = items.entry(key).or_default();
+            entry.0.insert(activity.application.clone());
+            entry.1 += 1;
+            entry.2 += duration;
+        }
+    }
+
+    let mut items: Vec<ReviewItem> = items
+        .into_iter()
+        .map(|((kind, value), (applications, occurrences, total_seconds))| {
+            let mut applications: Vec<String> = applications.into_iter().collect();
+            applications.sort();
+            ReviewItem { kind, value, applications, occurrences, total_seconds }
+        })
+        .collect();
+    items.sort_by(|a, b| b.total_seconds.cmp(&a.total_seconds).then_with(|| a.value.cmp(&b.value)));
+    items
+}
+
+pub const REDACTED: &str = "[redacted]";
+
+// Entradas excluídas na revisão; o tempo continua no relatório, só o texto some
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ExportExclusions {
+    pub titles: Vec<String>,
+    pub urls: Vec<String>,
+    pub cwds: Vec<String>,
+    pub projects: Vec<String>,
+    pub desktops: Vec<String>,
+}
+
+pub fn redact(activities: &mut [WindowActivity], exclusions: &ExportExclusions) {
+    let set = |values: &[String]| values.iter().cloned().collect::<HashSet<String>>();
+    let (titles, urls) = (set(&exclusions.titles), set(&exclusions.urls));
+    let (cwds, projects, desktops) = (set(&exclusions.cwds), set(&exclusions.projects), set(&exclusions.desktops));
+    let excluded = |value: &Option<String>, set: &HashSet<String>| value.as_ref().map_or(false, |v| set.contains(v));
+
+    for activity in activities {
+        // O projeto exportado também é deduzido do título e do diretório: fixa o valor
+        // antes de redigi-los, e o marcador impede que a dedução traga de volta um excluído
+        activity.project = activity_project(activity)
+            .map(|project| if projects.contains(&project) { REDACTED.to_string() } else { project });
+        if titles.contains(&activity.title) {
+            activity.title = REDACTED.to_string();
+        }
+        if excluded(&activity.url, &urls) {
+            activity.url = None;
+        }
+        if excluded(&activity.cwd, &cwds) {
+            activity.cwd = None;
+        }
+        if excluded(&activity.desktop, &desktops) {
+            activity.desktop = None;
+        }
+    }
+}
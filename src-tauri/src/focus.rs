@@ -0,0 +1,232 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+use tracing::{error, info};
+
+use crate::category::{Category, SharedConfig};
+use crate::database::{self, DbConnection, FocusSummary};
+use crate::menu::{format_duration, SharedTrayDisplay, TrayOverride, PRIORITY_SESSION};
+use crate::tracker::{TrackerHandle, WindowActivity};
+
+const TRAY_SOURCE: &str = "focus_session";
+
+// Bloco de foco/timebox em andamento
+#[derive(Debug, Clone, Serialize)]
+pub struct FocusSession {
+    pub label: String,
+    pub started_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    // Modo foco: avisa ao trocar para um app de categoria improdutiva
+    pub distraction_alerts: bool,
+    pub bring_to_front: bool,
+    pub distraction_count: i64,
+    pub distracting_apps: Vec<String>,
+    // App improdutivo em uso agora, para não repetir o aviso a cada troca de título
+    #[serde(skip)]
+    current_distraction: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FocusOptions {
+    pub distraction_alerts: bool,
+    // Traz o dashboard para frente junto com a notificação
+    pub bring_to_front: bool,
+}
+
+pub type SharedFocusSession = Arc<Mutex<Option<FocusSession>>>;
+
+pub fn start(app: &AppHandle, minutes: i64, label: String, options: FocusOptions) -> Result<FocusSession, String> {
+    let now = Utc::now();
+    let session = FocusSession {
+        label,
+        started_at: now,
+        ends_at: now + Duration::minutes(minutes),
+        distraction_alerts: options.distraction_alerts,
+        bring_to_front: options.bring_to_front,
+        distraction_count: 0,
+        distracting_apps: Vec::new(),
+        current_distraction: None,
+    };
+
+    *app.state::<SharedFocusSession>().lock().map_err(|e| e.to_string())? = Some(session.clone());
+    app.state::<SharedTrayDisplay>()
+        .lock()
+        .map_err(|e| e.to_string())?
+        .set(TrayOverride {
+            source: TRAY_SOURCE.to_string(),
+            priority: PRIORITY_SESSION,
+            label: format!("🎯 {}", session.label),
+            ends_at: Some(session.ends_at),
+        });
+
+    info!("Focus session \"{}\" started until {}", session.label, session.ends_at);
+    Ok(session)
+}
+
+pub fn stop(app: &AppHandle) -> Result<Option<FocusSession>, String> {
+    let session = app.state::<SharedFocusSession>().lock().map_err(|e| e.to_string())?.take();
+    app.state::<SharedTrayDisplay>()
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clear(TRAY_SOURCE);
+    Ok(session)
+}
+
+pub fn current(app: &AppHandle) -> Option<FocusSession> {
+    app.state::<SharedFocusSession>()
+        .lock()
+        .ok()?
+        .clone()
+        .filter(|session| session.ends_at > Utc::now())
+}
+
+fn is_distracting(category: &Category) -> bool {
+    !category.is_productive && !category.neutral
+}
+
+// Chamado a cada troca de janela: no modo foco, entrar num app improdutivo
+// gera um aviso imediato (uma vez por app até sair dele)
+pub fn on_activity_changed(app: &AppHandle, activity: &WindowActivity) {
+    if activity.is_idle {
+        return;
+    }
+    let category = match app.state::<SharedConfig>().lock() {
+        Ok(config) => config.category_for_activity(activity).filter(|c| is_distracting(c)).cloned(),
+        Err(e) => {
+            error!("Failed to lock config: {}", e);
+            return;
+        }
+    };
+
+    let session = {
+        let state = app.state::<SharedFocusSession>();
+        let mut session = match state.lock() {
+            Ok(session) => session,
+            Err(e) => {
+                error!("Failed to lock focus session: {}", e);
+                return;
+            }
+        };
+        let session = match session.as_mut() {
+            Some(session) if session.distraction_alerts && session.ends_at > Utc::now() => session,
+            _ => return,
+        };
+        if category.is_none() {
+            session.current_distraction = None;
+            return;
+        }
+        if session.current_distraction.as_deref() == Some(activity.application.as_str()) {
+            return;
+        }
+
+        session.current_distraction = Some(activity.application.clone());
+        session.distraction_count += 1;
+        if !session.distracting_apps.contains(&activity.application) {
+            session.distracting_apps.push(activity.application.clone());
+        }
+        session.clone()
+    };
+    let category = category.map_or_else(String::new, |c| c.name);
+    info!("Focus distraction: {} ({})", activity.application, category);
+
+    if session.bring_to_front {
+        if let Some(window) = app.get_window("main") {
+            if let Err(e) = window.show().and_then(|_| window.set_focus()) {
+                error!("Failed to bring dashboard forward: {}", e);
+            }
+        }
+    }
+
+    let app = app.clone();
+    let body = format!(
+        "{} ({}) isn't part of \"{}\". {} left.",
+        activity.application,
+        category,
+        session.label,
+        format_duration((session.ends_at - Utc::now()).num_seconds().max(0)),
+    );
+    tokio::spawn(async move {
+        if let Err(e) = crate::notifications::notify(&app, "Stay focused", &body).await {
+            error!("Failed to send notification: {}", e);
+        }
+    });
+}
+
+// Tempo produtivo/improdutivo dentro da sessão, a partir das atividades gravadas
+pub async fn record_summary(app: &AppHandle, session: &FocusSession, ended_at: DateTime<Utc>) {
+    let ended_at = ended_at.min(session.ends_at).max(session.started_at);
+    // O evento em andamento e o buffer do tracker ainda não estão no banco
+    app.state::<TrackerHandle>().flush().await;
+    let db = app.state::<DbConnection>();
+    let activities = match database::get_activities_between(&db, session.started_at, ended_at).await {
+        Ok(activities) => activities,
+        Err(e) => {
+            error!("Failed to read focus session activities: {}", e);
+            return;
+        }
+    };
+
+    let mut summary = FocusSummary {
+        id: 0,
+        label: session.label.clone(),
+        started_at: session.started_at,
+        planned_end: session.ends_at,
+        ended_at,
+        completed: ended_at >= session.ends_at,
+        productive_seconds: 0,
+        unproductive_seconds: 0,
+        distraction_count: session.distraction_count,
+        distracting_apps: session.distracting_apps.clone(),
+    };
+    match app.state::<SharedConfig>().lock() {
+        Ok(config) => {
+            for activity in activities.iter().filter(|a| !a.is_idle) {
+                let seconds = (activity.end_time.min(ended_at) - activity.start_time.max(session.started_at))
+                    .num_seconds()
+                    .max(0);
+                match config.category_for_activity(activity) {
+                    Some(category) if category.is_productive => summary.productive_seconds += seconds,
+                    Some(category) if is_distracting(category) => summary.unproductive_seconds += seconds,
+                    _ => {}
+                }
+            }
+        }
+        Err(e) => error!("Failed to lock config: {}", e),
+    }
+
+    if let Err(e) = database::save_focus_summary(&db, &summary).await {
+        error!("Failed to save focus session summary: {}", e);
+    }
+}
+
+// Chamado a cada atualização da bandeja: encerra a sessão vencida e avisa
+pub async fn finish_if_expired(app: &AppHandle) {
+    let expired = match app.state::<SharedFocusSession>().lock() {
+        Ok(mut session) => {
+            if session.as_ref().map_or(false, |s| s.ends_at <= Utc::now()) {
+                session.take()
+            } else {
+                None
+            }
+        }
+        Err(e) => {
+            error!("Failed to lock focus session: {}", e);
+            None
+        }
+    };
+
+    if let Some(session) = expired {
+        info!("Focus session \"{}\" finished", session.label);
+        record_summary(app, &session, session.ends_at).await;
+        if let Err(e) = crate::notifications::notify(
+            app,
+            "Focus session finished",
+            &format!("\"{}\" is over, time for a break", session.label),
+        )
+        .await
+        {
+            error!("Failed to notify end of focus session: {}", e);
+        }
+    }
+}
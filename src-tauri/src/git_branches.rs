@@ -0,0 +1,71 @@
+use chrono::Utc;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, error};
+
+use crate::category::SharedConfig;
+use crate::database::{self, DbConnection};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+// Intervalos do mesmo branch separados por mais que isso viram registros distintos
+// (app fechado, máquina suspensa)
+const MAX_GAP_SECONDS: i64 = 120;
+
+// Resolve o diretório .git, inclusive em worktrees onde .git é um arquivo "gitdir: ..."
+fn git_dir(repository: &Path) -> Option<PathBuf> {
+    let dot_git = repository.join(".git");
+    if dot_git.is_dir() {
+        return Some(dot_git);
+    }
+
+    let content = fs::read_to_string(&dot_git).ok()?;
+    let gitdir = content.trim().strip_prefix("gitdir:")?.trim();
+    Some(repository.join(gitdir))
+}
+
+// Lê o HEAD diretamente, sem depender do binário do git
+pub fn current_branch(repository: &Path) -> Option<String> {
+    let head = fs::read_to_string(git_dir(repository)?.join("HEAD")).ok()?;
+    let head = head.trim();
+
+    match head.strip_prefix("ref: refs/heads/") {
+        Some(branch) => Some(branch.to_string()),
+        // HEAD destacado: usa o hash abreviado
+        None => head.get(..7).map(|sha| format!("detached@{}", sha)),
+    }
+}
+
+pub async fn run_branch_watcher(db: DbConnection, shared_config: SharedConfig) {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let config = match shared_config.lock() {
+            Ok(config) => config.git_branches.clone(),
+            Err(e) => {
+                error!("Failed to lock config: {}", e);
+                continue;
+            }
+        };
+
+        // Durante o blackout nada é registrado, nem o branch
+        if !config.enabled || crate::category::blackout_now(&shared_config) {
+            continue;
+        }
+
+        let now = Utc::now();
+        for repository in &config.repositories {
+            let Some(branch) = current_branch(Path::new(repository)) else {
+                debug!("No git HEAD found in {}", repository);
+                continue;
+            };
+
+            if let Err(e) = database::record_branch(&db, repository, &branch, now, MAX_GAP_SECONDS).await {
+                error!("Failed to record branch for {}: {}", repository, e);
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::{error, info, warn};
+
+use crate::category::{self, SharedConfig};
+use crate::database::{self, DbConnection, HighFrequencySample};
+use crate::tracker::{self, PlatformBackend, SharedTrackerHealth};
+
+// Modo opt-in para autoexperimentos: amostra por segundo com geometria da janela e
+// contagem de cliques, teclas e rolagens, lidas pelo backend da plataforma
+pub const MAX_SESSION_MINUTES: i64 = 120;
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+// Amostras são gravadas em lote para não escrever no banco a cada segundo
+const BATCH_SIZE: usize = 30;
+// Ao sair do app, quanto esperar a sessão gravar as últimas amostras
+const STOP_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveSession {
+    pub id: i64,
+    pub label: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    // Sinalizado quando o loop grava o restante e fecha a sessão
+    #[serde(skip)]
+    pub finished: Arc<Notify>,
+}
+
+pub type SharedHighFrequency = Arc<Mutex<Option<ActiveSession>>>;
+
+fn is_running(state: &SharedHighFrequency, session_id: i64) -> bool {
+    state
+        .lock()
+        .map_or(false, |session| session.as_ref().map_or(false, |s| s.id == session_id))
+}
+
+fn sample(backend: &mut dyn PlatformBackend) -> Option<HighFrequencySample> {
+    let input = backend.input_counts();
+    let window = backend.active_window().ok()?;
+    Some(HighFrequencySample {
+        sampled_at: Utc::now(),
+        application: window.app_name,
+        title: window.title,
+        x: window.bounds.map(|b| b.x),
+        y: window.bounds.map(|b| b.y),
+        width: window.bounds.map(|b| b.width),
+        height: window.bounds.map(|b| b.height),
+        clicks: input.clicks,
+        key_presses: input.key_presses,
+        scrolls: input.scrolls,
+    })
+}
+
+async fn save(db: &DbConnection, session_id: i64, buffer: &mut Vec<HighFrequencySample>) {
+    if buffer.is_empty() {
+        return;
+    }
+    if let Err(e) = database::save_high_frequency_samples(db, session_id, buffer).await {
+        error!("Failed to save high-frequency samples: {}", e);
+    }
+    buffer.clear();
+}
+
+// Roda até o fim do prazo da sessão, até ela ser interrompida ou até o tracking ser pausado
+pub async fn run_session(
+    db: DbConnection,
+    config: SharedConfig,
+    health: SharedTrackerHealth,
+    state: SharedHighFrequency,
+    session: ActiveSession,
+) {
+    info!("Starting high-frequency session {} until {}", session.id, session.ends_at);
+
+    let mut backend = tracker::detect_backend();
+    let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+    let mut buffer: Vec<HighFrequencySample> = Vec::with_capacity(BATCH_SIZE);
+    // Descarta o que foi contado antes da sessão começar
+    backend.input_counts();
+
+    loop {
+        interval.tick().await;
+        if !is_running(&state, session.id) || Utc::now() >= session.ends_at {
+            break;
+        }
+        // Pausar o tracking encerra a sessão; sem conseguir ler o estado, também
+        if health.lock().map_or(true, |health| health.paused) {
+            info!("Tracking paused, stopping high-frequency session {}", session.id);
+            break;
+        }
+
+        // O blackout vale também aqui: a janela e as contagens do intervalo são descartadas
+        if category::blackout_now(&config) {
+            backend.input_counts();
+        } else if let Some(sample) = sample(backend.as_mut()) {
+            buffer.push(sample);
+        }
+
+        if buffer.len() >= BATCH_SIZE {
+            save(&db, session.id, &mut buffer).await;
+        }
+    }
+
+    save(&db, session.id, &mut buffer).await;
+    if let Err(e) = database::finish_high_frequency_session(&db, session.id, Utc::now()).await {
+        error!("Failed to finish high-frequency session: {}", e);
+    }
+
+    if let Ok(mut current) = state.lock() {
+        if current.as_ref().map_or(false, |s| s.id == session.id) {
+            *current = None;
+        }
+    }
+    session.finished.notify_one();
+    info!("High-frequency session {} finished", session.id);
+}
+
+// Interrompe a sessão em andamento e espera ela gravar as últimas amostras (saída do app)
+pub async fn stop(state: &SharedHighFrequency) {
+    let Some(session) = state.lock().ok().and_then(|mut current| current.take()) else {
+        return;
+    };
+    if tokio::time::timeout(STOP_TIMEOUT, session.finished.notified()).await.is_err() {
+        warn!("High-frequency session {} did not finish in time", session.id);
+    }
+}
@@ -0,0 +1,304 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local, NaiveDateTime, TimeZone, Utc};
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use tracing::{debug, info};
+
+use crate::database::{self, DbConnection};
+use crate::tracker::WindowActivity;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportFormat {
+    ActivityWatchJson,
+    RescueTimeCsv,
+    TogglCsv,
+    ChronosBackup,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportPreview {
+    pub format: ImportFormat,
+    pub rows: usize,
+    pub skipped: usize,
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    pub conflicts: usize,
+    pub committed: bool,
+}
+
+pub fn detect_format(path: &Path) -> Result<ImportFormat> {
+    let bytes = fs::read(path)?;
+
+    // Backups do Chronos são bancos SQLite
+    if bytes.starts_with(b"SQLite format 3\0") {
+        return Ok(ImportFormat::ChronosBackup);
+    }
+
+    let content = String::from_utf8_lossy(&bytes);
+    let trimmed = content.trim_start_matches('\u{feff}').trim_start();
+
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        return Ok(ImportFormat::ActivityWatchJson);
+    }
+
+    let header = trimmed.lines().next().unwrap_or_default();
+    if header.contains("Time Spent (seconds)") && header.contains("Activity") {
+        return Ok(ImportFormat::RescueTimeCsv);
+    }
+    if header.contains("Start date") && header.contains("Start time") && header.contains("Description") {
+        return Ok(ImportFormat::TogglCsv);
+    }
+
+    Err(anyhow::anyhow!("Unrecognized import format: {}", path.display()))
+}
+
+pub async fn import_file(db: &DbConnection, path: &Path, dry_run: bool) -> Result<ImportPreview> {
+    let format = detect_format(path)?;
+    info!("Importing {:?} as {:?} (dry run: {})", path, format, dry_run);
+
+    let (activities, skipped) = match format {
+        ImportFormat::ActivityWatchJson => parse_activitywatch(path)?,
+        ImportFormat::RescueTimeCsv => parse_rescuetime(path)?,
+        ImportFormat::TogglCsv => parse_toggl(path)?,
+        ImportFormat::ChronosBackup => parse_chronos_backup(path)?,
+    };
+
+    let start = activities.iter().map(|a| a.start_time).min();
+    let end = activities.iter().map(|a| a.end_time).max();
+    let conflicts = database::count_overlapping(db, &activities).await?;
+
+    debug!("Parsed {} rows ({} skipped, {} conflicts)", activities.len(), skipped, conflicts);
+
+    let committed = if dry_run {
+        false
+    } else {
+        database::insert_activities(db, &activities).await?;
+        true
+    };
+
+    Ok(ImportPreview {
+        format,
+        rows: activities.len(),
+        skipped,
+        start,
+        end,
+        conflicts,
+        committed,
+    })
+}
+
+fn parse_activitywatch(path: &Path) -> Result<(Vec<WindowActivity>, usize)> {
+    let content = fs::read_to_string(path)?;
+    let json: serde_json::Value = serde_json::from_str(&content)?;
+
+    // Aceita tanto o export completo ({"buckets": {...}}) quanto uma lista de eventos
+    let mut events = Vec::new();
+    if let Some(buckets) = json.get("buckets").and_then(|b| b.as_object()) {
+        for bucket in buckets.values() {
+            if let Some(list) = bucket.get("events").and_then(|e| e.as_array()) {
+                events.extend(list.iter().cloned());
+            }
+        }
+    } else if let Some(list) = json.as_array() {
+        events.extend(list.iter().cloned());
+    }
+
+    let mut activities = Vec::new();
+    let mut skipped = 0;
+    for event in events {
+        let data = &event["data"];
+        let (Some(app), Some(timestamp)) = (data["app"].as_str(), event["timestamp"].as_str()) else {
+            // Eventos de AFK e de outros watchers não têm app
+            skipped += 1;
+            continue;
+        };
+        let Ok(start) = DateTime::parse_from_rfc3339(timestamp) else {
+            skipped += 1;
+            continue;
+        };
+        let start = start.with_timezone(&Utc);
+        let seconds = event["duration"].as_f64().unwrap_or(0.0);
+
+        activities.push(WindowActivity {
+            title: data["title"].as_str().unwrap_or_default().to_string(),
+            application: app.to_string(),
+            start_time: start,
+            end_time: start + Duration::milliseconds((seconds * 1000.0) as i64),
+            is_browser: data["url"].is_string(),
+            url: data["url"].as_str().map(|u| u.to_string()),
+            is_idle: false,
+            ..Default::default()
+        });
+    }
+
+    Ok((activities, skipped))
+}
+
+fn parse_rescuetime(path: &Path) -> Result<(Vec<WindowActivity>, usize)> {
+    let content = fs::read_to_string(path)?;
+    let mut lines = content.lines();
+    let header = parse_csv_line(lines.next().unwrap_or_default());
+
+    let date_col = column_index(&header, "Date")?;
+    let seconds_col = column_index(&header, "Time Spent (seconds)")?;
+    let activity_col = column_index(&header, "Activity")?;
+    let category_col = column_index(&header, "Category").ok();
+
+    let mut activities = Vec::new();
+    let mut skipped = 0;
+    for line in lines.filter(|l| !l.trim().is_empty()) {
+        let fields = parse_csv_line(line);
+        let start = fields.get(date_col).and_then(|d| parse_local_datetime(d));
+        let seconds = fields.get(seconds_col).and_then(|s| s.parse::<i64>().ok());
+
+        let (Some(start), Some(seconds), Some(activity)) = (start, seconds, fields.get(activity_col)) else {
+            skipped += 1;
+            continue;
+        };
+
+        activities.push(WindowActivity {
+            title: category_col
+                .and_then(|c| fields.get(c))
+                .cloned()
+                .unwrap_or_default(),
+            application: activity.clone(),
+            start_time: start,
+            end_time: start + Duration::seconds(seconds),
+            is_browser: false,
+            url: None,
+            is_idle: false,
+            ..Default::default()
+        });
+    }
+
+    Ok((activities, skipped))
+}
+
+fn parse_toggl(path: &Path) -> Result<(Vec<WindowActivity>, usize)> {
+    let content = fs::read_to_string(path)?;
+    let mut lines = content.trim_start_matches('\u{feff}').lines();
+    let header = parse_csv_line(lines.next().unwrap_or_default());
+
+    let description_col = column_index(&header, "Description")?;
+    let start_date_col = column_index(&header, "Start date")?;
+    let start_time_col = column_index(&header, "Start time")?;
+    let end_date_col = column_index(&header, "End date")?;
+    let end_time_col = column_index(&header, "End time")?;
+    let project_col = column_index(&header, "Project").ok();
+
+    let mut activities = Vec::new();
+    let mut skipped = 0;
+    for line in lines.filter(|l| !l.trim().is_empty()) {
+        let fields = parse_csv_line(line);
+        let field = |i: usize| fields.get(i).map(|s| s.as_str()).unwrap_or_default();
+
+        let start = parse_local_datetime(&format!("{} {}", field(start_date_col), field(start_time_col)));
+        let end = parse_local_datetime(&format!("{} {}", field(end_date_col), field(end_time_col)));
+
+        let (Some(start), Some(end)) = (start, end) else {
+            skipped += 1;
+            continue;
+        };
+
+        let project = project_col.map(field).filter(|p| !p.is_empty());
+        activities.push(WindowActivity {
+            title: field(description_col).to_string(),
+            application: project.map_or("Toggl".to_string(), |p| format!("Toggl: {}", p)),
+            start_time: start,
+            end_time: end,
+            is_browser: false,
+            url: None,
+            is_idle: false,
+            ..Default::default()
+        });
+    }
+
+    Ok((activities, skipped))
+}
+
+fn parse_chronos_backup(path: &Path) -> Result<(Vec<WindowActivity>, usize)> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let mut stmt = conn.prepare(
+        "SELECT title, application, start_time, end_time, is_browser, url, is_idle FROM activities",
+    )?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, bool>(4)?,
+                row.get::<_, Option<String>>(5)?,
+                row.get::<_, bool>(6).unwrap_or(false),
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut activities = Vec::new();
+    let mut skipped = 0;
+    for (title, application, start_time, end_time, is_browser, url, is_idle) in rows {
+        let (Ok(start), Ok(end)) = (
+            DateTime::parse_from_rfc3339(&start_time),
+            DateTime::parse_from_rfc3339(&end_time),
+        ) else {
+            skipped += 1;
+            continue;
+        };
+
+        activities.push(WindowActivity {
+            title,
+            application,
+            start_time: start.with_timezone(&Utc),
+            end_time: end.with_timezone(&Utc),
+            is_browser,
+            url,
+            is_idle,
+            ..Default::default()
+        });
+    }
+
+    Ok((activities, skipped))
+}
+
+pub fn column_index(header: &[String], name: &str) -> Result<usize> {
+    header
+        .iter()
+        .position(|h| h.trim() == name)
+        .ok_or_else(|| anyhow::anyhow!("Missing column: {}", name))
+}
+
+fn parse_local_datetime(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(value, fmt).ok())
+        .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+pub fn parse_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    fields.push(current);
+
+    fields
+}
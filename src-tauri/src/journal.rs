@@ -0,0 +1,239 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::category::{CategoryConfig, JournalFormat};
+use crate::database;
+use crate::export;
+use crate::importer::{column_index, parse_csv_line};
+use crate::tracker::WindowActivity;
+
+// Diário somente de acréscimo, um arquivo por dia local, escrito junto com o
+// SQLite. Serve de cópia legível caso o banco se corrompa e de integração simples
+// para scripts. As colunas são fixas para não quebrar arquivos já existentes
+const CSV_COLUMNS: &[&str] = &[
+    "start_time",
+    "end_time",
+    "application",
+    "title",
+    "url",
+    "is_browser",
+    "is_idle",
+    "duration_seconds",
+    "category_name",
+    "project",
+    "cwd",
+    "desktop",
+    "network",
+];
+
+pub fn journal_dir(config: &CategoryConfig) -> Result<PathBuf> {
+    let dir = match config.journal.directory.as_deref().map(str::trim).filter(|d| !d.is_empty()) {
+        Some(directory) => PathBuf::from(directory),
+        None => database::get_database_path()?
+            .parent()
+            .ok_or_else(|| anyhow::anyhow!("Database path has no parent directory"))?
+            .join("journal"),
+    };
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn line(activity: &WindowActivity, config: &CategoryConfig) -> Result<String> {
+    match config.journal.format {
+        JournalFormat::Csv => Ok(CSV_COLUMNS
+            .iter()
+            .map(|column| export::csv_field(&export::csv_value(activity, config, column)))
+            .collect::<Vec<_>>()
+            .join(",")),
+        JournalFormat::Jsonl => {
+            let mut value = serde_json::to_value(activity)?;
+            if let Some(object) = value.as_object_mut() {
+                object.insert("category_name".to_string(), export::csv_value(activity, config, "category_name").into());
+            }
+            Ok(value.to_string())
+        }
+    }
+}
+
+// Acrescenta os trechos encerrados ao arquivo do dia em que começaram
+pub fn append(config: &CategoryConfig, activities: &[WindowActivity]) -> Result<usize> {
+    if activities.is_empty() {
+        return Ok(0);
+    }
+
+    let dir = journal_dir(config)?;
+    let extension = match config.journal.format {
+        JournalFormat::Csv => "csv",
+        JournalFormat::Jsonl => "jsonl",
+    };
+
+    let mut by_day: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for activity in activities {
+        let day = config.calendar.local_date(activity.start_time).format("%Y-%m-%d").to_string();
+        by_day.entry(day).or_default().push(line(activity, config)?);
+    }
+
+    for (day, lines) in &by_day {
+        let path = dir.join(format!("journal-{}.{}", day, extension));
+        let is_new = std::fs::metadata(&path).map_or(true, |m| m.len() == 0);
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&path)?;
+
+        let mut output = String::new();
+        if is_new && config.journal.format == JournalFormat::Csv {
+            output.push_str(&CSV_COLUMNS.join(","));
+            output.push('\n');
+        }
+        for line in lines {
+            output.push_str(line);
+            output.push('\n');
+        }
+        file.write_all(output.as_bytes())?;
+    }
+
+    Ok(activities.len())
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value.trim()).ok().map(|dt| dt.with_timezone(&Utc))
+}
+
+// Mesmo critério de database::delete_activities: começa no intervalo e/ou é do app
+fn matches(
+    start_time: Option<DateTime<Utc>>,
+    app: Option<&str>,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+    application: Option<&str>,
+) -> bool {
+    let Some(start_time) = start_time else {
+        return false;
+    };
+    start.map_or(true, |start| start_time >= start)
+        && end.map_or(true, |end| start_time < end)
+        && application.map_or(true, |application| app == Some(application))
+}
+
+// Tira do diário as linhas apagadas do banco (delete_activities). As demais linhas
+// são mantidas como estão; o arquivo é reescrito só se algo foi removido
+pub fn purge(
+    config: &CategoryConfig,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+    application: Option<&str>,
+) -> Result<usize> {
+    let dir = journal_dir(config)?;
+    let mut removed = 0;
+
+    for entry in std::fs::read_dir(&dir)? {
+        let path = entry?.path();
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        let jsonl = name.ends_with(".jsonl");
+        if !name.starts_with("journal-") || !(jsonl || name.ends_with(".csv")) {
+            continue;
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+        let mut lines = content.lines();
+        let mut kept = Vec::new();
+        let mut columns = None;
+        if !jsonl {
+            let Some(header) = lines.next() else {
+                continue;
+            };
+            let fields = parse_csv_line(header);
+            columns = Some((column_index(&fields, "start_time")?, column_index(&fields, "application")?));
+            kept.push(header);
+        }
+
+        let before = removed;
+        for line in lines {
+            let (start_time, app) = match columns {
+                Some((start_col, app_col)) => {
+                    let fields = parse_csv_line(line);
+                    (fields.get(start_col).and_then(|v| parse_time(v)), fields.get(app_col).cloned())
+                }
+                None => match serde_json::from_str::<WindowActivity>(line) {
+                    Ok(activity) => (Some(activity.start_time), Some(activity.application)),
+                    Err(_) => (None, None),
+                },
+            };
+            if matches(start_time, app.as_deref(), start, end, application) {
+                removed += 1;
+            } else {
+                kept.push(line);
+            }
+        }
+        if removed == before {
+            continue;
+        }
+
+        let mut output = kept.join("\n");
+        output.push('\n');
+        let temp = path.with_extension("tmp");
+        std::fs::write(&temp, output)?;
+        std::fs::rename(&temp, &path)?;
+    }
+
+    Ok(removed)
+}
+
+// Lê um arquivo do diário (CSV ou JSONL, pela extensão) de volta em atividades,
+// em ordem cronológica. Linhas ilegíveis são ignoradas e contadas. Só o replay usa
+#[cfg(debug_assertions)]
+pub fn read(path: &Path) -> Result<(Vec<WindowActivity>, usize)> {
+    let content = std::fs::read_to_string(path)?;
+    let mut activities = Vec::new();
+    let mut skipped = 0;
+
+    if path.extension().map_or(false, |ext| ext == "jsonl") {
+        for line in content.lines().filter(|l| !l.trim().is_empty()) {
+            match serde_json::from_str::<WindowActivity>(line) {
+                Ok(activity) => activities.push(activity),
+                Err(_) => skipped += 1,
+            }
+        }
+    } else {
+        let mut lines = content.lines();
+        let header = parse_csv_line(lines.next().unwrap_or_default());
+        let column = |name: &str| column_index(&header, name);
+        let (start_col, end_col, app_col, title_col) =
+            (column("start_time")?, column("end_time")?, column("application")?, column("title")?);
+        let optional = |name: &str| column_index(&header, name).ok();
+        let (url_col, browser_col, idle_col) = (optional("url"), optional("is_browser"), optional("is_idle"));
+        let (project_col, cwd_col, desktop_col, network_col) =
+            (optional("project"), optional("cwd"), optional("desktop"), optional("network"));
+
+        for line in lines.filter(|l| !l.trim().is_empty()) {
+            let fields = parse_csv_line(line);
+            let text = |col: Option<usize>| col.and_then(|c| fields.get(c)).filter(|v| !v.is_empty()).cloned();
+            let (Some(start_time), Some(end_time)) = (
+                fields.get(start_col).and_then(|v| parse_time(v)),
+                fields.get(end_col).and_then(|v| parse_time(v)),
+            ) else {
+                skipped += 1;
+                continue;
+            };
+
+            activities.push(WindowActivity {
+                title: text(Some(title_col)).unwrap_or_default(),
+                application: text(Some(app_col)).unwrap_or_default(),
+                start_time,
+                end_time,
+                is_browser: text(browser_col).map_or(false, |v| v == "true"),
+                url: text(url_col),
+                is_idle: text(idle_col).map_or(false, |v| v == "true"),
+                project: text(project_col),
+                cwd: text(cwd_col),
+                desktop: text(desktop_col),
+                network: text(network_col),
+                ..Default::default()
+            });
+        }
+    }
+
+    activities.sort_by_key(|a| a.start_time);
+    Ok((activities, skipped))
+}
@@ -1,11 +1,58 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 use anyhow::Result;
+use std::sync::{Arc, Mutex};
 use tracing::info;
 
 mod database;
 mod tracker;
 mod commands;
 mod category;
+mod importer;
+mod network;
+mod events;
+mod stats_cache;
+mod project;
+mod notifications;
+mod terminal;
+mod git_branches;
+mod export;
+mod suggestions;
+mod clustering;
+mod watchdog;
+mod locale;
+mod high_frequency;
+mod permissions;
+mod coverage;
+mod migrations;
+mod status_line;
+mod rollups;
+mod focus;
+mod retention;
+mod backup;
+mod self_test;
+mod archive;
+mod screenshots;
+mod review;
+mod encryption;
+mod db_location;
+mod allocation;
+mod maintenance;
+mod sync_feed;
+mod sync;
+mod cloud_backup;
+mod journal;
+mod perf;
+mod secrets;
+mod score;
+mod browser;
+mod streaks;
+mod timesheet;
+#[cfg(debug_assertions)]
+mod replay;
+mod timeline;
+mod alerts;
+mod breaks;
+mod pomodoro;
 pub mod menu;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -49,8 +96,14 @@ async fn init_tracking() -> Result<()> {
     // Inicializa o banco de dados
     let db = database::init_database().await?;
     
+    // Carrega a configuração de categorias
+    let config = category::CategoryConfig::load()
+        .unwrap_or_else(|_| category::CategoryConfig::default());
+    
     // Inicializa o rastreador
-    let mut tracker = tracker::ActivityTracker::new(db).await;
+    // Este ponto de entrada não encaminha os eventos do tracker para o frontend
+    let (events, _) = events::tracker_channel();
+    let mut tracker = tracker::ActivityTracker::new(db, Arc::new(Mutex::new(config)), events).await;
     
     // Inicia o rastreamento
     tracker.start_tracking().await;
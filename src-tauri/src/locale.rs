@@ -0,0 +1,132 @@
+use serde::Serialize;
+use std::process::Command;
+use tauri::{AppHandle, Manager};
+use tracing::{debug, error, info};
+
+use crate::category::{CalendarConfig, SharedConfig, WeekStart};
+
+pub const CALENDAR_SUGGESTION_EVENT: &str = "calendar-settings-suggestion";
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CalendarSuggestion {
+    pub current: CalendarConfig,
+    pub system_week_start: WeekStart,
+    pub suggested: CalendarConfig,
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// Convenção gregoriana usada por macOS e glibc: 1 = domingo, 2 = segunda...
+fn from_gregorian(day: u32) -> Option<WeekStart> {
+    match day {
+        1 => Some(WeekStart::Sunday),
+        2 => Some(WeekStart::Monday),
+        7 => Some(WeekStart::Saturday),
+        _ => None,
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn native_week_start() -> Option<WeekStart> {
+    let day = command_output("defaults", &["read", "-g", "AppleFirstWeekday"])?;
+    // Formato: { gregorian = 2; }
+    let value = day.split('=').nth(1)?.split(';').next()?.trim();
+    from_gregorian(value.parse().ok()?)
+}
+
+#[cfg(target_os = "linux")]
+fn native_week_start() -> Option<WeekStart> {
+    let day = command_output("locale", &["first_weekday"])?;
+    from_gregorian(day.lines().next()?.trim().parse().ok()?)
+}
+
+#[cfg(target_os = "windows")]
+fn native_week_start() -> Option<WeekStart> {
+    let output = command_output(
+        "reg",
+        &["query", r"HKCU\Control Panel\International", "/v", "iFirstDayOfWeek"],
+    )?;
+    // 0 = segunda ... 6 = domingo
+    match output.split_whitespace().last()? {
+        "0" => Some(WeekStart::Monday),
+        "5" => Some(WeekStart::Saturday),
+        "6" => Some(WeekStart::Sunday),
+        _ => None,
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn native_week_start() -> Option<WeekStart> {
+    None
+}
+
+// Sem configuração nativa, deduz pela região do LANG (ex: en_US.UTF-8)
+fn week_start_from_env() -> Option<WeekStart> {
+    let lang = ["LC_ALL", "LC_TIME", "LANG"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()))?;
+    let region = lang.split(['.', '@']).next()?.split(['_', '-']).nth(1)?.to_uppercase();
+
+    match region.as_str() {
+        "US" | "CA" | "BR" | "MX" | "JP" | "KR" | "IL" | "PH" | "ZA" | "AU" | "IN" | "TW" | "HK" => {
+            Some(WeekStart::Sunday)
+        }
+        "AE" | "EG" | "SA" | "QA" | "KW" | "BH" | "OM" | "JO" | "IR" => Some(WeekStart::Saturday),
+        _ => Some(WeekStart::Monday),
+    }
+}
+
+pub fn system_week_start() -> Option<WeekStart> {
+    native_week_start().or_else(week_start_from_env)
+}
+
+// Sugestão apenas quando o usuário ainda não confirmou e o sistema diverge da configuração
+pub fn calendar_suggestion(calendar: &CalendarConfig) -> Option<CalendarSuggestion> {
+    if calendar.confirmed {
+        return None;
+    }
+
+    let system_week_start = system_week_start()?;
+    if system_week_start == calendar.week_start {
+        return None;
+    }
+
+    Some(CalendarSuggestion {
+        current: calendar.clone(),
+        system_week_start,
+        suggested: CalendarConfig {
+            week_start: system_week_start,
+            ..calendar.clone()
+        },
+    })
+}
+
+// Na inicialização, avisa o frontend (uma vez) se as convenções do sistema diferem
+pub fn check_calendar_settings(app: &AppHandle) {
+    let calendar = match app.state::<SharedConfig>().lock() {
+        Ok(config) => config.calendar.clone(),
+        Err(e) => {
+            error!("Failed to lock config: {}", e);
+            return;
+        }
+    };
+
+    match calendar_suggestion(&calendar) {
+        Some(suggestion) => {
+            info!(
+                "System week starts on {:?} but settings use {:?}",
+                suggestion.system_week_start, calendar.week_start
+            );
+            if let Err(e) = app.emit_all(CALENDAR_SUGGESTION_EVENT, suggestion) {
+                error!("Failed to emit calendar suggestion: {}", e);
+            }
+        }
+        None => debug!("Calendar settings match system conventions"),
+    }
+}
@@ -6,12 +6,61 @@ mod tracker;
 mod commands;
 mod menu;
 mod category;
+mod importer;
+mod network;
+mod events;
+mod stats_cache;
+mod project;
+mod notifications;
+mod terminal;
+mod git_branches;
+mod export;
+mod suggestions;
+mod clustering;
+mod watchdog;
+mod locale;
+mod high_frequency;
+mod permissions;
+mod coverage;
+mod migrations;
+mod status_line;
+mod rollups;
+mod focus;
+mod retention;
+mod backup;
+mod self_test;
+mod archive;
+mod screenshots;
+mod review;
+mod encryption;
+mod db_location;
+mod allocation;
+mod maintenance;
+mod sync_feed;
+mod sync;
+mod cloud_backup;
+mod journal;
+mod perf;
+mod secrets;
+mod score;
+mod browser;
+mod streaks;
+mod timesheet;
+#[cfg(debug_assertions)]
+mod replay;
+mod timeline;
+mod alerts;
+mod breaks;
+mod pomodoro;
 
 use anyhow::Result;
 use tauri::Manager;
 use tracing::{info, error, debug, warn};
-use std::sync::Mutex;
+use tracing_subscriber::prelude::*;
+use std::sync::{Arc, Mutex};
 use category::CategoryConfig;
+use stats_cache::TodayStatsCache;
+use notifications::AlertLog;
 use std::path::PathBuf;
 
 fn get_app_dir() -> Result<PathBuf> {
@@ -37,6 +86,28 @@ fn get_app_dir() -> Result<PathBuf> {
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Modo CLI para barras de status (tmux, polybar): imprime e sai sem abrir a janela
+    if std::env::args().any(|arg| arg == "--status-line") {
+        println!("{}", status_line::read());
+        return Ok(());
+    }
+
+    // Modo CLI para hooks de commit: `--time-for PROJ-123 [--days 30]` imprime o tempo gasto
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(pattern) = args.iter().position(|arg| arg == "--time-for").and_then(|i| args.get(i + 1)) {
+        let days = args
+            .iter()
+            .position(|arg| arg == "--days")
+            .and_then(|i| args.get(i + 1))
+            .and_then(|days| days.parse::<i64>().ok())
+            .unwrap_or(30);
+        let end = chrono::Utc::now();
+        let db = database::init_database().await?;
+        let time = database::get_time_for_pattern(&db, pattern, end - chrono::Duration::days(days), end).await?;
+        println!("{}", menu::format_duration(time.total_seconds));
+        return Ok(());
+    }
+
     // Configura o logger para escrever em um arquivo
     let app_dir = get_app_dir()?;
     let log_dir = app_dir.join("logs");
@@ -48,14 +119,20 @@ async fn main() -> Result<()> {
         "chronos-track.log",
     );
     
-    tracing_subscriber::fmt()
-        .with_writer(file_appender)
-        .with_ansi(false)
-        .with_line_number(true)
-        .with_file(true)
-        .with_thread_ids(true)
-        .with_thread_names(true)
-        .with_target(false)
+    // Log em arquivo mais a coleta de latências dos spans (get_performance_metrics)
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::filter::LevelFilter::INFO)
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(file_appender)
+                .with_ansi(false)
+                .with_line_number(true)
+                .with_file(true)
+                .with_thread_ids(true)
+                .with_thread_names(true)
+                .with_target(false),
+        )
+        .with(perf::PerfLayer)
         .init();
 
     info!("Starting Chronos Track");
@@ -76,18 +153,13 @@ async fn main() -> Result<()> {
     };
 
     let db_for_state = db.clone();
-    
-    // Inicializa o rastreador
-    debug!("Initializing activity tracker...");
-    let mut tracker = tracker::ActivityTracker::new(db).await;
-    info!("Activity tracker initialized successfully");
-    
-    // Inicia o rastreamento em uma nova thread
-    tokio::spawn(async move {
-        info!("Starting activity tracking");
-        tracker.start_tracking().await;
-        error!("Activity tracking loop ended unexpectedly");
-    });
+
+    // Sessões de alta frequência que ficaram abertas quando o app foi encerrado à força
+    match database::close_interrupted_high_frequency_sessions(&db).await {
+        Ok(0) => {}
+        Ok(closed) => info!("Closed {} interrupted high-frequency sessions", closed),
+        Err(e) => error!("Failed to close interrupted high-frequency sessions: {}", e),
+    }
 
     // Carrega a configuração de categorias
     debug!("Loading category configuration...");
@@ -103,12 +175,56 @@ async fn main() -> Result<()> {
             CategoryConfig::default()
         }
     };
+    let category_config = Arc::new(Mutex::new(category_config));
+    
+    // Inicializa o rastreador
+    debug!("Initializing activity tracker...");
+    let (tracker_events, tracker_events_receiver) = events::tracker_channel();
+    let tracker_handle = tracker::TrackerHandle::default();
+    let tracker_health = tracker::SharedTrackerHealth::default();
+    
+    // Inicia o rastreamento em uma nova thread, supervisionado pelo watchdog
+    tokio::spawn(watchdog::supervise(
+        db.clone(),
+        category_config.clone(),
+        tracker_events,
+        tracker_handle.clone(),
+        tracker_health.clone(),
+    ));
+    info!("Activity tracker initialized successfully");
+
+    // Observa o branch ativo dos repositórios configurados (só grava se habilitado)
+    tokio::spawn(git_branches::run_branch_watcher(db.clone(), category_config.clone()));
+
+    // Mantém os resumos por hora usados nas estatísticas semanais/mensais
+    tokio::spawn(rollups::run_rollup_job(db.clone()));
+
+    // Limpeza periódica conforme o período de retenção (desligada por padrão)
+    tokio::spawn(retention::run_retention_job(db.clone(), category_config.clone()));
+
+    // Resultado da meta diária de cada dia encerrado, base das sequências
+    tokio::spawn(streaks::run_streak_job(db.clone(), category_config.clone()));
+
+    // Miniaturas periódicas da tela (só quando habilitado)
+    tokio::spawn(screenshots::run_screenshot_job(db, category_config.clone(), tracker_health.clone()));
 
     // Inicia a aplicação Tauri
     debug!("Starting Tauri application...");
     let app = tauri::Builder::default()
         .manage(db_for_state)
-        .manage(Mutex::new(category_config))
+        .manage(category_config)
+        .manage(Arc::new(Mutex::new(TodayStatsCache::load())))
+        .manage(Arc::new(Mutex::new(AlertLog::default())))
+        .manage(tracker_handle)
+        .manage(tracker_health)
+        .manage(high_frequency::SharedHighFrequency::default())
+        .manage(menu::SharedTrayDisplay::default())
+        .manage(focus::SharedFocusSession::default())
+        .manage(notifications::SharedNotificationQueue::default())
+        .manage(breaks::SharedBreakReminder::default())
+        .manage(pomodoro::SharedPomodoro::default())
+        .manage(sync_feed::SharedSyncFeed::default())
+        .manage(events::feed_channel())
         .system_tray(menu::create_tray_menu())
         .on_system_tray_event(menu::handle_tray_event)
         .invoke_handler(tauri::generate_handler![
@@ -123,9 +239,164 @@ async fn main() -> Result<()> {
             commands::delete_category,
             commands::set_app_category,
             commands::get_uncategorized_apps,
+            commands::get_rule_suggestions,
+            commands::get_activity_clusters,
+            commands::get_tracker_status,
             commands::get_today_stats,
             commands::get_daily_goal,
             commands::set_daily_goal,
+            commands::get_tray_state,
+            commands::get_weekly_goal,
+            commands::set_weekly_goal,
+            commands::get_media_playback_config,
+            commands::set_media_playback_config,
+            commands::get_network_tagging_config,
+            commands::set_network_tagging_config,
+            commands::get_window_snapshot_config,
+            commands::set_window_snapshot_config,
+            commands::get_notification_config,
+            commands::set_notification_config,
+            commands::get_git_branch_config,
+            commands::set_git_branch_config,
+            commands::send_test_notification,
+            commands::get_window_snapshots,
+            commands::import,
+            commands::get_storage_forecast,
+            commands::get_desktop_stats,
+            commands::get_project_stats,
+            commands::get_branch_stats,
+            commands::get_config_history,
+            commands::get_stats_as_of,
+            commands::export_org_clock,
+            commands::export_csv,
+            commands::get_export_columns,
+            commands::get_calendar_config,
+            commands::get_calendar_suggestion,
+            commands::confirm_calendar_settings,
+            commands::set_tracking_paused,
+            commands::start_high_frequency_session,
+            commands::stop_high_frequency_session,
+            commands::get_high_frequency_status,
+            commands::get_high_frequency_sessions,
+            commands::get_high_frequency_samples,
+            commands::delete_high_frequency_session,
+            commands::get_permission_status,
+            commands::request_permission,
+            commands::open_permission_settings,
+            commands::get_idle_config,
+            commands::set_idle_config,
+            commands::add_adjustment,
+            commands::get_adjustments,
+            commands::delete_adjustment,
+            commands::get_schedule_config,
+            commands::set_schedule_config,
+            commands::get_data_coverage,
+            commands::get_status_line,
+            commands::get_tray_config,
+            commands::set_tray_config,
+            commands::start_focus_session,
+            commands::stop_focus_session,
+            commands::get_focus_session,
+            commands::start_focus_mode,
+            commands::get_focus_summaries,
+            commands::get_queued_notifications,
+            commands::get_domain_stats,
+            commands::get_domain_labels,
+            commands::set_domain_label,
+            commands::get_retention_config,
+            commands::set_retention_config,
+            commands::run_retention_purge,
+            commands::get_export_review,
+            commands::backup_database,
+            commands::restore_database,
+            commands::get_goals_overview,
+            commands::run_self_test,
+            commands::export_archive,
+            commands::import_archive,
+            commands::get_time_for_pattern,
+            commands::get_screenshot_config,
+            commands::set_screenshot_config,
+            commands::get_screenshots,
+            commands::delete_screenshot,
+            commands::get_daily_review,
+            commands::submit_daily_review,
+            commands::get_encryption_status,
+            commands::set_database_encryption,
+            commands::get_database_location,
+            commands::set_database_location,
+            commands::get_allocation_config,
+            commands::set_allocation_config,
+            commands::get_project_allocation,
+            commands::get_category_limits,
+            commands::set_category_limits,
+            commands::get_project_patterns,
+            commands::set_project_patterns,
+            commands::delete_activities,
+            commands::update_activity,
+            commands::get_blackout_config,
+            commands::set_blackout_config,
+            commands::get_app_stats_page,
+            commands::repair_activity_overlaps,
+            commands::maintain_database,
+            commands::get_maintenance_config,
+            commands::set_maintenance_config,
+            commands::get_database_info,
+            commands::get_sync_feed_config,
+            commands::set_sync_feed_config,
+            commands::regenerate_sync_feed_token,
+            commands::add_api_client,
+            commands::set_api_client_permissions,
+            commands::remove_api_client,
+            commands::get_sync_config,
+            commands::set_sync_config,
+            commands::sync_now,
+            commands::get_cloud_backup_config,
+            commands::set_cloud_backup_config,
+            commands::run_cloud_backup,
+            commands::list_cloud_backups,
+            commands::get_hourly_stats,
+            commands::get_journal_config,
+            commands::set_journal_config,
+            commands::get_category_stats,
+            commands::get_performance_metrics,
+            commands::reset_performance_metrics,
+            commands::recategorize_by_pattern,
+            commands::clear_category_overrides,
+            commands::get_yearly_stats,
+            commands::restore_from_cloud,
+            commands::get_cloud_backup_key,
+            commands::get_top_websites,
+            commands::get_domain_categories,
+            commands::set_domain_category,
+            commands::get_productivity_score,
+            commands::get_score_weights,
+            commands::set_score_weights,
+            commands::get_browser_profiles,
+            commands::set_browser_profiles,
+            commands::get_streaks,
+            commands::get_timesheet_config,
+            commands::set_timesheet_config,
+            commands::generate_timesheet_draft,
+            commands::compare_periods,
+            commands::get_app_diff,
+            commands::get_timeline,
+            commands::get_context_switch_metrics,
+            commands::get_break_reminder_config,
+            commands::set_break_reminder_config,
+            commands::get_break_status,
+            commands::snooze_break_reminder,
+            commands::get_idle_prompt_config,
+            commands::set_idle_prompt_config,
+            commands::label_idle_period,
+            commands::get_pomodoro_config,
+            commands::set_pomodoro_config,
+            commands::start_pomodoro,
+            commands::stop_pomodoro,
+            commands::skip_pomodoro,
+            commands::get_pomodoro,
+            commands::get_pomodoro_stats,
+            commands::add_manual_activity,
+            commands::replay_from_journal,
         ])
         .setup(|app| {
             debug!("Setting up main window...");
@@ -161,6 +432,38 @@ async fn main() -> Result<()> {
                 }
             });
 
+            debug!("Backfilling today's stats...");
+            tokio::spawn(stats_cache::backfill(app.handle()));
+
+            debug!("Setting up stats delta emitter...");
+            tokio::spawn(events::run_stats_delta_loop(app.handle()));
+
+            debug!("Forwarding tracker events...");
+            tokio::spawn(events::forward_tracker_events(app.handle(), tracker_events_receiver));
+
+            let missing = permissions::missing_permissions();
+            if !missing.is_empty() {
+                warn!("Missing system permissions, window titles may be empty: {:?}", missing);
+            }
+
+            tokio::spawn(notifications::run_dnd_watcher(app.handle()));
+            tokio::spawn(allocation::run_drift_alerts(app.handle()));
+            tokio::spawn(maintenance::run_maintenance_job(app.handle()));
+            tokio::spawn(sync::run_sync_job(app.handle()));
+            tokio::spawn(cloud_backup::run_cloud_backup_job(app.handle()));
+            tokio::spawn(timesheet::run_timesheet_job(app.handle()));
+            tokio::spawn(alerts::run_alerts(app.handle()));
+            tokio::spawn(breaks::run_break_reminders(app.handle()));
+            let feed_handle = app.handle();
+            tokio::spawn(async move {
+                if let Err(e) = sync_feed::restart(&feed_handle).await {
+                    error!("Failed to start sync feed: {}", e);
+                }
+            });
+
+            debug!("Checking calendar conventions...");
+            locale::check_calendar_settings(&app.handle());
+
             Ok(())
         })
         .on_window_event(|event| {
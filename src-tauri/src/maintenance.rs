@@ -0,0 +1,245 @@
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::Connection;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tracing::{error, info};
+
+use crate::category::SharedConfig;
+use crate::database::{self, DbConnection};
+use crate::notifications;
+use crate::tracker::WindowActivity;
+
+// Linhas que terminaram há pouco podem ainda estar sendo estendidas pelo tracker
+const SETTLE_MINUTES: i64 = 10;
+const SCHEDULE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(6 * 60 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepairAction {
+    // Mesma janela, mesmos horários: a cópia é apagada
+    RemovedDuplicate,
+    // Mesma janela sobreposta: a anterior é estendida e a outra apagada
+    Merged,
+    // Janela diferente sobreposta: o início é empurrado para o fim da anterior
+    Trimmed,
+    // Janela diferente inteiramente dentro da anterior
+    RemovedContained,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ActivityRepair {
+    pub action: RepairAction,
+    pub id: i64,
+    pub application: String,
+    pub title: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    // Atividade que ficou com o intervalo
+    pub kept_id: i64,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct OverlapReport {
+    pub scanned: usize,
+    pub removed_duplicates: usize,
+    pub merged: usize,
+    pub trimmed: usize,
+    pub removed_contained: usize,
+    pub repairs: Vec<ActivityRepair>,
+    pub applied: bool,
+}
+
+fn same_window(a: &WindowActivity, b: &WindowActivity) -> bool {
+    a.application == b.application && a.title == b.title && a.is_idle == b.is_idle
+}
+
+// Percorre em ordem de início: quem começou antes fica com o trecho sobreposto.
+// Retorna as exclusões, os novos intervalos por id e o relatório
+fn plan(activities: &[WindowActivity]) -> (Vec<i64>, Vec<(i64, DateTime<Utc>, DateTime<Utc>)>, OverlapReport) {
+    let mut report = OverlapReport { scanned: activities.len(), ..Default::default() };
+    let mut deletes = Vec::new();
+    let mut updates: Vec<(i64, DateTime<Utc>, DateTime<Utc>)> = Vec::new();
+    let mut previous: Option<WindowActivity> = None;
+
+    for activity in activities {
+        let Some(id) = activity.id else { continue };
+        let overlaps = previous.as_ref().map_or(false, |p| activity.start_time < p.end_time);
+        if !overlaps {
+            previous = Some(activity.clone());
+            continue;
+        }
+        let Some(kept) = previous.as_mut() else { continue };
+        let kept_id = kept.id.unwrap_or_default();
+
+        let action = if same_window(kept, activity)
+            && kept.start_time == activity.start_time
+            && kept.end_time == activity.end_time
+        {
+            RepairAction::RemovedDuplicate
+        } else if same_window(kept, activity) {
+            RepairAction::Merged
+        } else if activity.end_time <= kept.end_time {
+            RepairAction::RemovedContained
+        } else {
+            RepairAction::Trimmed
+        };
+
+        match action {
+            RepairAction::RemovedDuplicate => {
+                deletes.push(id);
+                report.removed_duplicates += 1;
+            }
+            RepairAction::Merged => {
+                deletes.push(id);
+                if activity.end_time > kept.end_time {
+                    kept.end_time = activity.end_time;
+                    updates.retain(|(update_id, _, _)| *update_id != kept_id);
+                    updates.push((kept_id, kept.start_time, kept.end_time));
+                }
+                report.merged += 1;
+            }
+            RepairAction::RemovedContained => {
+                deletes.push(id);
+                report.removed_contained += 1;
+            }
+            RepairAction::Trimmed => {
+                updates.push((id, kept.end_time, activity.end_time));
+                report.trimmed += 1;
+            }
+        }
+
+        report.repairs.push(ActivityRepair {
+            action,
+            id,
+            application: activity.application.clone(),
+            title: activity.title.clone(),
+            start_time: activity.start_time,
+            end_time: activity.end_time,
+            kept_id,
+        });
+
+        if action == RepairAction::Trimmed {
+            let mut trimmed = activity.clone();
+            trimmed.start_time = kept.end_time;
+            previous = Some(trimmed);
+        }
+    }
+
+    (deletes, updates, report)
+}
+
+// Detecta duplicatas/sobreposições e, sem dry_run, corrige tudo numa transação
+pub async fn repair_overlaps(db: &DbConnection, dry_run: bool) -> anyhow::Result<OverlapReport> {
+    let settled_before = Utc::now() - Duration::minutes(SETTLE_MINUTES);
+    // Linhas de outros dispositivos só mudam na origem (e o tempo deles pode se sobrepor ao daqui)
+    let mut activities: Vec<WindowActivity> = database::get_all_activities(db)
+        .await?
+        .into_iter()
+        .filter(|a| a.device_id.is_none() && a.end_time < settled_before)
+        .collect();
+    activities.sort_by(|a, b| a.start_time.cmp(&b.start_time).then_with(|| a.id.cmp(&b.id)));
+
+    let (deletes, updates, mut report) = plan(&activities);
+    if !dry_run && !report.repairs.is_empty() {
+        database::apply_activity_repairs(db, &deletes, &updates).await?;
+        report.applied = true;
+    }
+
+    Ok(report)
+}
+
+#[derive(Debug, Serialize)]
+pub struct MaintenanceReport {
+    // Linhas do integrity_check; ["ok"] quando o banco está íntegro
+    pub integrity: Vec<String>,
+    pub integrity_ok: bool,
+    pub vacuumed: bool,
+    pub size_before: i64,
+    pub size_after: i64,
+    pub duration_ms: i64,
+}
+
+fn database_size(conn: &Connection) -> rusqlite::Result<i64> {
+    let page_count: i64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+    let page_size: i64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+    Ok(page_count * page_size)
+}
+
+// integrity_check e, se o banco estiver íntegro, VACUUM + ANALYZE. Um banco
+// corrompido não é reescrito: o relatório mostra os problemas e a restauração
+// de um backup fica a cargo do usuário
+pub async fn maintain(db: &DbConnection) -> anyhow::Result<MaintenanceReport> {
+    let started = Utc::now();
+    let conn = db.lock().await;
+    let size_before = database_size(&conn)?;
+
+    let integrity = {
+        let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        rows.collect::<Result<Vec<_>, _>>()?
+    };
+    let integrity_ok = integrity.len() == 1 && integrity[0] == "ok";
+
+    if integrity_ok {
+        conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE); VACUUM; ANALYZE;")?;
+    }
+    let size_after = database_size(&conn)?;
+
+    Ok(MaintenanceReport {
+        integrity,
+        integrity_ok,
+        vacuumed: integrity_ok,
+        size_before,
+        size_after,
+        duration_ms: (Utc::now() - started).num_milliseconds(),
+    })
+}
+
+// Executa e avisa o resultado; usado pelo item da bandeja e pelo agendamento
+pub async fn maintain_and_notify(app: &AppHandle) -> anyhow::Result<MaintenanceReport> {
+    let db = app.state::<DbConnection>().inner().clone();
+    let report = maintain(&db).await?;
+
+    let config = app.state::<SharedConfig>();
+    if let Ok(mut config) = config.lock() {
+        if let Err(e) = config.record_maintenance_run(Utc::now()) {
+            error!("Failed to record maintenance run: {}", e);
+        }
+    }
+
+    let body = if report.integrity_ok {
+        let freed = (report.size_before - report.size_after).max(0);
+        format!("Database optimized, {:.1} MB freed", freed as f64 / 1_048_576.0)
+    } else {
+        format!("Integrity check found problems: {}", report.integrity.join("; "))
+    };
+    info!("🛠️ Database maintenance: {}", body);
+    if let Err(e) = notifications::notify(app, "Database maintenance", &body).await {
+        error!("Failed to send maintenance notification: {}", e);
+    }
+
+    Ok(report)
+}
+
+pub async fn run_maintenance_job(app: AppHandle) {
+    let mut interval = tokio::time::interval(SCHEDULE_CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let due = match app.state::<SharedConfig>().lock() {
+            Ok(config) => config.maintenance.is_due(Utc::now()),
+            Err(e) => {
+                error!("Failed to lock config: {}", e);
+                continue;
+            }
+        };
+        if !due {
+            continue;
+        }
+
+        if let Err(e) = maintain_and_notify(&app).await {
+            error!("Scheduled database maintenance failed: {}", e);
+        }
+    }
+}
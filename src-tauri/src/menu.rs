@@ -1,16 +1,71 @@
+use chrono::{DateTime, Utc};
+use std::sync::{Arc, Mutex};
 use image::{ImageBuffer, Rgba, RgbaImage};
 use tauri::{
     AppHandle, CustomMenuItem, Manager, SystemTray, SystemTrayEvent, SystemTrayMenu,
     SystemTrayMenuItem,
 };
-use std::sync::Mutex;
-use tracing::info;
-use crate::database::DbConnection;
-use crate::category::CategoryConfig;
+use serde::Serialize;
+use tracing::{error, info};
+use crate::commands::{CategoryLimitProgress, WeeklyGoalProgress};
+use crate::database::{self, DbConnection};
+use crate::high_frequency::SharedHighFrequency;
+use crate::category::{SharedConfig, TrayConfig, TrayTitle};
+use crate::stats_cache::StatsCache;
+use crate::tracker::TrackerHandle;
 use imageproc::drawing::draw_text_mut;
 use rusttype::{Font, Scale};
 
-fn format_duration(seconds: i64) -> String {
+pub const TRAY_STATE_EVENT: &str = "tray-state";
+
+// Prioridades do que pode ocupar o título da bandeja no lugar da meta do dia.
+// O maior valor ativo vence; quando expira ou é removido, a meta volta
+pub const PRIORITY_SESSION: u8 = 10;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrayOverride {
+    pub source: String,
+    pub priority: u8,
+    pub label: String,
+    // Com prazo, o título vira uma contagem regressiva até lá
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default)]
+pub struct TrayDisplay {
+    overrides: Vec<TrayOverride>,
+}
+
+pub type SharedTrayDisplay = Arc<Mutex<TrayDisplay>>;
+
+impl TrayDisplay {
+    // Cada origem (sessão de foco, timer...) tem no máximo um override
+    pub fn set(&mut self, entry: TrayOverride) {
+        self.overrides.retain(|o| o.source != entry.source);
+        self.overrides.push(entry);
+    }
+
+    pub fn clear(&mut self, source: &str) {
+        self.overrides.retain(|o| o.source != source);
+    }
+
+    pub fn current(&mut self, now: DateTime<Utc>) -> Option<TrayOverride> {
+        self.overrides.retain(|o| o.ends_at.map_or(true, |end| end > now));
+        self.overrides.iter().max_by_key(|o| o.priority).cloned()
+    }
+}
+
+fn override_title(entry: &TrayOverride, now: DateTime<Utc>) -> String {
+    match entry.ends_at {
+        Some(end) => {
+            let remaining = (end - now).num_seconds().max(0);
+            format!("{} {}:{:02}", entry.label, remaining / 60, remaining % 60)
+        }
+        None => entry.label.clone(),
+    }
+}
+
+pub fn format_duration(seconds: i64) -> String {
     let hours = seconds / 3600;
     let minutes = (seconds % 3600) / 60;
     
@@ -66,10 +121,11 @@ fn generate_dynamic_icon(value: i64) -> Result<Vec<u8>, String> {
 }
 
 pub fn create_tray_menu() -> SystemTray {
-    let tracked = CustomMenuItem::new("tracked".to_string(), "Tracked: --");
-    let productive = CustomMenuItem::new("productive".to_string(), "Productive: --");
-    let progress = CustomMenuItem::new("progress".to_string(), "▱▱▱▱▱▱▱▱▱▱ 0%");
-    let quit = CustomMenuItem::new("quit".to_string(), "Quit");
+    let tracked = CustomMenuItem::new("tracked".to_string(), "Tracked today: --");
+    let productive = CustomMenuItem::new("productive".to_string(), "Productive today: --");
+    let progress = CustomMenuItem::new("progress".to_string(), "Daily goal: loading");
+    let maintain = CustomMenuItem::new("maintain_database".to_string(), "Optimize database");
+    let quit = CustomMenuItem::new("quit".to_string(), "Quit Chronos Track");
     
     let tray_menu = SystemTrayMenu::new()
         .add_item(progress.disabled())
@@ -77,10 +133,12 @@ pub fn create_tray_menu() -> SystemTray {
         .add_item(tracked.disabled())
         .add_item(productive.disabled())
         .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(maintain)
         .add_item(quit);
 
     SystemTray::new()
         .with_menu(tray_menu)
+        .with_tooltip("Chronos Track")
 }
 
 pub fn handle_tray_event(app: &AppHandle, event: SystemTrayEvent) {
@@ -104,8 +162,22 @@ pub fn handle_tray_event(app: &AppHandle, event: SystemTrayEvent) {
                 window.show().unwrap();
                 window.set_focus().unwrap();
             }
+            "maintain_database" => {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = crate::maintenance::maintain_and_notify(&app).await {
+                        error!("Database maintenance failed: {}", e);
+                    }
+                });
+            }
             "quit" => {
-                app.exit(0);
+                // Grava a atividade em andamento antes de sair
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    crate::high_frequency::stop(app.state::<SharedHighFrequency>().inner()).await;
+                    app.state::<TrackerHandle>().shutdown().await;
+                    app.exit(0);
+                });
             }
             _ => {}
         },
@@ -113,30 +185,99 @@ pub fn handle_tray_event(app: &AppHandle, event: SystemTrayEvent) {
     }
 }
 
-fn create_progress_bar(percentage: i64) -> String {
-    let filled = (percentage as f64 / 100.0 * 10.0).round() as usize;
-    let empty = 10 - filled;
-    
-    let filled_chars = "▰".repeat(filled);
-    let empty_chars = "▱".repeat(empty);
-    
-    format!("{}{} {}%", filled_chars, empty_chars, percentage)
+pub fn progress_cells(percentage: i64, length: usize, tray: &TrayConfig) -> String {
+    let filled = (percentage.clamp(0, 100) as f64 / 100.0 * length as f64).round() as usize;
+    let (filled_glyph, empty_glyph) = tray.glyphs();
+    format!("{}{}", filled_glyph.repeat(filled), empty_glyph.repeat(length - filled))
 }
 
-pub async fn update_tray_menu(app: &AppHandle) -> Result<(), String> {
-    info!("Updating tray menu");
-    
-    // Get today's stats using the internal function directly
-    let db = app.state::<DbConnection>();
-    let config = app.state::<Mutex<CategoryConfig>>();
-    let config_clone = config.clone();
+fn create_progress_bar(percentage: i64, tray: &TrayConfig) -> String {
+    format!("{} {}%", progress_cells(percentage, tray.bar_length, tray), percentage)
+}
+
+fn tray_title(state: &TrayState, tray: &TrayConfig) -> String {
+    match tray.title {
+        TrayTitle::Percentage => format!("{}%", state.goal_percentage),
+        TrayTitle::RemainingMinutes => {
+            let remaining = (state.daily_goal_minutes - state.productive_seconds / 60).max(0);
+            format!("{}m left", remaining)
+        }
+        TrayTitle::ProductiveHours => format!("{:.1}h", state.productive_seconds as f64 / 3600.0),
+        TrayTitle::Score => state.productivity_score.map_or(String::new(), |score| format!("★{}", score)),
+        TrayTitle::Hidden => String::new(),
+    }
+}
+
+// Estado exibido na bandeja, também exposto para leitores de tela via comando/evento
+#[derive(Debug, Clone, Serialize)]
+pub struct TrayState {
+    pub tracked_seconds: i64,
+    pub productive_seconds: i64,
+    pub daily_goal_minutes: i64,
+    pub goal_percentage: i64,
+    // Meta reduzida às horas de expediente restantes quando o registro começou
+    pub prorated_goal_percentage: Option<i64>,
+    // Nota de produtividade de hoje (0–100); None enquanto o cache esquenta
+    pub productivity_score: Option<i64>,
+    pub weekly_goal: Option<WeeklyGoalProgress>,
+    // Limites de categoria de hoje (orçamento restante e se estourou)
+    pub category_limits: Vec<CategoryLimitProgress>,
+    // Resumo em texto corrido, sem símbolos gráficos
+    pub description: String,
+}
+
+pub fn describe_daily_goal(state: &TrayState) -> String {
+    if state.daily_goal_minutes > 0 {
+        let prorated = state
+            .prorated_goal_percentage
+            .map(|percentage| format!(", {}% of the prorated goal", percentage))
+            .unwrap_or_default();
+        format!(
+            "Daily goal: {}% complete ({} of {}{})",
+            state.goal_percentage,
+            format_duration(state.productive_seconds),
+            format_duration(state.daily_goal_minutes * 60),
+            prorated
+        )
+    } else {
+        "Daily goal: not set".to_string()
+    }
+}
+
+pub fn describe_weekly_goal(weekly: &WeeklyGoalProgress) -> String {
+    format!(
+        "Weekly goal: {}% complete ({} of {}), {}-week streak",
+        weekly.percentage,
+        format_duration(weekly.productive_minutes * 60),
+        format_duration(weekly.goal_minutes * 60),
+        weekly.streak
+    )
+}
+
+pub fn describe_category_limit(limit: &CategoryLimitProgress) -> String {
+    if limit.exceeded {
+        format!(
+            "{} limit exceeded ({} of {})",
+            limit.category_name,
+            format_duration(limit.used_seconds),
+            format_duration(limit.limit_minutes * 60)
+        )
+    } else {
+        format!(
+            "{} limit: {} left of {}",
+            limit.category_name,
+            format_duration(limit.remaining_seconds),
+            format_duration(limit.limit_minutes * 60)
+        )
+    }
+}
+
+pub async fn get_tray_state(app: &AppHandle) -> TrayState {
+    // Get today's stats (served from the cache while the app is still cold)
+    let config = app.state::<SharedConfig>();
     
-    let (total_minutes, productive_minutes) = match crate::commands::get_today_stats_internal(db, config).await {
-        Ok((total, productive)) => {
-            let total_minutes = total / 60;
-            let productive_minutes = productive / 60;
-            (total_minutes, productive_minutes)
-        },
+    let (total_seconds, productive_seconds) = match crate::stats_cache::get_today_totals(app).await {
+        Ok(totals) => totals,
         Err(e) => {
             info!("Error getting today's stats: {}", e);
             (0, 0)
@@ -144,42 +285,204 @@ pub async fn update_tray_menu(app: &AppHandle) -> Result<(), String> {
     };
     
     // Calculate goal percentage
-    let goal_percentage = if let Ok(config) = config_clone.inner().lock() {
-        if config.daily_goal_minutes > 0 {
-            ((productive_minutes as f64 / config.daily_goal_minutes as f64) * 100.0).round() as i64
-        } else {
-            0
+    let config = match config.inner().lock() {
+        Ok(config) => Some(config.clone()),
+        Err(_) => {
+            info!("Failed to lock config");
+            None
         }
+    };
+    let daily_goal_minutes = config.as_ref().map_or(0, |config| config.daily_goal_minutes);
+    let goal_percentage = if daily_goal_minutes > 0 {
+        ((productive_seconds / 60) as f64 / daily_goal_minutes as f64 * 100.0).round() as i64
     } else {
-        info!("Failed to lock config");
         0
     };
+
+    // Proporção da meta a partir do primeiro registro de hoje
+    let prorated_goal_percentage = if let Some(config) = config.as_ref().filter(|config| config.schedule.prorate_goal) {
+        let today = config.calendar.logical_date(Utc::now());
+        let (start, end) = config.calendar.day_bounds(today);
+        match database::get_first_activity_between(app.state::<DbConnection>().inner(), start, end).await {
+            Ok(first_activity) => {
+                crate::commands::prorated_goal_percentage(config, today, first_activity, productive_seconds)
+            }
+            Err(e) => {
+                info!("Error getting first activity of the day: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    
+    // Weekly goal progress (only when a weekly goal is configured, and skipped
+    // while the cold-start backfill hasn't finished reading the database)
+    let cache_warm = app.state::<StatsCache>().lock().map_or(false, |c| c.is_warm());
+    let weekly_goal = if !cache_warm {
+        None
+    } else {
+        match crate::commands::get_weekly_goal_progress(
+            app.state::<DbConnection>().inner(),
+            app.state::<SharedConfig>().inner(),
+            Utc::now(),
+        ).await {
+            Ok(progress) => progress,
+            Err(e) => {
+                info!("Error getting weekly goal progress: {}", e);
+                None
+            }
+        }
+    };
+    
+    // A nota relê as atividades de hoje a cada atualização; só é calculada quando é o
+    // título da bandeja, e também espera o cache
+    let productivity_score = match config.as_ref().filter(|c| cache_warm && c.tray.title == TrayTitle::Score) {
+        Some(config) => {
+            let today = config.calendar.logical_date(Utc::now());
+            let (start, end) = config.calendar.day_bounds(today);
+            match database::get_activities_between(app.state::<DbConnection>().inner(), start, end).await {
+                Ok(mut activities) => {
+                    activities.retain(|a| !a.off_hours);
+                    crate::score::compute(&activities, config, 1).score
+                }
+                Err(e) => {
+                    info!("Error getting activities for the productivity score: {}", e);
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    // Limites lidos dos totais do dia, também só depois do cache esquentar
+    let category_limits = match config.as_ref().filter(|_| cache_warm) {
+        Some(config) => {
+            let today = config.calendar.logical_date(Utc::now());
+            let db = app.state::<DbConnection>();
+            match crate::commands::category_limits_for_day(db.inner(), config, today).await {
+                Ok(limits) => limits,
+                Err(e) => {
+                    info!("Error getting category limits: {}", e);
+                    Vec::new()
+                }
+            }
+        }
+        None => Vec::new(),
+    };
+
+    let mut state = TrayState {
+        tracked_seconds: total_seconds,
+        productive_seconds,
+        daily_goal_minutes,
+        goal_percentage,
+        prorated_goal_percentage,
+        productivity_score,
+        weekly_goal,
+        category_limits,
+        description: String::new(),
+    };
+    
+    let mut description = vec![
+        describe_daily_goal(&state),
+        format!("Tracked today: {}", format_duration(state.tracked_seconds)),
+        format!("Productive today: {}", format_duration(state.productive_seconds)),
+    ];
+    if let Some(weekly) = &state.weekly_goal {
+        description.push(describe_weekly_goal(weekly));
+    }
+    description.extend(state.category_limits.iter().map(describe_category_limit));
+    if let Some(score) = state.productivity_score {
+        description.push(format!("Productivity score: {} out of 100", score));
+    }
+    state.description = description.join(". ");
+    
+    state
+}
+
+pub async fn update_tray_menu(app: &AppHandle) -> Result<(), String> {
+    info!("Updating tray menu");
+    
+    let state = get_tray_state(app).await;
+    let tray = app
+        .state::<SharedConfig>()
+        .lock()
+        .map(|config| config.tray.clone())
+        .unwrap_or_default();
     
-    // Format durations
-    let tracked = CustomMenuItem::new("tracked", format!("Tracked: {}", format_duration(total_minutes * 60)));
-    let productive = CustomMenuItem::new("productive", format!("Productive: {} ({}%)", format_duration(productive_minutes * 60), goal_percentage));
-    let progress = CustomMenuItem::new("progress", format!("🎯 {}%", goal_percentage));
-    let quit = CustomMenuItem::new("quit", "Quit");
+    // Os rótulos são frases completas para que leitores de tela leiam o mesmo que é visto
+    let tracked = CustomMenuItem::new("tracked", format!("Tracked today: {}", format_duration(state.tracked_seconds)));
+    let productive = CustomMenuItem::new(
+        "productive",
+        format!("Productive today: {} ({}% of goal)", format_duration(state.productive_seconds), state.goal_percentage),
+    );
+    let progress = CustomMenuItem::new("progress", describe_daily_goal(&state));
+    let maintain = CustomMenuItem::new("maintain_database", "Optimize database");
+    let quit = CustomMenuItem::new("quit", "Quit Chronos Track");
     
     // Create menu
-    let tray_menu = SystemTrayMenu::new()
-        .add_item(progress.disabled())
+    let mut tray_menu = SystemTrayMenu::new()
+        .add_item(progress.disabled());
+    
+    if let Some(weekly) = &state.weekly_goal {
+        let weekly_progress = CustomMenuItem::new(
+            "weekly_progress",
+            format!("Week: {}", create_progress_bar(weekly.percentage.min(100), &tray)),
+        );
+        let weekly_detail = CustomMenuItem::new("weekly_detail", describe_weekly_goal(weekly));
+        tray_menu = tray_menu
+            .add_item(weekly_progress.disabled())
+            .add_item(weekly_detail.disabled());
+    }
+
+    for limit in &state.category_limits {
+        let item = CustomMenuItem::new(format!("limit_{}", limit.category_id), describe_category_limit(limit));
+        tray_menu = tray_menu.add_item(item.disabled());
+    }
+    
+    let tray_menu = tray_menu
         .add_native_item(SystemTrayMenuItem::Separator)
         .add_item(tracked.disabled())
         .add_item(productive.disabled())
         .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(maintain)
         .add_item(quit);
     
     // Update the menu
     let tray_handle = app.tray_handle();
     tray_handle.set_menu(tray_menu).map_err(|e| e.to_string())?;
     
-    // Update the title with percentage
-    let title = format!("{}%", goal_percentage);
+    // Update the title: an active override (e.g. focus session countdown) takes
+    // precedence over the goal (percentage, remaining minutes or productive hours)
+    crate::focus::finish_if_expired(app).await;
+    crate::pomodoro::advance_if_expired(app).await;
+    let now = Utc::now();
+    let active_override = app
+        .state::<SharedTrayDisplay>()
+        .lock()
+        .ok()
+        .and_then(|mut display| display.current(now));
+    let title = match &active_override {
+        Some(entry) => override_title(entry, now),
+        None => tray_title(&state, &tray),
+    };
     info!("Setting tray title to: {}", title);
     if let Err(e) = tray_handle.set_title(&title) {
         info!("Failed to set tray title: {}", e);
     }
     
+    // O tooltip é o que leitores de tela anunciam ao focar o ícone
+    if let Err(e) = tray_handle.set_tooltip(&state.description) {
+        info!("Failed to set tray tooltip: {}", e);
+    }
+    
+    if let Err(e) = app.emit_all(TRAY_STATE_EVENT, &state) {
+        info!("Failed to emit tray state: {}", e);
+    }
+    
+    if let Err(e) = crate::status_line::write(&crate::status_line::status_line(&state, &tray)) {
+        info!("Failed to write status line: {}", e);
+    }
+    
     Ok(())
-} 
\ No newline at end of file
+}
@@ -0,0 +1,461 @@
+use anyhow::Result;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use tracing::info;
+
+// Migrações versionadas do schema. Cada uma roda uma única vez, em ordem, dentro de
+// uma transação junto com o registro em schema_version. Para mudar o schema, adicione
+// uma nova entrada no fim da lista; nunca altere uma migração já publicada.
+struct Migration {
+    version: i64,
+    description: &'static str,
+    up: fn(&Connection) -> Result<()>,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create activities",
+        up: |conn| {
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS activities (
+                    id INTEGER PRIMARY KEY,
+                    title TEXT NOT NULL,
+                    application TEXT NOT NULL,
+                    start_time TEXT NOT NULL,
+                    end_time TEXT NOT NULL,
+                    is_browser BOOLEAN NOT NULL,
+                    url TEXT
+                )",
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 2,
+        description: "add activities.is_idle",
+        up: |conn| add_column(conn, "activities", "is_idle", "BOOLEAN NOT NULL DEFAULT 0"),
+    },
+    Migration {
+        version: 3,
+        description: "add activities.desktop",
+        up: |conn| add_column(conn, "activities", "desktop", "TEXT"),
+    },
+    Migration {
+        version: 4,
+        description: "add activities.network",
+        up: |conn| add_column(conn, "activities", "network", "TEXT"),
+    },
+    Migration {
+        version: 5,
+        description: "add activities.project",
+        up: |conn| add_column(conn, "activities", "project", "TEXT"),
+    },
+    Migration {
+        version: 6,
+        description: "add activities.cwd",
+        up: |conn| add_column(conn, "activities", "cwd", "TEXT"),
+    },
+    Migration {
+        version: 7,
+        description: "create window_snapshots",
+        up: |conn| {
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS window_snapshots (
+                    id INTEGER PRIMARY KEY,
+                    taken_at TEXT NOT NULL,
+                    application TEXT NOT NULL,
+                    title TEXT NOT NULL
+                )",
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 8,
+        description: "create category_assignments",
+        up: |conn| {
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS category_assignments (
+                    id INTEGER PRIMARY KEY,
+                    assigned_at TEXT NOT NULL,
+                    application TEXT NOT NULL,
+                    title TEXT,
+                    category_id TEXT NOT NULL
+                )",
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 9,
+        description: "create branch_history",
+        up: |conn| {
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS branch_history (
+                    id INTEGER PRIMARY KEY,
+                    repository TEXT NOT NULL,
+                    branch TEXT NOT NULL,
+                    start_time TEXT NOT NULL,
+                    end_time TEXT NOT NULL
+                )",
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 10,
+        description: "create high-frequency sessions and samples",
+        up: |conn| {
+            // Modo de alta frequência fica em tabelas próprias para não inchar activities
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS high_frequency_sessions (
+                    id INTEGER PRIMARY KEY,
+                    label TEXT,
+                    started_at TEXT NOT NULL,
+                    ends_at TEXT NOT NULL,
+                    stopped_at TEXT
+                );
+                CREATE TABLE IF NOT EXISTS high_frequency_samples (
+                    id INTEGER PRIMARY KEY,
+                    session_id INTEGER NOT NULL,
+                    sampled_at TEXT NOT NULL,
+                    application TEXT NOT NULL,
+                    title TEXT NOT NULL,
+                    x REAL,
+                    y REAL,
+                    width REAL,
+                    height REAL,
+                    clicks INTEGER NOT NULL,
+                    key_presses INTEGER NOT NULL
+                )",
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 11,
+        description: "create adjustments",
+        up: |conn| {
+            // Correções manuais por dia (ex.: trabalho feito longe do computador)
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS adjustments (
+                    id INTEGER PRIMARY KEY,
+                    date TEXT NOT NULL,
+                    category_id TEXT NOT NULL,
+                    minutes INTEGER NOT NULL,
+                    note TEXT NOT NULL,
+                    created_at TEXT NOT NULL
+                )",
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 12,
+        description: "index time ranges",
+        up: |conn| {
+            // Consultas por intervalo, por app (merge) e agrupadas por dia
+            conn.execute_batch(
+                "CREATE INDEX IF NOT EXISTS idx_activities_start_time ON activities (start_time);
+                 CREATE INDEX IF NOT EXISTS idx_activities_application ON activities (application, end_time);
+                 CREATE INDEX IF NOT EXISTS idx_activities_day ON activities (date(start_time));
+                 CREATE INDEX IF NOT EXISTS idx_adjustments_date ON adjustments (date);
+                 CREATE INDEX IF NOT EXISTS idx_window_snapshots_taken_at ON window_snapshots (taken_at);
+                 CREATE INDEX IF NOT EXISTS idx_branch_history_repository ON branch_history (repository, end_time);
+                 CREATE INDEX IF NOT EXISTS idx_high_frequency_samples_session ON high_frequency_samples (session_id);",
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 13,
+        description: "add epoch and duration columns to activities",
+        up: |conn| {
+            // Colunas geradas acompanham qualquer escrita em start_time/end_time sem
+            // precisar mudar os INSERT/UPDATE; permitem agregar direto em SQL
+            add_column(
+                conn,
+                "activities",
+                "start_epoch",
+                "INTEGER GENERATED ALWAYS AS (CAST(strftime('%s', start_time) AS INTEGER)) VIRTUAL",
+            )?;
+            add_column(
+                conn,
+                "activities",
+                "end_epoch",
+                "INTEGER GENERATED ALWAYS AS (CAST(strftime('%s', end_time) AS INTEGER)) VIRTUAL",
+            )?;
+            add_column(
+                conn,
+                "activities",
+                "duration_seconds",
+                "INTEGER GENERATED ALWAYS AS \
+                 (CAST(strftime('%s', end_time) AS INTEGER) - CAST(strftime('%s', start_time) AS INTEGER)) VIRTUAL",
+            )?;
+            conn.execute_batch(
+                "CREATE INDEX IF NOT EXISTS idx_activities_start_epoch ON activities (start_epoch, end_epoch)",
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 14,
+        description: "create hourly rollups",
+        up: |conn| {
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS hourly_rollups (
+                    hour_start INTEGER NOT NULL,
+                    application TEXT NOT NULL,
+                    total_seconds INTEGER NOT NULL,
+                    idle_seconds INTEGER NOT NULL,
+                    activity_count INTEGER NOT NULL,
+                    PRIMARY KEY (hour_start, application)
+                );
+                CREATE TABLE IF NOT EXISTS rollup_state (
+                    id INTEGER PRIMARY KEY CHECK (id = 1),
+                    refreshed_through INTEGER NOT NULL
+                );",
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 15,
+        description: "create screenshots",
+        up: |conn| {
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS screenshots (
+                    id INTEGER PRIMARY KEY,
+                    taken_at TEXT NOT NULL,
+                    path TEXT NOT NULL,
+                    application TEXT NOT NULL,
+                    title TEXT NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS idx_screenshots_taken_at ON screenshots(taken_at);",
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 16,
+        description: "add device sync columns",
+        up: |conn| {
+            // device_id NULL = gravada neste computador; source_id é o id na origem
+            add_column(conn, "activities", "device_id", "TEXT")?;
+            add_column(conn, "activities", "source_id", "INTEGER")?;
+            conn.execute_batch(
+                "CREATE UNIQUE INDEX IF NOT EXISTS idx_activities_device_source
+                    ON activities (device_id, source_id) WHERE device_id IS NOT NULL;
+                CREATE TABLE IF NOT EXISTS sync_state (
+                    id INTEGER PRIMARY KEY CHECK (id = 1),
+                    last_pushed_id INTEGER NOT NULL DEFAULT 0,
+                    pull_cursor TEXT
+                );",
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 17,
+        description: "create category_overrides",
+        up: |conn| {
+            // Categoria escolhida para uma atividade específica, acima do mapeamento do app
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS category_overrides (
+                    activity_id INTEGER PRIMARY KEY,
+                    category_id TEXT NOT NULL,
+                    pattern TEXT,
+                    created_at TEXT NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS idx_category_overrides_category
+                    ON category_overrides (category_id);
+                CREATE TRIGGER IF NOT EXISTS trg_activities_delete_override
+                    AFTER DELETE ON activities
+                    BEGIN
+                        DELETE FROM category_overrides WHERE activity_id = OLD.id;
+                    END;",
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 18,
+        description: "add activities.profile",
+        up: |conn| add_column(conn, "activities", "profile", "TEXT"),
+    },
+    Migration {
+        version: 19,
+        description: "create daily_goal_outcomes",
+        up: |conn| {
+            // Resultado da meta de cada dia encerrado, com a meta em vigor na época
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS daily_goal_outcomes (
+                    date TEXT PRIMARY KEY,
+                    productive_seconds INTEGER NOT NULL,
+                    goal_minutes INTEGER NOT NULL,
+                    met BOOLEAN NOT NULL,
+                    workday BOOLEAN NOT NULL,
+                    recorded_at TEXT NOT NULL
+                );",
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 20,
+        description: "add activities.off_hours",
+        up: |conn| add_column(conn, "activities", "off_hours", "BOOLEAN NOT NULL DEFAULT 0"),
+    },
+    Migration {
+        version: 21,
+        description: "create focus_sessions",
+        up: |conn| {
+            // Resumo de cada sessão de foco encerrada, para revisão posterior
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS focus_sessions (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    label TEXT NOT NULL,
+                    started_at TEXT NOT NULL,
+                    planned_end TEXT NOT NULL,
+                    ended_at TEXT NOT NULL,
+                    completed BOOLEAN NOT NULL,
+                    productive_seconds INTEGER NOT NULL,
+                    unproductive_seconds INTEGER NOT NULL,
+                    distraction_count INTEGER NOT NULL,
+                    distracting_apps TEXT NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS idx_focus_sessions_started_at
+                    ON focus_sessions (started_at);",
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 22,
+        description: "create pomodoros",
+        up: |conn| {
+            // Só pomodoros concluídos; interrompidos ou pulados não entram
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS pomodoros (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    label TEXT NOT NULL,
+                    started_at TEXT NOT NULL,
+                    ended_at TEXT NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS idx_pomodoros_started_at
+                    ON pomodoros (started_at);",
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 23,
+        description: "add activities.manual",
+        up: |conn| add_column(conn, "activities", "manual", "BOOLEAN NOT NULL DEFAULT 0"),
+    },
+    Migration {
+        version: 24,
+        description: "track sync revisions and deletions",
+        up: |conn| {
+            // Cada alteração numa linha local incrementa revision; a sincronização reenvia
+            // as linhas com revision > pushed_revision. Linhas já enviadas que forem
+            // apagadas viram tombstones, enviados na próxima sincronização
+            add_column(conn, "activities", "revision", "INTEGER NOT NULL DEFAULT 1")?;
+            add_column(conn, "activities", "pushed_revision", "INTEGER NOT NULL DEFAULT 0")?;
+            conn.execute_batch(
+                "UPDATE activities SET pushed_revision = 1
+                    WHERE device_id IS NULL
+                    AND id <= COALESCE((SELECT last_pushed_id FROM sync_state WHERE id = 1), 0);
+                CREATE INDEX IF NOT EXISTS idx_activities_unpushed
+                    ON activities (id) WHERE device_id IS NULL AND revision > pushed_revision;
+                CREATE TRIGGER IF NOT EXISTS trg_activities_revision
+                    AFTER UPDATE OF title, application, start_time, end_time, is_browser, url, is_idle,
+                        desktop, network, project, cwd, profile, off_hours, manual
+                    ON activities
+                    WHEN NEW.device_id IS NULL
+                    BEGIN
+                        UPDATE activities SET revision = OLD.revision + 1 WHERE id = NEW.id;
+                    END;
+                CREATE TABLE IF NOT EXISTS sync_tombstones (
+                    source_id INTEGER PRIMARY KEY,
+                    start_epoch INTEGER NOT NULL,
+                    deleted_at TEXT NOT NULL
+                );
+                CREATE TRIGGER IF NOT EXISTS trg_activities_tombstone
+                    AFTER DELETE ON activities
+                    WHEN OLD.device_id IS NULL AND OLD.pushed_revision > 0
+                    BEGIN
+                        INSERT OR REPLACE INTO sync_tombstones (source_id, start_epoch, deleted_at)
+                            VALUES (OLD.id, OLD.start_epoch, strftime('%Y-%m-%dT%H:%M:%SZ', 'now'));
+                    END;",
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 25,
+        description: "add high_frequency_samples.scrolls",
+        up: |conn| add_column(conn, "high_frequency_samples", "scrolls", "INTEGER NOT NULL DEFAULT 0"),
+    },
+];
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(columns.iter().any(|c| c == column))
+}
+
+// Bancos anteriores ao controle de versão podem já ter a coluna
+fn add_column(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    if !has_column(conn, table, column)? {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(())
+}
+
+pub fn current_version(conn: &Connection) -> Result<i64> {
+    let version: Option<i64> = conn
+        .query_row("SELECT MAX(version) FROM schema_version", [], |row| row.get(0))
+        .optional()?
+        .flatten();
+    Ok(version.unwrap_or(0))
+}
+
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+pub fn run(conn: &mut Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at TEXT NOT NULL
+        )",
+    )?;
+
+    let current = current_version(conn)?;
+    if current > latest_version() {
+        anyhow::bail!(
+            "Database schema version {} is newer than this app supports ({})",
+            current,
+            latest_version()
+        );
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        info!("Applying migration {}: {}", migration.version, migration.description);
+        let tx = conn.transaction()?;
+        (migration.up)(&tx)?;
+        tx.execute(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (?1, ?2, ?3)",
+            params![migration.version, migration.description, Utc::now().to_rfc3339()],
+        )?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use std::process::Command;
+use tracing::debug;
+
+use crate::category::NetworkTaggingConfig;
+
+// Classifica a rede atual: "vpn" se alguma interface de VPN configurada está ativa,
+// "office" se o Wi-Fi conectado é um dos SSIDs do escritório, "other" caso contrário
+pub fn probe(config: &NetworkTaggingConfig) -> Option<String> {
+    let interfaces = active_interfaces();
+    let vpn_connected = interfaces.iter().any(|iface| {
+        config
+            .vpn_interfaces
+            .iter()
+            .any(|prefix| !prefix.is_empty() && iface.starts_with(prefix.as_str()))
+    });
+
+    if vpn_connected {
+        return Some("vpn".to_string());
+    }
+
+    let ssid = current_ssid();
+    debug!("Active interfaces: {:?}, SSID: {:?}", interfaces, ssid);
+
+    match ssid {
+        Some(ssid) if config.office_ssids.iter().any(|s| s == &ssid) => Some("office".to_string()),
+        _ => Some("other".to_string()),
+    }
+}
+
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(target_os = "macos")]
+fn active_interfaces() -> Vec<String> {
+    // -u lista apenas as interfaces que estão "up"
+    run("ifconfig", &["-l", "-u"])
+        .map(|out| out.split_whitespace().map(|s| s.to_string()).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(target_os = "linux")]
+fn active_interfaces() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir("/sys/class/net") else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            // Interfaces de túnel costumam reportar "unknown" em vez de "up"
+            std::fs::read_to_string(entry.path().join("operstate"))
+                .map(|state| state.trim() != "down")
+                .unwrap_or(false)
+        })
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn active_interfaces() -> Vec<String> {
+    // Admin State  State          Type             Interface Name
+    // Enabled      Connected      Dedicated        Wi-Fi
+    run("netsh", &["interface", "show", "interface"])
+        .map(|out| {
+            out.lines()
+                .filter_map(|line| {
+                    let columns: Vec<&str> = line.split_whitespace().collect();
+                    (columns.len() >= 4 && columns[1] == "Connected").then(|| columns[3..].join(" "))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn active_interfaces() -> Vec<String> {
+    Vec::new()
+}
+
+#[cfg(target_os = "macos")]
+fn current_ssid() -> Option<String> {
+    // Current Wi-Fi Network: MinhaRede
+    run("networksetup", &["-getairportnetwork", "en0"])?
+        .split_once(": ")
+        .map(|(_, ssid)| ssid.trim().to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn current_ssid() -> Option<String> {
+    // yes:MinhaRede
+    run("nmcli", &["-t", "-f", "active,ssid", "dev", "wifi"])?
+        .lines()
+        .find_map(|line| line.strip_prefix("yes:").map(|ssid| ssid.to_string()))
+}
+
+#[cfg(target_os = "windows")]
+fn current_ssid() -> Option<String> {
+    //     SSID                   : MinhaRede
+    run("netsh", &["wlan", "show", "interfaces"])?
+        .lines()
+        .map(|line| line.trim())
+        .find(|line| line.starts_with("SSID"))
+        .and_then(|line| line.split_once(':'))
+        .map(|(_, ssid)| ssid.trim().to_string())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn current_ssid() -> Option<String> {
+    None
+}
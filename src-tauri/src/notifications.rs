@@ -0,0 +1,344 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local, Utc};
+use serde::Serialize;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+use tracing::{debug, error, info};
+
+use crate::category::{NotificationBackendKind, NotificationConfig, SharedConfig};
+
+pub trait Notifier: Send {
+    fn name(&self) -> &'static str;
+    fn send(&self, title: &str, body: &str) -> Result<()>;
+}
+
+pub struct DesktopNotifier {
+    identifier: String,
+}
+
+impl Notifier for DesktopNotifier {
+    fn name(&self) -> &'static str {
+        "desktop"
+    }
+
+    fn send(&self, title: &str, body: &str) -> Result<()> {
+        tauri::api::notification::Notification::new(&self.identifier)
+            .title(title)
+            .body(body)
+            .show()?;
+        Ok(())
+    }
+}
+
+pub struct WebhookNotifier {
+    url: String,
+}
+
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn send(&self, title: &str, body: &str) -> Result<()> {
+        reqwest::blocking::Client::new()
+            .post(&self.url)
+            .json(&serde_json::json!({
+                "source": "chronos-track",
+                "title": title,
+                "body": body,
+                "sent_at": Utc::now(),
+            }))
+            .send()?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+pub struct NtfyNotifier {
+    server: String,
+    topic: String,
+}
+
+impl Notifier for NtfyNotifier {
+    fn name(&self) -> &'static str {
+        "ntfy"
+    }
+
+    fn send(&self, title: &str, body: &str) -> Result<()> {
+        let url = format!("{}/{}", self.server.trim_end_matches('/'), self.topic);
+        reqwest::blocking::Client::new()
+            .post(url)
+            .header("Title", title)
+            .body(body.to_string())
+            .send()?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+pub struct PushoverNotifier {
+    token: String,
+    user: String,
+}
+
+impl Notifier for PushoverNotifier {
+    fn name(&self) -> &'static str {
+        "pushover"
+    }
+
+    fn send(&self, title: &str, body: &str) -> Result<()> {
+        reqwest::blocking::Client::new()
+            .post("https://api.pushover.net/1/messages.json")
+            .form(&[
+                ("token", self.token.as_str()),
+                ("user", self.user.as_str()),
+                ("title", title),
+                ("message", body),
+            ])
+            .send()?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+fn required(value: &Option<String>, field: &str) -> Result<String> {
+    value
+        .clone()
+        .filter(|v| !v.trim().is_empty())
+        .ok_or_else(|| anyhow::anyhow!("Missing notification setting: {}", field))
+}
+
+pub fn build_notifier(config: &NotificationConfig, identifier: &str) -> Result<Box<dyn Notifier>> {
+    Ok(match config.backend {
+        NotificationBackendKind::Desktop => Box::new(DesktopNotifier {
+            identifier: identifier.to_string(),
+        }),
+        NotificationBackendKind::Webhook => Box::new(WebhookNotifier {
+            url: required(&config.webhook_url, "webhook_url")?,
+        }),
+        NotificationBackendKind::Ntfy => Box::new(NtfyNotifier {
+            server: config.ntfy_server.clone(),
+            topic: required(&config.ntfy_topic, "ntfy_topic")?,
+        }),
+        NotificationBackendKind::Pushover => Box::new(PushoverNotifier {
+            token: required(&config.pushover_token, "pushover_token")?,
+            user: required(&config.pushover_user, "pushover_user")?,
+        }),
+    })
+}
+
+// "Não perturbe" do sistema. Na dúvida, considera desativado para não segurar avisos
+#[cfg(target_os = "macos")]
+fn dnd_active() -> bool {
+    // macOS 12+: Foco ativo aparece como asserções em ~/Library/DoNotDisturb
+    let assertions = dirs::home_dir()
+        .map(|home| home.join("Library/DoNotDisturb/DB/Assertions.json"))
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok());
+    if let Some(assertions) = assertions {
+        return assertions["data"]
+            .as_array()
+            .map_or(false, |data| data.iter().any(|d| {
+                d["storeAssertionRecords"].as_array().map_or(false, |records| !records.is_empty())
+            }));
+    }
+
+    // Versões anteriores
+    std::process::Command::new("defaults")
+        .args(["-currentHost", "read", "com.apple.notificationcenterui", "doNotDisturb"])
+        .output()
+        .map_or(false, |output| String::from_utf8_lossy(&output.stdout).trim() == "1")
+}
+
+#[cfg(target_os = "linux")]
+fn dnd_active() -> bool {
+    // GNOME: "Não perturbe" desliga os banners
+    std::process::Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.notifications", "show-banners"])
+        .output()
+        .map_or(false, |output| String::from_utf8_lossy(&output.stdout).trim() == "false")
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn dnd_active() -> bool {
+    false
+}
+
+async fn is_dnd_active() -> bool {
+    tokio::task::spawn_blocking(dnd_active).await.unwrap_or(false)
+}
+
+fn notification_config(app: &AppHandle) -> Result<NotificationConfig> {
+    let config = app.state::<SharedConfig>();
+    let config = config.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    Ok(config.notifications.clone())
+}
+
+async fn send_now(app: &AppHandle, config: &NotificationConfig, title: &str, body: &str) -> Result<()> {
+    let notifier = build_notifier(config, &app.config().tauri.bundle.identifier)?;
+    let (title, body) = (title.to_string(), body.to_string());
+
+    info!("Sending notification via {}: {}", notifier.name(), title);
+    tokio::task::spawn_blocking(move || notifier.send(&title, &body)).await??;
+    Ok(())
+}
+
+// Envia pelo backend configurado; as chamadas HTTP rodam fora do runtime async.
+// Notificações de desktop durante o "não perturbe" ficam na fila até ele acabar
+pub async fn notify(app: &AppHandle, title: &str, body: &str) -> Result<()> {
+    let config = notification_config(app)?;
+
+    if !config.enabled {
+        debug!("Notifications disabled, dropping: {}", title);
+        return Ok(());
+    }
+
+    if config.backend == NotificationBackendKind::Desktop && is_dnd_active().await {
+        info!("Do Not Disturb active, queueing notification: {}", title);
+        app.state::<SharedNotificationQueue>()
+            .lock()
+            .map_err(|e| anyhow::anyhow!(e.to_string()))?
+            .push(title, body);
+        return Ok(());
+    }
+
+    send_now(app, &config, title, body).await
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QueuedNotification {
+    pub title: String,
+    pub body: String,
+    pub queued_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default)]
+pub struct NotificationQueue {
+    pending: Vec<QueuedNotification>,
+}
+
+pub type SharedNotificationQueue = Arc<Mutex<NotificationQueue>>;
+
+const MAX_QUEUED_NOTIFICATIONS: usize = 50;
+
+impl NotificationQueue {
+    fn push(&mut self, title: &str, body: &str) {
+        if self.pending.len() >= MAX_QUEUED_NOTIFICATIONS {
+            self.pending.remove(0);
+        }
+        self.pending.push(QueuedNotification {
+            title: title.to_string(),
+            body: body.to_string(),
+            queued_at: Utc::now(),
+        });
+    }
+
+    pub fn pending(&self) -> Vec<QueuedNotification> {
+        self.pending.clone()
+    }
+}
+
+const DND_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+// Quando o "não perturbe" termina, entrega o que ficou na fila (uma só notificação
+// se houver várias)
+pub async fn run_dnd_watcher(app: AppHandle) {
+    let mut interval = tokio::time::interval(DND_POLL_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let has_pending = app
+            .state::<SharedNotificationQueue>()
+            .lock()
+            .map_or(false, |queue| !queue.pending.is_empty());
+        if !has_pending || is_dnd_active().await {
+            continue;
+        }
+
+        let pending = match app.state::<SharedNotificationQueue>().lock() {
+            Ok(mut queue) => std::mem::take(&mut queue.pending),
+            Err(e) => {
+                error!("Failed to lock notification queue: {}", e);
+                continue;
+            }
+        };
+
+        let (title, body) = match pending.as_slice() {
+            [single] => (single.title.clone(), single.body.clone()),
+            _ => (
+                format!("{} notifications while Do Not Disturb was on", pending.len()),
+                pending
+                    .iter()
+                    .map(|n| format!("{} ({}): {}", n.title, n.queued_at.with_timezone(&Local).format("%H:%M"), n.body))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
+        };
+
+        let result = match notification_config(&app) {
+            Ok(config) => send_now(&app, &config, &title, &body).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            error!("Failed to deliver queued notifications: {}", e);
+        }
+    }
+}
+
+// Registra quais alertas já foram enviados, para não repetir a cada atualização.
+// A chave inclui o período (dia/semana) a que o alerta se refere
+#[derive(Debug, Default)]
+pub struct AlertLog {
+    sent: HashMap<String, DateTime<Utc>>,
+}
+
+pub type SharedAlertLog = Arc<Mutex<AlertLog>>;
+
+// Nenhuma chave se refere a mais de uma semana; as mais antigas já não se repetem
+const ALERT_LOG_RETENTION_DAYS: i64 = 8;
+
+impl AlertLog {
+    // Reserva a chave enquanto o alerta é enviado, para duas verificações simultâneas
+    // não o enviarem em dobro
+    pub fn should_send(&mut self, key: &str, now: DateTime<Utc>) -> bool {
+        self.sent.retain(|_, sent_at| now - *sent_at < Duration::days(ALERT_LOG_RETENTION_DAYS));
+        match self.sent.entry(key.to_string()) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(now);
+                true
+            }
+        }
+    }
+
+    // A entrega falhou: a próxima verificação tenta de novo
+    pub fn release(&mut self, key: &str) {
+        self.sent.remove(key);
+    }
+}
+
+// Envia o alerta uma única vez para a chave informada; só conta como enviado se a
+// entrega der certo (ou ficar na fila do "não perturbe")
+pub async fn notify_once(app: &AppHandle, key: &str, title: &str, body: &str) {
+    let log = app.state::<SharedAlertLog>();
+    let should_send = match log.lock() {
+        Ok(mut log) => log.should_send(key, Utc::now()),
+        Err(e) => {
+            error!("Failed to lock alert log: {}", e);
+            false
+        }
+    };
+
+    if should_send {
+        if let Err(e) = notify(app, title, body).await {
+            error!("Failed to send notification: {}", e);
+            match log.lock() {
+                Ok(mut log) => log.release(key),
+                Err(e) => error!("Failed to lock alert log: {}", e),
+            }
+        }
+    }
+}
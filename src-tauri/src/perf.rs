@@ -0,0 +1,164 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Instant;
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+// Latências dos spans de comandos e funções do banco (#[instrument]), medidas da
+// criação ao fechamento do span, então incluem a espera pelo lock do banco.
+// Só os spans do próprio app entram; os números ficam em memória até fechar o app
+const RECENT_SAMPLES: usize = 512;
+const SLOWEST_QUERIES: usize = 20;
+
+struct SpanSamples {
+    count: u64,
+    total_micros: u64,
+    max_micros: u64,
+    recent: VecDeque<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SlowCall {
+    pub name: String,
+    pub duration_ms: f64,
+    pub at: DateTime<Utc>,
+}
+
+struct Metrics {
+    spans: BTreeMap<String, SpanSamples>,
+    slowest: Vec<SlowCall>,
+}
+
+static METRICS: Mutex<Metrics> = Mutex::new(Metrics {
+    spans: BTreeMap::new(),
+    slowest: Vec::new(),
+});
+
+// Guardado nas extensões do span para medir a duração no fechamento
+struct SpanStart(Instant);
+
+pub struct PerfLayer;
+
+impl<S> Layer<S> for PerfLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if !attrs.metadata().target().starts_with(env!("CARGO_CRATE_NAME")) {
+            return;
+        }
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanStart(Instant::now()));
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else { return };
+        let Some(micros) = span
+            .extensions()
+            .get::<SpanStart>()
+            .map(|start| start.0.elapsed().as_micros() as u64)
+        else {
+            return;
+        };
+
+        // "chronos_track::database" -> "database::get_activities_between"
+        let metadata = span.metadata();
+        let module = metadata.target().split_once("::").map_or(metadata.target(), |(_, module)| module);
+        record(format!("{}::{}", module, metadata.name()), micros);
+    }
+}
+
+fn record(name: String, micros: u64) {
+    let Ok(mut metrics) = METRICS.lock() else { return };
+
+    if name.starts_with("database::") {
+        let duration_ms = micros as f64 / 1000.0;
+        let fastest = metrics.slowest.last().map(|s| s.duration_ms);
+        if metrics.slowest.len() < SLOWEST_QUERIES || fastest.map_or(true, |fastest| duration_ms > fastest) {
+            metrics.slowest.push(SlowCall { name: name.clone(), duration_ms, at: Utc::now() });
+            metrics.slowest.sort_by(|a, b| b.duration_ms.total_cmp(&a.duration_ms));
+            metrics.slowest.truncate(SLOWEST_QUERIES);
+        }
+    }
+
+    let samples = metrics.spans.entry(name).or_insert_with(|| SpanSamples {
+        count: 0,
+        total_micros: 0,
+        max_micros: 0,
+        recent: VecDeque::with_capacity(RECENT_SAMPLES),
+    });
+    samples.count += 1;
+    samples.total_micros += micros;
+    samples.max_micros = samples.max_micros.max(micros);
+    if samples.recent.len() == RECENT_SAMPLES {
+        samples.recent.pop_front();
+    }
+    samples.recent.push_back(micros);
+}
+
+#[derive(Debug, Serialize)]
+pub struct SpanMetrics {
+    pub name: String,
+    pub count: u64,
+    // Percentis sobre as últimas RECENT_SAMPLES chamadas
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    pub total_ms: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PerformanceMetrics {
+    pub spans: Vec<SpanMetrics>,
+    pub slowest_queries: Vec<SlowCall>,
+}
+
+fn percentile(sorted: &[u64], percentile: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let index = ((sorted.len() - 1) as f64 * percentile).round() as usize;
+    sorted[index] as f64 / 1000.0
+}
+
+// Ordenado pelo p95, o mais lento primeiro
+pub fn snapshot() -> PerformanceMetrics {
+    let Ok(metrics) = METRICS.lock() else {
+        return PerformanceMetrics { spans: Vec::new(), slowest_queries: Vec::new() };
+    };
+
+    let mut spans: Vec<SpanMetrics> = metrics
+        .spans
+        .iter()
+        .map(|(name, samples)| {
+            let mut sorted: Vec<u64> = samples.recent.iter().copied().collect();
+            sorted.sort_unstable();
+            SpanMetrics {
+                name: name.clone(),
+                count: samples.count,
+                p50_ms: percentile(&sorted, 0.5),
+                p95_ms: percentile(&sorted, 0.95),
+                max_ms: samples.max_micros as f64 / 1000.0,
+                total_ms: samples.total_micros as f64 / 1000.0,
+            }
+        })
+        .collect();
+    spans.sort_by(|a, b| b.p95_ms.total_cmp(&a.p95_ms));
+
+    PerformanceMetrics {
+        spans,
+        slowest_queries: metrics.slowest.clone(),
+    }
+}
+
+pub fn reset() {
+    if let Ok(mut metrics) = METRICS.lock() {
+        metrics.spans.clear();
+        metrics.slowest.clear();
+    }
+}
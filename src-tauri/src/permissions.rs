@@ -0,0 +1,212 @@
+use serde::{Deserialize, Serialize};
+
+// Permissões do macOS sem as quais o tracker recebe títulos vazios ou não vê entrada
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    Accessibility,
+    ScreenRecording,
+    InputMonitoring,
+}
+
+pub const ALL_PERMISSIONS: &[Permission] = &[
+    Permission::Accessibility,
+    Permission::ScreenRecording,
+    Permission::InputMonitoring,
+];
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionStatus {
+    Granted,
+    Denied,
+    NotDetermined,
+    // Outros sistemas não têm esse controle
+    NotApplicable,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PermissionInfo {
+    pub permission: Permission,
+    pub status: PermissionStatus,
+    pub required_for: &'static str,
+    pub settings_url: &'static str,
+}
+
+impl Permission {
+    fn required_for(&self) -> &'static str {
+        match self {
+            Permission::Accessibility => "Reading the focused application and window",
+            Permission::ScreenRecording => "Reading window titles of other applications",
+            Permission::InputMonitoring => "Detecting keyboard activity for idle tracking",
+        }
+    }
+
+    fn settings_url(&self) -> &'static str {
+        match self {
+            Permission::Accessibility => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility"
+            }
+            Permission::ScreenRecording => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_ScreenCapture"
+            }
+            Permission::InputMonitoring => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_ListenEvent"
+            }
+        }
+    }
+
+    pub fn info(&self) -> PermissionInfo {
+        PermissionInfo {
+            permission: *self,
+            status: check(*self),
+            required_for: self.required_for(),
+            settings_url: self.settings_url(),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use std::ffi::c_void;
+
+    type CFTypeRef = *const c_void;
+
+    // kIOHIDRequestTypeListenEvent e os valores de IOHIDAccessType
+    pub const LISTEN_EVENT: u32 = 1;
+    pub const ACCESS_GRANTED: u32 = 0;
+    pub const ACCESS_DENIED: u32 = 1;
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        pub fn AXIsProcessTrusted() -> bool;
+        fn AXIsProcessTrustedWithOptions(options: CFTypeRef) -> bool;
+        static kAXTrustedCheckOptionPrompt: CFTypeRef;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        static kCFBooleanTrue: CFTypeRef;
+        static kCFTypeDictionaryKeyCallBacks: c_void;
+        static kCFTypeDictionaryValueCallBacks: c_void;
+        fn CFDictionaryCreate(
+            allocator: CFTypeRef,
+            keys: *const CFTypeRef,
+            values: *const CFTypeRef,
+            count: isize,
+            key_callbacks: *const c_void,
+            value_callbacks: *const c_void,
+        ) -> CFTypeRef;
+        fn CFRelease(value: CFTypeRef);
+    }
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        pub fn CGPreflightScreenCaptureAccess() -> bool;
+        pub fn CGRequestScreenCaptureAccess() -> bool;
+    }
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        pub fn IOHIDCheckAccess(request: u32) -> u32;
+        pub fn IOHIDRequestAccess(request: u32) -> bool;
+    }
+
+    // Equivale a AXIsProcessTrustedWithOptions(@{kAXTrustedCheckOptionPrompt: @YES})
+    pub fn prompt_accessibility() -> bool {
+        unsafe {
+            let keys = [kAXTrustedCheckOptionPrompt];
+            let values = [kCFBooleanTrue];
+            let options = CFDictionaryCreate(
+                std::ptr::null(),
+                keys.as_ptr(),
+                values.as_ptr(),
+                1,
+                &kCFTypeDictionaryKeyCallBacks as *const c_void,
+                &kCFTypeDictionaryValueCallBacks as *const c_void,
+            );
+            let trusted = AXIsProcessTrustedWithOptions(options);
+            if !options.is_null() {
+                CFRelease(options);
+            }
+            trusted
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn check(permission: Permission) -> PermissionStatus {
+    let granted = unsafe {
+        match permission {
+            Permission::Accessibility => macos::AXIsProcessTrusted(),
+            Permission::ScreenRecording => macos::CGPreflightScreenCaptureAccess(),
+            Permission::InputMonitoring => {
+                return match macos::IOHIDCheckAccess(macos::LISTEN_EVENT) {
+                    macos::ACCESS_GRANTED => PermissionStatus::Granted,
+                    macos::ACCESS_DENIED => PermissionStatus::Denied,
+                    _ => PermissionStatus::NotDetermined,
+                };
+            }
+        }
+    };
+
+    if granted {
+        PermissionStatus::Granted
+    } else {
+        PermissionStatus::Denied
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn check(_permission: Permission) -> PermissionStatus {
+    PermissionStatus::NotApplicable
+}
+
+// Dispara o prompt do sistema; o macOS só mostra o prompt uma vez por permissão,
+// depois disso o usuário precisa ir aos Ajustes do Sistema
+#[cfg(target_os = "macos")]
+pub fn request(permission: Permission) -> PermissionStatus {
+    unsafe {
+        match permission {
+            Permission::Accessibility => {
+                macos::prompt_accessibility();
+            }
+            Permission::ScreenRecording => {
+                macos::CGRequestScreenCaptureAccess();
+            }
+            Permission::InputMonitoring => {
+                macos::IOHIDRequestAccess(macos::LISTEN_EVENT);
+            }
+        }
+    }
+    check(permission)
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn request(_permission: Permission) -> PermissionStatus {
+    PermissionStatus::NotApplicable
+}
+
+#[cfg(target_os = "macos")]
+pub fn open_settings(permission: Permission) -> std::io::Result<()> {
+    std::process::Command::new("open")
+        .arg(permission.settings_url())
+        .spawn()
+        .map(|_| ())
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn open_settings(_permission: Permission) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "Permission settings are only available on macOS",
+    ))
+}
+
+pub fn missing_permissions() -> Vec<Permission> {
+    ALL_PERMISSIONS
+        .iter()
+        .copied()
+        .filter(|p| matches!(check(*p), PermissionStatus::Denied | PermissionStatus::NotDetermined))
+        .collect()
+}
@@ -0,0 +1,270 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+use tracing::{error, info};
+
+use crate::category::{CategoryConfig, PomodoroConfig, SharedConfig};
+use crate::database::{self, DbConnection, Pomodoro};
+use crate::menu::{SharedTrayDisplay, TrayOverride, PRIORITY_SESSION};
+
+const TRAY_SOURCE: &str = "pomodoro";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PomodoroPhase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+// Ciclo em andamento: trabalho e pausas se alternam até stop
+#[derive(Debug, Clone, Serialize)]
+pub struct PomodoroTimer {
+    pub label: String,
+    pub phase: PomodoroPhase,
+    pub phase_started_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    // Pomodoros concluídos neste ciclo
+    pub completed: u32,
+}
+
+pub type SharedPomodoro = Arc<Mutex<Option<PomodoroTimer>>>;
+
+fn phase_minutes(config: &PomodoroConfig, phase: PomodoroPhase) -> i64 {
+    match phase {
+        PomodoroPhase::Work => config.work_minutes,
+        PomodoroPhase::ShortBreak => config.short_break_minutes,
+        PomodoroPhase::LongBreak => config.long_break_minutes,
+    }
+    .max(1)
+}
+
+// Próxima fase; um pomodoro pulado não conta para a pausa longa
+fn next(timer: &PomodoroTimer, config: &PomodoroConfig, now: DateTime<Utc>, finished: bool) -> PomodoroTimer {
+    let completed = match timer.phase {
+        PomodoroPhase::Work if finished => timer.completed + 1,
+        _ => timer.completed,
+    };
+    let long_break = completed % config.long_break_every.max(1) == 0;
+    let phase = match timer.phase {
+        PomodoroPhase::Work if finished && long_break => PomodoroPhase::LongBreak,
+        PomodoroPhase::Work => PomodoroPhase::ShortBreak,
+        _ => PomodoroPhase::Work,
+    };
+    PomodoroTimer {
+        label: timer.label.clone(),
+        phase,
+        phase_started_at: now,
+        ends_at: now + Duration::minutes(phase_minutes(config, phase)),
+        completed,
+    }
+}
+
+fn pomodoro_config(app: &AppHandle) -> Result<PomodoroConfig, String> {
+    Ok(app.state::<SharedConfig>().lock().map_err(|e| e.to_string())?.pomodoro.clone())
+}
+
+// Contagem regressiva da fase atual no título da bandeja
+fn show_in_tray(app: &AppHandle, timer: &PomodoroTimer) -> Result<(), String> {
+    let label = match timer.phase {
+        PomodoroPhase::Work => format!("🍅 {}", timer.label),
+        PomodoroPhase::ShortBreak | PomodoroPhase::LongBreak => "☕ Break".to_string(),
+    };
+    app.state::<SharedTrayDisplay>()
+        .lock()
+        .map_err(|e| e.to_string())?
+        .set(TrayOverride {
+            source: TRAY_SOURCE.to_string(),
+            priority: PRIORITY_SESSION,
+            label,
+            ends_at: Some(timer.ends_at),
+        });
+    Ok(())
+}
+
+fn enter(app: &AppHandle, timer: PomodoroTimer) -> Result<PomodoroTimer, String> {
+    *app.state::<SharedPomodoro>().lock().map_err(|e| e.to_string())? = Some(timer.clone());
+    show_in_tray(app, &timer)?;
+    Ok(timer)
+}
+
+pub fn start(app: &AppHandle, label: String) -> Result<PomodoroTimer, String> {
+    let config = pomodoro_config(app)?;
+    let now = Utc::now();
+    let timer = enter(
+        app,
+        PomodoroTimer {
+            label,
+            phase: PomodoroPhase::Work,
+            phase_started_at: now,
+            ends_at: now + Duration::minutes(phase_minutes(&config, PomodoroPhase::Work)),
+            completed: 0,
+        },
+    )?;
+    info!("Pomodoro \"{}\" started until {}", timer.label, timer.ends_at);
+    Ok(timer)
+}
+
+// Interrompe o ciclo; o pomodoro em andamento não é gravado
+pub fn stop(app: &AppHandle) -> Result<Option<PomodoroTimer>, String> {
+    let timer = app.state::<SharedPomodoro>().lock().map_err(|e| e.to_string())?.take();
+    app.state::<SharedTrayDisplay>()
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clear(TRAY_SOURCE);
+    Ok(timer)
+}
+
+// Encerra a fase atual sem concluí-la e passa para a próxima
+pub fn skip(app: &AppHandle) -> Result<Option<PomodoroTimer>, String> {
+    let config = pomodoro_config(app)?;
+    let current = app.state::<SharedPomodoro>().lock().map_err(|e| e.to_string())?.clone();
+    current
+        .map(|timer| enter(app, next(&timer, &config, Utc::now(), false)))
+        .transpose()
+}
+
+pub fn current(app: &AppHandle) -> Option<PomodoroTimer> {
+    app.state::<SharedPomodoro>().lock().ok()?.clone()
+}
+
+// Chamado a cada atualização da bandeja: fase vencida grava o pomodoro
+// concluído, avisa e emenda a próxima
+pub async fn advance_if_expired(app: &AppHandle) {
+    let config = match pomodoro_config(app) {
+        Ok(config) => config,
+        Err(e) => {
+            error!("Failed to read pomodoro config: {}", e);
+            return;
+        }
+    };
+    let now = Utc::now();
+    let (finished, timer) = match app.state::<SharedPomodoro>().lock() {
+        Ok(mut state) => {
+            let finished = match state.clone().filter(|timer| timer.ends_at <= now) {
+                Some(finished) => finished,
+                None => return,
+            };
+            let timer = next(&finished, &config, now, true);
+            *state = Some(timer.clone());
+            (finished, timer)
+        }
+        Err(e) => {
+            error!("Failed to lock pomodoro: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = show_in_tray(app, &timer) {
+        error!("Failed to update pomodoro countdown: {}", e);
+    }
+
+    let (title, body) = if finished.phase == PomodoroPhase::Work {
+        info!("Pomodoro \"{}\" completed", finished.label);
+        let db = app.state::<DbConnection>();
+        let saved =
+            database::save_pomodoro(&db, &finished.label, finished.phase_started_at, finished.ends_at).await;
+        if let Err(e) = saved {
+            error!("Failed to save pomodoro: {}", e);
+        }
+        (
+            "Pomodoro complete",
+            format!("\"{}\" done. Take a {}-minute break.", finished.label, phase_minutes(&config, timer.phase)),
+        )
+    } else {
+        ("Break over", format!("Next pomodoro: \"{}\".", timer.label))
+    };
+    if let Err(e) = crate::notifications::notify(app, title, &body).await {
+        error!("Failed to send notification: {}", e);
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PomodoroApplication {
+    pub application: String,
+    pub seconds: i64,
+}
+
+// Pomodoro com o que foi registrado durante ele
+#[derive(Debug, Clone, Serialize)]
+pub struct PomodoroDetail {
+    #[serde(flatten)]
+    pub pomodoro: Pomodoro,
+    pub productive_seconds: i64,
+    pub idle_seconds: i64,
+    pub applications: Vec<PomodoroApplication>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PomodoroStats {
+    pub completed: usize,
+    pub focused_seconds: i64,
+    pub productive_seconds: i64,
+    pub pomodoros: Vec<PomodoroDetail>,
+}
+
+pub async fn stats(
+    db: &DbConnection,
+    config: &CategoryConfig,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<PomodoroStats> {
+    let pomodoros = database::get_pomodoros_between(db, start, end).await?;
+    let activities = match (pomodoros.first(), pomodoros.iter().map(|p| p.ended_at).max()) {
+        (Some(first), Some(last)) => database::get_activities_between(db, first.started_at, last).await?,
+        _ => Vec::new(),
+    };
+
+    let pomodoros: Vec<PomodoroDetail> = pomodoros
+        .into_iter()
+        .map(|pomodoro| {
+            let mut detail = PomodoroDetail {
+                productive_seconds: 0,
+                idle_seconds: 0,
+                applications: Vec::new(),
+                pomodoro,
+            };
+            let mut applications: HashMap<&str, i64> = HashMap::new();
+            for activity in &activities {
+                let seconds = (activity.end_time.min(detail.pomodoro.ended_at)
+                    - activity.start_time.max(detail.pomodoro.started_at))
+                .num_seconds();
+                if seconds <= 0 {
+                    continue;
+                }
+                if activity.is_idle {
+                    detail.idle_seconds += seconds;
+                    continue;
+                }
+                if config.category_for_activity(activity).map_or(false, |c| c.is_productive) {
+                    detail.productive_seconds += seconds;
+                }
+                *applications.entry(activity.application.as_str()).or_default() += seconds;
+            }
+
+            detail.applications = applications
+                .into_iter()
+                .map(|(application, seconds)| PomodoroApplication {
+                    application: application.to_string(),
+                    seconds,
+                })
+                .collect();
+            detail
+                .applications
+                .sort_by(|a, b| b.seconds.cmp(&a.seconds).then_with(|| a.application.cmp(&b.application)));
+            detail
+        })
+        .collect();
+
+    Ok(PomodoroStats {
+        completed: pomodoros.len(),
+        focused_seconds: pomodoros
+            .iter()
+            .map(|p| (p.pomodoro.ended_at - p.pomodoro.started_at).num_seconds())
+            .sum(),
+        productive_seconds: pomodoros.iter().map(|p| p.productive_seconds).sum(),
+        pomodoros,
+    })
+}
@@ -0,0 +1,145 @@
+use crate::category::ProjectPattern;
+use crate::tracker::WindowActivity;
+
+// Extrai o nome do projeto/workspace do título da janela de editores conhecidos
+
+const VSCODE_APPS: &[&str] = &["Code", "Visual Studio Code", "Code - Insiders", "VSCodium", "Cursor"];
+
+const JETBRAINS_APPS: &[&str] = &[
+    "IntelliJ IDEA",
+    "PyCharm",
+    "WebStorm",
+    "GoLand",
+    "RustRover",
+    "CLion",
+    "PhpStorm",
+    "Rider",
+    "RubyMine",
+    "DataGrip",
+    "Android Studio",
+];
+
+// Separadores usados nos títulos: em dash no macOS, hífen no Windows/Linux,
+// en dash nos IDEs da JetBrains
+const SEPARATORS: &[&str] = &[" — ", " – ", " - "];
+
+pub fn detect_project(application: &str, title: &str) -> Option<String> {
+    let application = application.trim_end_matches(".exe");
+
+    if VSCODE_APPS.iter().any(|app| application.eq_ignore_ascii_case(app)) {
+        return vscode_project(title);
+    }
+    if JETBRAINS_APPS.iter().any(|app| application.starts_with(app)) {
+        return jetbrains_project(title);
+    }
+
+    None
+}
+
+fn split_title(title: &str) -> Vec<&str> {
+    let separator = SEPARATORS
+        .iter()
+        .find(|sep| title.contains(*sep))
+        .copied()
+        .unwrap_or(" - ");
+
+    title
+        .split(separator)
+        .map(|part| part.trim())
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+// "● main.rs — chronos-track [SSH: devbox] — Visual Studio Code"
+fn vscode_project(title: &str) -> Option<String> {
+    let mut parts = split_title(title.trim_start_matches('●').trim());
+
+    if parts
+        .last()
+        .map_or(false, |last| VSCODE_APPS.iter().any(|app| last.contains(app)))
+    {
+        parts.pop();
+    }
+
+    // Com apenas um trecho restante, o título é o próprio workspace (nenhum arquivo aberto)
+    let project = parts.last()?;
+    clean_project_name(project)
+}
+
+// "chronos-track [~/dev/chronos-track] – main.rs" ou "chronos-track – main.rs"
+fn jetbrains_project(title: &str) -> Option<String> {
+    let parts = split_title(title);
+    let project = parts.first()?;
+    clean_project_name(project)
+}
+
+fn clean_project_name(name: &str) -> Option<String> {
+    // Remove sufixos como "[SSH: host]", "[~/caminho]" e "(Workspace)"
+    let name = name
+        .split(" [")
+        .next()
+        .unwrap_or(name)
+        .trim_end_matches("(Workspace)")
+        .trim();
+
+    if name.is_empty() || name == "Welcome" || name == "Get Started" {
+        return None;
+    }
+
+    Some(name.to_string())
+}
+
+// Primeiro caminho absoluto no título, ex: "vim ~/dev/client-a/src/main.rs" ou
+// "user@host: /srv/app" (terminais e editores costumam mostrar o arquivo/diretório)
+pub fn path_in_title(title: &str) -> Option<String> {
+    title
+        .split(|c: char| c.is_whitespace() || c == '"' || c == '\'' || c == '(' || c == '[')
+        .map(|token| token.trim_end_matches([':', ',', ')', ']', '"', '\'']))
+        .find(|token| {
+            token.starts_with('/')
+                || token.starts_with("~/")
+                || (token.len() > 2 && token.as_bytes()[1] == b':' && matches!(token.as_bytes()[2], b'\\' | b'/'))
+        })
+        .filter(|token| token.len() > 1)
+        .map(str::to_string)
+}
+
+fn normalize_path(path: &str) -> String {
+    let expanded = match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().map_or(path.to_string(), |home| home.join(rest).to_string_lossy().into_owned()),
+        None => path.to_string(),
+    };
+    expanded.replace('\\', "/").trim_end_matches('/').to_string()
+}
+
+// Prefixo configurado mais específico que contém o caminho
+pub fn project_for_patterns(path: &str, patterns: &[ProjectPattern]) -> Option<String> {
+    let path = normalize_path(path);
+    patterns
+        .iter()
+        .filter(|pattern| {
+            let prefix = normalize_path(&pattern.prefix);
+            !prefix.is_empty()
+                && (path == prefix || path.strip_prefix(&prefix).map_or(false, |rest| rest.starts_with('/')))
+        })
+        .max_by_key(|pattern| normalize_path(&pattern.prefix).len())
+        .map(|pattern| pattern.project.clone())
+}
+
+// Terminais: o projeto é a raiz do repositório git que contém o diretório atual
+pub fn project_for_directory(directory: &str) -> Option<String> {
+    std::path::Path::new(directory)
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .and_then(|root| root.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+}
+
+// Atividades gravadas antes da coluna existir são resolvidas pelo título
+pub fn activity_project(activity: &WindowActivity) -> Option<String> {
+    activity
+        .project
+        .clone()
+        .or_else(|| detect_project(&activity.application, &activity.title))
+        .or_else(|| activity.cwd.as_deref().and_then(project_for_directory))
+}
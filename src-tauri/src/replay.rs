@@ -0,0 +1,104 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::category::CategoryConfig;
+use crate::database::{self, DbConnection};
+use crate::journal;
+use crate::tracker::{ActivityTracker, WindowActivity};
+
+// Reproduz um diário (journal.rs) pelo pipeline do tracker (heartbeat, tolerância de
+// ociosidade, flush e merge) num banco descartável, para depurar regressões a partir
+// de diários enviados por usuários. Cada trecho vira amostras no mesmo intervalo do
+// tracker; speed = quantas vezes mais rápido que o tempo real (0 = sem espera)
+const SAMPLE_INTERVAL_SECONDS: i64 = 5;
+const FLUSH_INTERVAL_SECONDS: i64 = 60;
+
+#[derive(Debug, Serialize)]
+pub struct ReplayReport {
+    pub journal_segments: usize,
+    pub skipped_lines: usize,
+    pub samples: usize,
+    pub rows: usize,
+    pub idle_rows: usize,
+    pub elapsed_ms: u128,
+    pub activities: Vec<WindowActivity>,
+}
+
+// Banco descartável do replay; apagado (com o WAL) ao sair, com ou sem erro
+struct ScratchDatabase(PathBuf);
+
+impl Drop for ScratchDatabase {
+    fn drop(&mut self) {
+        for suffix in ["", "-wal", "-shm"] {
+            let file = format!("{}{}", self.0.display(), suffix);
+            if let Err(e) = std::fs::remove_file(&file) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    warn!("Failed to remove replay scratch file {}: {}", file, e);
+                }
+            }
+        }
+    }
+}
+
+pub async fn replay_from_journal(config: &CategoryConfig, path: &Path, speed: f64) -> Result<ReplayReport> {
+    let (segments, skipped_lines) = journal::read(path)?;
+    let (Some(first), Some(last)) = (segments.first(), segments.iter().map(|s| s.end_time).max()) else {
+        return Err(anyhow::anyhow!("No activities in journal: {}", path.display()));
+    };
+    let range_start = first.start_time;
+
+    // Declarado antes da conexão e do tracker para ser descartado depois deles
+    let scratch = ScratchDatabase(
+        std::env::temp_dir().join(format!("chronos-replay-{}.db", uuid::Uuid::new_v4().simple())),
+    );
+    // Sem chave: o banco descartável não herda a criptografia do banco real
+    let db: DbConnection = Arc::new(Mutex::new(database::open_connection_with_key(&scratch.0, None)?));
+    // O replay nunca escreve no diário real
+    let mut config = config.clone();
+    config.journal.enabled = false;
+    let mut tracker = ActivityTracker::for_replay(db.clone(), Arc::new(std::sync::Mutex::new(config)));
+
+    info!("Replaying {} journal segments from {:?} into {:?}", segments.len(), path, scratch.0);
+    let started = std::time::Instant::now();
+    let pause = (speed > 0.0).then(|| std::time::Duration::from_secs_f64(SAMPLE_INTERVAL_SECONDS as f64 / speed));
+    let mut samples = 0;
+    let mut last_flush: Option<DateTime<Utc>> = None;
+
+    for segment in &segments {
+        let mut now = segment.start_time;
+        loop {
+            tracker.replay_sample(segment, now).await?;
+            samples += 1;
+
+            if last_flush.map_or(true, |at| now - at >= Duration::seconds(FLUSH_INTERVAL_SECONDS)) {
+                tracker.replay_flush().await?;
+                last_flush = Some(now);
+            }
+            if let Some(pause) = pause {
+                tokio::time::sleep(pause).await;
+            }
+
+            if now >= segment.end_time {
+                break;
+            }
+            now = (now + Duration::seconds(SAMPLE_INTERVAL_SECONDS)).min(segment.end_time);
+        }
+    }
+    tracker.replay_flush().await?;
+
+    let activities = database::get_activities_between(&db, range_start, last).await?;
+    Ok(ReplayReport {
+        journal_segments: segments.len(),
+        skipped_lines,
+        samples,
+        rows: activities.len(),
+        idle_rows: activities.iter().filter(|a| a.is_idle).count(),
+        elapsed_ms: started.elapsed().as_millis(),
+        activities,
+    })
+}
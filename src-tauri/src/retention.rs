@@ -0,0 +1,50 @@
+use chrono::{DateTime, Duration, Utc};
+use tracing::{error, info};
+
+use crate::category::{RetentionConfig, SharedConfig};
+use crate::database::{self, DbConnection, PurgeResult};
+use crate::rollups;
+
+const PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(6 * 3600);
+// Os resumos recalculam as últimas 24h, então nunca apaga dados tão recentes
+const MIN_RETENTION_DAYS: u32 = 2;
+
+// Corte alinhado à hora para que nenhuma hora de resumo fique com dados parciais
+fn cutoff(retention: &RetentionConfig, now: DateTime<Utc>) -> DateTime<Utc> {
+    let cutoff = now - Duration::days(retention.activity_days.max(MIN_RETENTION_DAYS) as i64);
+    let timestamp = cutoff.timestamp() - cutoff.timestamp().rem_euclid(3600);
+    DateTime::<Utc>::from_timestamp(timestamp, 0).unwrap_or(cutoff)
+}
+
+pub async fn purge(db: &DbConnection, retention: &RetentionConfig) -> anyhow::Result<PurgeResult> {
+    // Garante que as horas a apagar já estão nos resumos
+    rollups::refresh(db).await?;
+
+    let cutoff = cutoff(retention, Utc::now());
+    let result = database::purge_before(db, cutoff).await?;
+    info!("Purged data older than {}: {:?}", cutoff, result);
+    Ok(result)
+}
+
+pub async fn run_retention_job(db: DbConnection, config: SharedConfig) {
+    let mut interval = tokio::time::interval(PURGE_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let retention = match config.lock() {
+            Ok(config) => config.retention.clone(),
+            Err(e) => {
+                error!("Failed to read retention config: {}", e);
+                continue;
+            }
+        };
+        if !retention.enabled {
+            continue;
+        }
+
+        if let Err(e) = purge(&db, &retention).await {
+            error!("Retention purge failed: {}", e);
+        }
+    }
+}
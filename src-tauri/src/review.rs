@@ -0,0 +1,155 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::category::CategoryConfig;
+use crate::tracker::WindowActivity;
+
+// Blocos sem categoria menores que isso não valem a revisão
+const MIN_UNCATEGORIZED_SECONDS: i64 = 5 * 60;
+const MIN_IDLE_GAP_SECONDS: i64 = 15 * 60;
+// Interrupções curtas entre dois trechos do mesmo app viram sugestão de mescla
+const MAX_INTERRUPTION_SECONDS: i64 = 60;
+// Trechos separados por menos que isso formam um único bloco
+const BLOCK_GAP_SECONDS: i64 = 2 * 60;
+
+#[derive(Debug, Serialize)]
+pub struct TimeBlock {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub duration_seconds: i64,
+    pub applications: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MergeSuggestion {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub application: String,
+    pub interruptions: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DailyReview {
+    pub date: NaiveDate,
+    pub uncategorized: Vec<TimeBlock>,
+    pub idle_gaps: Vec<TimeBlock>,
+    pub suggested_merges: Vec<MergeSuggestion>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReviewResolution {
+    CategorizeApp {
+        application: String,
+        category_id: String,
+    },
+    // Credita o intervalo ocioso/sem registro a uma categoria (vira um ajuste manual)
+    LabelIdle {
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        category_id: String,
+        note: String,
+    },
+    // Junta todas as atividades do intervalo em uma só do app indicado
+    Merge {
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        application: String,
+    },
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ReviewResult {
+    pub categorized_apps: usize,
+    pub labeled_minutes: i64,
+    pub merged_activities: usize,
+}
+
+fn blocks(spans: Vec<(DateTime<Utc>, DateTime<Utc>, String)>, min_seconds: i64) -> Vec<TimeBlock> {
+    let mut blocks: Vec<TimeBlock> = Vec::new();
+    for (start, end, application) in spans {
+        match blocks.last_mut() {
+            Some(block) if (start - block.end).num_seconds() <= BLOCK_GAP_SECONDS => {
+                block.end = block.end.max(end);
+                if !application.is_empty() && !block.applications.contains(&application) {
+                    block.applications.push(application);
+                }
+            }
+            _ => blocks.push(TimeBlock {
+                start,
+                end,
+                duration_seconds: 0,
+                applications: Some(application).filter(|a| !a.is_empty()).into_iter().collect(),
+            }),
+        }
+    }
+
+    for block in blocks.iter_mut() {
+        block.duration_seconds = (block.end - block.start).num_seconds();
+    }
+    blocks.retain(|block| block.duration_seconds >= min_seconds);
+    blocks
+}
+
+pub fn build(
+    date: NaiveDate,
+    day_start: DateTime<Utc>,
+    day_end: DateTime<Utc>,
+    activities: &[WindowActivity],
+    config: &CategoryConfig,
+) -> DailyReview {
+    let mut sorted: Vec<&WindowActivity> = activities.iter().collect();
+    sorted.sort_by_key(|a| a.start_time);
+
+    let uncategorized = blocks(
+        sorted
+            .iter()
+            .filter(|a| !a.is_idle && config.get_category_for_app(&a.application).is_none())
+            .map(|a| (a.start_time, a.end_time, a.application.clone()))
+            .collect(),
+        MIN_UNCATEGORIZED_SECONDS,
+    );
+
+    // Ociosidade registrada e buracos sem nenhum registro entre a primeira e a última atividade
+    let mut idle_spans: Vec<(DateTime<Utc>, DateTime<Utc>, String)> = sorted
+        .iter()
+        .filter(|a| a.is_idle)
+        .map(|a| (a.start_time, a.end_time, String::new()))
+        .collect();
+    let mut covered_until: Option<DateTime<Utc>> = None;
+    for activity in &sorted {
+        if let Some(until) = covered_until {
+            if activity.start_time > until {
+                idle_spans.push((until.max(day_start), activity.start_time.min(day_end), String::new()));
+            }
+        }
+        covered_until = Some(covered_until.map_or(activity.end_time, |until| until.max(activity.end_time)));
+    }
+    idle_spans.sort_by_key(|span| span.0);
+    let idle_gaps = blocks(idle_spans, MIN_IDLE_GAP_SECONDS);
+
+    let active: Vec<&&WindowActivity> = sorted.iter().filter(|a| !a.is_idle).collect();
+    let mut suggested_merges = Vec::new();
+    for window in active.windows(3) {
+        let (before, middle, after) = (window[0], window[1], window[2]);
+        let interruption = (middle.end_time - middle.start_time).num_seconds();
+        if before.application == after.application
+            && middle.application != before.application
+            && interruption <= MAX_INTERRUPTION_SECONDS
+        {
+            suggested_merges.push(MergeSuggestion {
+                start: before.start_time,
+                end: after.end_time,
+                application: before.application.clone(),
+                interruptions: vec![middle.application.clone()],
+            });
+        }
+    }
+
+    DailyReview {
+        date,
+        uncategorized,
+        idle_gaps,
+        suggested_merges,
+    }
+}
@@ -0,0 +1,44 @@
+use chrono::Utc;
+use std::time::Duration;
+use tracing::{debug, error, info};
+
+use crate::database::{self, DbConnection};
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+// Horas recentes são sempre recalculadas: eventos são gravados em lote e podem
+// ser mesclados/corrigidos depois de a hora terminar
+const LOOKBACK_SECONDS: i64 = 24 * 3600;
+
+pub async fn refresh(db: &DbConnection) -> anyhow::Result<usize> {
+    let now = Utc::now();
+    let since = match database::get_rollups_refreshed_through(db).await? {
+        Some(refreshed_through) => refreshed_through.min(now.timestamp()) - LOOKBACK_SECONDS,
+        // Primeira execução: calcula todo o histórico
+        None => database::get_first_activity_epoch(db).await?.unwrap_or(now.timestamp()),
+    };
+
+    database::refresh_hourly_rollups(db, since, now).await
+}
+
+// Recalcula tudo, usado após alterações em massa (importação, exclusões)
+pub async fn rebuild(db: &DbConnection) -> anyhow::Result<usize> {
+    let since = database::get_first_activity_epoch(db).await?.unwrap_or(0);
+    database::refresh_hourly_rollups(db, since, Utc::now()).await
+}
+
+pub async fn run_rollup_job(db: DbConnection) {
+    let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+    let mut first_run = true;
+
+    loop {
+        interval.tick().await;
+
+        let started = std::time::Instant::now();
+        match refresh(&db).await {
+            Ok(rows) if first_run => info!("Hourly rollups ready ({} rows) in {:.1?}", rows, started.elapsed()),
+            Ok(rows) => debug!("Refreshed {} hourly rollup rows", rows),
+            Err(e) => error!("Failed to refresh hourly rollups: {}", e),
+        }
+        first_run = false;
+    }
+}
@@ -0,0 +1,196 @@
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::category::{CategoryConfig, ScoreWeights};
+use crate::tracker::WindowActivity;
+
+// Intervalo sem registro a partir do qual um bloco de foco termina mesmo sem troca de app
+const FOCUS_BLOCK_MAX_GAP_SECONDS: i64 = 5 * 60;
+
+// Nota de 0 a 100 como média ponderada de quatro componentes, cada um em 0–1:
+//   productive: tempo produtivo / tempo ativo (categorias neutras ficam de fora)
+//   idle:       1 - tempo ocioso / tempo registrado
+//   focus:      1 - trocas de app por hora ativa / switches_per_hour_ceiling
+//   goal:       tempo produtivo / meta diária × dias do intervalo (até 1)
+// Componentes sem dados (ou meta desligada) saem da média em vez de contar zero
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoreComponents {
+    pub productive: Option<f64>,
+    pub idle: Option<f64>,
+    pub focus: Option<f64>,
+    pub goal: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProductivityScore {
+    pub score: Option<i64>,
+    pub components: ScoreComponents,
+    pub weights: ScoreWeights,
+    pub context_switches: usize,
+    pub switches_per_hour: f64,
+}
+
+// Trocas entre apps diferentes na sequência de trechos ativos
+pub fn context_switches(activities: &[WindowActivity]) -> usize {
+    let mut active: Vec<&WindowActivity> = activities.iter().filter(|a| !a.is_idle).collect();
+    active.sort_by_key(|a| a.start_time);
+    active.windows(2).filter(|pair| pair[0].application != pair[1].application).count()
+}
+
+pub fn compute(activities: &[WindowActivity], config: &CategoryConfig, days: i64) -> ProductivityScore {
+    let weights = config.score_weights.clone();
+    let mut total = 0;
+    let mut idle = 0;
+    let mut productive = 0;
+    let mut neutral = 0;
+    for activity in activities {
+        let duration = (activity.end_time - activity.start_time).num_seconds();
+        total += duration;
+        if activity.is_idle {
+            idle += duration;
+            continue;
+        }
+        match config.category_for_activity(activity) {
+            Some(category) if category.neutral => neutral += duration,
+            Some(category) if category.is_productive => productive += duration,
+            _ => {}
+        }
+    }
+
+    let active = total - idle;
+    let considered = active - neutral;
+    let switches = context_switches(activities);
+    let switches_per_hour = if active > 0 { switches as f64 / (active as f64 / 3600.0) } else { 0.0 };
+    let goal_seconds = config.daily_goal_minutes * 60 * days.max(1);
+
+    let components = ScoreComponents {
+        productive: (considered > 0).then(|| productive as f64 / considered as f64),
+        idle: (total > 0).then(|| 1.0 - idle as f64 / total as f64),
+        focus: (active > 0 && weights.switches_per_hour_ceiling > 0.0)
+            .then(|| 1.0 - (switches_per_hour / weights.switches_per_hour_ceiling).min(1.0)),
+        goal: (goal_seconds > 0 && total > 0).then(|| (productive as f64 / goal_seconds as f64).min(1.0)),
+    };
+
+    let weighted = [
+        (components.productive, weights.productive),
+        (components.idle, weights.idle),
+        (components.focus, weights.focus),
+        (components.goal, weights.goal),
+    ];
+    let weight_sum: f64 = weighted.iter().filter(|(value, _)| value.is_some()).map(|(_, weight)| weight).sum();
+    let score = (weight_sum > 0.0).then(|| {
+        let sum: f64 = weighted.iter().filter_map(|(value, weight)| value.map(|v| v * weight)).sum();
+        (sum / weight_sum * 100.0).round().clamp(0.0, 100.0) as i64
+    });
+
+    ProductivityScore {
+        score,
+        components,
+        weights,
+        context_switches: switches,
+        switches_per_hour: (switches_per_hour * 10.0).round() / 10.0,
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HourSwitches {
+    pub hour_start: DateTime<Utc>,
+    pub app_switches: usize,
+    pub window_switches: usize,
+}
+
+// app_switches conta trocas de app; window_switches também trocas de janela (título)
+// dentro do mesmo app. Bloco de foco = trechos ativos seguidos no mesmo app, até uma
+// troca, um ocioso ou um buraco maior que FOCUS_BLOCK_MAX_GAP_SECONDS
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextSwitchMetrics {
+    pub active_seconds: i64,
+    pub app_switches: usize,
+    pub window_switches: usize,
+    pub app_switches_per_hour: f64,
+    pub window_switches_per_hour: f64,
+    pub focus_blocks: usize,
+    pub average_focus_block_seconds: i64,
+    pub longest_focus_block_seconds: i64,
+    pub hours: Vec<HourSwitches>,
+}
+
+// Início da hora local do instante (fusos com meia hora não caem na hora UTC)
+fn local_hour_start(at: DateTime<Utc>, tz: Tz) -> DateTime<Utc> {
+    at.with_timezone(&tz)
+        .duration_trunc(Duration::hours(1))
+        .map(|hour| hour.with_timezone(&Utc))
+        .unwrap_or(at)
+}
+
+pub fn context_switch_metrics(activities: &[WindowActivity], tz: Tz) -> ContextSwitchMetrics {
+    let mut sorted: Vec<&WindowActivity> = activities.iter().collect();
+    sorted.sort_by_key(|a| a.start_time);
+
+    let active_seconds: i64 = sorted
+        .iter()
+        .filter(|a| !a.is_idle)
+        .map(|a| (a.end_time - a.start_time).num_seconds())
+        .sum();
+
+    let mut hours: BTreeMap<DateTime<Utc>, HourSwitches> = BTreeMap::new();
+    let (mut app_switches, mut window_switches) = (0, 0);
+    let mut blocks: Vec<i64> = Vec::new();
+    let mut block: Option<i64> = None;
+    let mut previous: Option<&WindowActivity> = None;
+
+    for activity in sorted {
+        if activity.is_idle {
+            blocks.extend(block.take());
+            continue;
+        }
+        let duration = (activity.end_time - activity.start_time).num_seconds();
+
+        if let Some(previous) = previous {
+            let app_changed = previous.application != activity.application;
+            let window_changed = app_changed || previous.title != activity.title;
+            if window_changed {
+                let hour_start = local_hour_start(activity.start_time, tz);
+                let hour = hours.entry(hour_start).or_insert_with(|| HourSwitches {
+                    hour_start,
+                    app_switches: 0,
+                    window_switches: 0,
+                });
+                hour.window_switches += 1;
+                window_switches += 1;
+                if app_changed {
+                    hour.app_switches += 1;
+                    app_switches += 1;
+                }
+            }
+
+            let gap = (activity.start_time - previous.end_time).num_seconds();
+            if app_changed || gap > FOCUS_BLOCK_MAX_GAP_SECONDS {
+                blocks.extend(block.take());
+            }
+        }
+
+        *block.get_or_insert(0) += duration;
+        previous = Some(activity);
+    }
+    blocks.extend(block);
+
+    let active_hours = active_seconds as f64 / 3600.0;
+    let per_hour = |switches: usize| {
+        if active_hours > 0.0 { (switches as f64 / active_hours * 10.0).round() / 10.0 } else { 0.0 }
+    };
+
+    ContextSwitchMetrics {
+        active_seconds,
+        app_switches,
+        window_switches,
+        app_switches_per_hour: per_hour(app_switches),
+        window_switches_per_hour: per_hour(window_switches),
+        focus_blocks: blocks.len(),
+        average_focus_block_seconds: if blocks.is_empty() { 0 } else { blocks.iter().sum::<i64>() / blocks.len() as i64 },
+        longest_focus_block_seconds: blocks.iter().copied().max().unwrap_or(0),
+        hours: hours.into_values().collect(),
+    }
+}
@@ -0,0 +1,168 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::{debug, error, info};
+
+use crate::category::{ScreenshotConfig, SharedConfig};
+use crate::database::{self, DbConnection};
+use crate::tracker::{self, SharedTrackerHealth};
+
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+fn screenshots_dir() -> Result<PathBuf> {
+    let dir = database::get_database_path()?
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("Database path has no parent directory"))?
+        .join("screenshots");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+// Captura a tela inteira com a ferramenta do sistema
+#[cfg(target_os = "macos")]
+fn capture_screen(path: &Path) -> Result<()> {
+    let status = Command::new("screencapture").arg("-x").arg("-t").arg("png").arg(path).status()?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("screencapture exited with {}", status));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn capture_screen(path: &Path) -> Result<()> {
+    use std::ffi::OsStr;
+
+    let wayland = std::env::var("WAYLAND_DISPLAY").is_ok();
+    let attempts: Vec<(&str, Vec<&OsStr>)> = if wayland {
+        vec![
+            ("grim", vec![path.as_os_str()]),
+            ("gnome-screenshot", vec![OsStr::new("-f"), path.as_os_str()]),
+        ]
+    } else {
+        vec![
+            ("import", vec![OsStr::new("-window"), OsStr::new("root"), path.as_os_str()]),
+            ("gnome-screenshot", vec![OsStr::new("-f"), path.as_os_str()]),
+        ]
+    };
+
+    for (program, args) in attempts {
+        if Command::new(program).args(&args).status().map_or(false, |s| s.success()) && path.exists() {
+            return Ok(());
+        }
+        debug!("Screenshot with {} failed", program);
+    }
+    Err(anyhow::anyhow!("No screenshot tool available (install grim or ImageMagick)"))
+}
+
+#[cfg(target_os = "windows")]
+fn capture_screen(path: &Path) -> Result<()> {
+    let script = format!(
+        "Add-Type -AssemblyName System.Windows.Forms,System.Drawing; \
+         $b = [System.Windows.Forms.SystemInformation]::VirtualScreen; \
+         $bmp = New-Object System.Drawing.Bitmap $b.Width, $b.Height; \
+         $g = [System.Drawing.Graphics]::FromImage($bmp); \
+         $g.CopyFromScreen($b.Left, $b.Top, 0, 0, $bmp.Size); \
+         $bmp.Save('{}', [System.Drawing.Imaging.ImageFormat]::Png)",
+        path.display()
+    );
+    let status = Command::new("powershell").args(["-NoProfile", "-Command", &script]).status()?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("PowerShell capture exited with {}", status));
+    }
+    Ok(())
+}
+
+// Só a miniatura fica salva; a captura em tamanho real é apagada em seguida
+fn capture_thumbnail(path: &Path, max_width: u32) -> Result<()> {
+    let full = path.with_extension("full.png");
+    let result = capture_screen(&full).and_then(|_| {
+        let image = image::open(&full)?;
+        let thumbnail = image.thumbnail(max_width, max_width);
+        thumbnail.to_rgb8().save_with_format(path, image::ImageFormat::Jpeg)?;
+        Ok(())
+    });
+    let _ = std::fs::remove_file(&full);
+    result
+}
+
+async fn take_screenshot(db: &DbConnection, config: &ScreenshotConfig, application: String, title: String) -> Result<()> {
+    let taken_at = Utc::now();
+    let path = screenshots_dir()?.join(format!("{}.jpg", taken_at.format("%Y%m%d-%H%M%S")));
+
+    let max_width = config.max_width;
+    let capture_path = path.clone();
+    tokio::task::spawn_blocking(move || capture_thumbnail(&capture_path, max_width)).await??;
+
+    database::save_screenshot(db, taken_at, &path.to_string_lossy(), &application, &title).await?;
+    debug!("Saved screenshot {:?} for {}", path, application);
+    Ok(())
+}
+
+pub async fn purge_expired(db: &DbConnection, retention_days: u32) -> Result<usize> {
+    let cutoff: DateTime<Utc> = Utc::now() - Duration::days(retention_days as i64);
+    let paths = database::delete_screenshots_before(db, cutoff).await?;
+    for path in &paths {
+        if let Err(e) = std::fs::remove_file(path) {
+            debug!("Failed to remove screenshot {}: {}", path, e);
+        }
+    }
+    Ok(paths.len())
+}
+
+pub async fn run_screenshot_job(db: DbConnection, config: SharedConfig, health: SharedTrackerHealth) {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    // Mantido entre as iterações para que o movimento do mouse entre elas conte como uso
+    let mut backend = tracker::detect_backend();
+    let mut last_capture: Option<DateTime<Utc>> = None;
+
+    loop {
+        interval.tick().await;
+
+        let (config, blackout) = match config.lock() {
+            Ok(config) => (config.screenshots.clone(), config.in_blackout(Utc::now())),
+            Err(e) => {
+                error!("Failed to lock config: {}", e);
+                continue;
+            }
+        };
+        if !config.enabled {
+            continue;
+        }
+
+        match purge_expired(&db, config.retention_days).await {
+            Ok(0) => {}
+            Ok(removed) => info!("Removed {} expired screenshots", removed),
+            Err(e) => error!("Failed to purge screenshots: {}", e),
+        }
+
+        let now = Utc::now();
+        let interval_minutes = config.interval_minutes.max(1) as i64;
+        if last_capture.map_or(false, |last| now - last < Duration::minutes(interval_minutes)) {
+            continue;
+        }
+
+        let paused = health.lock().map_or(false, |health| health.paused);
+        let idle = backend.idle_time() > std::time::Duration::from_secs(interval_minutes as u64 * 60);
+        if paused || idle || blackout {
+            continue;
+        }
+
+        let window = match backend.active_window() {
+            Ok(window) => window,
+            Err(e) => {
+                debug!("Skipping screenshot, no active window: {}", e);
+                continue;
+            }
+        };
+        if config.is_excluded(&window.app_name) {
+            debug!("Skipping screenshot of excluded app {}", window.app_name);
+            continue;
+        }
+
+        last_capture = Some(now);
+        if let Err(e) = take_screenshot(&db, &config, window.app_name, window.title).await {
+            error!("Failed to take screenshot: {}", e);
+        }
+    }
+}
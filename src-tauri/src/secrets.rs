@@ -0,0 +1,24 @@
+use anyhow::Result;
+
+// Segredos no chaveiro do sistema (Keychain, Credential Manager, Secret Service),
+// um por conta dentro do mesmo serviço do app. Nunca vão para arquivos em disco
+const SERVICE: &str = "com.chronos.track";
+
+fn entry(account: &str) -> Result<keyring::Entry> {
+    Ok(keyring::Entry::new(SERVICE, account)?)
+}
+
+pub fn get(account: &str) -> Option<String> {
+    entry(account).ok()?.get_password().ok()
+}
+
+pub fn set(account: &str, value: &str) -> Result<()> {
+    Ok(entry(account)?.set_password(value)?)
+}
+
+pub fn delete(account: &str) -> Result<()> {
+    match entry(account)?.delete_password() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -0,0 +1,155 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tracing::info;
+
+use crate::database::DbConnection;
+use crate::permissions;
+use crate::tracker::{self, SharedTrackerHealth};
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+    Skipped,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SelfTestCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SelfTestReport {
+    pub ran_at: DateTime<Utc>,
+    pub passed: bool,
+    pub checks: Vec<SelfTestCheck>,
+}
+
+fn check(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> SelfTestCheck {
+    SelfTestCheck { name, status, detail: detail.into() }
+}
+
+// Usa um backend novo, independente do tracker em execução
+async fn check_window_capture() -> SelfTestCheck {
+    let result = tokio::task::spawn_blocking(|| {
+        let backend = tracker::detect_backend();
+        (backend.name(), backend.active_window())
+    })
+    .await;
+
+    match result {
+        Ok((backend, Ok(window))) if window.title.trim().is_empty() => check(
+            "window_capture",
+            CheckStatus::Warn,
+            format!("{} backend sees {} but the window title is empty", backend, window.app_name),
+        ),
+        Ok((backend, Ok(window))) => check(
+            "window_capture",
+            CheckStatus::Pass,
+            format!("{} backend sees {}: {}", backend, window.app_name, window.title),
+        ),
+        Ok((backend, Err(e))) => check("window_capture", CheckStatus::Fail, format!("{} backend: {}", backend, e)),
+        Err(e) => check("window_capture", CheckStatus::Fail, format!("Window capture crashed: {}", e)),
+    }
+}
+
+// Sem interação do usuário não dá para provar que a entrada é vista, só que a leitura funciona
+async fn check_idle_detection() -> SelfTestCheck {
+    let result = tokio::task::spawn_blocking(|| {
+        let mut backend = tracker::detect_backend();
+        backend.idle_time();
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        backend.idle_time()
+    })
+    .await;
+
+    match result {
+        Ok(idle) => check(
+            "idle_detection",
+            CheckStatus::Pass,
+            format!("Input polling works (idle for {:.1?})", idle),
+        ),
+        Err(e) => check("idle_detection", CheckStatus::Fail, format!("Input polling crashed: {}", e)),
+    }
+}
+
+// BEGIN IMMEDIATE pega o lock de escrita sem alterar nada
+async fn check_database(db: &DbConnection) -> SelfTestCheck {
+    let conn = db.lock().await;
+    let result = conn
+        .execute_batch("BEGIN IMMEDIATE; ROLLBACK;")
+        .and_then(|_| conn.query_row("PRAGMA quick_check", [], |row| row.get::<_, String>(0)));
+
+    match result {
+        Ok(status) if status == "ok" => check("database", CheckStatus::Pass, "Database is writable"),
+        Ok(status) => check("database", CheckStatus::Fail, format!("Integrity check failed: {}", status)),
+        Err(e) => check("database", CheckStatus::Fail, format!("Database is not writable: {}", e)),
+    }
+}
+
+fn check_permissions() -> SelfTestCheck {
+    let missing = permissions::missing_permissions();
+    if missing.is_empty() {
+        check("permissions", CheckStatus::Pass, "All required permissions granted")
+    } else {
+        check("permissions", CheckStatus::Fail, format!("Missing permissions: {:?}", missing))
+    }
+}
+
+fn check_tracker(health: &SharedTrackerHealth) -> SelfTestCheck {
+    let health = match health.lock() {
+        Ok(health) => health.clone(),
+        Err(e) => return check("tracker", CheckStatus::Fail, e.to_string()),
+    };
+
+    if health.paused {
+        return check("tracker", CheckStatus::Warn, "Tracking is paused");
+    }
+    match health.last_successful_tick {
+        Some(tick) if Utc::now() - tick <= Duration::seconds(30) => {
+            check("tracker", CheckStatus::Pass, format!("Last activity recorded at {}", tick))
+        }
+        Some(tick) => check(
+            "tracker",
+            CheckStatus::Fail,
+            format!("No activity recorded since {} ({} restarts)", tick, health.restarts),
+        ),
+        None => check("tracker", CheckStatus::Fail, "Tracker has not recorded any activity yet"),
+    }
+}
+
+async fn check_notifications(app: &AppHandle) -> SelfTestCheck {
+    match crate::notifications::notify(app, "Chronos Track", "Self-test notification").await {
+        Ok(()) => check("notifications", CheckStatus::Pass, "Test notification sent"),
+        Err(e) => check("notifications", CheckStatus::Fail, e.to_string()),
+    }
+}
+
+pub async fn run(app: &AppHandle, send_notification: bool) -> SelfTestReport {
+    let mut checks = vec![
+        check_window_capture().await,
+        check_idle_detection().await,
+        check_database(app.state::<DbConnection>().inner()).await,
+        check_permissions(),
+        check_tracker(app.state::<SharedTrackerHealth>().inner()),
+    ];
+    checks.push(if send_notification {
+        check_notifications(app).await
+    } else {
+        check("notifications", CheckStatus::Skipped, "Not requested")
+    });
+
+    let passed = checks.iter().all(|c| c.status != CheckStatus::Fail);
+    info!("Self-test finished: {}", if passed { "passed" } else { "failed" });
+
+    SelfTestReport {
+        ran_at: Utc::now(),
+        passed,
+        checks,
+    }
+}
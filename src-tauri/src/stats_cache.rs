@@ -0,0 +1,167 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tauri::api::path::config_dir;
+use tauri::{AppHandle, Manager};
+use tracing::{debug, error, info};
+
+use crate::category::SharedConfig;
+use crate::database::DbConnection;
+
+pub const TODAY_STATS_EVENT: &str = "today-stats-updated";
+
+// Intervalo mínimo entre gravações do cache em disco
+const PERSIST_INTERVAL_SECONDS: i64 = 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedTodayStats {
+    pub date: NaiveDate,
+    pub total_time: i64,
+    pub productive_time: i64,
+    pub computed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default)]
+pub struct TodayStatsCache {
+    cached: Option<CachedTodayStats>,
+    // false até o primeiro cálculo completo após a inicialização
+    warm: bool,
+    persisted_at: Option<DateTime<Utc>>,
+}
+
+pub type StatsCache = Arc<Mutex<TodayStatsCache>>;
+
+impl TodayStatsCache {
+    pub fn load() -> Self {
+        let cached = Self::get_cache_path()
+            .and_then(|path| Ok(fs::read_to_string(path)?))
+            .and_then(|content| Ok(serde_json::from_str::<CachedTodayStats>(&content)?))
+            .ok();
+
+        debug!("Loaded today stats cache: {:?}", cached);
+        TodayStatsCache {
+            cached,
+            warm: false,
+            persisted_at: None,
+        }
+    }
+
+    pub fn is_warm(&self) -> bool {
+        self.warm
+    }
+
+    // Enquanto o cálculo completo não terminou, serve o último valor salvo do dia
+    // (dia lógico do calendário configurado)
+    pub fn cold_start_value(&self, today: NaiveDate) -> Option<(i64, i64)> {
+        if self.warm {
+            return None;
+        }
+
+        self.cached
+            .as_ref()
+            .filter(|c| c.date == today)
+            .map(|c| (c.total_time, c.productive_time))
+    }
+
+    pub fn store(&mut self, today: NaiveDate, total_time: i64, productive_time: i64) {
+        let now = Utc::now();
+        self.warm = true;
+        self.cached = Some(CachedTodayStats {
+            date: today,
+            total_time,
+            productive_time,
+            computed_at: now,
+        });
+
+        let should_persist = self
+            .persisted_at
+            .map_or(true, |at| (now - at).num_seconds() >= PERSIST_INTERVAL_SECONDS);
+        if should_persist {
+            if let Err(e) = self.save() {
+                error!("Failed to persist today stats cache: {}", e);
+            }
+            self.persisted_at = Some(now);
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = Self::get_cache_path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(&self.cached)?)?;
+        Ok(())
+    }
+
+    fn get_cache_path() -> Result<PathBuf> {
+        let mut path = config_dir()
+            .ok_or_else(|| anyhow::anyhow!("Failed to get config directory"))?;
+        path.push("chronos-track");
+        path.push("stats_cache.json");
+        Ok(path)
+    }
+}
+
+// Totais de hoje (total, produtivo), usando o cache enquanto o banco ainda não foi lido
+pub async fn get_today_totals(app: &AppHandle) -> Result<(i64, i64), String> {
+    let cache = app.state::<StatsCache>();
+
+    let today = logical_today(app)?;
+    let cold_start_value = cache.lock().map_err(|e| e.to_string())?.cold_start_value(today);
+    if let Some(value) = cold_start_value {
+        debug!("Serving today stats from cold-start cache");
+        return Ok(value);
+    }
+
+    let (total, productive) = crate::commands::get_today_stats_internal(
+        app.state::<DbConnection>(),
+        app.state::<SharedConfig>(),
+    ).await?;
+
+    cache.lock().map_err(|e| e.to_string())?.store(today, total, productive);
+    Ok((total, productive))
+}
+
+fn logical_today(app: &AppHandle) -> Result<NaiveDate, String> {
+    let config = app.state::<SharedConfig>();
+    let config = config.lock().map_err(|e| e.to_string())?;
+    Ok(config.calendar.logical_date(Utc::now()))
+}
+
+// Calcula os números precisos em segundo plano e avisa o frontend quando terminar
+pub async fn backfill(app: AppHandle) {
+    let started = std::time::Instant::now();
+    let today = match logical_today(&app) {
+        Ok(today) => today,
+        Err(e) => {
+            error!("Failed to read calendar config: {}", e);
+            return;
+        }
+    };
+
+    let result = crate::commands::get_today_stats_internal(
+        app.state::<DbConnection>(),
+        app.state::<SharedConfig>(),
+    ).await;
+
+    match result {
+        Ok((total, productive)) => {
+            match app.state::<StatsCache>().lock() {
+                Ok(mut cache) => cache.store(today, total, productive),
+                Err(e) => error!("Failed to lock stats cache: {}", e),
+            }
+            info!("Today stats backfilled in {:.1?}", started.elapsed());
+
+            if let Err(e) = app.emit_all(TODAY_STATS_EVENT, (total, productive)) {
+                error!("Failed to emit today stats: {}", e);
+            }
+            if let Err(e) = crate::menu::update_tray_menu(&app).await {
+                error!("Failed to update tray menu: {}", e);
+            }
+        }
+        Err(e) => error!("Failed to backfill today stats: {}", e),
+    }
+}
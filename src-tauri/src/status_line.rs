@@ -0,0 +1,71 @@
+use anyhow::Result;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use tauri::api::path::config_dir;
+
+use crate::category::TrayConfig;
+use crate::menu::{progress_cells, TrayState};
+
+// Sem atualização por mais que isso, o app provavelmente não está rodando
+const STALE_AFTER: Duration = Duration::from_secs(5 * 60);
+const BAR_CELLS: usize = 4;
+
+fn compact_duration(seconds: i64) -> String {
+    let hours = seconds / 3600;
+    let minutes = (seconds % 3600) / 60;
+    if hours > 0 {
+        format!("{}h{:02}", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+// Linha única para tmux/polybar, ex: "▰▰▰▱ 68% · 3h12 prod"
+pub fn status_line(state: &TrayState, tray: &TrayConfig) -> String {
+    let duration = compact_duration(state.productive_seconds);
+    if state.daily_goal_minutes <= 0 {
+        return format!("{} prod", duration);
+    }
+
+    format!(
+        "{} {}% · {} prod",
+        progress_cells(state.goal_percentage, BAR_CELLS, tray),
+        state.goal_percentage,
+        duration
+    )
+}
+
+fn status_line_path() -> Result<PathBuf> {
+    let mut path = config_dir().ok_or_else(|| anyhow::anyhow!("Failed to get config directory"))?;
+    path.push("chronos-track");
+    path.push("status_line.txt");
+    Ok(path)
+}
+
+// Gravado junto com a atualização da bandeja, para ser lido sem abrir o banco
+pub fn write(line: &str) -> Result<()> {
+    let path = status_line_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, line)?;
+    Ok(())
+}
+
+// Usado pelo modo CLI (`chronos-track --status-line`)
+pub fn read() -> String {
+    let Ok(path) = status_line_path() else {
+        return "chronos: unavailable".to_string();
+    };
+
+    let stale = fs::metadata(&path)
+        .and_then(|m| m.modified())
+        .map(|modified| SystemTime::now().duration_since(modified).unwrap_or_default() > STALE_AFTER)
+        .unwrap_or(true);
+
+    match fs::read_to_string(&path) {
+        Ok(line) if !stale => line,
+        _ => "chronos: not running".to_string(),
+    }
+}
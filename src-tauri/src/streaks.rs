@@ -0,0 +1,188 @@
+use anyhow::Result;
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use tracing::{debug, error, info};
+
+use crate::category::{CategoryConfig, SharedConfig};
+use crate::commands::{override_productive_delta, profile_overrides};
+use crate::database::{self, DbConnection, GoalOutcome};
+use crate::rollups;
+
+const RECORD_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3600);
+// Dias recém-encerrados são regravados: sincronização e importação ainda podem trazer dados
+const REVISIT_DAYS: i64 = 2;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Streaks {
+    pub current: i64,
+    pub best: i64,
+    pub today_met: bool,
+    pub history: Vec<GoalOutcome>,
+}
+
+async fn productive_seconds(db: &DbConnection, config: &CategoryConfig, day: NaiveDate) -> Result<i64> {
+    let (start, end) = config.calendar.day_bounds(day);
+    let totals = database::get_app_totals_from_rollups(db, start, end).await?;
+    let mut overrides = database::get_override_totals(db, start, end, false).await?;
+    overrides.extend(profile_overrides(config, &database::get_profile_totals(db, start, end, false).await?));
+    let adjustments = database::get_adjustments_between(db, day, day).await?;
+
+    let mut productive: i64 = totals
+        .iter()
+        .filter(|t| config.get_category_for_app(&t.application).map_or(false, |c| c.is_productive))
+        .map(|t| t.total_duration - t.idle_duration)
+        .sum();
+    productive += override_productive_delta(config, &overrides);
+    productive += adjustments
+        .iter()
+        .filter(|a| config.categories.iter().any(|c| c.id == a.category_id && c.is_productive))
+        .map(|a| a.minutes * 60)
+        .sum::<i64>();
+    Ok(productive.max(0))
+}
+
+async fn outcome_for(db: &DbConnection, config: &CategoryConfig, day: NaiveDate) -> Result<GoalOutcome> {
+    let (_, end) = config.calendar.day_bounds(day);
+    // Meta em vigor naquele dia, não a atual
+    let goal_minutes = CategoryConfig::version_at(end)?
+        .map_or(config.daily_goal_minutes, |version| version.daily_goal_minutes);
+    let productive_seconds = productive_seconds(db, config, day).await?;
+    Ok(GoalOutcome {
+        date: day,
+        productive_seconds,
+        goal_minutes,
+        met: goal_minutes > 0 && productive_seconds >= goal_minutes * 60,
+        workday: config.schedule.workdays.contains(&day.weekday()),
+    })
+}
+
+// Grava os dias encerrados ainda sem resultado, só até onde os resumos por hora chegaram
+pub async fn record_outcomes(db: &DbConnection, config: &CategoryConfig) -> Result<usize> {
+    rollups::refresh(db).await?;
+    let Some(refreshed_through) = database::get_rollups_refreshed_through(db).await? else {
+        return Ok(0);
+    };
+
+    let yesterday = config.calendar.logical_date(Utc::now()) - Duration::days(1);
+    let first_day = match database::get_last_goal_outcome_date(db).await? {
+        Some(last) => (last + Duration::days(1)).min(yesterday - Duration::days(REVISIT_DAYS - 1)),
+        None => match database::get_first_activity_epoch(db).await? {
+            Some(epoch) => config.calendar.logical_date(
+                chrono::DateTime::<Utc>::from_timestamp(epoch, 0).unwrap_or_else(Utc::now),
+            ),
+            None => return Ok(0),
+        },
+    };
+
+    let mut outcomes = Vec::new();
+    let mut day = first_day;
+    while day <= yesterday {
+        let (_, end) = config.calendar.day_bounds(day);
+        if end.timestamp() > refreshed_through {
+            break;
+        }
+        outcomes.push(outcome_for(db, config, day).await?);
+        day += Duration::days(1);
+    }
+
+    database::save_goal_outcomes(db, &outcomes).await?;
+    Ok(outcomes.len())
+}
+
+// Dias fora do expediente só contam quando a meta foi batida; nunca quebram a sequência.
+// Hoje ainda está em andamento: soma se já bateu a meta, mas não zera a sequência
+pub fn streaks(outcomes: &[GoalOutcome], today_met: bool) -> (i64, i64) {
+    let mut best = 0;
+    let mut run = 0;
+    for outcome in outcomes {
+        if outcome.met {
+            run += 1;
+            best = best.max(run);
+        } else if outcome.workday {
+            run = 0;
+        }
+    }
+
+    let current = run + today_met as i64;
+    (current, best.max(current))
+}
+
+// Tempo produtivo da semana de `day` e sequência de semanas com a meta semanal batida.
+// Semanas anteriores vêm dos resultados diários gravados; dias da semana ainda sem
+// resultado (hoje, ou ontem até o job rodar) são somados a partir dos resumos por hora
+pub async fn weekly_goal(
+    db: &DbConnection,
+    config: &CategoryConfig,
+    day: NaiveDate,
+    max_weeks: i64,
+) -> Result<(i64, i64)> {
+    let calendar = &config.calendar;
+    let week_start = calendar.week_first_day(day);
+    let last_day = (week_start + Duration::days(6)).min(calendar.logical_date(Utc::now()));
+
+    // 0 = semana de `day`, 1 = anterior...
+    let mut seconds_by_week: HashMap<i64, i64> = HashMap::new();
+    let mut recorded = HashSet::new();
+    let outcomes = database::get_goal_outcomes(db, Some(week_start - Duration::weeks(max_weeks))).await?;
+    for outcome in outcomes.iter().filter(|o| o.date <= last_day) {
+        let week = (week_start - calendar.week_first_day(outcome.date)).num_weeks();
+        *seconds_by_week.entry(week).or_default() += outcome.productive_seconds;
+        recorded.insert(outcome.date);
+    }
+
+    let mut date = week_start;
+    while date <= last_day {
+        if !recorded.contains(&date) {
+            *seconds_by_week.entry(0).or_default() += productive_seconds(db, config, date).await?;
+        }
+        date += Duration::days(1);
+    }
+
+    let goal_met = |week: i64| seconds_by_week.get(&week).copied().unwrap_or(0) / 60 >= config.weekly_goal_minutes;
+    // A semana atual ainda está em andamento, então só soma se já bateu a meta
+    let previous_streak = (1..=max_weeks).take_while(|week| goal_met(*week)).count() as i64;
+    Ok((seconds_by_week.get(&0).copied().unwrap_or(0), previous_streak + goal_met(0) as i64))
+}
+
+pub async fn get_streaks(db: &DbConnection, config: &CategoryConfig, history_days: i64) -> Result<Streaks> {
+    record_outcomes(db, config).await?;
+    read_streaks(db, config, history_days).await
+}
+
+// Só lê os resultados já gravados (o job grava a cada hora); barato o bastante para o widget
+pub async fn read_streaks(db: &DbConnection, config: &CategoryConfig, history_days: i64) -> Result<Streaks> {
+    let outcomes = database::get_goal_outcomes(db, None).await?;
+    let today = outcome_for(db, config, config.calendar.logical_date(Utc::now())).await?;
+    let (current, best) = streaks(&outcomes, today.met);
+
+    let since = today.date - Duration::days(history_days);
+    Ok(Streaks {
+        current,
+        best,
+        today_met: today.met,
+        history: outcomes.into_iter().filter(|o| o.date >= since).collect(),
+    })
+}
+
+pub async fn run_streak_job(db: DbConnection, config: SharedConfig) {
+    let mut interval = tokio::time::interval(RECORD_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let config = match config.lock() {
+            Ok(config) => config.clone(),
+            Err(e) => {
+                error!("Failed to read config: {}", e);
+                continue;
+            }
+        };
+
+        match record_outcomes(&db, &config).await {
+            Ok(0) => debug!("No new daily goal outcomes"),
+            Ok(days) => info!("Recorded goal outcomes for {} days", days),
+            Err(e) => error!("Failed to record goal outcomes: {}", e),
+        }
+    }
+}
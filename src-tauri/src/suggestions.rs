@@ -0,0 +1,115 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::category::CategoryConfig;
+use crate::database::CategoryAssignment;
+
+// Mínimo de correções manuais com o mesmo padrão antes de sugerir uma regra
+const MIN_OCCURRENCES: usize = 2;
+// Fração das correções com o padrão que precisam apontar para a mesma categoria
+const MIN_CONFIDENCE: f64 = 0.8;
+
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "with", "new", "tab", "window", "untitled", "home", "page",
+    "google", "chrome", "firefox", "safari", "edge", "mozilla", "microsoft", "app",
+];
+
+#[derive(Debug, Serialize)]
+pub struct RuleSuggestion {
+    pub pattern: String,
+    pub category_id: String,
+    pub category_name: String,
+    pub occurrences: usize,
+    pub confidence: f64,
+    pub examples: Vec<String>,
+    // Apps que casam com o padrão mas ainda estão em outra categoria (ou sem nenhuma)
+    pub matching_apps: Vec<String>,
+}
+
+pub fn tokens(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(|token| token.to_lowercase())
+        .filter(|token| token.chars().count() >= 3)
+        .filter(|token| !token.chars().all(|c| c.is_ascii_digit()))
+        .filter(|token| !STOPWORDS.contains(&token.as_str()))
+        .collect()
+}
+
+// Análise de frequência: palavras que aparecem em várias correções manuais
+// (nome do app + título da janela no momento) e quase sempre na mesma categoria
+pub fn suggest_rules(
+    assignments: &[CategoryAssignment],
+    config: &CategoryConfig,
+    known_apps: &[String],
+) -> Vec<RuleSuggestion> {
+    // Só a correção mais recente de cada app conta
+    let mut latest: HashMap<&str, &CategoryAssignment> = HashMap::new();
+    for assignment in assignments {
+        let newer = latest
+            .get(assignment.application.as_str())
+            .map_or(true, |existing| assignment.assigned_at > existing.assigned_at);
+        if newer {
+            latest.insert(&assignment.application, assignment);
+        }
+    }
+
+    // token -> categoria -> exemplos
+    let mut by_token: HashMap<String, HashMap<&str, Vec<&CategoryAssignment>>> = HashMap::new();
+    for assignment in latest.values().copied() {
+        let text = format!("{} {}", assignment.application, assignment.title.as_deref().unwrap_or_default());
+        for token in tokens(&text) {
+            by_token
+                .entry(token)
+                .or_default()
+                .entry(assignment.category_id.as_str())
+                .or_default()
+                .push(assignment);
+        }
+    }
+
+    let mut suggestions: Vec<RuleSuggestion> = by_token
+        .into_iter()
+        .filter_map(|(token, categories)| {
+            let total: usize = categories.values().map(|a| a.len()).sum();
+            let (category_id, examples) = categories.into_iter().max_by_key(|(_, a)| a.len())?;
+            let confidence = examples.len() as f64 / total as f64;
+
+            if examples.len() < MIN_OCCURRENCES || confidence < MIN_CONFIDENCE {
+                return None;
+            }
+
+            let category = config.categories.iter().find(|c| c.id == category_id)?;
+            let matching_apps: Vec<String> = known_apps
+                .iter()
+                .filter(|app| app.to_lowercase().contains(&token))
+                .filter(|app| config.app_categories.get(*app).map(|c| c.as_str()) != Some(category_id))
+                .cloned()
+                .collect();
+
+            Some(RuleSuggestion {
+                pattern: token,
+                category_id: category_id.to_string(),
+                category_name: category.name.clone(),
+                occurrences: examples.len(),
+                confidence,
+                examples: examples
+                    .iter()
+                    .map(|a| match &a.title {
+                        Some(title) if !title.is_empty() => format!("{} — {}", a.application, title),
+                        _ => a.application.clone(),
+                    })
+                    .take(5)
+                    .collect(),
+                matching_apps,
+            })
+        })
+        .collect();
+
+    suggestions.sort_by(|a, b| {
+        b.occurrences
+            .cmp(&a.occurrences)
+            .then(b.confidence.total_cmp(&a.confidence))
+            .then(a.pattern.cmp(&b.pattern))
+    });
+    suggestions
+}
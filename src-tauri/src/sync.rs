@@ -0,0 +1,218 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tracing::{error, info};
+
+use crate::category::{SharedConfig, SyncConfig};
+use crate::database::{self, DbConnection};
+use crate::tracker::WindowActivity;
+
+// Cliente de um endpoint próprio (self-hosted) que junta as linhas de todos os
+// dispositivos:
+//   POST {endpoint}/devices/{device_id}/activities  { device_name, activities: [...] }
+//   POST {endpoint}/devices/{device_id}/deletions   { source_ids: [...] }
+//   GET  {endpoint}/activities?since={cursor}&exclude_device={device_id}
+//        -> { activities: [...], deleted: [{ device_id, source_id }], cursor }
+// Cada linha vai com o id de origem; só o dispositivo dono a altera, então a
+// mescla não tem conflitos. Correções são reenviadas (nova revisão) e exclusões
+// vão como tombstones, antes das linhas: um id reaproveitado chega depois da exclusão.
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+const SETTLE_MINUTES: i64 = 10;
+const PUSH_BATCH: i64 = 500;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncActivity {
+    pub device_id: String,
+    pub source_id: i64,
+    pub activity: WindowActivity,
+}
+
+#[derive(Debug, Serialize)]
+struct PushBody<'a> {
+    device_name: &'a str,
+    activities: Vec<SyncActivity>,
+}
+
+#[derive(Debug, Serialize)]
+struct DeletionsBody {
+    source_ids: Vec<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SyncDeletion {
+    device_id: String,
+    source_id: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct PullResponse {
+    activities: Vec<SyncActivity>,
+    #[serde(default)]
+    deleted: Vec<SyncDeletion>,
+    cursor: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct SyncReport {
+    pub pushed: usize,
+    pub pulled: usize,
+}
+
+fn client(config: &SyncConfig) -> Result<(reqwest::Client, String, String)> {
+    let endpoint = config.endpoint.trim().trim_end_matches('/').to_string();
+    if endpoint.is_empty() {
+        return Err(anyhow::anyhow!("Sync endpoint is not configured"));
+    }
+    let token = config.token.clone().unwrap_or_default();
+    Ok((reqwest::Client::new(), endpoint, token))
+}
+
+async fn push(db: &DbConnection, config: &SyncConfig) -> Result<usize> {
+    let (client, endpoint, token) = client(config)?;
+    let settled_before = Utc::now() - Duration::minutes(SETTLE_MINUTES);
+
+    let mut pushed = 0;
+    loop {
+        let source_ids = database::get_sync_tombstones(db, PUSH_BATCH).await?;
+        if source_ids.is_empty() {
+            break;
+        }
+        let count = source_ids.len();
+        client
+            .post(format!("{}/devices/{}/deletions", endpoint, config.device_id))
+            .bearer_auth(&token)
+            .json(&DeletionsBody { source_ids: source_ids.clone() })
+            .send()
+            .await?
+            .error_for_status()?;
+
+        database::clear_sync_tombstones(db, &source_ids).await?;
+        pushed += count;
+        if (count as i64) < PUSH_BATCH {
+            break;
+        }
+    }
+
+    loop {
+        let batch = database::get_unsynced_activities(db, settled_before, PUSH_BATCH).await?;
+        if batch.is_empty() {
+            break;
+        }
+
+        let count = batch.len();
+        let revisions: Vec<(i64, i64)> =
+            batch.iter().filter_map(|(activity, revision)| Some((activity.id?, *revision))).collect();
+        let body = PushBody {
+            device_name: &config.device_name,
+            activities: batch
+                .into_iter()
+                .map(|(activity, _)| SyncActivity {
+                    device_id: config.device_id.clone(),
+                    source_id: activity.id.unwrap_or_default(),
+                    activity,
+                })
+                .collect(),
+        };
+        client
+            .post(format!("{}/devices/{}/activities", endpoint, config.device_id))
+            .bearer_auth(&token)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        // Marca a cada lote aceito, para não reenviar tudo após uma falha
+        database::mark_activities_pushed(db, &revisions).await?;
+        pushed += count;
+        if (count as i64) < PUSH_BATCH {
+            break;
+        }
+    }
+
+    Ok(pushed)
+}
+
+async fn pull(db: &DbConnection, config: &SyncConfig) -> Result<(usize, Option<i64>)> {
+    let (client, endpoint, token) = client(config)?;
+    let cursor = database::get_pull_cursor(db).await?;
+
+    let mut request = client
+        .get(format!("{}/activities", endpoint))
+        .bearer_auth(&token)
+        .query(&[("exclude_device", config.device_id.as_str())]);
+    if let Some(cursor) = &cursor {
+        request = request.query(&[("since", cursor.as_str())]);
+    }
+    let response: PullResponse = request.send().await?.error_for_status()?.json().await?;
+
+    let rows: Vec<(String, i64, WindowActivity)> = response
+        .activities
+        .into_iter()
+        // O servidor já deveria filtrar, mas linhas próprias nunca entram como remotas
+        .filter(|row| row.device_id != config.device_id)
+        .map(|row| (row.device_id, row.source_id, row.activity))
+        .collect();
+    let (pulled, earliest) = database::upsert_remote_activities(db, &rows).await?;
+    let deleted: Vec<(String, i64)> = response
+        .deleted
+        .into_iter()
+        .filter(|row| row.device_id != config.device_id)
+        .map(|row| (row.device_id, row.source_id))
+        .collect();
+    let (removed, earliest_removed) = database::delete_remote_activities(db, &deleted).await?;
+    database::set_pull_cursor(db, response.cursor.as_deref().or(cursor.as_deref())).await?;
+
+    let earliest = match (earliest, earliest_removed) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+    Ok((pulled + removed, earliest))
+}
+
+pub async fn sync_now(db: &DbConnection, config: &SyncConfig) -> Result<SyncReport> {
+    let pushed = push(db, config).await?;
+    let (pulled, earliest) = pull(db, config).await?;
+
+    // Linhas recebidas entram nos resumos por hora a partir do início mais antigo
+    if let Some(since) = earliest {
+        database::refresh_hourly_rollups(db, since, Utc::now()).await?;
+    }
+
+    Ok(SyncReport { pushed, pulled })
+}
+
+pub async fn run_sync_job(app: AppHandle) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    let mut last_sync: Option<chrono::DateTime<Utc>> = None;
+
+    loop {
+        interval.tick().await;
+
+        let config = match app.state::<SharedConfig>().lock() {
+            Ok(config) => config.sync.clone(),
+            Err(e) => {
+                error!("Failed to lock config: {}", e);
+                continue;
+            }
+        };
+        if !config.enabled {
+            continue;
+        }
+        let now = Utc::now();
+        if last_sync.map_or(false, |last| now - last < Duration::minutes(config.interval_minutes.max(1) as i64)) {
+            continue;
+        }
+        last_sync = Some(now);
+
+        let db = app.state::<DbConnection>().inner().clone();
+        match sync_now(&db, &config).await {
+            Ok(report) if report.pushed + report.pulled > 0 => {
+                info!("🔁 Sync: pushed {}, pulled {}", report.pushed, report.pulled);
+                tokio::spawn(crate::stats_cache::backfill(app.clone()));
+            }
+            Ok(_) => {}
+            Err(e) => error!("Sync failed: {}", e),
+        }
+    }
+}
@@ -0,0 +1,436 @@
+use anyhow::Result;
+use chrono::{Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+use tracing::{error, info};
+
+use crate::category::{ApiPermission, CategoryConfig, SharedConfig};
+use crate::commands::{override_productive_delta, profile_overrides};
+use crate::database::{self, DbConnection};
+use crate::events::FeedEventSender;
+
+// API local, com o token no cabeçalho Authorization: Bearer. Cada rota exige uma permissão:
+//   GET  /feed/daily        read_aggregates (resumos diários, usado pelo app de celular)
+//   GET  /activities?date=  read_activities (atividades brutas de um dia)
+//   POST /adjustments       write_manual_entries
+//   GET  /config            manage_config (sem tokens)
+//   PUT  /config/daily-goal manage_config
+//   GET  /stream            read_aggregates (WebSocket, ver stream)
+// As permissões são lidas a cada requisição, então revogar vale na hora
+const DEFAULT_PER_PAGE: i64 = 14;
+const MAX_PER_PAGE: i64 = 90;
+const MAX_DAYS_BACK: i64 = 366;
+// O servidor anterior libera a porta só quando a thread dele termina
+const BIND_ATTEMPTS: u32 = 10;
+const MAX_BODY_BYTES: u64 = 64 * 1024;
+const STREAM_PATH: &str = "/stream";
+
+pub type SharedSyncFeed = Arc<Mutex<Option<Arc<tiny_http::Server>>>>;
+
+#[derive(Debug, Serialize)]
+pub struct DaySummary {
+    pub date: NaiveDate,
+    pub total_seconds: i64,
+    pub productive_seconds: i64,
+    pub idle_seconds: i64,
+    pub goal_minutes: i64,
+    pub goal_percentage: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeedPage {
+    pub generated_at: chrono::DateTime<Utc>,
+    pub page: i64,
+    pub days: Vec<DaySummary>,
+    pub next_page: Option<i64>,
+}
+
+// Um dia pelos resumos por hora mais os ajustes manuais, como nas estatísticas
+async fn day_summary(db: &DbConnection, config: &CategoryConfig, date: NaiveDate) -> Result<DaySummary> {
+    let (start, end) = config.calendar.day_bounds(date);
+    let totals = database::get_app_totals_from_rollups(db, start, end).await?;
+    let mut overrides = database::get_override_totals(db, start, end, false).await?;
+    overrides.extend(profile_overrides(config, &database::get_profile_totals(db, start, end, false).await?));
+    let adjustments = database::get_adjustments_between(db, date, date).await?;
+
+    let is_productive = |app: &str| config.get_category_for_app(app).map_or(false, |c| c.is_productive);
+    let mut total_seconds: i64 = totals.iter().map(|t| t.total_duration).sum();
+    let idle_seconds: i64 = totals.iter().map(|t| t.idle_duration).sum();
+    let mut productive_seconds: i64 = totals
+        .iter()
+        .filter(|t| is_productive(&t.application))
+        .map(|t| t.total_duration - t.idle_duration)
+        .sum();
+    // Tempo recategorizado por atividade ou pelo perfil do navegador
+    productive_seconds += override_productive_delta(config, &overrides);
+
+    for adjustment in &adjustments {
+        let seconds = adjustment.minutes * 60;
+        total_seconds += seconds;
+        if config.categories.iter().any(|c| c.id == adjustment.category_id && c.is_productive) {
+            productive_seconds += seconds;
+        }
+    }
+
+    let goal_minutes = config.daily_goal_minutes;
+    Ok(DaySummary {
+        date,
+        total_seconds: total_seconds.max(0),
+        productive_seconds: productive_seconds.max(0),
+        idle_seconds,
+        goal_minutes,
+        goal_percentage: if goal_minutes > 0 {
+            (productive_seconds.max(0) as f64 / 60.0 / goal_minutes as f64 * 100.0).round() as i64
+        } else {
+            0
+        },
+    })
+}
+
+// Página 0 = hoje e os dias anteriores, do mais recente para o mais antigo
+pub async fn feed_page(db: &DbConnection, config: &SharedConfig, page: i64, per_page: i64) -> Result<FeedPage> {
+    let config = config.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?.clone();
+    let per_page = per_page.clamp(1, MAX_PER_PAGE);
+    let page = page.max(0);
+    let today = config.calendar.logical_date(Utc::now());
+
+    let first = page * per_page;
+    let last = ((page + 1) * per_page).min(MAX_DAYS_BACK);
+    let mut days = Vec::new();
+    for offset in first..last {
+        days.push(day_summary(db, &config, today - Duration::days(offset)).await?);
+    }
+
+    Ok(FeedPage {
+        generated_at: Utc::now(),
+        page,
+        days,
+        next_page: (last < MAX_DAYS_BACK).then_some(page + 1),
+    })
+}
+
+fn query_param(url: &str, name: &str) -> Option<i64> {
+    let (_, query) = url.split_once('?')?;
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .and_then(|(_, value)| value.parse().ok())
+}
+
+fn json_response(status: u16, body: String) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+    let response = tiny_http::Response::from_string(body).with_status_code(status);
+    match tiny_http::Header::from_bytes("Content-Type", "application/json") {
+        Ok(header) => response.with_header(header),
+        Err(_) => response,
+    }
+}
+
+fn error_response(status: u16, message: &str) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+    json_response(status, serde_json::json!({ "error": message }).to_string())
+}
+
+fn query_value<'a>(url: &'a str, name: &str) -> Option<&'a str> {
+    let (_, query) = url.split_once('?')?;
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(request: &mut tiny_http::Request) -> Result<T> {
+    let mut body = String::new();
+    request.as_reader().take(MAX_BODY_BYTES).read_to_string(&mut body)?;
+    Ok(serde_json::from_str(&body)?)
+}
+
+#[derive(Debug, Deserialize)]
+struct AdjustmentRequest {
+    date: NaiveDate,
+    category: String,
+    minutes: i64,
+    note: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DailyGoalRequest {
+    minutes: i64,
+}
+
+enum Route {
+    DailyFeed,
+    Activities,
+    AddAdjustment,
+    GetConfig,
+    SetDailyGoal,
+}
+
+impl Route {
+    fn find(method: &tiny_http::Method, path: &str) -> Option<Route> {
+        use tiny_http::Method::{Get, Post, Put};
+        match (method, path) {
+            (Get, "/feed/daily") => Some(Route::DailyFeed),
+            (Get, "/activities") => Some(Route::Activities),
+            (Post, "/adjustments") => Some(Route::AddAdjustment),
+            (Get, "/config") => Some(Route::GetConfig),
+            (Put, "/config/daily-goal") => Some(Route::SetDailyGoal),
+            _ => None,
+        }
+    }
+
+    fn permission(&self) -> ApiPermission {
+        match self {
+            Route::DailyFeed => ApiPermission::ReadAggregates,
+            Route::Activities => ApiPermission::ReadActivities,
+            Route::AddAdjustment => ApiPermission::WriteManualEntries,
+            Route::GetConfig | Route::SetDailyGoal => ApiPermission::ManageConfig,
+        }
+    }
+}
+
+fn header<'a>(request: &'a tiny_http::Request, name: &'static str) -> Option<&'a str> {
+    request.headers().iter().find(|h| h.field.equiv(name)).map(|h| h.value.as_str())
+}
+
+fn bearer_token(request: &tiny_http::Request) -> Option<String> {
+    header(request, "Authorization").and_then(|value| value.strip_prefix("Bearer ")).map(str::to_string)
+}
+
+fn handle(app: &AppHandle, request: &mut tiny_http::Request) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+    let config = app.state::<SharedConfig>().inner().clone();
+    let token = bearer_token(request);
+    let permissions = match config.lock() {
+        Ok(config) => token.and_then(|token| config.sync_feed.permissions_for(&token)),
+        Err(e) => {
+            error!("Failed to lock config: {}", e);
+            return error_response(500, "internal error");
+        }
+    };
+    let Some(permissions) = permissions else {
+        return error_response(401, "unauthorized");
+    };
+
+    let path = request.url().split('?').next().unwrap_or_default();
+    let Some(route) = Route::find(request.method(), path) else {
+        return error_response(404, "not found");
+    };
+    if !route.permission().granted_by(&permissions) {
+        return error_response(403, "forbidden");
+    }
+
+    let db = app.state::<DbConnection>().inner().clone();
+    let result = match route {
+        Route::DailyFeed => {
+            let page = query_param(request.url(), "page").unwrap_or(0);
+            let per_page = query_param(request.url(), "per_page").unwrap_or(DEFAULT_PER_PAGE);
+            tauri::async_runtime::block_on(feed_page(&db, &config, page, per_page))
+                .and_then(|feed| Ok(serde_json::to_string(&feed)?))
+        }
+        Route::Activities => {
+            let Some(date) = query_value(request.url(), "date").and_then(|d| d.parse::<NaiveDate>().ok()) else {
+                return error_response(400, "date=YYYY-MM-DD is required");
+            };
+            let bounds = config.lock().map(|c| c.calendar.day_bounds(date));
+            let Ok((start, end)) = bounds else {
+                return error_response(500, "internal error");
+            };
+            tauri::async_runtime::block_on(database::get_activities_between(&db, start, end))
+                .and_then(|activities| Ok(serde_json::to_string(&activities)?))
+        }
+        Route::AddAdjustment => {
+            let adjustment = match read_json::<AdjustmentRequest>(request) {
+                Ok(adjustment) => adjustment,
+                Err(e) => return error_response(400, &e.to_string()),
+            };
+            let category_exists = config
+                .lock()
+                .map_or(false, |c| c.categories.iter().any(|cat| cat.id == adjustment.category));
+            if adjustment.minutes == 0 || adjustment.note.trim().is_empty() || !category_exists {
+                return error_response(400, "adjustments need a known category, non-zero minutes and a note");
+            }
+            tauri::async_runtime::block_on(database::add_adjustment(
+                &db,
+                adjustment.date,
+                &adjustment.category,
+                adjustment.minutes,
+                adjustment.note.trim(),
+            ))
+            .and_then(|created| Ok(serde_json::to_string(&created)?))
+        }
+        Route::GetConfig => config
+            .lock()
+            .map_err(|e| anyhow::anyhow!(e.to_string()))
+            .and_then(|config| Ok(serde_json::to_string(&config.without_secrets())?)),
+        Route::SetDailyGoal => {
+            let goal = match read_json::<DailyGoalRequest>(request) {
+                Ok(goal) if goal.minutes >= 0 => goal,
+                Ok(_) => return error_response(400, "minutes must not be negative"),
+                Err(e) => return error_response(400, &e.to_string()),
+            };
+            let saved = config.lock().map_err(|e| anyhow::anyhow!(e.to_string())).and_then(|mut config| {
+                config.daily_goal_minutes = goal.minutes;
+                config.save()
+            });
+            if saved.is_ok() {
+                if let Err(e) = tauri::async_runtime::block_on(crate::menu::update_tray_menu(app)) {
+                    error!("Failed to update tray menu: {}", e);
+                }
+            }
+            saved.map(|()| serde_json::json!({ "daily_goal_minutes": goal.minutes }).to_string())
+        }
+    };
+
+    match result {
+        Ok(body) => json_response(200, body),
+        Err(e) => {
+            error!("Sync feed request failed: {}", e);
+            error_response(500, "internal error")
+        }
+    }
+}
+
+// Permissões vigentes do token; trava envenenada ou feed desligado contam como recusa
+fn stream_permissions(config: &SharedConfig, token: &str) -> Option<Vec<ApiPermission>> {
+    let config = config.lock().ok()?;
+    config.sync_feed.enabled.then(|| config.sync_feed.permissions_for(token)).flatten()
+}
+
+// WebSocket só de envio para painéis (ex.: um telão no escritório): cada evento do app
+// vira uma mensagem {"event": ..., "payload": ...} (stats-delta, idle-started, idle-ended
+// e, com read_activities, activity-changed). O cliente busca o dia em /feed/daily e aplica
+// os stats-delta seguintes. Navegadores não mandam Authorization no WebSocket, então o
+// token também vale em ?access_token=. As permissões são relidas a cada evento
+fn stream(app: &AppHandle, request: tiny_http::Request) {
+    let config = app.state::<SharedConfig>().inner().clone();
+    let token = bearer_token(&request)
+        .or_else(|| query_value(request.url(), "access_token").map(str::to_string))
+        .unwrap_or_default();
+    let refusal = match stream_permissions(&config, &token) {
+        None => Some(error_response(401, "unauthorized")),
+        Some(permissions) if !ApiPermission::ReadAggregates.granted_by(&permissions) => {
+            Some(error_response(403, "forbidden"))
+        }
+        Some(_) if !matches!(request.method(), tiny_http::Method::Get) => Some(error_response(404, "not found")),
+        Some(_) => None,
+    };
+    let key = header(&request, "Sec-WebSocket-Key").map(str::to_string);
+    let refusal = refusal.or_else(|| key.is_none().then(|| error_response(426, "websocket upgrade required")));
+    if let Some(response) = refusal {
+        if let Err(e) = request.respond(response) {
+            error!("Failed to answer sync feed request: {}", e);
+        }
+        return;
+    }
+
+    let accept = tungstenite::handshake::derive_accept_key(key.unwrap_or_default().as_bytes());
+    let mut response = tiny_http::Response::empty(tiny_http::StatusCode(101));
+    let headers = [("Upgrade", "websocket"), ("Connection", "Upgrade"), ("Sec-WebSocket-Accept", accept.as_str())];
+    for (field, value) in headers {
+        if let Ok(header) = tiny_http::Header::from_bytes(field, value) {
+            response = response.with_header(header);
+        }
+    }
+    // Inscreve antes do handshake para não perder eventos emitidos no meio
+    let mut events = app.state::<FeedEventSender>().subscribe();
+    let connection = request.upgrade("websocket", response);
+    let mut socket = tungstenite::WebSocket::from_raw_socket(connection, tungstenite::protocol::Role::Server, None);
+
+    std::thread::spawn(move || {
+        info!("Sync feed stream client connected");
+        loop {
+            let event = match events.blocking_recv() {
+                Ok(event) => event,
+                // Perdeu eventos: fecha para o cliente reconectar e buscar o dia de novo
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    info!("Sync feed stream client lagged by {} events", skipped);
+                    break;
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            };
+            let Some(permissions) = stream_permissions(&config, &token) else {
+                break;
+            };
+            if !event.permission.granted_by(&permissions) {
+                continue;
+            }
+            let message = match serde_json::to_string(&event) {
+                Ok(message) => message,
+                Err(e) => {
+                    error!("Failed to serialize sync feed event: {}", e);
+                    continue;
+                }
+            };
+            if socket.send(tungstenite::Message::Text(message)).is_err() {
+                break;
+            }
+        }
+        let _ = socket.close(None);
+        let _ = socket.flush();
+        info!("Sync feed stream client disconnected");
+    });
+}
+
+// Para o servidor atual (se houver) e sobe outro com a configuração vigente
+pub async fn restart(app: &AppHandle) -> Result<()> {
+    let state = app.state::<SharedSyncFeed>();
+    let previous = state.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?.take();
+    if let Some(server) = previous {
+        server.unblock();
+    }
+
+    let feed = app
+        .state::<SharedConfig>()
+        .lock()
+        .map_err(|e| anyhow::anyhow!(e.to_string()))?
+        .sync_feed
+        .clone();
+    if !feed.enabled || !feed.has_credentials() {
+        return Ok(());
+    }
+
+    // Sem endereço configurado, só este computador
+    let bind_address = match feed.bind_address.trim() {
+        "" => "127.0.0.1",
+        address => address,
+    };
+    let address = format!("{}:{}", bind_address, feed.port);
+    let mut attempt = 0;
+    let server = loop {
+        match tiny_http::Server::http(&address) {
+            Ok(server) => break Arc::new(server),
+            Err(_) if attempt + 1 < BIND_ATTEMPTS => {
+                attempt += 1;
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            }
+            Err(e) => return Err(anyhow::anyhow!("Failed to listen on {}: {}", address, e)),
+        }
+    };
+    info!("Sync feed listening on {}", address);
+    // Outro restart pode ter subido um servidor enquanto este esperava a porta
+    let replaced = state.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?.replace(server.clone());
+    if let Some(replaced) = replaced {
+        replaced.unblock();
+    }
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        // Termina quando o servidor é desbloqueado por um novo restart
+        for mut request in server.incoming_requests() {
+            // O WebSocket assume a conexão numa thread própria
+            if request.url().split('?').next() == Some(STREAM_PATH) {
+                stream(&app, request);
+                continue;
+            }
+            let response = handle(&app, &mut request);
+            if let Err(e) = request.respond(response) {
+                error!("Failed to answer sync feed request: {}", e);
+            }
+        }
+        info!("Sync feed stopped");
+    });
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+// A consulta pelo processo custa vários pgrep (e lsof no macOS): repete no máximo a cada intervalo
+const PROCESS_PROBE_INTERVAL: Duration = Duration::from_secs(10);
+
+// Último resultado da consulta: pid do terminal, quando foi lido e o diretório
+static PROCESS_CWD: Mutex<Option<(u32, Instant, Option<PathBuf>)>> = Mutex::new(None);
+
+const TERMINAL_APPS: &[&str] = &[
+    "Terminal",
+    "iTerm",
+    "iTerm2",
+    "Warp",
+    "Ghostty",
+    "Alacritty",
+    "kitty",
+    "WezTerm",
+    "wezterm-gui",
+    "Hyper",
+    "gnome-terminal-server",
+    "Gnome-terminal",
+    "org.gnome.Terminal",
+    "konsole",
+    "xterm",
+    "Tilix",
+    "foot",
+    "WindowsTerminal",
+    "Windows Terminal",
+];
+
+pub fn is_terminal(application: &str) -> bool {
+    let application = application.trim_end_matches(".exe");
+    TERMINAL_APPS.iter().any(|app| application.eq_ignore_ascii_case(app))
+}
+
+// Diretório atual do shell em foco: primeiro pelo título, depois pelo processo
+pub fn working_directory(application: &str, title: &str, process_id: Option<u32>) -> Option<String> {
+    if !is_terminal(application) {
+        return None;
+    }
+
+    let cwd = cwd_from_title(title).or_else(|| process_id.and_then(cached_process_cwd))?;
+
+    debug!("Terminal working directory for {}: {:?}", application, cwd);
+    Some(cwd.to_string_lossy().into_owned())
+}
+
+fn cached_process_cwd(pid: u32) -> Option<PathBuf> {
+    let Ok(mut cache) = PROCESS_CWD.lock() else {
+        return process_cwd(pid);
+    };
+    if let Some((cached_pid, checked_at, cwd)) = cache.as_ref() {
+        if *cached_pid == pid && checked_at.elapsed() < PROCESS_PROBE_INTERVAL {
+            return cwd.clone();
+        }
+    }
+
+    let cwd = process_cwd(pid);
+    *cache = Some((pid, Instant::now(), cwd.clone()));
+    cwd
+}
+
+// Títulos comuns: "user@host: ~/dev/app", "~/dev/app (zsh)", "C:\dev\app"
+fn cwd_from_title(title: &str) -> Option<PathBuf> {
+    title
+        .split(|c: char| c.is_whitespace() || c == '—' || c == '–')
+        .map(|token| token.trim_start_matches([':', '(']).trim_end_matches([':', ')']))
+        .filter_map(expand_path)
+        .find(|path| path.is_dir())
+}
+
+fn expand_path(token: &str) -> Option<PathBuf> {
+    if token == "~" {
+        return dirs::home_dir();
+    }
+    if let Some(rest) = token.strip_prefix("~/") {
+        return dirs::home_dir().map(|home| home.join(rest));
+    }
+
+    let bytes = token.as_bytes();
+    let is_windows_path = bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && bytes[2] == b'\\';
+    if token.starts_with('/') || is_windows_path {
+        return Some(Path::new(token).to_path_buf());
+    }
+
+    None
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// Desce até o processo filho mais recente (terminal -> login -> shell -> programa)
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn newest_descendant(pid: u32) -> Option<u32> {
+    let mut current = None;
+    let mut parent = pid;
+
+    for _ in 0..4 {
+        let Some(child) = run("pgrep", &["-P", &parent.to_string()])
+            .and_then(|out| out.lines().filter_map(|l| l.trim().parse::<u32>().ok()).max())
+        else {
+            break;
+        };
+        current = Some(child);
+        parent = child;
+    }
+
+    current
+}
+
+#[cfg(target_os = "linux")]
+fn process_cwd(pid: u32) -> Option<PathBuf> {
+    let pid = newest_descendant(pid)?;
+    std::fs::read_link(format!("/proc/{}/cwd", pid)).ok()
+}
+
+#[cfg(target_os = "macos")]
+fn process_cwd(pid: u32) -> Option<PathBuf> {
+    let pid = newest_descendant(pid)?;
+
+    // p123
+    // fcwd
+    // n/Users/me/dev/app
+    run("lsof", &["-a", "-p", &pid.to_string(), "-d", "cwd", "-Fn"])?
+        .lines()
+        .find_map(|line| line.strip_prefix('n').map(PathBuf::from))
+}
+
+// No Windows o diretório de outro processo só é acessível lendo o PEB; usa apenas o título
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn process_cwd(_pid: u32) -> Option<PathBuf> {
+    None
+}
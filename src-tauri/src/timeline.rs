@@ -0,0 +1,115 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::category::{Category, CategoryConfig};
+use crate::project::activity_project;
+use crate::tracker::WindowActivity;
+
+// Buracos menores que isso (troca de janela, flush) não viram bloco
+pub const DEFAULT_MIN_GAP_SECONDS: i64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineBlockKind {
+    Activity,
+    Idle,
+    // Nada registrado (computador desligado, tracker pausado ou parado)
+    Gap,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineBlock {
+    pub kind: TimelineBlockKind,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub duration: i64,
+    pub application: Option<String>,
+    pub title: Option<String>,
+    pub project: Option<String>,
+    pub category: Option<Category>,
+    pub activity_ids: Vec<i64>,
+}
+
+impl TimelineBlock {
+    fn gap(start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        TimelineBlock {
+            kind: TimelineBlockKind::Gap,
+            start,
+            end,
+            duration: (end - start).num_seconds(),
+            application: None,
+            title: None,
+            project: None,
+            category: None,
+            activity_ids: Vec::new(),
+        }
+    }
+
+    // Mesmo app e título (ou ocioso seguido de ocioso) continuam o bloco anterior
+    fn continues(&self, kind: TimelineBlockKind, activity: &WindowActivity) -> bool {
+        self.kind == kind
+            && (kind == TimelineBlockKind::Idle
+                || (self.application.as_deref() == Some(activity.application.as_str())
+                    && self.title.as_deref() == Some(activity.title.as_str())))
+    }
+}
+
+// Costura as atividades de [start, end) em blocos contínuos, em ordem. Sobreposições
+// são cortadas no fim do bloco anterior; buracos a partir de min_gap_seconds viram
+// blocos Gap, menores são absorvidos pelo bloco anterior
+pub fn build(
+    activities: &[WindowActivity],
+    config: &CategoryConfig,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    min_gap_seconds: i64,
+) -> Vec<TimelineBlock> {
+    let mut sorted: Vec<&WindowActivity> = activities.iter().collect();
+    sorted.sort_by_key(|a| a.start_time);
+
+    let mut blocks: Vec<TimelineBlock> = Vec::new();
+    let mut cursor = start;
+    for activity in sorted {
+        let block_start = activity.start_time.max(cursor);
+        let block_end = activity.end_time.min(end);
+        if block_end <= block_start {
+            continue;
+        }
+
+        if (block_start - cursor).num_seconds() >= min_gap_seconds {
+            blocks.push(TimelineBlock::gap(cursor, block_start));
+        } else if let Some(previous) = blocks.last_mut() {
+            previous.end = block_start;
+        }
+
+        let kind = if activity.is_idle { TimelineBlockKind::Idle } else { TimelineBlockKind::Activity };
+        match blocks.last_mut().filter(|b| b.end == block_start && b.continues(kind, activity)) {
+            Some(previous) => {
+                previous.end = block_end;
+                previous.activity_ids.extend(activity.id);
+            }
+            None => blocks.push(TimelineBlock {
+                kind,
+                start: block_start,
+                end: block_end,
+                duration: 0,
+                application: (kind == TimelineBlockKind::Activity).then(|| activity.application.clone()),
+                title: (kind == TimelineBlockKind::Activity).then(|| activity.title.clone()),
+                project: (kind == TimelineBlockKind::Activity).then(|| activity_project(activity)).flatten(),
+                category: (kind == TimelineBlockKind::Activity)
+                    .then(|| config.category_for_activity(activity).cloned())
+                    .flatten(),
+                activity_ids: activity.id.into_iter().collect(),
+            }),
+        }
+        cursor = block_end;
+    }
+
+    if (end - cursor).num_seconds() >= min_gap_seconds {
+        blocks.push(TimelineBlock::gap(cursor, end));
+    }
+    for block in &mut blocks {
+        block.duration = (block.end - block.start).num_seconds();
+    }
+    blocks
+}
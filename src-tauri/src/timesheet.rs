@@ -0,0 +1,245 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use tracing::{error, info};
+
+use crate::category::{CategoryConfig, SharedConfig};
+use crate::coverage::{self, CoverageStatus};
+use crate::database::{self, DbConnection};
+use crate::export::csv_field;
+use crate::project::activity_project;
+
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+
+// Uma linha por dia, projeto e categoria; rounded_minutes é o que vai para o timesheet
+#[derive(Debug, Clone, Serialize)]
+pub struct TimesheetEntry {
+    pub date: NaiveDate,
+    pub project: Option<String>,
+    pub category: Option<String>,
+    pub minutes: i64,
+    pub rounded_minutes: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimesheetFlagKind {
+    MissingDay,
+    SparseDay,
+    Gap,
+}
+
+// Dias e trechos do expediente sem registro, para revisar antes de enviar
+#[derive(Debug, Clone, Serialize)]
+pub struct TimesheetFlag {
+    pub date: NaiveDate,
+    pub kind: TimesheetFlagKind,
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    pub minutes: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimesheetDraft {
+    pub week_start: NaiveDate,
+    pub week_end: NaiveDate,
+    pub increment_minutes: i64,
+    pub entries: Vec<TimesheetEntry>,
+    pub flags: Vec<TimesheetFlag>,
+    pub total_minutes: i64,
+    pub path: Option<String>,
+}
+
+// Arredonda para o incremento mais próximo (15 -> 7 vira 0, 8 vira 15)
+fn round_to_increment(minutes: i64, increment: i64) -> i64 {
+    if increment <= 1 {
+        return minutes;
+    }
+    (minutes + increment / 2) / increment * increment
+}
+
+pub fn timesheet_dir(config: &CategoryConfig) -> Result<PathBuf> {
+    let dir = match config.timesheet.directory.as_deref().map(str::trim).filter(|d| !d.is_empty()) {
+        Some(directory) => PathBuf::from(directory),
+        None => database::get_database_path()?
+            .parent()
+            .ok_or_else(|| anyhow::anyhow!("Database path has no parent directory"))?
+            .join("timesheets"),
+    };
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+// Semana (conforme week_start do calendário) que contém o dia informado
+pub async fn draft(db: &DbConnection, config: &CategoryConfig, day: NaiveDate) -> Result<TimesheetDraft> {
+    let calendar = &config.calendar;
+    let (start, end) = calendar.week_bounds(day);
+    let week_start = calendar.logical_date(start);
+    let week_end = calendar.logical_date(end);
+    let increment = config.timesheet.increment_minutes.max(1);
+    let now = Utc::now();
+
+    let mut activities = database::get_activities_between(db, start, end).await?;
+    activities.retain(|a| !a.off_hours);
+    let adjustments = database::get_adjustments_between(db, week_start, week_end).await?;
+
+    let mut seconds: BTreeMap<(NaiveDate, Option<String>, Option<String>), i64> = BTreeMap::new();
+    let mut flags = Vec::new();
+    for date in week_start.iter_days().take_while(|d| *d <= week_end) {
+        let day_activities: Vec<_> = activities
+            .iter()
+            .filter(|a| calendar.logical_date(a.start_time) == date)
+            .cloned()
+            .collect();
+
+        for activity in day_activities.iter().filter(|a| !a.is_idle) {
+            let category = config.category_for_activity(activity).map(|c| c.name.clone());
+            *seconds.entry((date, activity_project(activity), category)).or_default() +=
+                (activity.end_time - activity.start_time).num_seconds();
+        }
+
+        let day_coverage = coverage::day_coverage(date, &day_activities, &config.schedule, now);
+        match day_coverage.status {
+            CoverageStatus::Missing => flags.push(TimesheetFlag {
+                date,
+                kind: TimesheetFlagKind::MissingDay,
+                start: None,
+                end: None,
+                minutes: day_coverage.expected_seconds / 60,
+            }),
+            CoverageStatus::Sparse if day_coverage.gaps.is_empty() => flags.push(TimesheetFlag {
+                date,
+                kind: TimesheetFlagKind::SparseDay,
+                start: None,
+                end: None,
+                minutes: (day_coverage.expected_seconds - day_coverage.tracked_seconds - day_coverage.idle_seconds).max(0) / 60,
+            }),
+            _ => flags.extend(day_coverage.gaps.iter().map(|gap| TimesheetFlag {
+                date,
+                kind: TimesheetFlagKind::Gap,
+                start: Some(gap.start),
+                end: Some(gap.end),
+                minutes: gap.duration / 60,
+            })),
+        }
+    }
+
+    for adjustment in &adjustments {
+        let category = config.categories.iter().find(|c| c.id == adjustment.category_id).map(|c| c.name.clone());
+        *seconds.entry((adjustment.date, None, category)).or_default() += adjustment.minutes * 60;
+    }
+
+    let entries: Vec<TimesheetEntry> = seconds
+        .into_iter()
+        .map(|((date, project, category), seconds)| {
+            let minutes = (seconds / 60).max(0);
+            TimesheetEntry {
+                date,
+                project,
+                category,
+                minutes,
+                rounded_minutes: round_to_increment(minutes, increment),
+            }
+        })
+        .filter(|entry| entry.rounded_minutes > 0)
+        .collect();
+
+    Ok(TimesheetDraft {
+        week_start,
+        week_end,
+        increment_minutes: increment,
+        total_minutes: entries.iter().map(|e| e.rounded_minutes).sum(),
+        entries,
+        flags,
+        path: None,
+    })
+}
+
+// Lançamentos e, em seguida, os buracos (sem projeto/categoria, com a descrição em flag)
+pub fn to_csv(draft: &TimesheetDraft, config: &CategoryConfig) -> String {
+    let tz = config.calendar.tz();
+    let mut lines = vec!["date,project,category,minutes,rounded_minutes,flag".to_string()];
+    for entry in &draft.entries {
+        lines.push(format!(
+            "{},{},{},{},{},",
+            entry.date,
+            csv_field(entry.project.as_deref().unwrap_or_default()),
+            csv_field(entry.category.as_deref().unwrap_or_default()),
+            entry.minutes,
+            entry.rounded_minutes,
+        ));
+    }
+    for flag in &draft.flags {
+        let description = match (&flag.kind, flag.start, flag.end) {
+            (TimesheetFlagKind::Gap, Some(start), Some(end)) => format!(
+                "gap {}-{}",
+                start.with_timezone(&tz).format("%H:%M"),
+                end.with_timezone(&tz).format("%H:%M"),
+            ),
+            (TimesheetFlagKind::MissingDay, _, _) => "missing day".to_string(),
+            _ => "sparse day".to_string(),
+        };
+        lines.push(format!("{},,,{},,{}", flag.date, flag.minutes, csv_field(&description)));
+    }
+    lines.join("\n") + "\n"
+}
+
+// Gera o rascunho da semana e grava timesheet-<início da semana>.csv
+pub async fn generate(db: &DbConnection, config: &CategoryConfig, day: NaiveDate) -> Result<TimesheetDraft> {
+    let mut draft = draft(db, config, day).await?;
+    let path = timesheet_dir(config)?.join(format!("timesheet-{}.csv", draft.week_start));
+    std::fs::write(&path, to_csv(&draft, config))?;
+    draft.path = Some(path.to_string_lossy().to_string());
+    Ok(draft)
+}
+
+pub async fn run_timesheet_job(app: AppHandle) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let config = match app.state::<SharedConfig>().lock() {
+            Ok(config) => config.clone(),
+            Err(e) => {
+                error!("Failed to lock config: {}", e);
+                continue;
+            }
+        };
+        let local_now = Utc::now().with_timezone(&config.calendar.tz()).naive_local();
+        if !config.timesheet.is_due(local_now) {
+            continue;
+        }
+
+        let db = app.state::<DbConnection>().inner().clone();
+        let draft = match generate(&db, &config, local_now.date()).await {
+            Ok(draft) => draft,
+            Err(e) => {
+                error!("Failed to generate timesheet draft: {}", e);
+                continue;
+            }
+        };
+        if let Ok(mut config) = app.state::<SharedConfig>().lock() {
+            if let Err(e) = config.record_timesheet(local_now.date()) {
+                error!("Failed to record timesheet draft: {}", e);
+            }
+        }
+        info!("Timesheet draft written to {:?}", draft.path);
+
+        let body = format!(
+            "Week of {}: {}h{:02} in {} entries, {} gaps to review. Saved to {}",
+            draft.week_start.format("%b %d"),
+            draft.total_minutes / 60,
+            draft.total_minutes % 60,
+            draft.entries.len(),
+            draft.flags.len(),
+            draft.path.as_deref().unwrap_or_default(),
+        );
+        if let Err(e) = crate::notifications::notify(&app, "Timesheet draft ready", &body).await {
+            error!("Failed to send notification: {}", e);
+        }
+    }
+}
@@ -2,14 +2,39 @@ use active_win_pos_rs::get_active_window;
 use anyhow::Error as AnyhowError;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot};
 use tokio::time;
 use tracing::{debug, error, info};
 use device_query::{DeviceQuery, DeviceState};
 
+use crate::browser;
+use crate::category::SharedConfig;
 use crate::database::{self, DbConnection};
+use crate::events::{TrackerEvent, TrackerEventSender};
+use crate::journal;
+use crate::network;
+use crate::project;
+use crate::terminal;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+const NETWORK_PROBE_INTERVAL: Duration = Duration::from_secs(60);
+// A área de trabalho é lida por subprocessos (wmctrl, reg), então não a cada tick
+const DESKTOP_PROBE_INTERVAL: Duration = Duration::from_secs(15);
+// Sem essa flag cada subprocesso abre uma janela de console no build de release
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+// Eventos ficam em memória e são gravados em lote, numa única transação, no máximo
+// a cada minuto (ou quando o tracker é encerrado)
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+// Limite do buffer caso o banco fique indisponível por muito tempo
+const MAX_PENDING_ACTIVITIES: usize = 1000;
+const MERGE_THRESHOLD_SECONDS: i64 = 300;
+const MAX_HEARTBEAT_GAP_SECONDS: i64 = 30;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct WindowActivity {
     pub title: String,
     pub application: String,
@@ -18,6 +43,28 @@ pub struct WindowActivity {
     pub is_browser: bool,
     pub url: Option<String>,
     pub is_idle: bool,
+    #[serde(default)]
+    pub desktop: Option<String>, // Space/área de trabalho virtual da janela
+    #[serde(default)]
+    pub network: Option<String>, // "vpn", "office" ou "other" quando habilitado
+    #[serde(default)]
+    pub project: Option<String>, // workspace extraído do título de editores (VS Code, JetBrains)
+    #[serde(default)]
+    pub cwd: Option<String>, // diretório atual do shell quando o app é um terminal
+    #[serde(default)]
+    pub id: Option<i64>, // rowid no banco; None enquanto a atividade não foi gravada
+    #[serde(default)]
+    pub device_id: Option<String>, // dispositivo de origem quando veio da sincronização
+    #[serde(default)]
+    pub source_id: Option<i64>, // id na origem, junto com device_id identifica a cópia sincronizada
+    #[serde(default)]
+    pub category_override: Option<String>, // categoria recategorizada por padrão, acima da do app
+    #[serde(default)]
+    pub profile: Option<String>, // perfil do navegador ("Work", "Personal") quando habilitado
+    #[serde(default)]
+    pub off_hours: bool, // registrada fora do expediente (modo Tag do horário de trabalho)
+    #[serde(default)]
+    pub manual: bool, // lançada à mão (add_manual_activity): reunião, ligação, tempo longe do computador
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -30,32 +77,81 @@ pub enum TrackerError {
     Other(#[from] AnyhowError),
 }
 
-pub struct ActivityTracker {
-    db: DbConnection,
-    current_window: Option<WindowActivity>,
-    last_activity: DateTime<Utc>,
+#[derive(Default)]
+pub struct ActiveWindow {
+    pub title: String,
+    pub app_name: String,
+    pub process_id: Option<u32>,
+    pub bounds: Option<WindowBounds>, // posição e tamanho, quando a plataforma informa
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WindowBounds {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+// Entradas do usuário desde a leitura anterior (modo de alta frequência)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InputCounts {
+    pub clicks: u32,
+    pub key_presses: u32,
+    pub scrolls: u32,
+}
+
+// Abstração da plataforma: janela em foco e tempo desde a última interação do usuário
+pub trait PlatformBackend: Send {
+    fn name(&self) -> &'static str;
+    fn active_window(&self) -> Result<ActiveWindow, TrackerError>;
+    fn idle_time(&mut self) -> Duration;
+    // Todas as janelas abertas (usado nos snapshots opcionais)
+    fn open_windows(&self) -> Vec<ActiveWindow>;
+    // Área de trabalho virtual / Space em foco
+    fn current_desktop(&self) -> Option<String>;
+    // Cliques, teclas e rolagens desde a chamada anterior
+    fn input_counts(&mut self) -> InputCounts;
+}
+
+// X11, macOS e Windows: active-win-pos-rs + polling de mouse/teclado
+pub struct NativeBackend {
     device_state: DeviceState,
-    idle_threshold: Duration,
+    last_input: Instant,
     last_mouse_position: (i32, i32),
 }
 
-impl ActivityTracker {
-    pub async fn new(db: DbConnection) -> Self {
+impl NativeBackend {
+    pub fn new() -> Self {
         Self {
-            db,
-            current_window: None,
-            last_activity: Utc::now(),
             device_state: DeviceState::new(),
-            idle_threshold: Duration::from_secs(180), // 3 minutes default
+            last_input: Instant::now(),
             last_mouse_position: (0, 0),
         }
     }
+}
 
-    pub fn set_idle_threshold(&mut self, seconds: u64) {
-        self.idle_threshold = Duration::from_secs(seconds);
+impl PlatformBackend for NativeBackend {
+    fn name(&self) -> &'static str {
+        "native"
     }
 
-    fn check_activity(&mut self) -> bool {
+    fn active_window(&self) -> Result<ActiveWindow, TrackerError> {
+        let window = get_active_window().map_err(|_| TrackerError::WindowError(()))?;
+        Ok(ActiveWindow {
+            title: window.title,
+            app_name: window.app_name,
+            process_id: u32::try_from(window.process_id).ok(),
+            bounds: Some(WindowBounds {
+                x: window.position.x,
+                y: window.position.y,
+                width: window.position.width,
+                height: window.position.height,
+            }),
+        })
+    }
+
+    fn idle_time(&mut self) -> Duration {
         let current_mouse = self.device_state.get_mouse().coords;
         let keyboard_pressed = !self.device_state.get_keys().is_empty();
         let mouse_moved = current_mouse != self.last_mouse_position;
@@ -67,32 +163,437 @@ impl ActivityTracker {
                 keyboard_pressed,
                 self.last_mouse_position
             );
-            self.last_activity = Utc::now();
+            self.last_input = Instant::now();
             self.last_mouse_position = current_mouse;
-            true
-        } else {
-            let idle_duration = Utc::now()
-                .signed_duration_since(self.last_activity)
-                .to_std()
-                .unwrap_or(Duration::from_secs(0));
-            
-            let is_active = idle_duration < self.idle_threshold;
-            debug!(
-                "Checking idle - Duration: {:.1?}, Threshold: {:.1?}, Is Active: {}, Mouse: {:?}",
+        }
+
+        self.last_input.elapsed()
+    }
+
+    fn open_windows(&self) -> Vec<ActiveWindow> {
+        native_open_windows()
+    }
+
+    fn current_desktop(&self) -> Option<String> {
+        native_current_desktop()
+    }
+
+    fn input_counts(&mut self) -> InputCounts {
+        input_hook::start();
+        input_hook::take()
+    }
+}
+
+// O device_query só dá o estado atual (perde cliques entre leituras e não vê a rolagem);
+// as contagens vêm de um hook global do rdev, iniciado na primeira leitura. Só contadores
+// são mantidos, nunca quais teclas
+mod input_hook {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Once;
+    use tracing::error;
+
+    use super::InputCounts;
+
+    static CLICKS: AtomicU32 = AtomicU32::new(0);
+    static KEY_PRESSES: AtomicU32 = AtomicU32::new(0);
+    static SCROLLS: AtomicU32 = AtomicU32::new(0);
+    static STARTED: Once = Once::new();
+
+    pub fn start() {
+        STARTED.call_once(|| {
+            std::thread::spawn(|| {
+                let result = rdev::listen(|event| {
+                    let counter = match event.event_type {
+                        rdev::EventType::ButtonPress(_) => &CLICKS,
+                        rdev::EventType::KeyPress(_) => &KEY_PRESSES,
+                        rdev::EventType::Wheel { .. } => &SCROLLS,
+                        _ => return,
+                    };
+                    counter.fetch_add(1, Ordering::Relaxed);
+                });
+                if let Err(e) = result {
+                    error!("Failed to listen for input events: {:?}", e);
+                }
+            });
+        });
+    }
+
+    pub fn take() -> InputCounts {
+        InputCounts {
+            clicks: CLICKS.swap(0, Ordering::Relaxed),
+            key_presses: KEY_PRESSES.swap(0, Ordering::Relaxed),
+            scrolls: SCROLLS.swap(0, Ordering::Relaxed),
+        }
+    }
+}
+
+pub fn detect_backend() -> Box<dyn PlatformBackend> {
+    #[cfg(target_os = "linux")]
+    {
+        if wayland::is_wayland_session() {
+            info!("Wayland session detected, using Wayland backend");
+            return Box::new(wayland::WaylandBackend::new());
+        }
+    }
+
+    Box::new(NativeBackend::new())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TrackerCommand {
+    // Grava o evento em andamento (encerramento do app)
+    Shutdown,
+    // Grava o buffer e o evento em andamento sem encerrá-lo
+    Flush,
+    // Grava e esquece o evento em andamento, para quando o banco é trocado ou apagado
+    Reset,
+}
+
+type CommandSender = mpsc::Sender<(TrackerCommand, oneshot::Sender<()>)>;
+type CommandReceiver = mpsc::Receiver<(TrackerCommand, oneshot::Sender<()>)>;
+
+// Permite controlar o tracker de fora da task em que ele roda (ex.: "Quit" na bandeja).
+// O canal é trocado a cada reinício do tracker pelo watchdog
+#[derive(Clone, Default)]
+pub struct TrackerHandle {
+    commands: Arc<std::sync::Mutex<Option<CommandSender>>>,
+}
+
+impl TrackerHandle {
+    // Pede ao tracker para gravar o evento em andamento e espera a confirmação
+    pub async fn shutdown(&self) {
+        if self.send(TrackerCommand::Shutdown).await {
+            info!("Tracker flushed before shutdown");
+        }
+    }
+
+    // Garante que o que foi registrado até agora está no banco antes de uma leitura
+    pub async fn flush(&self) {
+        self.send(TrackerCommand::Flush).await;
+    }
+
+    // Antes de restaurar, mover ou apagar dados: nada do que está em memória deve ser
+    // regravado depois (nem atualizar uma linha pelo id antigo)
+    pub async fn reset(&self) {
+        self.send(TrackerCommand::Reset).await;
+    }
+
+    // Sem tracker rodando não há nada em memória; retorna se o tracker confirmou
+    async fn send(&self, command: TrackerCommand) -> bool {
+        let sender = match self.commands.lock() {
+            Ok(sender) => sender.clone(),
+            Err(e) => {
+                error!("Failed to lock tracker handle: {}", e);
+                return false;
+            }
+        };
+
+        let (ack, done) = oneshot::channel();
+        let Some(sender) = sender else {
+            return false;
+        };
+        if sender.send((command, ack)).await.is_err() {
+            return false;
+        }
+
+        match time::timeout(Duration::from_secs(3), done).await {
+            Ok(Ok(())) => true,
+            _ => {
+                error!("Timed out waiting for tracker to handle {:?}", command);
+                false
+            }
+        }
+    }
+}
+
+// Estado de saúde do loop do tracker, compartilhado com o watchdog e o comando de status
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TrackerHealth {
+    pub started_at: Option<DateTime<Utc>>,
+    pub last_tick: Option<DateTime<Utc>>,
+    // Último tick em que a janela ativa foi lida e registrada sem erro
+    pub last_successful_tick: Option<DateTime<Utc>>,
+    pub restarts: u32,
+    pub last_restart_reason: Option<String>,
+    pub paused: bool,
+    pub consecutive_window_errors: u32,
+    // Títulos vazios seguidos costumam indicar falta de permissão (ex.: Screen Recording no macOS)
+    pub consecutive_empty_titles: u32,
+    pub recent_errors: VecDeque<TrackerErrorRecord>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackerErrorRecord {
+    pub at: DateTime<Utc>,
+    pub message: String,
+}
+
+const MAX_RECENT_ERRORS: usize = 100;
+
+impl TrackerHealth {
+    pub fn record_error(&mut self, message: String) {
+        if self.recent_errors.len() >= MAX_RECENT_ERRORS {
+            self.recent_errors.pop_front();
+        }
+        self.recent_errors.push_back(TrackerErrorRecord { at: Utc::now(), message });
+    }
+
+    pub fn errors_since(&self, since: DateTime<Utc>) -> usize {
+        self.recent_errors.iter().filter(|e| e.at >= since).count()
+    }
+}
+
+pub type SharedTrackerHealth = Arc<std::sync::Mutex<TrackerHealth>>;
+
+pub struct ActivityTracker {
+    db: DbConnection,
+    config: SharedConfig,
+    backend: Box<dyn PlatformBackend>,
+    current_window: Option<WindowActivity>,
+    current_row_id: Option<i64>,
+    // Eventos encerrados aguardando o próximo flush, com a linha já gravada (se houver)
+    pending: Vec<(WindowActivity, Option<i64>)>,
+    last_flush: Instant,
+    events: TrackerEventSender,
+    idle_since: Option<DateTime<Utc>>,
+    commands: Option<CommandReceiver>,
+    health: SharedTrackerHealth,
+    idle_threshold: Duration,
+    // Quando a ociosidade passou do limite mas ainda está dentro da tolerância
+    tentative_idle_since: Option<DateTime<Utc>>,
+    network_context: Option<(Instant, Option<String>)>,
+    desktop_context: Option<(Instant, Option<String>)>,
+    // Mídia tocando segurou o ocioso no tick anterior (o log sai só na transição)
+    suppressing_idle_for_media: bool,
+    last_window_snapshot: Option<Instant>,
+}
+
+impl ActivityTracker {
+    pub async fn new(db: DbConnection, config: SharedConfig, events: TrackerEventSender) -> Self {
+        let backend = detect_backend();
+        info!("Using {} platform backend", backend.name());
+
+        Self {
+            db,
+            config,
+            backend,
+            current_window: None,
+            current_row_id: None,
+            pending: Vec::new(),
+            last_flush: Instant::now(),
+            events,
+            idle_since: None,
+            commands: None,
+            health: SharedTrackerHealth::default(),
+            idle_threshold: Duration::from_secs(180), // 3 minutes default
+            tentative_idle_since: None,
+            network_context: None,
+            desktop_context: None,
+            suppressing_idle_for_media: false,
+            last_window_snapshot: None,
+        }
+    }
+
+    pub fn set_idle_threshold(&mut self, seconds: u64) {
+        self.idle_threshold = Duration::from_secs(seconds);
+    }
+
+    fn check_activity(&mut self) -> bool {
+        let idle_duration = self.backend.idle_time();
+        let is_active = idle_duration < self.idle_threshold;
+        debug!(
+            "Checking idle - Duration: {:.1?}, Threshold: {:.1?}, Is Active: {}",
+            idle_duration,
+            self.idle_threshold,
+            is_active
+        );
+
+        if !is_active {
+            info!(
+                "🔍 IDLE DETECTED - No activity for {:.1?} (threshold: {:.1?})",
                 idle_duration,
-                self.idle_threshold,
-                is_active,
-                current_mouse
+                self.idle_threshold
             );
-            
-            if !is_active {
-                info!(
-                    "🔍 IDLE DETECTED - No activity for {:.1?} (threshold: {:.1?})",
-                    idle_duration,
-                    self.idle_threshold
-                );
+        }
+        is_active
+    }
+
+    // Padrões configurados têm prioridade sobre a detecção automática
+    fn project_from_patterns(&self, cwd: Option<&str>, title: &str) -> Option<String> {
+        let config = match self.config.lock() {
+            Ok(config) => config,
+            Err(e) => {
+                error!("Failed to lock config: {}", e);
+                return None;
             }
-            is_active
+        };
+        if config.project_patterns.is_empty() {
+            return None;
+        }
+
+        cwd.map(str::to_string)
+            .into_iter()
+            .chain(project::path_in_title(title))
+            .find_map(|path| project::project_for_patterns(&path, &config.project_patterns))
+    }
+
+    fn idle_grace(&self) -> chrono::Duration {
+        let seconds = match self.config.lock() {
+            Ok(config) => config.idle.grace_seconds,
+            Err(e) => {
+                error!("Failed to lock config: {}", e);
+                0
+            }
+        };
+        chrono::Duration::seconds(seconds as i64)
+    }
+
+    // Segura a transição para ocioso durante a tolerância: se o usuário voltar antes,
+    // a atividade continua sem fragmento; se não, o ocioso começa no instante em que
+    // o limite foi atingido. Retorna o início do evento ocioso quando confirmado
+    fn apply_idle_grace(&mut self, is_active: &mut bool, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let current_is_active = self.current_window.as_ref().map_or(false, |c| !c.is_idle);
+        if *is_active || !current_is_active {
+            self.tentative_idle_since = None;
+            return None;
+        }
+
+        let since = *self.tentative_idle_since.get_or_insert(now);
+        if now - since < self.idle_grace() {
+            debug!("Idle since {} still within grace period", since.to_rfc3339());
+            *is_active = true;
+            return None;
+        }
+
+        self.tentative_idle_since = None;
+        Some(since)
+    }
+
+    fn is_playing_media(&self, app_name: &str) -> bool {
+        let applies = match self.config.lock() {
+            Ok(config) => config.media_playback.applies_to(app_name),
+            Err(e) => {
+                error!("Failed to lock config: {}", e);
+                false
+            }
+        };
+
+        if !applies || app_name.is_empty() {
+            return false;
+        }
+
+        // "Google Chrome" também casa com processos auxiliares como "Google Chrome Helper"
+        let app_name = app_name.to_lowercase();
+        media_playing_apps()
+            .iter()
+            .any(|playing| playing.to_lowercase().starts_with(&app_name))
+    }
+
+    // Perfil do navegador pelo título da janela, quando habilitado
+    fn browser_profile(&self, application: &str, title: &str) -> Option<String> {
+        let config = match self.config.lock() {
+            Ok(config) => config,
+            Err(e) => {
+                error!("Failed to lock config: {}", e);
+                return None;
+            }
+        };
+        if !config.browser_profiles.enabled {
+            return None;
+        }
+        browser::detect_profile(application, title, &config.browser_profiles.profiles)
+    }
+
+    // Consulta a rede no máximo uma vez por minuto
+    fn current_network(&mut self) -> Option<String> {
+        let config = match self.config.lock() {
+            Ok(config) => config.network_tagging.clone(),
+            Err(e) => {
+                error!("Failed to lock config: {}", e);
+                return None;
+            }
+        };
+
+        if !config.enabled {
+            return None;
+        }
+
+        if let Some((checked_at, network)) = &self.network_context {
+            if checked_at.elapsed() < NETWORK_PROBE_INTERVAL {
+                return network.clone();
+            }
+        }
+
+        let network = network::probe(&config);
+        debug!("Network context: {:?}", network);
+        self.network_context = Some((Instant::now(), network.clone()));
+        network
+    }
+
+    // Consulta a área de trabalho no máximo a cada DESKTOP_PROBE_INTERVAL
+    fn current_desktop(&mut self) -> Option<String> {
+        if let Some((checked_at, desktop)) = &self.desktop_context {
+            if checked_at.elapsed() < DESKTOP_PROBE_INTERVAL {
+                return desktop.clone();
+            }
+        }
+
+        let desktop = self.backend.current_desktop();
+        self.desktop_context = Some((Instant::now(), desktop.clone()));
+        desktop
+    }
+
+    // Snapshot opcional de todas as janelas abertas, em tabela separada
+    async fn snapshot_open_windows(&mut self) -> Result<(), TrackerError> {
+        let config = match self.config.lock() {
+            Ok(config) => config.window_snapshots.clone(),
+            Err(e) => {
+                error!("Failed to lock config: {}", e);
+                return Ok(());
+            }
+        };
+
+        if !config.enabled {
+            return Ok(());
+        }
+
+        let interval = Duration::from_secs(config.interval_minutes.max(1) * 60);
+        if self.last_window_snapshot.map_or(false, |last| last.elapsed() < interval) {
+            return Ok(());
+        }
+        self.last_window_snapshot = Some(Instant::now());
+
+        let windows = self.backend.open_windows();
+        debug!("📸 Snapshot of {} open windows", windows.len());
+
+        let windows: Vec<(String, String)> = windows
+            .into_iter()
+            .map(|w| (w.app_name, w.title))
+            .collect();
+        database::save_window_snapshot(&self.db, Utc::now(), &windows)
+            .await
+            .map_err(AnyhowError::from)?;
+
+        Ok(())
+    }
+
+    pub fn attach(&mut self, handle: &TrackerHandle) {
+        let (sender, receiver) = mpsc::channel(1);
+        self.commands = Some(receiver);
+        match handle.commands.lock() {
+            Ok(mut commands) => *commands = Some(sender),
+            Err(e) => error!("Failed to lock tracker handle: {}", e),
+        }
+    }
+
+    pub fn set_health(&mut self, health: SharedTrackerHealth) {
+        self.health = health;
+    }
+
+    async fn next_command(receiver: &mut Option<CommandReceiver>) -> Option<(TrackerCommand, oneshot::Sender<()>)> {
+        match receiver {
+            Some(receiver) => receiver.recv().await,
+            None => std::future::pending().await,
         }
     }
 
@@ -101,28 +602,150 @@ impl ActivityTracker {
         let mut interval = time::interval(Duration::from_secs(5)); // Check every 5 seconds
 
         loop {
-            interval.tick().await;
-            match self.track_current_window().await {
+            tokio::select! {
+                _ = interval.tick() => {}
+                Some((command, ack)) = Self::next_command(&mut self.commands) => {
+                    self.finish().await;
+                    if command == TrackerCommand::Reset {
+                        self.current_window = None;
+                        self.current_row_id = None;
+                        self.pending.clear();
+                    }
+                    let _ = ack.send(());
+                    continue;
+                }
+            }
+
+            // No horário sensível (ou fora do expediente, no modo Pause) a janela ativa
+            // nem é lida, como numa pausa
+            if self.is_paused() || self.in_blackout() || self.outside_schedule() {
+                // Fecha o evento em andamento uma única vez e não registra nada enquanto pausado
+                if self.current_window.is_some() {
+                    self.finish().await;
+                    self.current_window = None;
+                    self.current_row_id = None;
+                }
+                if let Ok(mut health) = self.health.lock() {
+                    health.last_tick = Some(Utc::now());
+                }
+                continue;
+            }
+
+            let result = self.track_current_window().await;
+            if let Ok(mut health) = self.health.lock() {
+                let now = Utc::now();
+                // Sinal de vida para o watchdog
+                health.last_tick = Some(now);
+                match &result {
+                    Ok(_) => {
+                        health.last_successful_tick = Some(now);
+                        health.consecutive_window_errors = 0;
+                    }
+                    Err(e) => {
+                        if matches!(e, TrackerError::WindowError(_)) {
+                            health.consecutive_window_errors += 1;
+                        }
+                        health.record_error(e.to_string());
+                    }
+                }
+            }
+
+            match result {
                 Ok(_) => debug!("Successfully tracked window"),
                 Err(e) => error!("Error tracking window: {}", e),
             }
+
+            if let Err(e) = self.snapshot_open_windows().await {
+                error!("Error saving window snapshot: {}", e);
+                if let Ok(mut health) = self.health.lock() {
+                    health.record_error(format!("Window snapshot: {}", e));
+                }
+            }
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        self.health.lock().map_or(false, |health| health.paused)
+    }
+
+    fn in_blackout(&self) -> bool {
+        crate::category::blackout_now(&self.config)
+    }
+
+    fn outside_schedule(&self) -> bool {
+        match self.config.lock() {
+            Ok(config) => config.schedule.pauses_at(&config.calendar, Utc::now()),
+            Err(e) => {
+                error!("Failed to lock config: {}", e);
+                false
+            }
+        }
+    }
+
+    fn off_hours(&self, now: DateTime<Utc>) -> bool {
+        self.config.lock().map_or(false, |config| config.schedule.tags_at(&config.calendar, now))
+    }
+
+    // Fecha o evento atual no instante do encerramento e grava no banco
+    async fn finish(&mut self) {
+        if let Some(current) = self.current_window.as_mut() {
+            current.end_time = Utc::now();
+        }
+
+        match self.flush().await {
+            Ok(_) => info!("Flushed current activity"),
+            Err(e) => error!("Failed to flush current activity: {}", e),
         }
     }
 
     async fn track_current_window(&mut self) -> Result<(), TrackerError> {
-        let window = get_active_window().map_err(|_| TrackerError::WindowError(()))?;
+        let window = self.backend.active_window()?;
+        if let Ok(mut health) = self.health.lock() {
+            if window.title.trim().is_empty() {
+                health.consecutive_empty_titles += 1;
+            } else {
+                health.consecutive_empty_titles = 0;
+            }
+        }
         
         let now = Utc::now();
-        let is_active = self.check_activity();
+        let mut is_active = self.check_activity();
+
+        // Vídeo/áudio tocando no app ativo não deve ser marcado como ocioso
+        let suppress_idle = !is_active && self.is_playing_media(&window.app_name);
+        if suppress_idle {
+            if !self.suppressing_idle_for_media {
+                info!("🎬 Media playback in {} - suppressing idle", window.app_name);
+            }
+            is_active = true;
+        }
+        self.suppressing_idle_for_media = suppress_idle;
+        let idle_start = self.apply_idle_grace(&mut is_active, now);
         
+        let cwd = terminal::working_directory(&window.app_name, &window.title, window.process_id);
+        let project = self.project_from_patterns(cwd.as_deref(), &window.title)
+            .or_else(|| project::detect_project(&window.app_name, &window.title))
+            .or_else(|| cwd.as_deref().and_then(project::project_for_directory));
+
         let activity = WindowActivity {
             title: window.title.clone(),
             application: window.app_name.clone(),
-            start_time: now,
+            start_time: idle_start.unwrap_or(now),
             end_time: now,
             is_browser: false,
             url: None,
             is_idle: !is_active,
+            desktop: self.current_desktop(),
+            network: self.current_network(),
+            project,
+            cwd,
+            id: None,
+            device_id: None,
+            source_id: None,
+            category_override: None,
+            profile: self.browser_profile(&window.app_name, &window.title),
+            off_hours: self.off_hours(now),
+            manual: false,
         };
 
         info!(
@@ -134,55 +757,648 @@ impl ActivityTracker {
             now.to_rfc3339()
         );
 
-        // Verifica se devemos criar uma nova atividade ou atualizar a existente
-        if let Some(current) = &self.current_window {
-            if current.application == activity.application 
-                && current.title == activity.title 
-                && current.is_idle == activity.is_idle {
-                // Atualiza a atividade existente
-                let mut updated = current.clone();
-                updated.end_time = now;
-                
-                info!(
-                    "🔄 Updating existing activity: {} - {} (idle: {}) | {} -> {}", 
-                    updated.application,
-                    updated.title,
-                    updated.is_idle,
-                    updated.start_time.to_rfc3339(),
-                    updated.end_time.to_rfc3339()
-                );
-
-                database::merge_activity(&self.db, &updated, 300)
-                    .await
-                    .map_err(AnyhowError::from)?;
-            } else {
-                // Cria uma nova atividade
-                info!(
-                    "➕ Creating new activity: {} - {} (idle: {})",
-                    activity.application,
-                    activity.title,
-                    activity.is_idle
-                );
-                
-                database::merge_activity(&self.db, &activity, 300)
-                    .await
-                    .map_err(AnyhowError::from)?;
+        self.track_idle_transition(activity.is_idle, activity.start_time);
+        self.heartbeat(activity, now).await
+    }
+
+    fn emit(&self, event: TrackerEvent) {
+        // Sem receptor (ex.: antes da janela existir) o evento é simplesmente descartado
+        if self.events.send(event).is_err() {
+            debug!("No listener for tracker events");
+        }
+    }
+
+    fn track_idle_transition(&mut self, is_idle: bool, now: DateTime<Utc>) {
+        match (is_idle, self.idle_since) {
+            (true, None) => {
+                self.idle_since = Some(now);
+                self.emit(TrackerEvent::IdleStarted { started_at: now });
+            }
+            (false, Some(started_at)) => {
+                self.idle_since = None;
+                self.emit(TrackerEvent::IdleEnded {
+                    started_at,
+                    ended_at: now,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    // Cada leitura é um heartbeat: estende o evento atual em memória; eventos encerrados
+    // vão para o buffer e tudo é gravado junto quando o intervalo de flush expira
+    async fn heartbeat(&mut self, activity: WindowActivity, now: DateTime<Utc>) -> Result<(), TrackerError> {
+        let continues_current = self.current_window.as_ref().map_or(false, |current| {
+            current.application == activity.application
+                && current.title == activity.title
+                && current.is_idle == activity.is_idle
+                && current.desktop == activity.desktop
+                && current.network == activity.network
+                && current.cwd == activity.cwd
+                && current.off_hours == activity.off_hours
+                // Um intervalo grande entre heartbeats (suspensão) encerra o evento
+                && (now - current.end_time).num_seconds() <= MAX_HEARTBEAT_GAP_SECONDS
+        });
+
+        if continues_current {
+            if let Some(current) = self.current_window.as_mut() {
+                current.end_time = now;
             }
         } else {
-            // Primeira atividade
+            // O evento anterior termina no último heartbeat que o confirmou
+            if let Some(mut previous) = self.current_window.take() {
+                // Ocioso confirmado após a tolerância começa antes do heartbeat atual, mas
+                // pode recuar até antes do início da janela anterior: nunca termina antes de começar
+                previous.end_time = previous.end_time.min(activity.start_time).max(previous.start_time);
+                if self.pending.len() >= MAX_PENDING_ACTIVITIES {
+                    error!("Activity buffer full, dropping oldest pending activity");
+                    self.pending.remove(0);
+                }
+                self.pending.push((previous, self.current_row_id.take()));
+            }
+
             info!(
-                "🆕 First activity: {} - {} (idle: {})",
+                "➕ New event: {} - {} (idle: {})",
                 activity.application,
                 activity.title,
                 activity.is_idle
             );
-            
-            database::merge_activity(&self.db, &activity, 300)
-                .await
-                .map_err(AnyhowError::from)?;
+            self.emit(TrackerEvent::ActivityChanged(activity.clone()));
+            self.current_window = Some(activity);
         }
-        
-        self.current_window = Some(activity);
+
+        if self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    // Grava o buffer e o evento atual numa única transação. Cada evento é gravado a
+    // primeira vez via merge (que pode reaproveitar uma linha recente igual), depois
+    // apenas atualiza o end_time da mesma linha
+    pub async fn flush(&mut self) -> Result<(), TrackerError> {
+        self.last_flush = Instant::now();
+
+        if self.pending.is_empty() && self.current_window.is_none() {
+            return Ok(());
+        }
+
+        debug!(
+            "💾 Flushing {} completed events and current event {:?}",
+            self.pending.len(),
+            self.current_window.as_ref().map(|c| (&c.application, &c.title))
+        );
+
+        // Em caso de erro o buffer é mantido e o próximo flush tenta de novo
+        let current_row_id = database::write_activity_batch(
+            &self.db,
+            &self.pending,
+            self.current_window.as_ref().map(|current| (current, self.current_row_id)),
+            MERGE_THRESHOLD_SECONDS,
+        )
+        .await
+        .map_err(AnyhowError::from)?;
+
+        self.write_journal();
+        self.pending.clear();
+        self.current_row_id = current_row_id;
         Ok(())
     }
-} 
\ No newline at end of file
+}
+
+// Entrada do replay (replay.rs): mesmo caminho de track_current_window a partir do
+// heartbeat, com a janela e o estado de ociosidade vindos de uma amostra gravada
+#[cfg(debug_assertions)]
+impl ActivityTracker {
+    pub fn for_replay(db: DbConnection, config: SharedConfig) -> Self {
+        // Ninguém escuta os eventos do replay
+        let (events, _) = mpsc::unbounded_channel();
+        let mut tracker = Self {
+            db,
+            config,
+            backend: detect_backend(),
+            current_window: None,
+            current_row_id: None,
+            pending: Vec::new(),
+            last_flush: Instant::now(),
+            events,
+            idle_since: None,
+            commands: None,
+            health: SharedTrackerHealth::default(),
+            idle_threshold: Duration::from_secs(180),
+            tentative_idle_since: None,
+            network_context: None,
+            desktop_context: None,
+            suppressing_idle_for_media: false,
+            last_window_snapshot: None,
+        };
+        // O flush segue o relógio simulado, controlado por replay_flush
+        tracker.last_flush = Instant::now() + Duration::from_secs(365 * 24 * 3600);
+        tracker
+    }
+
+    pub async fn replay_sample(&mut self, sample: &WindowActivity, now: DateTime<Utc>) -> Result<(), TrackerError> {
+        let mut is_active = !sample.is_idle;
+        let idle_start = self.apply_idle_grace(&mut is_active, now);
+        let activity = WindowActivity {
+            start_time: idle_start.unwrap_or(now),
+            end_time: now,
+            is_idle: !is_active,
+            id: None,
+            device_id: None,
+            source_id: None,
+            category_override: None,
+            ..sample.clone()
+        };
+
+        self.track_idle_transition(activity.is_idle, activity.start_time);
+        self.heartbeat(activity, now).await
+    }
+
+    pub async fn replay_flush(&mut self) -> Result<(), TrackerError> {
+        self.flush().await?;
+        self.last_flush = Instant::now() + Duration::from_secs(365 * 24 * 3600);
+        Ok(())
+    }
+}
+
+impl ActivityTracker {
+    // Cópia dos trechos encerrados no diário; falhas não impedem a gravação no banco
+    fn write_journal(&self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let config = match self.config.lock() {
+            Ok(config) if config.journal.enabled => config.clone(),
+            Ok(_) => return,
+            Err(e) => {
+                error!("Failed to lock config: {}", e);
+                return;
+            }
+        };
+
+        let completed: Vec<WindowActivity> = self.pending.iter().map(|(activity, _)| activity.clone()).collect();
+        if let Err(e) = journal::append(&config, &completed) {
+            error!("Failed to write activity journal: {}", e);
+        }
+    }
+}
+
+// Identifica o Space (macOS) ativo no momento
+#[cfg(target_os = "macos")]
+fn native_current_desktop() -> Option<String> {
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGSMainConnectionID() -> i32;
+        fn CGSGetActiveSpace(connection: i32) -> u64;
+    }
+
+    // API privada, mas estável e usada pela maioria dos gerenciadores de janela
+    let space = unsafe { CGSGetActiveSpace(CGSMainConnectionID()) };
+    (space != 0).then(|| format!("Space {}", space))
+}
+
+// X11: o gerenciador de janelas marca a área de trabalho atual com "*"
+//   0  * DG: 3840x1080  VP: 0,0  WA: 0,0 3840x1080  Work
+#[cfg(target_os = "linux")]
+fn native_current_desktop() -> Option<String> {
+    let output = std::process::Command::new("wmctrl").arg("-d").output().ok()?;
+
+    String::from_utf8_lossy(&output.stdout).lines().find_map(|line| {
+        let columns: Vec<&str> = line.split_whitespace().collect();
+        if columns.get(1) != Some(&"*") {
+            return None;
+        }
+
+        let index: usize = columns.first()?.parse().ok()?;
+        let name = line.split("  ").last().map(|n| n.trim()).unwrap_or_default();
+        if name.is_empty() || name.starts_with("WA:") || name == "N/A" {
+            Some(format!("Desktop {}", index + 1))
+        } else {
+            Some(name.to_string())
+        }
+    })
+}
+
+// Windows 10/11 guarda os GUIDs das áreas de trabalho e a atual no registro
+#[cfg(target_os = "windows")]
+fn native_current_desktop() -> Option<String> {
+    use std::os::windows::process::CommandExt;
+
+    const KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Explorer\VirtualDesktops";
+
+    let query = |value: &str| -> Option<String> {
+        let output = std::process::Command::new("reg")
+            .args(["query", KEY, "/v", value])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .ok()?;
+        // CurrentVirtualDesktop    REG_BINARY    1A2B3C...
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .find(|line| line.contains("REG_BINARY"))
+            .and_then(|line| line.split_whitespace().last())
+            .map(|hex| hex.to_string())
+    };
+
+    let current = query("CurrentVirtualDesktop")?;
+    let ids = query("VirtualDesktopIDs")?;
+
+    // Cada GUID ocupa 16 bytes (32 caracteres hex)
+    (0..ids.len() / 32)
+        .find(|i| ids.get(i * 32..(i + 1) * 32) == Some(current.as_str()))
+        .map(|i| format!("Desktop {}", i + 1))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn native_current_desktop() -> Option<String> {
+    None
+}
+
+#[cfg(target_os = "macos")]
+fn native_open_windows() -> Vec<ActiveWindow> {
+    const SCRIPT: &str = r#"
+        set output to ""
+        tell application "System Events"
+            repeat with proc in (processes whose background only is false)
+                repeat with win in windows of proc
+                    set output to output & (name of proc) & tab & (name of win) & linefeed
+                end repeat
+            end repeat
+        end tell
+        return output
+    "#;
+
+    let Ok(output) = std::process::Command::new("osascript").args(["-e", SCRIPT]).output() else {
+        return Vec::new();
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(app, title)| ActiveWindow {
+            title: title.to_string(),
+            app_name: app.to_string(),
+            ..Default::default()
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn native_open_windows() -> Vec<ActiveWindow> {
+    // 0x03a00003  0 Navigator.firefox  host Título da janela
+    let Ok(output) = std::process::Command::new("wmctrl").arg("-lx").output() else {
+        return Vec::new();
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.split_whitespace();
+            let class = columns.nth(2)?;
+            let _host = columns.next()?;
+            Some(ActiveWindow {
+                title: columns.collect::<Vec<_>>().join(" "),
+                app_name: class.rsplit('.').next().unwrap_or(class).to_string(),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn native_open_windows() -> Vec<ActiveWindow> {
+    use std::os::windows::process::CommandExt;
+
+    // "Image Name","PID",...,"Window Title"
+    let Ok(output) = std::process::Command::new("tasklist")
+        .args(["/v", "/fo", "csv", "/nh"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+    else {
+        return Vec::new();
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let columns: Vec<&str> = line.trim_matches('"').split("\",\"").collect();
+            let title = columns.last()?;
+            (columns.len() >= 9 && *title != "N/A").then(|| ActiveWindow {
+                title: title.to_string(),
+                app_name: columns[0].trim_end_matches(".exe").to_string(),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn native_open_windows() -> Vec<ActiveWindow> {
+    Vec::new()
+}
+
+// Lista os apps que estão reproduzindo mídia no momento
+#[cfg(target_os = "macos")]
+fn media_playing_apps() -> Vec<String> {
+    // Players de vídeo seguram um assertion PreventUserIdleDisplaySleep enquanto tocam:
+    //   pid 123(Google Chrome): [0x...] 00:01:02 PreventUserIdleDisplaySleep named: "Video Wake Lock"
+    let Ok(output) = std::process::Command::new("pmset").args(["-g", "assertions"]).output() else {
+        return Vec::new();
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| line.contains("PreventUserIdleDisplaySleep") || line.contains("PreventUserIdleSystemSleep"))
+        .filter_map(|line| {
+            let start = line.find('(')? + 1;
+            let end = line[start..].find(')')? + start;
+            Some(line[start..end].to_string())
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn media_playing_apps() -> Vec<String> {
+    // Streams do PulseAudio/PipeWire que não estão pausados (Corked: no)
+    let Ok(output) = std::process::Command::new("pactl").args(["list", "sink-inputs"]).output() else {
+        return Vec::new();
+    };
+
+    let mut apps = Vec::new();
+    let mut corked = true;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let line = line.trim();
+        if line.starts_with("Sink Input #") {
+            corked = true;
+        } else if let Some(value) = line.strip_prefix("Corked:") {
+            corked = value.trim() == "yes";
+        } else if let Some(value) = line.strip_prefix("application.name = ") {
+            if !corked {
+                apps.push(value.trim_matches('"').to_string());
+            }
+        }
+    }
+    apps
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn media_playing_apps() -> Vec<String> {
+    Vec::new()
+}
+
+// Wayland não permite consultar a janela ativa nem o estado do mouse/teclado
+// diretamente, então usamos o que cada compositor expõe
+#[cfg(target_os = "linux")]
+mod wayland {
+    use std::process::Command;
+    use std::time::Duration;
+    use tracing::{debug, warn};
+
+    use super::{ActiveWindow, InputCounts, PlatformBackend, TrackerError, WindowBounds};
+
+    pub fn is_wayland_session() -> bool {
+        std::env::var("XDG_SESSION_TYPE").map_or(false, |t| t == "wayland")
+            || std::env::var("WAYLAND_DISPLAY").is_ok()
+    }
+
+    pub struct WaylandBackend {
+        warned_idle: bool,
+        warned_input: bool,
+    }
+
+    impl WaylandBackend {
+        pub fn new() -> Self {
+            Self { warned_idle: false, warned_input: false }
+        }
+    }
+
+    impl PlatformBackend for WaylandBackend {
+        fn name(&self) -> &'static str {
+            "wayland"
+        }
+
+        fn active_window(&self) -> Result<ActiveWindow, TrackerError> {
+            hyprland_active_window()
+                .or_else(sway_active_window)
+                .or_else(gnome_active_window)
+                .or_else(kde_active_window)
+                .ok_or(TrackerError::WindowError(()))
+        }
+
+        fn idle_time(&mut self) -> Duration {
+            if let Some(idle) = gnome_idle_time().or_else(screensaver_idle_time) {
+                return idle;
+            }
+
+            // Sem fonte de ociosidade: considera sempre ativo em vez de marcar tudo como idle
+            if !self.warned_idle {
+                warn!("No Wayland idle source available; idle detection disabled");
+                self.warned_idle = true;
+            }
+            Duration::ZERO
+        }
+
+        fn open_windows(&self) -> Vec<ActiveWindow> {
+            hyprland_open_windows()
+                .or_else(sway_open_windows)
+                .unwrap_or_default()
+        }
+
+        fn current_desktop(&self) -> Option<String> {
+            hyprland_workspace().or_else(sway_workspace)
+        }
+
+        // O Wayland não permite hooks globais de entrada a clientes comuns
+        fn input_counts(&mut self) -> InputCounts {
+            if !self.warned_input {
+                warn!("Input events are not available on Wayland; clicks, keys and scrolls are not counted");
+                self.warned_input = true;
+            }
+            InputCounts::default()
+        }
+    }
+
+    fn run(program: &str, args: &[&str]) -> Option<String> {
+        let output = Command::new(program).args(args).output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    // Extrai o valor numérico de respostas do gdbus como "(uint64 1234,)"
+    fn parse_gdbus_number(output: &str) -> Option<u64> {
+        output
+            .trim()
+            .trim_start_matches('(')
+            .trim_end_matches(')')
+            .trim_end_matches(',')
+            .split_whitespace()
+            .last()?
+            .parse()
+            .ok()
+    }
+
+    // x, y, largura e altura; a janela continua valendo mesmo sem a geometria
+    fn bounds(values: [&serde_json::Value; 4]) -> Option<WindowBounds> {
+        Some(WindowBounds {
+            x: values[0].as_f64()?,
+            y: values[1].as_f64()?,
+            width: values[2].as_f64()?,
+            height: values[3].as_f64()?,
+        })
+    }
+
+    fn hyprland_active_window() -> Option<ActiveWindow> {
+        let json: serde_json::Value = serde_json::from_str(&run("hyprctl", &["activewindow", "-j"])?).ok()?;
+        let (at, size) = (&json["at"], &json["size"]);
+        Some(ActiveWindow {
+            title: json["title"].as_str()?.to_string(),
+            app_name: json["class"].as_str()?.to_string(),
+            process_id: json["pid"].as_u64().and_then(|pid| u32::try_from(pid).ok()),
+            bounds: bounds([&at[0], &at[1], &size[0], &size[1]]),
+        })
+    }
+
+    // wlroots (sway): procura o nó com "focused": true na árvore
+    fn sway_active_window() -> Option<ActiveWindow> {
+        fn find_focused(node: &serde_json::Value) -> Option<&serde_json::Value> {
+            if node["focused"].as_bool() == Some(true) && node["type"] == "con" {
+                return Some(node);
+            }
+            node["nodes"]
+                .as_array()
+                .into_iter()
+                .chain(node["floating_nodes"].as_array())
+                .flatten()
+                .find_map(find_focused)
+        }
+
+        let tree: serde_json::Value = serde_json::from_str(&run("swaymsg", &["-t", "get_tree"])?).ok()?;
+        let node = find_focused(&tree)?;
+        let app_name = node["app_id"]
+            .as_str()
+            .or_else(|| node["window_properties"]["class"].as_str())?;
+
+        let rect = &node["rect"];
+        Some(ActiveWindow {
+            title: node["name"].as_str().unwrap_or_default().to_string(),
+            app_name: app_name.to_string(),
+            process_id: node["pid"].as_u64().and_then(|pid| u32::try_from(pid).ok()),
+            bounds: bounds([&rect["x"], &rect["y"], &rect["width"], &rect["height"]]),
+        })
+    }
+
+    fn hyprland_workspace() -> Option<String> {
+        let json: serde_json::Value = serde_json::from_str(&run("hyprctl", &["activeworkspace", "-j"])?).ok()?;
+        json["name"].as_str().map(|name| name.to_string())
+    }
+
+    fn sway_workspace() -> Option<String> {
+        let json: serde_json::Value = serde_json::from_str(&run("swaymsg", &["-t", "get_workspaces"])?).ok()?;
+        json.as_array()?
+            .iter()
+            .find(|w| w["focused"].as_bool() == Some(true))
+            .and_then(|w| w["name"].as_str())
+            .map(|name| name.to_string())
+    }
+
+    fn hyprland_open_windows() -> Option<Vec<ActiveWindow>> {
+        let clients: serde_json::Value = serde_json::from_str(&run("hyprctl", &["clients", "-j"])?).ok()?;
+        Some(
+            clients
+                .as_array()?
+                .iter()
+                .filter_map(|c| Some(ActiveWindow {
+                    title: c["title"].as_str()?.to_string(),
+                    app_name: c["class"].as_str()?.to_string(),
+                    ..Default::default()
+                }))
+                .collect(),
+        )
+    }
+
+    fn sway_open_windows() -> Option<Vec<ActiveWindow>> {
+        fn collect(node: &serde_json::Value, windows: &mut Vec<ActiveWindow>) {
+            if node["type"] == "con" {
+                let app_name = node["app_id"]
+                    .as_str()
+                    .or_else(|| node["window_properties"]["class"].as_str());
+                if let (Some(app_name), Some(title)) = (app_name, node["name"].as_str()) {
+                    windows.push(ActiveWindow {
+                        title: title.to_string(),
+                        app_name: app_name.to_string(),
+                        ..Default::default()
+                    });
+                }
+            }
+            for child in node["nodes"].as_array().into_iter().chain(node["floating_nodes"].as_array()).flatten() {
+                collect(child, windows);
+            }
+        }
+
+        let tree: serde_json::Value = serde_json::from_str(&run("swaymsg", &["-t", "get_tree"])?).ok()?;
+        let mut windows = Vec::new();
+        collect(&tree, &mut windows);
+        Some(windows)
+    }
+
+    // GNOME: requer a extensão "Window Calls", que expõe a lista de janelas via D-Bus
+    fn gnome_active_window() -> Option<ActiveWindow> {
+        let output = run("gdbus", &[
+            "call", "--session",
+            "--dest", "org.gnome.Shell",
+            "--object-path", "/org/gnome/Shell/Extensions/Windows",
+            "--method", "org.gnome.Shell.Extensions.Windows.List",
+        ])?;
+
+        // ('[{"wm_class": "...", "title": "...", "focus": true}]',)
+        let start = output.find('\'')? + 1;
+        let end = output.rfind('\'')?;
+        let windows: serde_json::Value = serde_json::from_str(output.get(start..end)?).ok()?;
+        let focused = windows.as_array()?.iter().find(|w| w["focus"].as_bool() == Some(true))?;
+
+        Some(ActiveWindow {
+            title: focused["title"].as_str().unwrap_or_default().to_string(),
+            app_name: focused["wm_class"].as_str()?.to_string(),
+            process_id: focused["pid"].as_u64().and_then(|pid| u32::try_from(pid).ok()),
+            ..Default::default()
+        })
+    }
+
+    // KDE Plasma: kdotool implementa a interface do xdotool sobre KWin scripting
+    fn kde_active_window() -> Option<ActiveWindow> {
+        let title = run("kdotool", &["getactivewindow", "getwindowname"])?;
+        let class = run("kdotool", &["getactivewindow", "getwindowclassname"])?;
+        Some(ActiveWindow {
+            title: title.trim().to_string(),
+            app_name: class.trim().to_string(),
+            ..Default::default()
+        })
+    }
+
+    fn gnome_idle_time() -> Option<Duration> {
+        let output = run("gdbus", &[
+            "call", "--session",
+            "--dest", "org.gnome.Mutter.IdleMonitor",
+            "--object-path", "/org/gnome/Mutter/IdleMonitor/Core",
+            "--method", "org.gnome.Mutter.IdleMonitor.GetIdletime",
+        ])?;
+        let millis = parse_gdbus_number(&output)?;
+        debug!("Mutter idle time: {}ms", millis);
+        Some(Duration::from_millis(millis))
+    }
+
+    fn screensaver_idle_time() -> Option<Duration> {
+        let output = run("gdbus", &[
+            "call", "--session",
+            "--dest", "org.freedesktop.ScreenSaver",
+            "--object-path", "/org/freedesktop/ScreenSaver",
+            "--method", "org.freedesktop.ScreenSaver.GetSessionIdleTime",
+        ])?;
+        let seconds = parse_gdbus_number(&output)?;
+        debug!("ScreenSaver idle time: {}s", seconds);
+        Some(Duration::from_secs(seconds))
+    }
+}
@@ -0,0 +1,75 @@
+use chrono::Utc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::category::SharedConfig;
+use crate::database::DbConnection;
+use crate::events::TrackerEventSender;
+use crate::tracker::{ActivityTracker, SharedTrackerHealth, TrackerHandle};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+// Sem tick por mais que isso, o loop é considerado travado
+const STALL_TIMEOUT_SECONDS: i64 = 60;
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+// Roda o tracker e o reinicia quando a task morre (panic) ou para de dar sinais de vida
+pub async fn supervise(
+    db: DbConnection,
+    config: SharedConfig,
+    events: TrackerEventSender,
+    handle: TrackerHandle,
+    health: SharedTrackerHealth,
+) {
+    let mut backoff = Duration::from_secs(1);
+
+    loop {
+        let mut tracker = ActivityTracker::new(db.clone(), config.clone(), events.clone()).await;
+        tracker.attach(&handle);
+        tracker.set_health(health.clone());
+
+        if let Ok(mut health) = health.lock() {
+            let now = Utc::now();
+            health.started_at = Some(now);
+            health.last_tick = Some(now);
+        }
+
+        info!("Starting activity tracking");
+        let task = tokio::spawn(async move {
+            tracker.start_tracking().await;
+        });
+
+        let reason = loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+
+            if task.is_finished() {
+                break match task.await {
+                    Err(e) if e.is_panic() => "tracker panicked".to_string(),
+                    _ => "tracker loop ended".to_string(),
+                };
+            }
+
+            let stalled_for = health
+                .lock()
+                .ok()
+                .and_then(|h| h.last_tick)
+                .map(|tick| (Utc::now() - tick).num_seconds());
+            if let Some(seconds) = stalled_for.filter(|s| *s > STALL_TIMEOUT_SECONDS) {
+                task.abort();
+                break format!("no tick for {}s", seconds);
+            }
+
+            // Rodando normalmente: zera o backoff
+            backoff = Duration::from_secs(1);
+        };
+
+        error!("Activity tracking stopped ({}), restarting in {:?}", reason, backoff);
+        if let Ok(mut health) = health.lock() {
+            health.restarts += 1;
+            health.last_restart_reason = Some(reason);
+        }
+
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+        warn!("Restarting activity tracker");
+    }
+}
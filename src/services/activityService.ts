@@ -1,11 +1,61 @@
 import { invoke } from "@tauri-apps/api/tauri";
-import { WindowActivity, DailyStats } from "../types/activity";
+import { WindowActivity, DailyStats, ActivityPage, ActivityFilters, ActivityUpdate, AppStatsPage, HourlyStats, CategoryStats, RecategorizeResult, YearlyStats, WebsiteStats, ProductivityScore, Streaks, TimesheetDraft, PeriodComparison, AppDiff, TimelineBlock, ContextSwitchMetrics, CategoryLimit, BreakReminderConfig, BreakStatus, IdlePromptConfig, FocusSession, FocusSummary, PomodoroConfig, PomodoroTimer, PomodoroStats } from "../types/activity";
+
+export async function getActivitiesPage(
+  startDate: Date,
+  endDate: Date,
+  limit?: number,
+  offset?: number,
+  filters?: ActivityFilters
+): Promise<ActivityPage> {
+  return invoke("get_activities", {
+    range: {
+      start: startDate.toISOString(),
+      end: endDate.toISOString(),
+    },
+    limit,
+    offset,
+    filters,
+  });
+}
 
 export async function getActivitiesBetween(
   startDate: Date,
   endDate: Date
 ): Promise<WindowActivity[]> {
-  return invoke("get_activities", {
+  const page = await getActivitiesPage(startDate, endDate);
+  return page.activities;
+}
+
+// topN: quantos apps listar antes do agrupamento "other" (0 = todos);
+// includeOffHours: soma também o que foi registrado fora do expediente
+export async function getDailyStats(date: Date, topN?: number, includeOffHours?: boolean): Promise<DailyStats> {
+  return invoke("get_daily_stats", {
+    date: date.toISOString(),
+    topN,
+    includeOffHours,
+  });
+}
+
+export async function updateActivity(
+  id: number,
+  update: ActivityUpdate
+): Promise<WindowActivity> {
+  return invoke("update_activity", { id, update });
+}
+
+export async function getAppStatsPage(
+  cursor: string,
+  limit?: number
+): Promise<AppStatsPage> {
+  return invoke("get_app_stats_page", { cursor, limit });
+}
+
+export async function getCategoryStats(
+  startDate: Date,
+  endDate: Date
+): Promise<CategoryStats[]> {
+  return invoke("get_category_stats", {
     range: {
       start: startDate.toISOString(),
       end: endDate.toISOString(),
@@ -13,8 +63,211 @@ export async function getActivitiesBetween(
   });
 }
 
-export async function getDailyStats(date: Date): Promise<DailyStats> {
-  return invoke("get_daily_stats", {
+export async function recategorizeByPattern(
+  pattern: string,
+  categoryId: string,
+  startDate: Date,
+  endDate: Date,
+  dryRun = true
+): Promise<RecategorizeResult> {
+  return invoke("recategorize_by_pattern", {
+    pattern,
+    categoryId,
+    range: {
+      start: startDate.toISOString(),
+      end: endDate.toISOString(),
+    },
+    dryRun,
+  });
+}
+
+export async function getYearlyStats(year: number): Promise<YearlyStats> {
+  return invoke("get_yearly_stats", { year });
+}
+
+export async function getTopWebsites(
+  startDate: Date,
+  endDate: Date,
+  limit?: number
+): Promise<WebsiteStats[]> {
+  return invoke("get_top_websites", {
+    range: {
+      start: startDate.toISOString(),
+      end: endDate.toISOString(),
+    },
+    limit,
+  });
+}
+
+export async function getProductivityScore(
+  startDate: Date,
+  endDate: Date
+): Promise<ProductivityScore> {
+  return invoke("get_productivity_score", {
+    range: {
+      start: startDate.toISOString(),
+      end: endDate.toISOString(),
+    },
+  });
+}
+
+export async function getStreaks(historyDays?: number): Promise<Streaks> {
+  return invoke("get_streaks", { historyDays });
+}
+
+// date no formato YYYY-MM-DD; sem data usa a semana atual
+export async function generateTimesheetDraft(date?: string): Promise<TimesheetDraft> {
+  return invoke("generate_timesheet_draft", { date });
+}
+
+export async function comparePeriods(
+  startA: Date,
+  endA: Date,
+  startB: Date,
+  endB: Date
+): Promise<PeriodComparison> {
+  return invoke("compare_periods", {
+    rangeA: { start: startA.toISOString(), end: endA.toISOString() },
+    rangeB: { start: startB.toISOString(), end: endB.toISOString() },
+  });
+}
+
+export async function getAppDiff(
+  startA: Date,
+  endA: Date,
+  startB: Date,
+  endB: Date
+): Promise<AppDiff> {
+  return invoke("get_app_diff", {
+    rangeA: { start: startA.toISOString(), end: endA.toISOString() },
+    rangeB: { start: startB.toISOString(), end: endB.toISOString() },
+  });
+}
+
+export async function getTimeline(date: Date, minGapSeconds?: number): Promise<TimelineBlock[]> {
+  return invoke("get_timeline", { date: date.toISOString(), minGapSeconds });
+}
+
+export async function getContextSwitchMetrics(
+  startDate: Date,
+  endDate: Date
+): Promise<ContextSwitchMetrics> {
+  return invoke("get_context_switch_metrics", {
+    range: {
+      start: startDate.toISOString(),
+      end: endDate.toISOString(),
+    },
+  });
+}
+
+export async function getCategoryLimits(): Promise<CategoryLimit[]> {
+  return invoke("get_category_limits");
+}
+
+export async function setCategoryLimits(limits: CategoryLimit[]): Promise<void> {
+  return invoke("set_category_limits", { limits });
+}
+
+export async function getBreakReminderConfig(): Promise<BreakReminderConfig> {
+  return invoke("get_break_reminder_config");
+}
+
+export async function setBreakReminderConfig(breakReminders: BreakReminderConfig): Promise<void> {
+  return invoke("set_break_reminder_config", { breakReminders });
+}
+
+export async function getBreakStatus(): Promise<BreakStatus> {
+  return invoke("get_break_status");
+}
+
+export async function snoozeBreakReminder(minutes?: number): Promise<BreakStatus> {
+  return invoke("snooze_break_reminder", { minutes });
+}
+
+export async function getIdlePromptConfig(): Promise<IdlePromptConfig> {
+  return invoke("get_idle_prompt_config");
+}
+
+export async function setIdlePromptConfig(idlePrompt: IdlePromptConfig): Promise<void> {
+  return invoke("set_idle_prompt_config", { idlePrompt });
+}
+
+// Rotula a ausência informada pelo evento "idle-return-prompt"
+export async function labelIdlePeriod(
+  start: string,
+  end: string,
+  label: string,
+  categoryId?: string
+): Promise<number> {
+  return invoke("label_idle_period", { start, end, label, categoryId });
+}
+
+// duration em minutos; avisa ao abrir apps de categorias improdutivas
+export async function startFocusMode(
+  duration: number,
+  label?: string,
+  bringToFront?: boolean
+): Promise<FocusSession> {
+  return invoke("start_focus_mode", { duration, label, bringToFront });
+}
+
+export async function getFocusSummaries(limit?: number): Promise<FocusSummary[]> {
+  return invoke("get_focus_summaries", { limit });
+}
+
+export async function getPomodoroConfig(): Promise<PomodoroConfig> {
+  return invoke("get_pomodoro_config");
+}
+
+export async function setPomodoroConfig(pomodoro: PomodoroConfig): Promise<void> {
+  return invoke("set_pomodoro_config", { pomodoro });
+}
+
+export async function startPomodoro(label?: string): Promise<PomodoroTimer> {
+  return invoke("start_pomodoro", { label });
+}
+
+export async function stopPomodoro(): Promise<PomodoroTimer | null> {
+  return invoke("stop_pomodoro");
+}
+
+// Encerra a fase atual sem contar o pomodoro
+export async function skipPomodoro(): Promise<PomodoroTimer | null> {
+  return invoke("skip_pomodoro");
+}
+
+export async function getPomodoro(): Promise<PomodoroTimer | null> {
+  return invoke("get_pomodoro");
+}
+
+export async function getPomodoroStats(startDate: Date, endDate: Date): Promise<PomodoroStats> {
+  return invoke("get_pomodoro_stats", {
+    range: {
+      start: startDate.toISOString(),
+      end: endDate.toISOString(),
+    },
+  });
+}
+
+// Tempo fora do computador; sem application, fica como "Manual"
+export async function addManualActivity(
+  title: string,
+  start: Date,
+  end: Date,
+  application?: string,
+  categoryId?: string
+): Promise<WindowActivity> {
+  return invoke("add_manual_activity", {
+    title,
+    application,
+    categoryId,
+    start: start.toISOString(),
+    end: end.toISOString(),
+  });
+}
+
+export async function getHourlyStats(date: Date): Promise<HourlyStats[]> {
+  return invoke("get_hourly_stats", {
     date: date.toISOString(),
   });
 }
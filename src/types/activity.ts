@@ -7,6 +7,26 @@ export interface WindowActivity {
     is_browser: boolean;
     url?: string;
     is_idle: boolean;
+    category_override?: string | null; // categoria recategorizada por padrão
+    profile?: string | null; // perfil do navegador ("Work", "Personal")
+    off_hours?: boolean; // registrada fora do expediente (modo "tag")
+    manual?: boolean; // lançada à mão (add_manual_activity)
+}
+
+export interface ActivityPage {
+    activities: WindowActivity[];
+    total_count: number;
+    limit: number;
+    offset: number;
+    has_more: boolean;
+}
+
+export interface ActivityFilters {
+    applications?: string[];
+    category?: string;
+    is_idle?: boolean;
+    is_browser?: boolean;
+    title?: string;
 }
 
 export interface Category {
@@ -14,6 +34,7 @@ export interface Category {
     name: string;
     color: string;
     is_productive: boolean;
+    neutral?: boolean; // fora das contas de produtivo/distração
 }
 
 export interface ApplicationStats {
@@ -28,7 +49,325 @@ export interface DailyStats {
     total_time: number; // em segundos
     productive_time: number; // em segundos
     goal_percentage: number;
+    prorated_goal_percentage?: number | null; // meta reduzida às horas de expediente restantes
     idle_time: number; // tempo total em idle
+    neutral_time: number; // em segundos, categorias neutras (System)
     top_applications: ApplicationStats[];
     activities: WindowActivity[];
-} 
\ No newline at end of file
+    other_applications?: OtherApplications | null;
+    idle_breakdown: IdleBreakdown;
+    category_limits: CategoryLimitProgress[];
+}
+
+// Meta de limite: no máximo max_minutes por dia na categoria
+export interface CategoryLimit {
+    category_id: string;
+    max_minutes: number;
+}
+
+export interface CategoryLimitProgress {
+    category_id: string;
+    category_name: string;
+    limit_minutes: number; // já multiplicado pelos dias do período
+    used_seconds: number;
+    remaining_seconds: number;
+    exceeded: boolean;
+}
+
+export interface IdleBucket {
+    count: number;
+    total_seconds: number;
+}
+
+// short < 5 min, medium 5–30 min, long > 30 min
+export interface IdleBreakdown {
+    short: IdleBucket;
+    medium: IdleBucket;
+    long: IdleBucket;
+}
+
+export interface OtherApplications {
+    application_count: number;
+    total_duration: number;
+    idle_duration: number;
+    cursor: string; // passar para get_app_stats_page
+}
+
+export interface AppStatsPage {
+    applications: ApplicationStats[];
+    next_cursor: string | null;
+}
+
+export interface CategoryStats {
+    category_id: string | null;
+    name: string;
+    color: string | null;
+    is_productive: boolean;
+    neutral: boolean;
+    total_duration: number; // em segundos
+    idle_duration: number;
+    active_duration: number; // inclui ajustes manuais
+    percentage: number; // parcela do tempo ativo do intervalo
+    application_count: number;
+}
+
+export interface RecategorizeResult {
+    matched: number;
+    total_seconds: number;
+    applications: [string, number][];
+    applied: boolean;
+}
+
+export interface MonthStats {
+    month: number; // 1-12
+    start: string; // ISO string
+    end: string; // ISO string
+    total_time: number; // em segundos
+    productive_time: number;
+    idle_time: number;
+    top_applications: [string, number][];
+}
+
+export interface YearlyStats {
+    year: number;
+    total_time: number; // em segundos
+    productive_time: number;
+    idle_time: number;
+    months: MonthStats[];
+    top_applications: [string, number][];
+}
+
+export interface WebsiteStats {
+    domain: string;
+    label: string | null;
+    category: Category | null;
+    total_duration: number; // em segundos
+    visits: number;
+    percentage: number; // parcela do tempo em sites
+    browsers: string[];
+}
+
+export interface ProductivityScore {
+    score: number | null; // 0-100
+    components: {
+        productive: number | null; // cada componente em 0-1
+        idle: number | null;
+        focus: number | null;
+        goal: number | null;
+    };
+    weights: {
+        productive: number;
+        idle: number;
+        focus: number;
+        goal: number;
+        switches_per_hour_ceiling: number;
+    };
+    context_switches: number;
+    switches_per_hour: number;
+}
+
+export interface GoalOutcome {
+    date: string; // YYYY-MM-DD
+    productive_seconds: number;
+    goal_minutes: number; // meta em vigor no dia
+    met: boolean;
+    workday: boolean;
+}
+
+export interface Streaks {
+    current: number;
+    best: number;
+    today_met: boolean;
+    history: GoalOutcome[];
+}
+
+export interface TimesheetEntry {
+    date: string; // YYYY-MM-DD
+    project: string | null;
+    category: string | null;
+    minutes: number;
+    rounded_minutes: number;
+}
+
+export interface TimesheetFlag {
+    date: string;
+    kind: 'missing_day' | 'sparse_day' | 'gap';
+    start: string | null; // ISO string, só em gaps
+    end: string | null;
+    minutes: number;
+}
+
+export interface TimesheetDraft {
+    week_start: string;
+    week_end: string;
+    increment_minutes: number;
+    entries: TimesheetEntry[];
+    flags: TimesheetFlag[];
+    total_minutes: number;
+    path: string | null;
+}
+
+export interface PeriodSummary {
+    start: string; // ISO string
+    end: string;
+    total_time: number;
+    productive_time: number;
+    idle_time: number;
+}
+
+export interface PeriodDelta {
+    name: string;
+    category_id: string | null;
+    a: number;
+    b: number;
+    delta: number; // a - b, em segundos
+    percent_change: number | null; // null quando b = 0
+}
+
+export interface AppDiff {
+    applications: PeriodDelta[];
+    categories: PeriodDelta[];
+}
+
+export interface PeriodComparison {
+    a: PeriodSummary;
+    b: PeriodSummary;
+    total_delta: number;
+    productive_delta: number;
+    idle_delta: number;
+    applications: PeriodDelta[];
+    categories: PeriodDelta[];
+}
+
+export interface TimelineBlock {
+    kind: 'activity' | 'idle' | 'gap';
+    start: string; // ISO string
+    end: string;
+    duration: number; // segundos
+    application: string | null;
+    title: string | null;
+    project: string | null;
+    category: Category | null;
+    activity_ids: number[];
+}
+
+export interface HourSwitches {
+    hour_start: string; // ISO string, início da hora local
+    app_switches: number;
+    window_switches: number;
+}
+
+export interface ContextSwitchMetrics {
+    active_seconds: number;
+    app_switches: number;
+    window_switches: number;
+    app_switches_per_hour: number;
+    window_switches_per_hour: number;
+    focus_blocks: number;
+    average_focus_block_seconds: number;
+    longest_focus_block_seconds: number;
+    hours: HourSwitches[];
+}
+
+export interface BreakReminderConfig {
+    enabled: boolean;
+    interval_minutes: number; // tempo ativo contínuo até o lembrete
+    break_minutes: number; // pausa mínima que zera o contador
+    snooze_minutes: number;
+}
+
+export interface BreakStatus {
+    enabled: boolean;
+    working_since?: string | null; // null = em pausa agora
+    continuous_seconds: number;
+    next_reminder_at?: string | null;
+    snoozed_until?: string | null;
+}
+
+export interface IdlePromptConfig {
+    enabled: boolean;
+    after_minutes: number; // ausência mínima para oferecer o rótulo
+    labels: string[];
+}
+
+// Payload do evento "idle-return-prompt"
+export interface IdleReturnPrompt {
+    started_at: string;
+    ended_at: string;
+    duration_seconds: number;
+    labels: string[];
+}
+
+export interface FocusSession {
+    label: string;
+    started_at: string;
+    ends_at: string;
+    distraction_alerts: boolean; // modo foco
+    bring_to_front: boolean;
+    distraction_count: number;
+    distracting_apps: string[];
+}
+
+// Resumo gravado ao fim de cada sessão de foco
+export interface FocusSummary {
+    id: number;
+    label: string;
+    started_at: string;
+    planned_end: string;
+    ended_at: string;
+    completed: boolean; // false = interrompida antes do fim
+    productive_seconds: number;
+    unproductive_seconds: number;
+    distraction_count: number;
+    distracting_apps: string[];
+}
+
+export interface PomodoroConfig {
+    work_minutes: number;
+    short_break_minutes: number;
+    long_break_minutes: number;
+    long_break_every: number; // pausa longa a cada N pomodoros
+}
+
+export type PomodoroPhase = "work" | "short_break" | "long_break";
+
+export interface PomodoroTimer {
+    label: string;
+    phase: PomodoroPhase;
+    phase_started_at: string;
+    ends_at: string;
+    completed: number; // pomodoros concluídos no ciclo atual
+}
+
+export interface PomodoroDetail {
+    id: number;
+    label: string;
+    started_at: string;
+    ended_at: string;
+    productive_seconds: number;
+    idle_seconds: number;
+    applications: { application: string; seconds: number }[];
+}
+
+export interface PomodoroStats {
+    completed: number;
+    focused_seconds: number;
+    productive_seconds: number;
+    pomodoros: PomodoroDetail[];
+}
+
+export interface HourlyStats {
+    hour_start: string; // ISO string
+    hour: number;
+    total_duration: number;
+    productive_duration: number;
+    idle_duration: number;
+    top_application: string | null;
+}
+
+export interface ActivityUpdate {
+    start_time?: string; // ISO string
+    end_time?: string; // ISO string
+    title?: string;
+    is_idle?: boolean;
+}